@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use serde_json::json;
+
+use crate::score::ErrorData;
+
+/// Most fired alerts kept for the alerts tab
+pub const ALERT_LOG_CAPACITY: usize = 100;
+
+/// Score the runtime gives nodes with too few reports to be scored, never alerted about
+const INSUFFICIENT_REPORTS: i16 = i16::MAX;
+
+/// When a score raises an alert, editable from the alerts tab
+#[derive(Clone)]
+pub struct AlertConfig {
+    /// Score above which an account without its own threshold raises an alert
+    pub threshold: i16,
+    /// Thresholds of single accounts by name, replacing the global one
+    pub per_node: BTreeMap<String, i16>,
+    /// Consecutive blocks a score must stay above its threshold before the alert fires
+    pub blocks: u32,
+    /// URL the alerts are POSTed to as JSON
+    pub webhook: Option<String>,
+}
+
+impl AlertConfig {
+    /// Alerting from `ALERT_THRESHOLD`, `ALERT_NODE_THRESHOLDS`, `ALERT_BLOCKS` and
+    /// `ALERT_WEBHOOK_URL`
+    pub fn from_env() -> Result<Self, String> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+
+        let threshold = match var("ALERT_THRESHOLD") {
+            Some(v) => v
+                .parse()
+                .map_err(|_| format!("Invalid ALERT_THRESHOLD: {}", v))?,
+            None => 10,
+        };
+        let blocks = match var("ALERT_BLOCKS") {
+            Some(v) => v
+                .parse()
+                .ok()
+                .filter(|&blocks| blocks > 0)
+                .ok_or_else(|| format!("Invalid ALERT_BLOCKS: {}", v))?,
+            None => 3,
+        };
+
+        // Comma separated `name=threshold` pairs, e.g. `node1.local:3000=5,node2.local:3000=8`
+        let mut per_node = BTreeMap::new();
+        for pair in var("ALERT_NODE_THRESHOLDS").unwrap_or_default().split(',') {
+            if pair.trim().is_empty() {
+                continue;
+            }
+            let (name, threshold) = pair
+                .split_once('=')
+                .and_then(|(name, threshold)| Some((name.trim(), threshold.trim().parse().ok()?)))
+                .ok_or_else(|| format!("Invalid ALERT_NODE_THRESHOLDS entry: {}", pair))?;
+            per_node.insert(name.to_string(), threshold);
+        }
+
+        Ok(Self {
+            threshold,
+            per_node,
+            blocks,
+            webhook: var("ALERT_WEBHOOK_URL"),
+        })
+    }
+
+    pub fn threshold_of(&self, account_name: &str) -> i16 {
+        self.per_node
+            .get(account_name)
+            .copied()
+            .unwrap_or(self.threshold)
+    }
+}
+
+/// A score that stayed above its threshold for the configured number of blocks
+#[derive(Clone)]
+pub struct Alert {
+    pub block: u32,
+    pub account_name: String,
+    pub score: i16,
+    pub threshold: i16,
+}
+
+/// Accounts above their threshold and the alerts fired so far
+#[derive(Default)]
+pub struct Alerts {
+    /// Consecutive blocks each account has been above its threshold
+    streaks: HashMap<String, u32>,
+    /// Last block whose scores were tracked
+    last_block: Option<u32>,
+    /// Accounts whose alert fired and has not cleared yet
+    pub active: BTreeSet<String>,
+    /// Fired alerts, oldest first
+    pub log: VecDeque<Alert>,
+}
+
+impl Alerts {
+    /// Track the scores of a block, returning the alerts that fire with it
+    ///
+    /// An alert fires once when its streak reaches the configured length, and clears once the
+    /// score drops back or the account goes unscored. Finality can skip blocks, whose scores are
+    /// unknown, so the streaks start over after a gap.
+    pub fn update(&mut self, config: &AlertConfig, block: u32, scores: &[ErrorData]) -> Vec<Alert> {
+        if self
+            .last_block
+            .is_none_or(|last| last.checked_add(1) != Some(block))
+        {
+            self.streaks.clear();
+        }
+        self.last_block = Some(block);
+
+        let mut fired = Vec::new();
+        let mut above = HashMap::new();
+        for d in scores {
+            let threshold = config.threshold_of(&d.account_name);
+            if d.error_value == INSUFFICIENT_REPORTS || d.error_value <= threshold {
+                continue;
+            }
+            let streak = self.streaks.get(&d.account_name).copied().unwrap_or(0) + 1;
+            if streak >= config.blocks && self.active.insert(d.account_name.clone()) {
+                fired.push(Alert {
+                    block,
+                    account_name: d.account_name.clone(),
+                    score: d.error_value,
+                    threshold,
+                });
+            }
+            above.insert(d.account_name.clone(), streak);
+        }
+        self.active.retain(|name| above.contains_key(name));
+        self.streaks = above;
+
+        for alert in &fired {
+            self.log.push_back(alert.clone());
+            if self.log.len() > ALERT_LOG_CAPACITY {
+                self.log.pop_front();
+            }
+        }
+        fired
+    }
+}
+
+/// Show an alert as a desktop notification and POST it to the webhook, if one is set
+///
+/// The notification goes through `notify-send`, so it is skipped where libnotify is missing.
+pub async fn notify(alert: &Alert, webhook: Option<&str>) {
+    let message = format!(
+        "{} scored {} in block {}, above {} for the configured number of blocks",
+        alert.account_name, alert.score, alert.block, alert.threshold
+    );
+    println!("Alert: {}", message);
+
+    if let Err(e) = tokio::process::Command::new("notify-send")
+        .arg("Trust Score Alert")
+        .arg(&message)
+        .status()
+        .await
+    {
+        eprintln!("Failed to show a desktop notification: {}", e);
+    }
+
+    if let Some(webhook) = webhook {
+        let body = json!({
+            "block": alert.block,
+            "account": alert.account_name,
+            "score": alert.score,
+            "threshold": alert.threshold,
+            "message": message,
+        });
+        let result = reqwest::Client::new()
+            .post(webhook)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            eprintln!("Failed to POST the alert to {}: {}", webhook, e);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::{Arc, Mutex};
+
+use subxt::backend::{legacy::LegacyRpcMethods, rpc::RpcClient};
+use subxt::config::substrate::AccountId32;
+use subxt::{OnlineClient, SubstrateConfig};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::score::{get_account_names, name_of, substrate};
+
+/// One neighbor's report about the selected account
+#[derive(Clone)]
+pub struct PairError {
+    pub reporter: [u8; 32],
+    pub reporter_name: String,
+    pub measured: i16,
+    pub estimated: i16,
+    pub error: i16,
+}
+
+/// State of the breakdown shown in the UI
+#[derive(Clone, Default)]
+pub enum Breakdown {
+    #[default]
+    Idle,
+    Loading,
+    Done {
+        block: u32,
+        account_name: String,
+        pairs: Vec<PairError>,
+    },
+    Failed(String),
+}
+
+/// Fetch the reports behind an account's trust score using the runtime and state of that block
+pub async fn breakdown_at(
+    api: &OnlineClient<SubstrateConfig>,
+    rpc: &LegacyRpcMethods<SubstrateConfig>,
+    block: u32,
+    account: [u8; 32],
+) -> Result<Vec<PairError>, Box<dyn std::error::Error>> {
+    let hash = rpc
+        .chain_get_block_hash(Some(block.into()))
+        .await?
+        .ok_or_else(|| format!("Block #{} not found", block))?;
+
+    let rpc_call = substrate::apis()
+        .trust_score_api()
+        .trust_score_breakdown(block, AccountId32(account));
+    let pairs: Vec<(AccountId32, i16, i16, i16)> =
+        api.runtime_api().at(hash).call(rpc_call).await?;
+
+    let account_name = get_account_names(api).await?;
+
+    Ok(pairs
+        .into_iter()
+        .map(|(reporter, measured, estimated, error)| PairError {
+            reporter: reporter.0,
+            reporter_name: name_of(&account_name, &reporter.0),
+            measured,
+            estimated,
+            error,
+        })
+        .collect())
+}
+
+/// Answer breakdown requests from the UI until the request channel is closed
+pub async fn breakdown_task(
+    mut requests: UnboundedReceiver<(u32, [u8; 32])>,
+    breakdown: Arc<Mutex<Breakdown>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Get RPC URL from environment variable or use default
+    let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| "ws://127.0.0.1:9944".into());
+
+    // Block hashes are looked up through the legacy RPC methods
+    let rpc_client = RpcClient::from_url(&rpc_url).await?;
+    let rpc = LegacyRpcMethods::<SubstrateConfig>::new(rpc_client.clone());
+    let api = OnlineClient::<SubstrateConfig>::from_rpc_client(rpc_client).await?;
+
+    while let Some((block, account)) = requests.recv().await {
+        let name = name_of(&get_account_names(&api).await?, &account);
+        println!("Breaking down trust score of {} at block {}", name, block);
+        *breakdown.lock().unwrap() = Breakdown::Loading;
+
+        let result = match breakdown_at(&api, &rpc, block, account).await {
+            Ok(pairs) => Breakdown::Done {
+                block,
+                account_name: name,
+                pairs,
+            },
+            Err(e) => Breakdown::Failed(e.to_string()),
+        };
+
+        *breakdown.lock().unwrap() = result;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Live view of the trust scores calculated by a proof-of-location node
+///
+/// The node is picked with the RPC_URL environment variable.
+#[derive(Parser, Debug, Clone)]
+#[command(version, about)]
+pub struct Cli {
+    /// File the collected scores are exported to when the monitor closes, as JSON for a `.json`
+    /// path and as CSV otherwise; also the default path of the Export button
+    #[arg(long, value_name = "PATH")]
+    pub export: Option<PathBuf>,
+}
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use subxt::backend::{legacy::LegacyRpcMethods, rpc::RpcClient};
+use subxt::config::substrate::AccountId32;
+use subxt::{OnlineClient, SubstrateConfig};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::score::{get_account_names, name_of, substrate};
+
+/// Change of one account's trust score between two blocks
+#[derive(Clone)]
+pub struct ScoreDiff {
+    pub account: [u8; 32],
+    pub account_name: String,
+    pub before: Option<i16>,
+    pub after: Option<i16>,
+}
+
+impl ScoreDiff {
+    /// Score change from the first to the second block, if the account was scored in both
+    pub fn delta(&self) -> Option<i32> {
+        Some(self.after? as i32 - self.before? as i32)
+    }
+}
+
+/// State of the comparison shown in the UI
+#[derive(Clone, Default)]
+pub enum Comparison {
+    #[default]
+    Idle,
+    Loading,
+    Done {
+        from: u32,
+        to: u32,
+        diffs: Vec<ScoreDiff>,
+    },
+    Failed(String),
+}
+
+/// Calculate the trust scores of a block using the runtime and state of that block
+pub async fn scores_at(
+    api: &OnlineClient<SubstrateConfig>,
+    rpc: &LegacyRpcMethods<SubstrateConfig>,
+    block: u32,
+) -> Result<Vec<(AccountId32, i16)>, Box<dyn std::error::Error>> {
+    let hash = rpc
+        .chain_get_block_hash(Some(block.into()))
+        .await?
+        .ok_or_else(|| format!("Block #{} not found", block))?;
+
+    let rpc_call = substrate::apis()
+        .trust_score_api()
+        .calculate_trust_scores(block);
+
+    Ok(api.runtime_api().at(hash).call(rpc_call).await?)
+}
+
+async fn compare_blocks(
+    api: &OnlineClient<SubstrateConfig>,
+    rpc: &LegacyRpcMethods<SubstrateConfig>,
+    from: u32,
+    to: u32,
+) -> Result<Vec<ScoreDiff>, Box<dyn std::error::Error>> {
+    let account_name = get_account_names(api).await?;
+
+    // Pair up the scores of both blocks by account
+    let mut scores: BTreeMap<[u8; 32], (Option<i16>, Option<i16>)> = BTreeMap::new();
+    for (account_id, error_value) in scores_at(api, rpc, from).await? {
+        scores.entry(account_id.0).or_default().0 = Some(error_value);
+    }
+    for (account_id, error_value) in scores_at(api, rpc, to).await? {
+        scores.entry(account_id.0).or_default().1 = Some(error_value);
+    }
+
+    Ok(scores
+        .into_iter()
+        .map(|(account_id, (before, after))| ScoreDiff {
+            account: account_id,
+            account_name: name_of(&account_name, &account_id),
+            before,
+            after,
+        })
+        .collect())
+}
+
+/// Answer comparison requests from the UI until the request channel is closed
+pub async fn comparison_task(
+    mut requests: UnboundedReceiver<(u32, u32)>,
+    comparison: Arc<Mutex<Comparison>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Get RPC URL from environment variable or use default
+    let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| "ws://127.0.0.1:9944".into());
+
+    // Block hashes are looked up through the legacy RPC methods
+    let rpc_client = RpcClient::from_url(&rpc_url).await?;
+    let rpc = LegacyRpcMethods::<SubstrateConfig>::new(rpc_client.clone());
+    let api = OnlineClient::<SubstrateConfig>::from_rpc_client(rpc_client).await?;
+
+    while let Some((from, to)) = requests.recv().await {
+        println!("Comparing trust scores of block {} and {}", from, to);
+        *comparison.lock().unwrap() = Comparison::Loading;
+
+        let result = match compare_blocks(&api, &rpc, from, to).await {
+            Ok(diffs) => Comparison::Done { from, to, diffs },
+            Err(e) => Comparison::Failed(e.to_string()),
+        };
+
+        *comparison.lock().unwrap() = result;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+
+use crate::graph::Graph;
+use crate::score::{short_ss58, ScoreHistory};
+
+/// Per-pair errors of the graph, as (block, reporter, subject, error) with account names
+fn pair_errors(graph: &Graph) -> Vec<(u32, String, String, i16)> {
+    let Graph::Done {
+        block,
+        nodes,
+        edges,
+    } = graph
+    else {
+        return Vec::new();
+    };
+    let names: HashMap<[u8; 32], &str> = nodes
+        .iter()
+        .map(|n| (n.account, n.account_name.as_str()))
+        .collect();
+    let name = |account| {
+        names
+            .get(account)
+            .map_or_else(|| short_ss58(account), |name| name.to_string())
+    };
+
+    edges
+        .iter()
+        .map(|e| (*block, name(&e.reporter), name(&e.subject), e.error))
+        .collect()
+}
+
+/// Path of the CSV file with the per-pair errors, next to the history one
+fn pairs_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}_pairs.csv", stem))
+}
+
+/// Write the score history and the per-pair errors of the block shown in the graph
+///
+/// A `.json` path gets one JSON document. Any other path gets the history as CSV, with the
+/// per-pair errors in a `_pairs.csv` file next to it. Returns the files written.
+pub fn export(path: &Path, history: &ScoreHistory, graph: &Graph) -> std::io::Result<Vec<PathBuf>> {
+    let pairs = pair_errors(graph);
+
+    if path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+    {
+        let document = json!({
+            "history": history
+                .iter()
+                .map(|(block, scores)| json!({
+                    "block": block,
+                    "scores": scores
+                        .iter()
+                        .map(|d| json!({ "account": d.account_name, "score": d.error_value }))
+                        .collect::<Vec<_>>(),
+                }))
+                .collect::<Vec<_>>(),
+            "pairs": pairs
+                .iter()
+                .map(|(block, reporter, subject, error)| json!({
+                    "block": block,
+                    "reporter": reporter,
+                    "subject": subject,
+                    "error": error,
+                }))
+                .collect::<Vec<_>>(),
+        });
+        fs::write(path, serde_json::to_string_pretty(&document)?)?;
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut file = fs::File::create(path)?;
+    writeln!(file, "block,account,score")?;
+    for (block, scores) in history {
+        for d in scores {
+            writeln!(file, "{},{},{}", block, d.account_name, d.error_value)?;
+        }
+    }
+
+    let pairs_path = pairs_path(path);
+    let mut file = fs::File::create(&pairs_path)?;
+    writeln!(file, "block,reporter,subject,error")?;
+    for (block, reporter, subject, error) in &pairs {
+        writeln!(file, "{},{},{},{}", block, reporter, subject, error)?;
+    }
+
+    Ok(vec![path.to_path_buf(), pairs_path])
+}
@@ -0,0 +1,112 @@
+use std::sync::{Arc, Mutex};
+
+use subxt::backend::{legacy::LegacyRpcMethods, rpc::RpcClient};
+use subxt::{OnlineClient, SubstrateConfig};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::breakdown::breakdown_at;
+use crate::diff::scores_at;
+use crate::score::{get_account_names, name_of};
+
+/// An account in the graph
+#[derive(Clone)]
+pub struct GraphNode {
+    pub account: [u8; 32],
+    pub account_name: String,
+    /// Trust score, none for a reporter nobody reported about
+    pub score: Option<i16>,
+}
+
+/// One neighbor's report about another, with the error it adds to the subject's score
+#[derive(Clone)]
+pub struct Edge {
+    pub reporter: [u8; 32],
+    pub subject: [u8; 32],
+    pub error: i16,
+}
+
+/// State of the graph shown in the UI
+#[derive(Clone, Default)]
+pub enum Graph {
+    #[default]
+    Idle,
+    Loading,
+    Done {
+        block: u32,
+        nodes: Vec<GraphNode>,
+        edges: Vec<Edge>,
+    },
+    Failed(String),
+}
+
+/// Fetch the reports behind the trust score of every scored account of a block
+async fn graph_at(
+    api: &OnlineClient<SubstrateConfig>,
+    rpc: &LegacyRpcMethods<SubstrateConfig>,
+    block: u32,
+) -> Result<(Vec<GraphNode>, Vec<Edge>), Box<dyn std::error::Error>> {
+    let account_name = get_account_names(api).await?;
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for (account_id, score) in scores_at(api, rpc, block).await? {
+        for pair in breakdown_at(api, rpc, block, account_id.0).await? {
+            edges.push(Edge {
+                reporter: pair.reporter,
+                subject: account_id.0,
+                error: pair.error,
+            });
+        }
+        nodes.push(GraphNode {
+            account: account_id.0,
+            account_name: name_of(&account_name, &account_id.0),
+            score: Some(score),
+        });
+    }
+
+    // Reporters that were not scored themselves still get a place in the graph
+    for edge in &edges {
+        if !nodes.iter().any(|n| n.account == edge.reporter) {
+            nodes.push(GraphNode {
+                account: edge.reporter,
+                account_name: name_of(&account_name, &edge.reporter),
+                score: None,
+            });
+        }
+    }
+    nodes.sort_by(|a, b| a.account_name.cmp(&b.account_name));
+
+    Ok((nodes, edges))
+}
+
+/// Answer graph requests from the UI until the request channel is closed
+pub async fn graph_task(
+    mut requests: UnboundedReceiver<u32>,
+    graph: Arc<Mutex<Graph>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Get RPC URL from environment variable or use default
+    let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| "ws://127.0.0.1:9944".into());
+
+    // Block hashes are looked up through the legacy RPC methods
+    let rpc_client = RpcClient::from_url(&rpc_url).await?;
+    let rpc = LegacyRpcMethods::<SubstrateConfig>::new(rpc_client.clone());
+    let api = OnlineClient::<SubstrateConfig>::from_rpc_client(rpc_client).await?;
+
+    while let Some(block) = requests.recv().await {
+        println!("Building the RSSI graph of block {}", block);
+        *graph.lock().unwrap() = Graph::Loading;
+
+        let result = match graph_at(&api, &rpc, block).await {
+            Ok((nodes, edges)) => Graph::Done {
+                block,
+                nodes,
+                edges,
+            },
+            Err(e) => Graph::Failed(e.to_string()),
+        };
+
+        *graph.lock().unwrap() = result;
+    }
+
+    Ok(())
+}
@@ -1,24 +1,890 @@
+mod alert;
+mod breakdown;
+mod cli;
+mod diff;
+mod export;
+mod graph;
+mod map;
 mod score;
+mod scrub;
 
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+use clap::Parser;
 use eframe::egui;
-use egui_plot::{Bar, BarChart, Legend, Plot, PlotBounds};
+use egui_plot::{
+    Bar, BarChart, Legend, Line, MarkerShape, Plot, PlotBounds, PlotPoint, PlotPoints, Points, Text,
+};
+use tokio::sync::mpsc::UnboundedSender;
 
-use score::ErrorData;
+use alert::{AlertConfig, Alerts};
+use breakdown::Breakdown;
+use cli::Cli;
+use diff::Comparison;
+use graph::Graph;
+use map::MapNode;
+use score::{ss58, ErrorData, ScoreHistory, HISTORY_CAPACITY};
+use scrub::Scrub;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    /// Scores of the latest finalized block
+    Live,
+    /// Scores of the latest finalized blocks over time
+    History,
+    /// Score changes between two selected blocks
+    Compare,
+    /// Reports behind one account's score
+    Breakdown,
+    /// Reports between all accounts of a block as a network
+    Graph,
+    /// Registered locations of the accounts with their latest score
+    Map,
+    /// Alert thresholds and the alerts fired so far
+    Alerts,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DeltaSort {
+    LargestChange,
+    MostImproved,
+    MostWorsened,
+}
 
 struct TrustScoreApp {
     error_data: Arc<Mutex<Vec<ErrorData>>>,
     block_number: Arc<Mutex<u32>>,
+    history: Arc<Mutex<ScoreHistory>>,
+    comparison: Arc<Mutex<Comparison>>,
+    compare_tx: UnboundedSender<(u32, u32)>,
+    breakdown: Arc<Mutex<Breakdown>>,
+    breakdown_tx: UnboundedSender<(u32, [u8; 32])>,
+    graph: Arc<Mutex<Graph>>,
+    graph_tx: UnboundedSender<u32>,
+    map_nodes: Arc<Mutex<Vec<MapNode>>>,
+    alert_config: Arc<Mutex<AlertConfig>>,
+    alerts: Arc<Mutex<Alerts>>,
+    scrub: Arc<Mutex<Scrub>>,
+    scrub_tx: UnboundedSender<u32>,
+    mode: Mode,
+    /// Whether the live view follows the finalized head or shows `scrub_block`
+    follow_head: bool,
+    scrub_block: u32,
+    /// Name or SS58 prefix the shown accounts must match
+    filter: String,
+    /// Accounts listed first, and the only ones shown with `pinned_only`
+    pinned: BTreeSet<[u8; 32]>,
+    pinned_only: bool,
+    /// Blocks shown in the history chart
+    history_window: u32,
+    compare_from: u32,
+    compare_to: u32,
+    sort: DeltaSort,
+    breakdown_block: u32,
+    breakdown_account: Option<([u8; 32], String)>,
+    graph_block: u32,
+    /// Score above which a node is flagged as suspected on the map
+    suspect_threshold: i16,
+    /// File the Export button writes to
+    export_path: String,
+    /// Whether to export when the monitor closes, as asked with `--export`
+    export_on_exit: bool,
+    /// Outcome of the last export
+    export_status: Option<String>,
 }
 
 impl TrustScoreApp {
-    fn new(error_data: Arc<Mutex<Vec<ErrorData>>>, block_number: Arc<Mutex<u32>>) -> Self {
+    fn new(
+        error_data: Arc<Mutex<Vec<ErrorData>>>,
+        block_number: Arc<Mutex<u32>>,
+        history: Arc<Mutex<ScoreHistory>>,
+        comparison: Arc<Mutex<Comparison>>,
+        compare_tx: UnboundedSender<(u32, u32)>,
+        breakdown: Arc<Mutex<Breakdown>>,
+        breakdown_tx: UnboundedSender<(u32, [u8; 32])>,
+        graph: Arc<Mutex<Graph>>,
+        graph_tx: UnboundedSender<u32>,
+        map_nodes: Arc<Mutex<Vec<MapNode>>>,
+        alert_config: Arc<Mutex<AlertConfig>>,
+        alerts: Arc<Mutex<Alerts>>,
+        scrub: Arc<Mutex<Scrub>>,
+        scrub_tx: UnboundedSender<u32>,
+        export: Option<PathBuf>,
+    ) -> Self {
         Self {
             error_data,
             block_number,
+            history,
+            comparison,
+            compare_tx,
+            breakdown,
+            breakdown_tx,
+            graph,
+            graph_tx,
+            map_nodes,
+            alert_config,
+            alerts,
+            scrub,
+            scrub_tx,
+            mode: Mode::Live,
+            follow_head: true,
+            scrub_block: 0,
+            filter: String::new(),
+            pinned: BTreeSet::new(),
+            pinned_only: false,
+            history_window: 100,
+            compare_from: 0,
+            compare_to: 0,
+            sort: DeltaSort::LargestChange,
+            breakdown_block: 0,
+            breakdown_account: None,
+            graph_block: 0,
+            suspect_threshold: 10,
+            export_path: export.as_ref().map_or_else(
+                || "monitor-export.csv".to_string(),
+                |p| p.display().to_string(),
+            ),
+            export_on_exit: export.is_some(),
+            export_status: None,
+        }
+    }
+
+    /// Accounts scored in the latest block, by name
+    fn accounts(&self) -> Vec<([u8; 32], String)> {
+        let mut accounts: Vec<_> = self
+            .error_data
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|d| (d.account, d.account_name.clone()))
+            .collect();
+        accounts.sort_by(|a, b| a.1.cmp(&b.1));
+        accounts
+    }
+
+    /// Whether an account passes the filter and the pins
+    fn shown(&self, account: &[u8; 32], account_name: &str) -> bool {
+        if self.pinned_only && !self.pinned.contains(account) {
+            return false;
+        }
+        let filter = self.filter.trim();
+        filter.is_empty()
+            || account_name.to_lowercase().contains(&filter.to_lowercase())
+            || ss58(account).starts_with(filter)
+    }
+
+    fn show_filter(&mut self, ui: &mut egui::Ui) {
+        // Accounts that can be pinned, any scored in the latest block
+        let known = self.accounts();
+
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("Name or SS58 prefix"));
+            ui.menu_button(format!("Pinned ({})", self.pinned.len()), |ui| {
+                for (account, name) in &known {
+                    let mut pinned = self.pinned.contains(account);
+                    if ui.checkbox(&mut pinned, name).changed() {
+                        if pinned {
+                            self.pinned.insert(*account);
+                        } else {
+                            self.pinned.remove(account);
+                        }
+                    }
+                }
+            });
+            ui.checkbox(&mut self.pinned_only, "Pinned only");
+        });
+    }
+
+    fn show_live(&mut self, ui: &mut egui::Ui) {
+        let latest = *self.block_number.lock().unwrap();
+
+        // Scrubbing to a past block stops following the head until it is followed again
+        ui.horizontal(|ui| {
+            let following = self.follow_head;
+            ui.checkbox(&mut self.follow_head, "Follow head");
+            if following && !self.follow_head {
+                let _ = self.scrub_tx.send(self.scrub_block);
+            }
+            let block = self.scrub_block;
+            ui.add(egui::Slider::new(&mut self.scrub_block, 3..=latest.max(3)).text("Block"));
+            ui.add(egui::DragValue::new(&mut self.scrub_block));
+            if self.scrub_block != block {
+                self.follow_head = false;
+                let _ = self.scrub_tx.send(self.scrub_block);
+            }
+        });
+        if self.follow_head {
+            self.scrub_block = latest;
+            self.show_scores(ui, latest, self.error_data.lock().unwrap().clone());
+            return;
+        }
+
+        let scrub = self.scrub.lock().unwrap().clone();
+        match scrub {
+            Scrub::Idle => {
+                ui.label("Select a block");
+            }
+            Scrub::Loading => {
+                ui.label("Loading...");
+            }
+            Scrub::Failed(e) => {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!("Loading the block failed: {}", e),
+                );
+            }
+            Scrub::Done {
+                block,
+                scores,
+                reports,
+            } => {
+                // Half of the height for the scores, the rest for the RSSI reports under them
+                ui.allocate_ui(
+                    egui::vec2(ui.available_width(), ui.available_height() / 2.0),
+                    |ui| self.show_scores(ui, block, scores),
+                );
+                ui.label(format!("RSSI reports published in block #{}", block));
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("rssi_grid")
+                        .striped(true)
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            ui.strong("Reporter");
+                            ui.strong("Neighbor");
+                            ui.strong("RSSI");
+                            ui.end_row();
+
+                            for r in &reports {
+                                ui.label(&r.reporter_name);
+                                ui.label(&r.neighbor_name);
+                                ui.label(r.rssi.to_string());
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
         }
     }
+
+    /// Bar chart of the scores of a block
+    fn show_scores(&self, ui: &mut egui::Ui, block_num: u32, mut data: Vec<ErrorData>) {
+        ui.heading(format!("Trust Score Error Analysis - Block #{}", block_num));
+        ui.add_space(10.0);
+
+        if data.is_empty() {
+            ui.label("Waiting for data...");
+            return;
+        }
+
+        // Pinned accounts come first, the rest keep their order by name
+        data.retain(|d| self.shown(&d.account, &d.account_name));
+        data.sort_by_key(|d| !self.pinned.contains(&d.account));
+        if data.is_empty() {
+            ui.label("No account matches the filter");
+            return;
+        }
+
+        // Accounts with an active alert are highlighted
+        let active = self.alerts.lock().unwrap().active.clone();
+        if !active.is_empty() {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!(
+                    "⚠ Alert: {}",
+                    active.iter().cloned().collect::<Vec<_>>().join(", ")
+                ),
+            );
+        }
+
+        // Get available space for the plot
+        let available_height = ui.available_height();
+
+        // Create bar chart with custom axis formatter for X-axis labels
+        let num_bars = data.len();
+
+        // Clone data for the formatter closure
+        let data_for_formatter = data.clone();
+
+        Plot::new("error_plot")
+            .legend(Legend::default())
+            .show_axes(true)
+            .allow_zoom(false)
+            .allow_drag(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .height(available_height)
+            .x_axis_formatter(move |mark, _range| {
+                let index = mark.value as usize;
+                if index < data_for_formatter.len() {
+                    data_for_formatter[index].account_name.clone()
+                } else {
+                    String::new()
+                }
+            })
+            .show(ui, |plot_ui| {
+                // Set fixed Y-axis bounds from 0 to 10
+                let x_min = -0.5;
+                let x_max = num_bars as f64 - 0.5;
+                plot_ui.set_plot_bounds(PlotBounds::from_min_max([x_min, 0.0], [x_max, 10.0]));
+
+                let bars: Vec<Bar> = data
+                    .iter()
+                    .enumerate()
+                    .map(|(i, d)| {
+                        let bar = Bar::new(i as f64, d.error_value as f64)
+                            .width(0.7)
+                            .name(&d.account_name);
+                        if active.contains(&d.account_name) {
+                            bar.fill(egui::Color32::RED)
+                        } else {
+                            bar
+                        }
+                    })
+                    .collect();
+
+                let chart = BarChart::new(bars).color(egui::Color32::from_rgb(100, 150, 250));
+                plot_ui.bar_chart(chart);
+            });
+    }
+
+    fn show_history(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Trust Score History");
+        ui.add_space(10.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Window (blocks)");
+            ui.add(egui::Slider::new(
+                &mut self.history_window,
+                10..=HISTORY_CAPACITY as u32,
+            ));
+        });
+        ui.add_space(10.0);
+
+        // One line per account over the blocks in the window
+        let mut lines: BTreeMap<String, Vec<[f64; 2]>> = BTreeMap::new();
+        {
+            let history = self.history.lock().unwrap();
+            let Some(&(latest, _)) = history.back() else {
+                ui.label("Waiting for data...");
+                return;
+            };
+            let first = latest.saturating_sub(self.history_window - 1);
+            for (block, scores) in history.iter().filter(|(block, _)| *block >= first) {
+                for d in scores
+                    .iter()
+                    .filter(|d| self.shown(&d.account, &d.account_name))
+                {
+                    lines
+                        .entry(d.account_name.clone())
+                        .or_default()
+                        .push([*block as f64, d.error_value as f64]);
+                }
+            }
+        }
+
+        Plot::new("history_plot")
+            .legend(Legend::default())
+            .show_axes(true)
+            .x_axis_label("Block")
+            .y_axis_label("Error")
+            .include_y(0.0)
+            .height(ui.available_height())
+            .show(ui, |plot_ui| {
+                for (account_name, points) in lines {
+                    plot_ui.line(Line::new(PlotPoints::from(points)).name(account_name));
+                }
+            });
+    }
+
+    fn show_compare(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Trust Score Comparison");
+        ui.add_space(10.0);
+
+        // Block selection
+        ui.horizontal(|ui| {
+            ui.label("From block");
+            ui.add(egui::DragValue::new(&mut self.compare_from));
+            ui.label("To block");
+            ui.add(egui::DragValue::new(&mut self.compare_to));
+            if ui.button("Compare").clicked() {
+                let _ = self.compare_tx.send((self.compare_from, self.compare_to));
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Sort by");
+            ui.selectable_value(&mut self.sort, DeltaSort::LargestChange, "Largest change");
+            ui.selectable_value(&mut self.sort, DeltaSort::MostImproved, "Most improved");
+            ui.selectable_value(&mut self.sort, DeltaSort::MostWorsened, "Most worsened");
+        });
+        ui.add_space(10.0);
+
+        let comparison = self.comparison.lock().unwrap().clone();
+        let (from, to, mut diffs) = match comparison {
+            Comparison::Idle => {
+                ui.label("Select two blocks and press Compare");
+                return;
+            }
+            Comparison::Loading => {
+                ui.label("Loading...");
+                return;
+            }
+            Comparison::Failed(e) => {
+                ui.colored_label(egui::Color32::RED, format!("Comparison failed: {}", e));
+                return;
+            }
+            Comparison::Done { from, to, diffs } => (
+                from,
+                to,
+                diffs
+                    .into_iter()
+                    .filter(|d| self.shown(&d.account, &d.account_name))
+                    .collect::<Vec<_>>(),
+            ),
+        };
+
+        // Lower error is better, so a negative delta is an improvement.
+        // Accounts that were not scored in both blocks go last.
+        match self.sort {
+            DeltaSort::LargestChange => {
+                diffs.sort_by_key(|d| (d.delta().is_none(), Reverse(d.delta().map(i32::abs))))
+            }
+            DeltaSort::MostImproved => diffs.sort_by_key(|d| (d.delta().is_none(), d.delta())),
+            DeltaSort::MostWorsened => {
+                diffs.sort_by_key(|d| (d.delta().is_none(), Reverse(d.delta())))
+            }
+        }
+
+        egui::Grid::new("diff_grid")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                ui.strong("Account");
+                ui.strong(format!("Block #{}", from));
+                ui.strong(format!("Block #{}", to));
+                ui.strong("Change");
+                ui.end_row();
+
+                for d in &diffs {
+                    ui.label(&d.account_name);
+                    ui.label(format_score(d.before));
+                    ui.label(format_score(d.after));
+                    match d.delta() {
+                        Some(delta) if delta < 0 => {
+                            ui.colored_label(egui::Color32::GREEN, delta.to_string())
+                        }
+                        Some(delta) if delta > 0 => {
+                            ui.colored_label(egui::Color32::RED, format!("+{}", delta))
+                        }
+                        Some(_) => ui.label("0"),
+                        None => ui.label("-"),
+                    };
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+impl TrustScoreApp {
+    fn show_breakdown(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Trust Score Breakdown");
+        ui.add_space(10.0);
+
+        // Account and block selection
+        ui.horizontal(|ui| {
+            ui.label("Account");
+            egui::ComboBox::from_id_salt("breakdown_account")
+                .selected_text(
+                    self.breakdown_account
+                        .as_ref()
+                        .map_or("Select", |(_, name)| name.as_str()),
+                )
+                .show_ui(ui, |ui| {
+                    for account in self.accounts() {
+                        let name = account.1.clone();
+                        ui.selectable_value(&mut self.breakdown_account, Some(account), name);
+                    }
+                });
+            ui.label("Block");
+            ui.add(egui::DragValue::new(&mut self.breakdown_block));
+            if ui.button("Latest").clicked() {
+                self.breakdown_block = *self.block_number.lock().unwrap();
+            }
+            if let Some(&(account, _)) = self.breakdown_account.as_ref() {
+                if ui.button("Show").clicked() {
+                    let _ = self.breakdown_tx.send((self.breakdown_block, account));
+                }
+            }
+        });
+        ui.add_space(10.0);
+
+        let breakdown = self.breakdown.lock().unwrap().clone();
+        let (block, account_name, mut pairs) = match breakdown {
+            Breakdown::Idle => {
+                ui.label("Select an account and a block and press Show");
+                return;
+            }
+            Breakdown::Loading => {
+                ui.label("Loading...");
+                return;
+            }
+            Breakdown::Failed(e) => {
+                ui.colored_label(egui::Color32::RED, format!("Breakdown failed: {}", e));
+                return;
+            }
+            Breakdown::Done {
+                block,
+                account_name,
+                pairs,
+            } => (block, account_name, pairs),
+        };
+
+        if pairs.is_empty() {
+            ui.label(format!(
+                "No reports about {} in block #{}",
+                account_name, block
+            ));
+            return;
+        }
+
+        // Reports with the largest error drag the score the most
+        pairs.sort_by_key(|p| Reverse(p.error.unsigned_abs()));
+
+        ui.label(format!(
+            "Reports about {} in block #{}",
+            account_name, block
+        ));
+        egui::Grid::new("breakdown_grid")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                ui.strong("Reporter");
+                ui.strong("Measured");
+                ui.strong("Estimated");
+                ui.strong("Error");
+                ui.end_row();
+
+                for p in &pairs {
+                    ui.label(&p.reporter_name);
+                    ui.label(p.measured.to_string());
+                    ui.label(p.estimated.to_string());
+                    ui.label(p.error.to_string());
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+impl TrustScoreApp {
+    fn show_graph(&mut self, ui: &mut egui::Ui) {
+        ui.heading("RSSI Link Graph");
+        ui.add_space(10.0);
+
+        // Block selection
+        ui.horizontal(|ui| {
+            ui.label("Block");
+            ui.add(egui::DragValue::new(&mut self.graph_block));
+            if ui.button("Latest").clicked() {
+                self.graph_block = *self.block_number.lock().unwrap();
+            }
+            if ui.button("Show").clicked() {
+                let _ = self.graph_tx.send(self.graph_block);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Error:");
+            for (label, error) in [("< 3 dB", 0), ("< 6 dB", 3), ("≥ 6 dB", 6)] {
+                ui.colored_label(error_color(error), label);
+            }
+        });
+        ui.add_space(10.0);
+
+        let graph = self.graph.lock().unwrap().clone();
+        let (block, nodes, edges) = match graph {
+            Graph::Idle => {
+                ui.label("Select a block and press Show");
+                return;
+            }
+            Graph::Loading => {
+                ui.label("Loading...");
+                return;
+            }
+            Graph::Failed(e) => {
+                ui.colored_label(egui::Color32::RED, format!("Graph failed: {}", e));
+                return;
+            }
+            Graph::Done {
+                block,
+                nodes,
+                edges,
+            } => (block, nodes, edges),
+        };
+
+        // Links are only drawn between accounts that are both shown
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .filter(|n| self.shown(&n.account, &n.account_name))
+            .collect();
+
+        if nodes.is_empty() {
+            ui.label(format!("No reports in block #{}", block));
+            return;
+        }
+
+        let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::hover());
+        let rect = response.rect;
+        let radius = 0.4 * rect.width().min(rect.height());
+
+        // Accounts evenly spaced on a circle, in the order of their names
+        let position = |account: &[u8; 32]| {
+            let index = nodes.iter().position(|n| &n.account == account)?;
+            let angle = std::f32::consts::TAU * index as f32 / nodes.len() as f32;
+            Some(rect.center() + radius * egui::vec2(angle.cos(), angle.sin()))
+        };
+
+        // Both directions of a pair are drawn side by side, each shifted to its right
+        for edge in &edges {
+            let (Some(from), Some(to)) = (position(&edge.reporter), position(&edge.subject)) else {
+                continue;
+            };
+            let direction = (to - from).normalized();
+            let offset = 4.0 * egui::vec2(-direction.y, direction.x);
+            painter.arrow(
+                from + offset + 20.0 * direction,
+                (to - from) - 40.0 * direction,
+                egui::Stroke::new(3.0, error_color(edge.error)),
+            );
+        }
+
+        let font = egui::FontId::proportional(20.0);
+        for node in &nodes {
+            let Some(center) = position(&node.account) else {
+                continue;
+            };
+            painter.circle(
+                center,
+                16.0,
+                egui::Color32::from_rgb(100, 150, 250),
+                egui::Stroke::new(2.0, egui::Color32::WHITE),
+            );
+            painter.text(
+                center + egui::vec2(0.0, 20.0),
+                egui::Align2::CENTER_TOP,
+                format!("{} ({})", node.account_name, format_score(node.score)),
+                font.clone(),
+                ui.visuals().text_color(),
+            );
+        }
+    }
+}
+
+impl TrustScoreApp {
+    fn show_map(&mut self, ui: &mut egui::Ui) {
+        let block_num = *self.block_number.lock().unwrap();
+        ui.heading(format!("Node Map - Block #{}", block_num));
+        ui.add_space(10.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Suspect above");
+            ui.add(egui::DragValue::new(&mut self.suspect_threshold));
+        });
+        let suspect_threshold = self.suspect_threshold;
+        ui.horizontal(|ui| {
+            ui.colored_label(score_color(Some(0), suspect_threshold), "Trusted");
+            ui.colored_label(
+                score_color(Some(suspect_threshold), suspect_threshold),
+                "Close to the threshold",
+            );
+            ui.colored_label(
+                score_color(Some(suspect_threshold + 1), suspect_threshold),
+                "◆ Suspected",
+            );
+            ui.colored_label(score_color(None, suspect_threshold), "Unscored");
+        });
+        ui.add_space(10.0);
+
+        let nodes: Vec<MapNode> = self
+            .map_nodes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| self.shown(&n.account, &n.account_name))
+            .cloned()
+            .collect();
+        if nodes.is_empty() {
+            ui.label("Waiting for data...");
+            return;
+        }
+
+        // Plot in meters east and north of the nodes' center, so distances are true to scale
+        let center_latitude = nodes.iter().map(|n| n.latitude).sum::<f64>() / nodes.len() as f64;
+        let center_longitude = nodes.iter().map(|n| n.longitude).sum::<f64>() / nodes.len() as f64;
+        let to_meters = |node: &MapNode| {
+            const EARTH_RADIUS_M: f64 = 6_371_000.0;
+            [
+                (node.longitude - center_longitude).to_radians()
+                    * EARTH_RADIUS_M
+                    * center_latitude.to_radians().cos(),
+                (node.latitude - center_latitude).to_radians() * EARTH_RADIUS_M,
+            ]
+        };
+
+        Plot::new("map_plot")
+            .data_aspect(1.0)
+            .show_axes(true)
+            .x_axis_label("East (m)")
+            .y_axis_label("North (m)")
+            .height(ui.available_height())
+            .show(ui, |plot_ui| {
+                for node in &nodes {
+                    let [x, y] = to_meters(node);
+                    let suspected = node.score.is_some_and(|s| s > suspect_threshold);
+                    plot_ui.points(
+                        Points::new(vec![[x, y]])
+                            .shape(if suspected {
+                                MarkerShape::Diamond
+                            } else {
+                                MarkerShape::Circle
+                            })
+                            .radius(if suspected { 14.0 } else { 10.0 })
+                            .filled(true)
+                            .color(score_color(node.score, suspect_threshold)),
+                    );
+                    plot_ui.text(
+                        Text::new(
+                            PlotPoint::new(x, y),
+                            format!("{} ({})", node.account_name, format_score(node.score)),
+                        )
+                        .anchor(egui::Align2::CENTER_TOP),
+                    );
+                }
+            });
+    }
+}
+
+impl TrustScoreApp {
+    /// Export the score history and the per-pair errors of the graph to the export path
+    fn export(&self) -> Result<Vec<PathBuf>, std::io::Error> {
+        let history = self.history.lock().unwrap().clone();
+        let graph = self.graph.lock().unwrap().clone();
+        export::export(PathBuf::from(&self.export_path).as_path(), &history, &graph)
+    }
+
+    fn show_export(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Export to");
+            ui.text_edit_singleline(&mut self.export_path);
+            if ui.button("Export").clicked() {
+                self.export_status = Some(match self.export() {
+                    Ok(files) => format!(
+                        "Wrote {}",
+                        files
+                            .iter()
+                            .map(|f| f.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    Err(e) => format!("Export failed: {}", e),
+                });
+            }
+            if let Some(status) = &self.export_status {
+                ui.label(status);
+            }
+        });
+    }
+}
+
+impl TrustScoreApp {
+    fn show_alerts(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Alerts");
+        ui.add_space(10.0);
+
+        let accounts = self.accounts();
+        {
+            let mut config = self.alert_config.lock().unwrap();
+            ui.horizontal(|ui| {
+                ui.label("Alert above");
+                ui.add(egui::DragValue::new(&mut config.threshold));
+                ui.label("for");
+                ui.add(egui::DragValue::new(&mut config.blocks).range(1..=HISTORY_CAPACITY));
+                ui.label("blocks");
+            });
+            ui.label(match &config.webhook {
+                Some(webhook) => format!("Webhook: {}", webhook),
+                None => "Webhook: none, set ALERT_WEBHOOK_URL to POST alerts".to_string(),
+            });
+            ui.add_space(10.0);
+
+            // Accounts can replace the global threshold with their own
+            egui::Grid::new("alert_thresholds")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (_, account_name) in &accounts {
+                        let mut own = config.per_node.contains_key(account_name);
+                        ui.checkbox(&mut own, account_name);
+                        if own {
+                            let threshold = config.threshold;
+                            let threshold = config
+                                .per_node
+                                .entry(account_name.clone())
+                                .or_insert(threshold);
+                            ui.add(egui::DragValue::new(threshold));
+                        } else {
+                            config.per_node.remove(account_name);
+                            ui.label("global");
+                        }
+                        ui.end_row();
+                    }
+                });
+        }
+        ui.add_space(10.0);
+
+        ui.label("Fired alerts, latest first:");
+        let alerts = self.alerts.lock().unwrap();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for alert in alerts.log.iter().rev() {
+                let color = if alerts.active.contains(&alert.account_name) {
+                    egui::Color32::RED
+                } else {
+                    ui.visuals().text_color()
+                };
+                ui.colored_label(
+                    color,
+                    format!(
+                        "#{} {}: {} above {}",
+                        alert.block, alert.account_name, alert.score, alert.threshold
+                    ),
+                );
+            }
+        });
+    }
+}
+
+/// Color of a node by its score, red once it is suspected and gray when unscored
+fn score_color(score: Option<i16>, suspect_threshold: i16) -> egui::Color32 {
+    match score {
+        None => egui::Color32::GRAY,
+        Some(score) if score > suspect_threshold => egui::Color32::from_rgb(231, 76, 60),
+        Some(score) if score > suspect_threshold / 2 => egui::Color32::from_rgb(241, 196, 15),
+        Some(_) => egui::Color32::from_rgb(46, 204, 113),
+    }
+}
+
+/// Color of a link by the size of its error, green when the report matches the estimate
+fn error_color(error: i16) -> egui::Color32 {
+    match error.unsigned_abs() {
+        0..=2 => egui::Color32::from_rgb(46, 204, 113),
+        3..=5 => egui::Color32::from_rgb(241, 196, 15),
+        _ => egui::Color32::from_rgb(231, 76, 60),
+    }
+}
+
+fn format_score(score: Option<i16>) -> String {
+    score.map_or_else(|| "-".to_string(), |s| s.to_string())
 }
 
 impl eframe::App for TrustScoreApp {
@@ -50,85 +916,117 @@ impl eframe::App for TrustScoreApp {
         );
         ctx.set_style(style);
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let block_num = *self.block_number.lock().unwrap();
-            ui.heading(format!("Trust Score Error Analysis - Block #{}", block_num));
-            ui.add_space(10.0);
+        egui::TopBottomPanel::top("mode_panel").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.mode, Mode::Live, "Live");
+                ui.selectable_value(&mut self.mode, Mode::History, "History");
+                ui.selectable_value(&mut self.mode, Mode::Compare, "Compare");
+                ui.selectable_value(&mut self.mode, Mode::Breakdown, "Breakdown");
+                ui.selectable_value(&mut self.mode, Mode::Graph, "Graph");
+                ui.selectable_value(&mut self.mode, Mode::Map, "Map");
+                ui.selectable_value(&mut self.mode, Mode::Alerts, "Alerts");
+            });
+            self.show_filter(ui);
+            self.show_export(ui);
+        });
 
-            let data = self.error_data.lock().unwrap().clone();
+        let mode = self.mode;
+        egui::CentralPanel::default().show(ctx, |ui| match mode {
+            Mode::Live => self.show_live(ui),
+            Mode::History => self.show_history(ui),
+            Mode::Compare => self.show_compare(ui),
+            Mode::Breakdown => self.show_breakdown(ui),
+            Mode::Graph => self.show_graph(ui),
+            Mode::Map => self.show_map(ui),
+            Mode::Alerts => self.show_alerts(ui),
+        });
+    }
 
-            if data.is_empty() {
-                ui.label("Waiting for data...");
-                return;
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if !self.export_on_exit {
+            return;
+        }
+        match self.export() {
+            Ok(files) => {
+                for file in files {
+                    println!("Exported {}", file.display());
+                }
             }
-
-            // Get available space for the plot
-            let available_height = ui.available_height();
-
-            // Create bar chart with custom axis formatter for X-axis labels
-            let num_bars = data.len();
-
-            // Clone data for the formatter closure
-            let data_for_formatter = data.clone();
-
-            Plot::new("error_plot")
-                .legend(Legend::default())
-                .show_axes(true)
-                .allow_zoom(false)
-                .allow_drag(false)
-                .allow_scroll(false)
-                .allow_boxed_zoom(false)
-                .height(available_height)
-                .x_axis_formatter(move |mark, _range| {
-                    let index = mark.value as usize;
-                    if index < data_for_formatter.len() {
-                        data_for_formatter[index].account_name.clone()
-                    } else {
-                        String::new()
-                    }
-                })
-                .show(ui, |plot_ui| {
-                    // Set fixed Y-axis bounds from 0 to 10
-                    let x_min = -0.5;
-                    let x_max = num_bars as f64 - 0.5;
-                    plot_ui.set_plot_bounds(PlotBounds::from_min_max([x_min, 0.0], [x_max, 10.0]));
-
-                    let bars: Vec<Bar> = data
-                        .iter()
-                        .enumerate()
-                        .map(|(i, d)| {
-                            Bar::new(i as f64, d.error_value as f64)
-                                .width(0.7)
-                                .name(&d.account_name)
-                        })
-                        .collect();
-
-                    let chart = BarChart::new(bars).color(egui::Color32::from_rgb(100, 150, 250));
-                    plot_ui.bar_chart(chart);
-                });
-        });
+            Err(e) => eprintln!("Export failed: {}", e),
+        }
     }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
     // Load environment variables from .env file
     dotenvy::dotenv()?;
 
     // Shared state for error data and block number
     let error_data = Arc::new(Mutex::new(Vec::new()));
     let block_number = Arc::new(Mutex::new(0u32));
+    let history = Arc::new(Mutex::new(ScoreHistory::new()));
+    let comparison = Arc::new(Mutex::new(Comparison::default()));
+    let (compare_tx, compare_rx) = tokio::sync::mpsc::unbounded_channel();
+    let breakdown = Arc::new(Mutex::new(Breakdown::default()));
+    let (breakdown_tx, breakdown_rx) = tokio::sync::mpsc::unbounded_channel();
+    let graph = Arc::new(Mutex::new(Graph::default()));
+    let (graph_tx, graph_rx) = tokio::sync::mpsc::unbounded_channel();
+    let map_nodes = Arc::new(Mutex::new(Vec::new()));
+    let alert_config = Arc::new(Mutex::new(AlertConfig::from_env()?));
+    let alerts = Arc::new(Mutex::new(Alerts::default()));
+    let scrub = Arc::new(Mutex::new(Scrub::default()));
+    let (scrub_tx, scrub_rx) = tokio::sync::mpsc::unbounded_channel();
 
     // Clone for the blockchain thread
     let error_data_clone = Arc::clone(&error_data);
     let block_number_clone = Arc::clone(&block_number);
+    let history_clone = Arc::clone(&history);
+    let comparison_clone = Arc::clone(&comparison);
+    let breakdown_clone = Arc::clone(&breakdown);
+    let graph_clone = Arc::clone(&graph);
+    let map_nodes_clone = Arc::clone(&map_nodes);
+    let alert_config_clone = Arc::clone(&alert_config);
+    let alerts_clone = Arc::clone(&alerts);
+    let scrub_clone = Arc::clone(&scrub);
 
     // Spawn a thread to handle blockchain data fetching
     std::thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            if let Err(e) = score::blockchain_task(error_data_clone, block_number_clone).await {
+            let (live, compare, breakdown, graph, map, scrub) = tokio::join!(
+                score::blockchain_task(
+                    error_data_clone,
+                    block_number_clone,
+                    history_clone,
+                    alert_config_clone,
+                    alerts_clone,
+                ),
+                diff::comparison_task(compare_rx, comparison_clone),
+                breakdown::breakdown_task(breakdown_rx, breakdown_clone),
+                graph::graph_task(graph_rx, graph_clone),
+                map::map_task(map_nodes_clone),
+                scrub::scrub_task(scrub_rx, scrub_clone),
+            );
+            if let Err(e) = live {
                 eprintln!("Blockchain task error: {}", e);
             }
+            if let Err(e) = compare {
+                eprintln!("Comparison task error: {}", e);
+            }
+            if let Err(e) = breakdown {
+                eprintln!("Breakdown task error: {}", e);
+            }
+            if let Err(e) = graph {
+                eprintln!("Graph task error: {}", e);
+            }
+            if let Err(e) = map {
+                eprintln!("Map task error: {}", e);
+            }
+            if let Err(e) = scrub {
+                eprintln!("Scrub task error: {}", e);
+            }
         });
     });
 
@@ -143,7 +1041,25 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     eframe::run_native(
         "Trust Score Monitor",
         options,
-        Box::new(|_cc| Ok(Box::new(TrustScoreApp::new(error_data, block_number)))),
+        Box::new(|_cc| {
+            Ok(Box::new(TrustScoreApp::new(
+                error_data,
+                block_number,
+                history,
+                comparison,
+                compare_tx,
+                breakdown,
+                breakdown_tx,
+                graph,
+                graph_tx,
+                map_nodes,
+                alert_config,
+                alerts,
+                scrub,
+                scrub_tx,
+                cli.export,
+            )))
+        }),
     )?;
 
     Ok(())
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use subxt::{OnlineClient, SubstrateConfig};
+
+use crate::score::{get_account_names, name_of, substrate};
+
+/// A registered node with its location and latest trust score
+#[derive(Clone)]
+pub struct MapNode {
+    pub account: [u8; 32],
+    pub account_name: String,
+    /// Registered coordinates in degrees
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Trust score of the block, none if nobody reported about the node
+    pub score: Option<i16>,
+}
+
+/// Fetch the registered location and trust score of every node at a block
+async fn nodes_at(
+    api: &OnlineClient<SubstrateConfig>,
+    block: subxt::utils::H256,
+    number: u32,
+) -> Result<Vec<MapNode>, Box<dyn std::error::Error>> {
+    let account_name = get_account_names(api).await?;
+
+    let rpc_call = substrate::apis()
+        .trust_score_api()
+        .calculate_trust_scores(number);
+    let scores: HashMap<[u8; 32], i16> = api
+        .runtime_api()
+        .at(block)
+        .call(rpc_call)
+        .await?
+        .into_iter()
+        .map(|(account_id, score)| (account_id.0, score))
+        .collect();
+
+    let query = substrate::storage().proof_of_location().account_data_iter();
+    let mut account_data = api.storage().at(block).iter(query).await?;
+
+    let mut nodes = Vec::new();
+    while let Some(data) = account_data.next().await {
+        let data = data?;
+        // The account ID is the last 32 bytes of the key, after the blake2_128 hash
+        let len = data.key_bytes.len();
+        let account_id: [u8; 32] = data.key_bytes[len - 32..].try_into()?;
+        nodes.push(MapNode {
+            account: account_id,
+            account_name: name_of(&account_name, &account_id),
+            latitude: data.value.latitude as f64 / 1_000_000.0,
+            longitude: data.value.longitude as f64 / 1_000_000.0,
+            score: scores.get(&account_id).copied(),
+        });
+    }
+    nodes.sort_by(|a, b| a.account_name.cmp(&b.account_name));
+
+    Ok(nodes)
+}
+
+/// Keep the registered nodes and their scores up to date with every finalized block
+pub async fn map_task(nodes: Arc<Mutex<Vec<MapNode>>>) -> Result<(), Box<dyn std::error::Error>> {
+    // Get RPC URL from environment variable or use default
+    let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| "ws://127.0.0.1:9944".into());
+    let api = OnlineClient::<SubstrateConfig>::from_url(&rpc_url).await?;
+
+    let mut blocks_sub = api.blocks().subscribe_finalized().await?;
+    while let Some(Ok(block)) = blocks_sub.next().await {
+        if block.number() < 3 {
+            continue;
+        }
+
+        match nodes_at(&api, block.hash(), block.number()).await {
+            Ok(new_nodes) => *nodes.lock().unwrap() = new_nodes,
+            Err(e) => eprintln!(
+                "Failed to fetch the node locations of block {}: {}",
+                block.number(),
+                e
+            ),
+        }
+    }
+
+    Ok(())
+}
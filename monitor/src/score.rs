@@ -1,29 +1,84 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 
 use subxt::config::substrate::AccountId32;
 use subxt::{OnlineClient, SubstrateConfig};
-use subxt_signer::sr25519::dev;
+
+use crate::alert::{self, AlertConfig, Alerts};
 
 // This creates a complete, type-safe API for interacting with the runtime.
 #[subxt::subxt(runtime_metadata_path = "../metadata.scale")]
 pub mod substrate {}
 
-fn get_account_names() -> HashMap<[u8; 32], &'static str> {
-    let mut names = HashMap::new();
+/// SS58 address of an account, with the generic Substrate prefix
+pub fn ss58(account: &[u8; 32]) -> String {
+    AccountId32(*account).to_string()
+}
 
-    names.insert(dev::alice().public_key().0, "Alice");
-    names.insert(dev::bob().public_key().0, "Bob");
-    names.insert(dev::charlie().public_key().0, "Charlie");
-    names.insert(dev::dave().public_key().0, "Dave");
-    names.insert(dev::eve().public_key().0, "Eve");
-    names.insert(dev::ferdie().public_key().0, "Ferdie");
+/// Shortened SS58 address, naming accounts the chain has no name for
+pub fn short_ss58(account: &[u8; 32]) -> String {
+    let address = ss58(account);
+    format!("{}…{}", &address[..6], &address[address.len() - 4..])
+}
+
+/// Name accounts after the server they registered with `set_server_config`
+///
+/// Servers shared by several accounts, as with a simulator, get the shortened SS58 address
+/// appended. The runtime has no identity pallet, so accounts without a server have no name.
+pub async fn get_account_names(
+    api: &OnlineClient<SubstrateConfig>,
+) -> Result<HashMap<[u8; 32], String>, Box<dyn std::error::Error>> {
+    let query = substrate::storage()
+        .proof_of_location()
+        .server_config_iter();
+    let mut server_urls = api.storage().at_latest().await?.iter(query).await?;
+
+    let mut urls = HashMap::new();
+    while let Some(data) = server_urls.next().await {
+        let data = data?;
+        // The account ID is the last 32 bytes of the key, after the blake2_128 hash
+        let len = data.key_bytes.len();
+        let account_id: [u8; 32] = data.key_bytes[len - 32..].try_into()?;
+        urls.insert(
+            account_id,
+            String::from_utf8_lossy(&data.value.0).into_owned(),
+        );
+    }
 
+    let mut servers: HashMap<&str, usize> = HashMap::new();
+    for url in urls.values() {
+        *servers.entry(url).or_default() += 1;
+    }
+    Ok(urls
+        .iter()
+        .map(|(account, url)| {
+            let name = if servers[url.as_str()] > 1 {
+                format!("{} ({})", url, short_ss58(account))
+            } else {
+                url.clone()
+            };
+            (*account, name)
+        })
+        .collect())
+}
+
+/// Name of an account, its shortened SS58 address when it has none
+pub fn name_of(names: &HashMap<[u8; 32], String>, account: &[u8; 32]) -> String {
     names
+        .get(account)
+        .cloned()
+        .unwrap_or_else(|| short_ss58(account))
 }
 
+/// Most finalized blocks whose scores are kept for the history chart
+pub const HISTORY_CAPACITY: usize = 1000;
+
+/// Scores of the latest finalized blocks, oldest first
+pub type ScoreHistory = VecDeque<(u32, Vec<ErrorData>)>;
+
 #[derive(Clone)]
 pub struct ErrorData {
+    pub account: [u8; 32],
     pub account_name: String,
     pub error_value: i16,
 }
@@ -31,6 +86,9 @@ pub struct ErrorData {
 pub async fn blockchain_task(
     error_data: Arc<Mutex<Vec<ErrorData>>>,
     block_number: Arc<Mutex<u32>>,
+    history: Arc<Mutex<ScoreHistory>>,
+    alert_config: Arc<Mutex<AlertConfig>>,
+    alerts: Arc<Mutex<Alerts>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Get RPC URL from environment variable or use default
     let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| "ws://127.0.0.1:9944".into());
@@ -41,8 +99,6 @@ pub async fn blockchain_task(
 
     println!("Connected successfully!\n");
 
-    let account_name = get_account_names();
-
     let mut blocks_sub = api.blocks().subscribe_finalized().await?;
     while let Some(Ok(block)) = blocks_sub.next().await {
         if block.number() < 3 {
@@ -52,6 +108,9 @@ pub async fn blockchain_task(
         println!("New finalized block: {}", block.number());
         *block_number.lock().unwrap() = block.number();
 
+        // Names are looked up again with every block, as servers register over time
+        let account_name = get_account_names(&api).await?;
+
         let rpc_call = substrate::apis()
             .trust_score_api()
             .calculate_trust_scores(block.number());
@@ -64,10 +123,8 @@ pub async fn blockchain_task(
         let mut new_error_data: Vec<ErrorData> = scores
             .into_iter()
             .map(|(account_id, error_value)| ErrorData {
-                account_name: account_name
-                    .get(&account_id.0)
-                    .unwrap_or(&"Unknown")
-                    .to_string(),
+                account: account_id.0,
+                account_name: name_of(&account_name, &account_id.0),
                 error_value,
             })
             .collect();
@@ -77,11 +134,32 @@ pub async fn blockchain_task(
         for ErrorData {
             account_name,
             error_value,
+            ..
         } in &new_error_data
         {
             println!("{}: {}", account_name, error_value);
         }
 
+        // Keep a rolling history of the scores, dropping the oldest block once it is full
+        {
+            let mut history = history.lock().unwrap();
+            history.push_back((block.number(), new_error_data.clone()));
+            if history.len() > HISTORY_CAPACITY {
+                history.pop_front();
+            }
+        }
+
+        // Fire the alerts of the block without holding up the next one
+        let config = alert_config.lock().unwrap().clone();
+        let fired = alerts
+            .lock()
+            .unwrap()
+            .update(&config, block.number(), &new_error_data);
+        for alert in fired {
+            let webhook = config.webhook.clone();
+            tokio::spawn(async move { alert::notify(&alert, webhook.as_deref()).await });
+        }
+
         // Update the shared error data
         *error_data.lock().unwrap() = new_error_data;
     }
@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex};
+
+use subxt::backend::{legacy::LegacyRpcMethods, rpc::RpcClient};
+use subxt::{OnlineClient, SubstrateConfig};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::diff::scores_at;
+use crate::score::{get_account_names, name_of, substrate, ErrorData};
+
+/// RSSI one account measured of a neighbor in the selected block
+#[derive(Clone)]
+pub struct RssiReport {
+    pub reporter_name: String,
+    pub neighbor_name: String,
+    pub rssi: i16,
+}
+
+/// State of the past block shown in the UI instead of the live head
+#[derive(Clone, Default)]
+pub enum Scrub {
+    #[default]
+    Idle,
+    Loading,
+    Done {
+        block: u32,
+        scores: Vec<ErrorData>,
+        reports: Vec<RssiReport>,
+    },
+    Failed(String),
+}
+
+/// Fetch the RSSI reports published in a block from the `RssiData` storage of that block
+async fn reports_at(
+    api: &OnlineClient<SubstrateConfig>,
+    rpc: &LegacyRpcMethods<SubstrateConfig>,
+    block: u32,
+) -> Result<Vec<RssiReport>, Box<dyn std::error::Error>> {
+    let hash = rpc
+        .chain_get_block_hash(Some(block.into()))
+        .await?
+        .ok_or_else(|| format!("Block #{} not found", block))?;
+    let account_name = get_account_names(api).await?;
+    let name = |account: &[u8]| -> Result<String, std::array::TryFromSliceError> {
+        Ok(name_of(&account_name, &account.try_into()?))
+    };
+
+    let query = substrate::storage()
+        .proof_of_location()
+        .rssi_data_iter1(block);
+    let mut rssi_data = api.storage().at(hash).iter(query).await?;
+
+    let mut reports = Vec::new();
+    while let Some(data) = rssi_data.next().await {
+        let data = data?;
+        // The key ends with the neighbor and then the reporting account, each after its
+        // blake2_128 hash
+        let len = data.key_bytes.len();
+        reports.push(RssiReport {
+            reporter_name: name(&data.key_bytes[len - 32..])?,
+            neighbor_name: name(&data.key_bytes[len - 80..len - 48])?,
+            rssi: data.value,
+        });
+    }
+    reports.sort_by(|a, b| {
+        (&a.reporter_name, &a.neighbor_name).cmp(&(&b.reporter_name, &b.neighbor_name))
+    });
+
+    Ok(reports)
+}
+
+async fn scrub_to(
+    api: &OnlineClient<SubstrateConfig>,
+    rpc: &LegacyRpcMethods<SubstrateConfig>,
+    block: u32,
+) -> Result<(Vec<ErrorData>, Vec<RssiReport>), Box<dyn std::error::Error>> {
+    let account_name = get_account_names(api).await?;
+
+    let mut scores: Vec<ErrorData> = scores_at(api, rpc, block)
+        .await?
+        .into_iter()
+        .map(|(account_id, error_value)| ErrorData {
+            account: account_id.0,
+            account_name: name_of(&account_name, &account_id.0),
+            error_value,
+        })
+        .collect();
+    scores.sort_by_key(|x| x.account_name.clone());
+
+    Ok((scores, reports_at(api, rpc, block).await?))
+}
+
+/// Answer scrubbing requests from the UI until the request channel is closed
+pub async fn scrub_task(
+    mut requests: UnboundedReceiver<u32>,
+    scrub: Arc<Mutex<Scrub>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Get RPC URL from environment variable or use default
+    let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| "ws://127.0.0.1:9944".into());
+
+    // Block hashes are looked up through the legacy RPC methods
+    let rpc_client = RpcClient::from_url(&rpc_url).await?;
+    let rpc = LegacyRpcMethods::<SubstrateConfig>::new(rpc_client.clone());
+    let api = OnlineClient::<SubstrateConfig>::from_rpc_client(rpc_client).await?;
+
+    while let Some(mut block) = requests.recv().await {
+        // Dragging the slider queues a request per block, only the last one is worth answering
+        while let Ok(next) = requests.try_recv() {
+            block = next;
+        }
+
+        *scrub.lock().unwrap() = Scrub::Loading;
+
+        let result = match scrub_to(&api, &rpc, block).await {
+            Ok((scores, reports)) => Scrub::Done {
+                block,
+                scores,
+                reports,
+            },
+            Err(e) => Scrub::Failed(e.to_string()),
+        };
+
+        *scrub.lock().unwrap() = result;
+    }
+
+    Ok(())
+}
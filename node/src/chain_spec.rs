@@ -1,5 +1,8 @@
 use sc_service::ChainType;
-use solochain_template_runtime::WASM_BINARY;
+use solochain_template_runtime::{
+    genesis_config_presets::{POL_DEV_RUNTIME_PRESET, POL_LOCAL_RUNTIME_PRESET},
+    WASM_BINARY,
+};
 
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
 pub type ChainSpec = sc_service::GenericChainSpec;
@@ -27,3 +30,27 @@ pub fn local_chain_spec() -> Result<ChainSpec, String> {
     .with_genesis_config_preset_name(sp_genesis_builder::LOCAL_TESTNET_RUNTIME_PRESET)
     .build())
 }
+
+pub fn pol_development_chain_spec() -> Result<ChainSpec, String> {
+    Ok(ChainSpec::builder(
+        WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?,
+        None,
+    )
+    .with_name("Proof of Location Development")
+    .with_id("pol_dev")
+    .with_chain_type(ChainType::Development)
+    .with_genesis_config_preset_name(POL_DEV_RUNTIME_PRESET)
+    .build())
+}
+
+pub fn pol_local_chain_spec() -> Result<ChainSpec, String> {
+    Ok(ChainSpec::builder(
+        WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?,
+        None,
+    )
+    .with_name("Proof of Location Local Testnet")
+    .with_id("pol_local_testnet")
+    .with_chain_type(ChainType::Local)
+    .with_genesis_config_preset_name(POL_LOCAL_RUNTIME_PRESET)
+    .build())
+}
@@ -1,4 +1,5 @@
-use sc_cli::RunCmd;
+use sc_cli::{CryptoScheme, RunCmd};
+use std::path::PathBuf;
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +8,53 @@ pub struct Cli {
 
     #[clap(flatten)]
     pub run: RunCmd,
+
+    #[clap(flatten)]
+    pub location_key: LocationKeyParams,
+}
+
+/// Parameters for inserting the proof-of-location offchain worker key at startup.
+#[derive(Debug, Clone, clap::Args)]
+pub struct LocationKeyParams {
+    /// Secret URI of the key inserted into the keystore as the `loc!` key.
+    ///
+    /// The offchain worker signs its registration and RSSI transactions with this key.
+    #[arg(long, value_name = "SURI", conflicts_with = "location_key_file")]
+    pub location_key_suri: Option<String>,
+
+    /// File containing the secret URI of the `loc!` key.
+    #[arg(long, value_name = "PATH")]
+    pub location_key_file: Option<PathBuf>,
+
+    /// Crypto scheme of the `loc!` key.
+    ///
+    /// Must match the scheme of the runtime's `AuthorityId`, otherwise the offchain worker does
+    /// not find the key.
+    #[arg(
+        long,
+        value_name = "SCHEME",
+        value_enum,
+        ignore_case = true,
+        default_value = "sr25519"
+    )]
+    pub location_key_scheme: CryptoScheme,
+}
+
+impl LocationKeyParams {
+    /// Resolve the secret URI from either the command line or the given file.
+    pub fn suri(&self) -> sc_cli::Result<Option<String>> {
+        if let Some(suri) = &self.location_key_suri {
+            return Ok(Some(suri.clone()));
+        }
+
+        match &self.location_key_file {
+            Some(path) => {
+                let suri = std::fs::read_to_string(path)?;
+                Ok(Some(suri.trim().to_string()))
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 #[derive(Debug, clap::Subcommand)]
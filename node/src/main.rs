@@ -5,9 +5,16 @@ mod benchmarking;
 mod chain_spec;
 mod cli;
 mod command;
+mod metrics;
 mod rpc;
+#[cfg(not(feature = "runtime-api-only"))]
+mod rpc_rssi;
+#[cfg(not(feature = "runtime-api-only"))]
+mod rpc_run_summary;
+#[cfg(not(feature = "runtime-api-only"))]
 mod rpc_trust_score;
 mod service;
+mod telemetry;
 
 fn main() -> sc_cli::Result<()> {
     command::run()
@@ -0,0 +1,182 @@
+//! Prometheus metrics for proof-of-location pallet activity.
+//!
+//! Metrics are refreshed on every new best block by reading the pallet's storage and the offchain
+//! worker's failure counter. The trust scores are calculated by the runtime API, which scores
+//! every node, so they are only refreshed on every finalized block.
+
+use std::sync::Arc;
+
+use codec::{Decode, Encode};
+use futures::StreamExt;
+use pallet_proof_of_location::{rpc::TrustScoreApi, util::INSUFFICIENT_REPORTS};
+use sc_client_api::{BlockchainEvents, StorageProvider};
+use solochain_template_runtime::opaque::Block;
+use sp_api::ProvideRuntimeApi;
+use sp_core::{offchain::OffchainStorage, storage::StorageKey, twox_128};
+use sp_runtime::traits::Header;
+use substrate_prometheus_endpoint::{register, Gauge, PrometheusError, Registry, F64, U64};
+
+use crate::service::FullClient;
+
+/// Prometheus gauges describing the proof-of-location pallet.
+pub struct Metrics {
+    registered_nodes: Gauge<U64>,
+    rssi_reports: Gauge<U64>,
+    scored_nodes: Gauge<U64>,
+    average_trust_score: Gauge<F64>,
+    ocw_submission_failures: Gauge<U64>,
+}
+
+impl Metrics {
+    /// Create and register the metrics with the node's Prometheus registry.
+    pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+        Ok(Self {
+            registered_nodes: register(
+                Gauge::new(
+                    "substrate_pol_registered_nodes",
+                    "Number of nodes registered in the proof-of-location pallet",
+                )?,
+                registry,
+            )?,
+            rssi_reports: register(
+                Gauge::new(
+                    "substrate_pol_rssi_reports",
+                    "Number of RSSI reports stored in the best block",
+                )?,
+                registry,
+            )?,
+            scored_nodes: register(
+                Gauge::new(
+                    "substrate_pol_scored_nodes",
+                    "Number of nodes with enough RSSI reports to be scored in the last finalized block",
+                )?,
+                registry,
+            )?,
+            average_trust_score: register(
+                Gauge::new(
+                    "substrate_pol_average_trust_score",
+                    "Average trust score error of the nodes scored in the last finalized block, NaN if none",
+                )?,
+                registry,
+            )?,
+            ocw_submission_failures: register(
+                Gauge::new(
+                    "substrate_pol_ocw_submission_failures",
+                    "Number of transactions this node's offchain worker failed to submit",
+                )?,
+                registry,
+            )?,
+        })
+    }
+
+    /// Refresh the storage metrics for the best block with the given hash and number.
+    fn update<S: OffchainStorage>(
+        &self,
+        client: &FullClient,
+        hash: <Block as sp_runtime::traits::Block>::Hash,
+        number: u32,
+        offchain_db: Option<&S>,
+    ) -> sp_blockchain::Result<()> {
+        let nodes = registered_nodes(client, hash)?;
+        self.registered_nodes.set(nodes as u64);
+
+        let reports = rssi_reports(client, hash, number)?;
+        self.rssi_reports.set(reports as u64);
+
+        // Read the failure counter maintained by the offchain worker
+        if let Some(db) = offchain_db {
+            let failures = db
+                .get(
+                    sp_offchain::STORAGE_PREFIX,
+                    pallet_proof_of_location::SUBMISSION_FAILURES_KEY,
+                )
+                .and_then(|value| u32::decode(&mut &value[..]).ok())
+                .unwrap_or(0);
+            self.ocw_submission_failures.set(failures as u64);
+        }
+
+        Ok(())
+    }
+
+    /// Refresh the trust score metrics for the finalized block with the given hash and number.
+    ///
+    /// Nodes with too few reports are left out of the average, and blocks without any scored
+    /// node set it to NaN instead of keeping the last value.
+    fn update_scores(
+        &self,
+        client: &FullClient,
+        hash: <Block as sp_runtime::traits::Block>::Hash,
+        number: u32,
+    ) -> sp_blockchain::Result<()> {
+        let scores: Vec<f64> = client
+            .runtime_api()
+            .calculate_trust_scores(hash, number)?
+            .into_iter()
+            .filter(|(_, score)| *score != INSUFFICIENT_REPORTS)
+            .map(|(_, score)| score as f64)
+            .collect();
+
+        self.scored_nodes.set(scores.len() as u64);
+        self.average_trust_score
+            .set(scores.iter().sum::<f64>() / scores.len() as f64);
+
+        Ok(())
+    }
+}
+
+/// Count the nodes registered in the block with the given hash, from the counter of
+/// `AccountData`.
+pub fn registered_nodes(
+    client: &FullClient,
+    hash: <Block as sp_runtime::traits::Block>::Hash,
+) -> sp_blockchain::Result<usize> {
+    Ok(client
+        .storage(hash, &storage_prefix(b"CounterForAccountData"))?
+        .and_then(|value| u32::decode(&mut &value.0[..]).ok())
+        .unwrap_or(0) as usize)
+}
+
+/// Count the RSSI reports stored in the block with the given hash and number.
+pub fn rssi_reports(
+    client: &FullClient,
+    hash: <Block as sp_runtime::traits::Block>::Hash,
+    number: u32,
+) -> sp_blockchain::Result<usize> {
+    // Block number is the first, `Identity` hashed key
+    let mut rssi_prefix = storage_prefix(b"RssiData");
+    rssi_prefix.0.extend(number.encode());
+    Ok(client.storage_keys(hash, Some(&rssi_prefix), None)?.count())
+}
+
+/// Storage key prefix of a `ProofOfLocation` storage item.
+fn storage_prefix(item: &[u8]) -> StorageKey {
+    StorageKey([twox_128(b"ProofOfLocation"), twox_128(item)].concat())
+}
+
+/// Update the metrics on every new best block, and the trust scores on every finalized block,
+/// until the notification streams end.
+pub async fn run<S: OffchainStorage>(
+    client: Arc<FullClient>,
+    offchain_db: Option<S>,
+    metrics: Metrics,
+) {
+    let imports = client
+        .import_notification_stream()
+        .filter(|notification| futures::future::ready(notification.is_new_best))
+        .map(|notification| (false, notification.hash, *notification.header.number()));
+    let finality = client
+        .finality_notification_stream()
+        .map(|notification| (true, notification.hash, *notification.header.number()));
+    let mut notifications = futures::stream::select(imports, finality);
+
+    while let Some((finalized, hash, number)) = notifications.next().await {
+        let result = if finalized {
+            metrics.update_scores(&client, hash, number)
+        } else {
+            metrics.update(&client, hash, number, offchain_db.as_ref())
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to update proof-of-location metrics: {}", e);
+        }
+    }
+}
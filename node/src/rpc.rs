@@ -8,48 +8,81 @@
 use std::sync::Arc;
 
 use jsonrpsee::RpcModule;
+use sc_client_api::BlockchainEvents;
+use sc_rpc::SubscriptionTaskExecutor;
 use sc_transaction_pool_api::TransactionPool;
 use solochain_template_runtime::{opaque::Block, AccountId, Balance, Nonce};
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_core::offchain::OffchainStorage;
 
-use crate::rpc_trust_score;
+#[cfg(not(feature = "runtime-api-only"))]
+use crate::{rpc_rssi, rpc_run_summary, rpc_trust_score};
 
 /// Full client dependencies.
-pub struct FullDeps<C, P> {
+pub struct FullDeps<C, P, S> {
     /// The client instance to use.
     pub client: Arc<C>,
     /// Transaction pool instance.
     pub pool: Arc<P>,
+    /// Executor for RPC subscription tasks.
+    pub subscription_executor: SubscriptionTaskExecutor,
+    /// Offchain storage the offchain worker writes its run summaries to, if enabled.
+    pub offchain_db: Option<S>,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(
-    deps: FullDeps<C, P>,
+pub fn create_full<C, P, S>(
+    deps: FullDeps<C, P, S>,
 ) -> Result<RpcModule<()>, Box<dyn std::error::Error + Send + Sync>>
 where
     C: ProvideRuntimeApi<Block>,
     C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+    C: BlockchainEvents<Block>,
     C: Send + Sync + 'static,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: pallet_proof_of_location::rpc::TrustScoreApi<Block, AccountId>,
     C::Api: BlockBuilder<Block>,
     P: TransactionPool + 'static,
+    S: OffchainStorage + 'static,
 {
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
-    use rpc_trust_score::{TrustScore, TrustScoreApiServer};
     use substrate_frame_rpc_system::{System, SystemApiServer};
 
     let mut module = RpcModule::new(());
-    let FullDeps { client, pool } = deps;
+    #[cfg_attr(feature = "runtime-api-only", allow(unused_variables))]
+    let FullDeps {
+        client,
+        pool,
+        subscription_executor,
+        offchain_db,
+    } = deps;
 
     module.merge(System::new(client.clone(), pool).into_rpc())?;
     module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
 
-    // Add trust score RPC
-    module.merge(TrustScore::new(client).into_rpc())?;
+    // Custom RPCs are not served by light clients such as smoldot. With the `runtime-api-only`
+    // feature they are left out, so dApps are tested against the same surface a light client
+    // provides and query trust scores through `state_call` on the `TrustScoreApi` runtime API.
+    #[cfg(not(feature = "runtime-api-only"))]
+    {
+        use rpc_rssi::{Rssi, RssiApiServer};
+        use rpc_run_summary::{RunSummaries, RunSummaryApiServer};
+        use rpc_trust_score::{TrustScore, TrustScoreApiServer};
+
+        // Add trust score RPC
+        module.merge(TrustScore::new(client.clone()).into_rpc())?;
+
+        // Add offchain worker run summaries, kept only while offchain storage is enabled
+        if let Some(offchain_db) = offchain_db {
+            module.merge(RunSummaries::new(client.clone(), offchain_db).into_rpc())?;
+        }
+
+        // Add RSSI event subscription
+        module.merge(Rssi::new(client, subscription_executor).into_rpc())?;
+    }
 
     // Extend this RPC with a custom API by using the following syntax.
     // `YourRpcStruct` should have a reference to a client, which is needed
@@ -0,0 +1,146 @@
+//! RPC subscription for RSSI reports
+//!
+//! Streams `RssiStored` events decoded into typed JSON, so consumers do not need to fetch and
+//! filter raw `System::Events` themselves.
+
+use std::sync::Arc;
+
+use codec::Decode;
+use futures::{stream, StreamExt};
+use jsonrpsee::{proc_macros::rpc, types::ErrorObjectOwned, PendingSubscriptionSink};
+use sc_client_api::BlockchainEvents;
+use sc_rpc::{
+    utils::{pipe_from_stream, spawn_subscription_task},
+    SubscriptionTaskExecutor,
+};
+use serde::Serialize;
+use solochain_template_runtime::{opaque::Block, AccountId, BlockNumber, Hash, RuntimeEvent};
+use sp_core::{storage::StorageKey, twox_128};
+
+type EventRecord = frame_system::EventRecord<RuntimeEvent, Hash>;
+
+/// An `RssiStored` event emitted by the proof-of-location pallet
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RssiReport {
+    /// Hash of the block that emitted the event
+    pub block_hash: Hash,
+    /// Block number the measurement was stored under
+    pub block_number: BlockNumber,
+    /// Account that reported the measurement
+    pub who: AccountId,
+    /// Account that was measured
+    pub neighbor: AccountId,
+    /// Measured RSSI in dBm
+    pub rssi: i16,
+    /// TX power level the neighbor advertised in dBm, if it was reported
+    pub tx_power: Option<i16>,
+}
+
+impl RssiReport {
+    /// Whether `account` is either the reporter or the measured neighbor
+    fn involves(&self, account: &AccountId) -> bool {
+        &self.who == account || &self.neighbor == account
+    }
+}
+
+#[rpc(server)]
+pub trait RssiApi {
+    /// Subscribe to `RssiStored` events
+    ///
+    /// When `account_filter` is given, only events where that account is the reporter or the
+    /// neighbor are sent.
+    #[subscription(
+        name = "proofOfLocation_subscribeRssi" => "proofOfLocation_rssi",
+        unsubscribe = "proofOfLocation_unsubscribeRssi",
+        item = RssiReport
+    )]
+    fn subscribe_rssi(&self, account_filter: Option<AccountId>);
+}
+
+/// RSSI subscription RPC handler
+pub struct Rssi<C> {
+    client: Arc<C>,
+    executor: SubscriptionTaskExecutor,
+}
+
+impl<C> Rssi<C> {
+    /// Create new instance
+    pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+        Self { client, executor }
+    }
+}
+
+/// Decode the `RssiStored` events out of an encoded `System::Events` value
+fn decode_rssi_reports(block_hash: Hash, events: &[u8]) -> Vec<RssiReport> {
+    let Ok(records) = Vec::<EventRecord>::decode(&mut &events[..]) else {
+        return Vec::new();
+    };
+
+    records
+        .into_iter()
+        .filter_map(|record| match record.event {
+            RuntimeEvent::ProofOfLocation(pallet_proof_of_location::Event::RssiStored {
+                block_number,
+                neighbor,
+                who,
+                rssi,
+                tx_power,
+            }) => Some(RssiReport {
+                block_hash,
+                block_number,
+                who,
+                neighbor,
+                rssi,
+                tx_power,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+impl<C> RssiApiServer for Rssi<C>
+where
+    C: BlockchainEvents<Block> + Send + Sync + 'static,
+{
+    fn subscribe_rssi(&self, pending: PendingSubscriptionSink, account_filter: Option<AccountId>) {
+        // Events are stored in a single value that is rewritten every block
+        let events_key = StorageKey([twox_128(b"System"), twox_128(b"Events")].concat());
+
+        let notifications = match self
+            .client
+            .storage_changes_notification_stream(Some(&[events_key]), None)
+        {
+            Ok(stream) => stream,
+            Err(e) => {
+                let error = ErrorObjectOwned::owned(
+                    1,
+                    "Unable to subscribe to events",
+                    Some(format!("{:?}", e)),
+                );
+                spawn_subscription_task(&self.executor, pending.reject(error));
+                return;
+            }
+        };
+
+        let reports = notifications
+            .flat_map(move |notification| {
+                let reports: Vec<RssiReport> = notification
+                    .changes
+                    .iter()
+                    .filter_map(|(_, _, data)| data)
+                    .flat_map(|data| decode_rssi_reports(notification.block, &data.0))
+                    .filter(|report| {
+                        account_filter
+                            .as_ref()
+                            .is_none_or(|account| report.involves(account))
+                    })
+                    .collect();
+
+                stream::iter(reports)
+            })
+            .boxed();
+
+        spawn_subscription_task(&self.executor, pipe_from_stream(pending, reports));
+    }
+}
@@ -0,0 +1,138 @@
+//! RPC handler for the offchain worker's run summaries
+//!
+//! After every run the offchain worker writes what it did with the RSSI readings into the node's
+//! persistent offchain storage. These methods read the summaries back, so operators can find out
+//! why their readings do not show up on chain without searching the logs.
+
+use std::sync::Arc;
+
+use codec::Decode;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::ErrorObjectOwned,
+};
+use pallet_proof_of_location::util::{run_summary_key, RunSummary as StoredRunSummary};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::offchain::OffchainStorage;
+use sp_runtime::traits::{Block as BlockT, SaturatedConversion};
+
+/// What the offchain worker did with the RSSI readings of one run
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    /// Block the offchain worker ran for
+    pub block_number: u32,
+    /// Devices returned by the server
+    pub fetched: u32,
+    /// Devices whose Bluetooth address is not registered
+    pub skipped_unregistered: u32,
+    /// Readings the chain would reject, e.g. of too distant neighbors
+    pub skipped_invalid: u32,
+    /// Readings within `RssiHysteresis` of the last submitted ones
+    pub skipped_unchanged: u32,
+    /// Readings in transactions that were submitted
+    pub submitted: u32,
+    /// Readings in transactions that failed to submit
+    pub failed: u32,
+    /// Whether the readings were unchanged since the last submission and not sent again
+    pub unchanged: bool,
+    /// Error that ended the run early
+    pub error: Option<String>,
+}
+
+impl From<StoredRunSummary> for RunSummary {
+    fn from(summary: StoredRunSummary) -> Self {
+        Self {
+            block_number: summary.block,
+            fetched: summary.fetched,
+            skipped_unregistered: summary.skipped_unregistered,
+            skipped_invalid: summary.skipped_invalid,
+            skipped_unchanged: summary.skipped_unchanged,
+            submitted: summary.submitted,
+            failed: summary.failed,
+            unchanged: summary.unchanged,
+            error: summary
+                .error
+                .map(|error| String::from_utf8_lossy(&error).into_owned()),
+        }
+    }
+}
+
+#[rpc(client, server)]
+pub trait RunSummaryApi {
+    /// Get the summary of the offchain worker's run for a block
+    ///
+    /// Only the runs of the last `RUN_SUMMARY_HISTORY` blocks are kept.
+    #[method(name = "proofOfLocation_runSummary")]
+    fn run_summary(&self, block_number: u32) -> RpcResult<Option<RunSummary>>;
+
+    /// Get the summaries of the offchain worker's runs in the last `count` blocks, newest first
+    #[method(name = "proofOfLocation_recentRunSummaries")]
+    fn recent_run_summaries(&self, count: u32) -> RpcResult<Vec<RunSummary>>;
+}
+
+/// Run summary RPC handler
+pub struct RunSummaries<C, S, Block> {
+    client: Arc<C>,
+    offchain_db: S,
+    _marker: std::marker::PhantomData<Block>,
+}
+
+impl<C, S, Block> RunSummaries<C, S, Block> {
+    /// Create new instance
+    pub fn new(client: Arc<C>, offchain_db: S) -> Self {
+        Self {
+            client,
+            offchain_db,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, S: OffchainStorage, Block> RunSummaries<C, S, Block> {
+    /// Read the summary of the run for `block_number`, if it is still kept
+    fn read(&self, block_number: u32) -> Option<StoredRunSummary> {
+        let value = self
+            .offchain_db
+            .get(sp_offchain::STORAGE_PREFIX, &run_summary_key(block_number))?;
+        let summary = StoredRunSummary::decode(&mut &value[..]).ok()?;
+
+        // The key is shared with the runs `RUN_SUMMARY_HISTORY` blocks apart
+        (summary.block == block_number).then_some(summary)
+    }
+}
+
+#[async_trait]
+impl<C, S, Block> RunSummaryApiServer for RunSummaries<C, S, Block>
+where
+    Block: BlockT,
+    C: HeaderBackend<Block> + Send + Sync + 'static,
+    S: OffchainStorage + 'static,
+{
+    fn run_summary(&self, block_number: u32) -> RpcResult<Option<RunSummary>> {
+        Ok(self.read(block_number).map(Into::into))
+    }
+
+    fn recent_run_summaries(&self, count: u32) -> RpcResult<Vec<RunSummary>> {
+        let limit = pallet_proof_of_location::util::RUN_SUMMARY_HISTORY;
+        if count > limit {
+            return Err(ErrorObjectOwned::owned(
+                1,
+                "Too many run summaries requested",
+                Some(format!("At most {} summaries are kept", limit)),
+            ));
+        }
+
+        let best: u32 = self.client.info().best_number.saturated_into();
+        let first = best.saturating_sub(count.saturating_sub(1));
+
+        Ok((first..=best)
+            .rev()
+            .take(count as usize)
+            .filter_map(|block_number| self.read(block_number))
+            .map(Into::into)
+            .collect())
+    }
+}
@@ -1,4 +1,7 @@
 //! RPC handler for trust score calculation
+//!
+//! Every method is a thin wrapper around the `TrustScoreApi` runtime API. Light clients, which
+//! do not serve these JSON-RPC methods, can make the same queries through `state_call`.
 
 use std::sync::Arc;
 
@@ -8,11 +11,49 @@ use jsonrpsee::{
     proc_macros::rpc,
     types::ErrorObjectOwned,
 };
-use pallet_proof_of_location::rpc::TrustScoreApi as TrustScoreRuntimeApi;
+use pallet_proof_of_location::{
+    rpc::TrustScoreApi as TrustScoreRuntimeApi, util::EstimatorOverrides,
+};
+use serde::{Deserialize, Serialize};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_runtime::traits::Block as BlockT;
 
+/// Estimator parameters overriding the runtime constants for a single request.
+///
+/// Omitted fields keep the value configured in the runtime.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimatorParams {
+    /// Reference RSSI value at 1 meter distance
+    pub reference_rssi: Option<i16>,
+    /// Path loss exponent multiplied by 10
+    pub path_loss_exponent: Option<u8>,
+    /// Percentage of the highest errors discarded before taking the median
+    pub trim_percent: Option<u8>,
+}
+
+impl EstimatorParams {
+    /// Overrides for the runtime, rejecting parameters outside of the range it accepts
+    fn overrides(self) -> RpcResult<EstimatorOverrides> {
+        let overrides = EstimatorOverrides::from(self);
+        overrides.validate().map_err(|e| {
+            ErrorObjectOwned::owned(2, "Invalid estimator parameters", Some(e.to_string()))
+        })?;
+        Ok(overrides)
+    }
+}
+
+impl From<EstimatorParams> for EstimatorOverrides {
+    fn from(params: EstimatorParams) -> Self {
+        Self {
+            reference_rssi: params.reference_rssi,
+            path_loss_exponent: params.path_loss_exponent,
+            trim_percent: params.trim_percent,
+        }
+    }
+}
+
 #[rpc(client, server)]
 pub trait TrustScoreApi<BlockHash, AccountId> {
     /// Calculate trust scores for all accounts at a given block number
@@ -31,6 +72,34 @@ pub trait TrustScoreApi<BlockHash, AccountId> {
         account: AccountId,
         at: Option<BlockHash>,
     ) -> RpcResult<Option<i16>>;
+
+    /// Calculate trust scores for all accounts with custom estimator parameters
+    #[method(name = "trustScore_calculateAllWith")]
+    fn calculate_trust_scores_with(
+        &self,
+        params: EstimatorParams,
+        block_number: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(AccountId, i16)>>;
+
+    /// Calculate trust score for a specific account with custom estimator parameters
+    #[method(name = "trustScore_calculateWith")]
+    fn calculate_trust_score_with(
+        &self,
+        params: EstimatorParams,
+        block_number: u32,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<i16>>;
+
+    /// Get the RSSI reports behind the trust score of a specific account
+    #[method(name = "trustScore_breakdown")]
+    fn trust_score_breakdown(
+        &self,
+        block_number: u32,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<(AccountId, i16, i16, i16)>>;
 }
 
 /// Trust score RPC handler
@@ -95,4 +164,64 @@ where
                 )
             })
     }
+
+    fn calculate_trust_scores_with(
+        &self,
+        params: EstimatorParams,
+        block_number: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<(AccountId, i16)>> {
+        let overrides = params.overrides()?;
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.calculate_trust_scores_with(at, block_number, overrides)
+            .map_err(|e| {
+                ErrorObjectOwned::owned(
+                    1,
+                    "Unable to calculate trust scores",
+                    Some(format!("{:?}", e)),
+                )
+            })
+    }
+
+    fn calculate_trust_score_with(
+        &self,
+        params: EstimatorParams,
+        block_number: u32,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<i16>> {
+        let overrides = params.overrides()?;
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.calculate_trust_score_with(at, block_number, account, overrides)
+            .map_err(|e| {
+                ErrorObjectOwned::owned(
+                    1,
+                    "Unable to calculate trust score",
+                    Some(format!("{:?}", e)),
+                )
+            })
+    }
+
+    fn trust_score_breakdown(
+        &self,
+        block_number: u32,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<(AccountId, i16, i16, i16)>> {
+        let api = self.client.runtime_api();
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+        api.trust_score_breakdown(at, block_number, account)
+            .map_err(|e| {
+                ErrorObjectOwned::owned(
+                    1,
+                    "Unable to break down trust score",
+                    Some(format!("{:?}", e)),
+                )
+            })
+    }
 }
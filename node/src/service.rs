@@ -1,6 +1,7 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 use futures::FutureExt;
+use sc_cli::CryptoScheme;
 use sc_client_api::{Backend, BlockBackend};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 use sc_consensus_grandpa::SharedVoterState;
@@ -9,6 +10,8 @@ use sc_telemetry::{Telemetry, TelemetryWorker};
 use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use solochain_template_runtime::{self, apis::RuntimeApi, opaque::Block};
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
+use sp_core::Pair;
+use sp_keystore::KeystorePtr;
 use std::{sync::Arc, time::Duration};
 
 pub(crate) type FullClient = sc_service::TFullClient<
@@ -128,11 +131,39 @@ pub fn new_partial(config: &Configuration) -> Result<Service, ServiceError> {
     })
 }
 
+/// Derive the public key of scheme `P` from `suri`.
+fn location_public_key<P: Pair>(suri: &str) -> Result<Vec<u8>, ServiceError> {
+    let pair = P::from_string(suri, None)
+        .map_err(|e| ServiceError::Other(format!("Invalid location key SURI: {:?}", e)))?;
+    Ok(pair.public().as_ref().to_vec())
+}
+
+/// Insert the proof-of-location offchain worker key derived from `suri` into the keystore.
+///
+/// The key is stored under the pallet's `loc!` key type so the offchain worker can sign
+/// transactions without a separate `author_insertKey` call.
+fn insert_location_key(
+    keystore: &KeystorePtr,
+    scheme: CryptoScheme,
+    suri: &str,
+) -> Result<(), ServiceError> {
+    let public = match scheme {
+        CryptoScheme::Sr25519 => location_public_key::<sp_core::sr25519::Pair>(suri)?,
+        CryptoScheme::Ed25519 => location_public_key::<sp_core::ed25519::Pair>(suri)?,
+        CryptoScheme::Ecdsa => location_public_key::<sp_core::ecdsa::Pair>(suri)?,
+    };
+
+    keystore
+        .insert(pallet_proof_of_location::KEY_TYPE, suri, &public)
+        .map_err(|_| ServiceError::Other("Failed to insert location key into keystore".into()))
+}
+
 /// Builds a new service for a full client.
 pub fn new_full<
     N: sc_network::NetworkBackend<Block, <Block as sp_runtime::traits::Block>::Hash>,
 >(
     config: Configuration,
+    location_key: Option<(CryptoScheme, String)>,
 ) -> Result<TaskManager, ServiceError> {
     let sc_service::PartialComponents {
         client,
@@ -145,6 +176,10 @@ pub fn new_full<
         other: (block_import, grandpa_link, mut telemetry),
     } = new_partial(&config)?;
 
+    if let Some((scheme, suri)) = location_key {
+        insert_location_key(&keystore_container.keystore(), scheme, &suri)?;
+    }
+
     let mut net_config = sc_network::config::FullNetworkConfiguration::<
         Block,
         <Block as sp_runtime::traits::Block>::Hash,
@@ -212,6 +247,28 @@ pub fn new_full<
         );
     }
 
+    if let Some(registry) = config.prometheus_registry() {
+        let pol_metrics = crate::metrics::Metrics::register(registry)
+            .map_err(|e| ServiceError::Other(e.to_string()))?;
+        task_manager.spawn_handle().spawn(
+            "proof-of-location-metrics",
+            None,
+            crate::metrics::run(client.clone(), backend.offchain_storage(), pol_metrics),
+        );
+    }
+
+    if let Some(telemetry) = telemetry.as_ref() {
+        task_manager.spawn_handle().spawn(
+            "proof-of-location-telemetry",
+            None,
+            crate::telemetry::run(
+                client.clone(),
+                keystore_container.keystore(),
+                telemetry.handle(),
+            ),
+        );
+    }
+
     let role = config.role;
     let force_authoring = config.force_authoring;
     let backoff_authoring_blocks: Option<()> = None;
@@ -222,11 +279,14 @@ pub fn new_full<
     let rpc_extensions_builder = {
         let client = client.clone();
         let pool = transaction_pool.clone();
+        let offchain_db = backend.offchain_storage();
 
-        Box::new(move |_| {
+        Box::new(move |subscription_executor| {
             let deps = crate::rpc::FullDeps {
                 client: client.clone(),
                 pool: pool.clone(),
+                subscription_executor,
+                offchain_db: offchain_db.clone(),
             };
             crate::rpc::create_full(deps).map_err(Into::into)
         })
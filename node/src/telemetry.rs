@@ -0,0 +1,88 @@
+//! Telemetry messages with proof-of-location network health.
+//!
+//! On every finalized block a `pol.stats` message with the number of registered nodes, the RSSI
+//! reports of the block and this node's own trust score is sent to the telemetry endpoints, so
+//! public dashboards can show the state of a testnet.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use pallet_proof_of_location::{rpc::TrustScoreApi, KEY_TYPE};
+use sc_client_api::BlockchainEvents;
+use sc_telemetry::{telemetry, TelemetryHandle, SUBSTRATE_INFO};
+use solochain_template_runtime::{opaque::Block, AccountId};
+use sp_api::ProvideRuntimeApi;
+use sp_keystore::{Keystore, KeystorePtr};
+use sp_runtime::{
+    traits::{Block as BlockT, Header, IdentifyAccount},
+    MultiSigner,
+};
+
+use crate::metrics::{registered_nodes, rssi_reports};
+use crate::service::FullClient;
+
+/// Account of the first proof-of-location key in the keystore, whichever its scheme.
+///
+/// The offchain worker's `local_account` signs with the first key `RuntimeAppPublic::all()`
+/// returns, which is the keystore's list of keys of the scheme in the same order, so the trust
+/// score reported here is the one of the account the worker submits from.
+fn local_account(keystore: &KeystorePtr) -> Option<AccountId> {
+    let signer: MultiSigner =
+        if let Some(public) = keystore.sr25519_public_keys(KEY_TYPE).into_iter().next() {
+            public.into()
+        } else if let Some(public) = keystore.ed25519_public_keys(KEY_TYPE).into_iter().next() {
+            public.into()
+        } else {
+            keystore
+                .ecdsa_public_keys(KEY_TYPE)
+                .into_iter()
+                .next()?
+                .into()
+        };
+
+    Some(signer.into_account())
+}
+
+/// Send the telemetry message for the block with the given hash and number.
+fn send_stats(
+    client: &FullClient,
+    keystore: &KeystorePtr,
+    telemetry: &TelemetryHandle,
+    hash: <Block as BlockT>::Hash,
+    number: u32,
+) -> sp_blockchain::Result<()> {
+    let nodes = registered_nodes(client, hash)?;
+    let reports = rssi_reports(client, hash, number)?;
+
+    // Nodes without a location key are not scored
+    let trust_score = match local_account(keystore) {
+        Some(account) => client
+            .runtime_api()
+            .calculate_trust_score(hash, number, account)?,
+        None => None,
+    };
+
+    telemetry!(
+        Some(telemetry.clone());
+        SUBSTRATE_INFO;
+        "pol.stats";
+        "block" => number,
+        "registered_nodes" => nodes,
+        "rssi_reports" => reports,
+        "trust_score" => trust_score
+    );
+
+    Ok(())
+}
+
+/// Send telemetry on every finalized block until the finality stream ends.
+pub async fn run(client: Arc<FullClient>, keystore: KeystorePtr, telemetry: TelemetryHandle) {
+    let mut finality = client.finality_notification_stream();
+
+    while let Some(notification) = finality.next().await {
+        let number = *notification.header.number();
+        if let Err(e) = send_stats(&client, &keystore, &telemetry, notification.hash, number) {
+            log::warn!("Failed to send proof-of-location telemetry: {}", e);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use frame_support::pallet_macros::*;
+
+/// A [`pallet_section`] that defines how stored RSSI reports are evaluated into trust scores.
+///
+/// This is the security-critical path of the pallet: it only reads storage written by the
+/// ingestion section and never mutates it.
+#[pallet_section]
+mod evaluation {
+    use crate::util::{EstimatorOverrides, EstimatorParams, DEFAULT_TRIM_PERCENT};
+
+    /// Internal API for scoring the RSSI reports of a block.
+    pub trait TrustEvaluation<T: Config> {
+        /// `(reporter, measured, estimated, error)` for every report about `account`.
+        ///
+        /// The estimate is derived from the registered locations of `account` and the reporter,
+        /// and from the TX power `account` advertised if the reporter published it. Returns an
+        /// empty vector if `account` is not registered or nobody reported it.
+        fn rssi_breakdown(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+            params: &EstimatorParams,
+        ) -> Vec<(T::AccountId, i16, i16, i16)>;
+
+        /// Errors between measured and estimated RSSI for every report about `account`.
+        fn rssi_errors(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+            params: &EstimatorParams,
+        ) -> Vec<i16>;
+
+        /// Trust score of `account`, the trimmed median of its RSSI errors.
+        fn trust_score(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+            params: &EstimatorParams,
+        ) -> Option<i16>;
+    }
+
+    impl<T: Config> TrustEvaluation<T> for Pallet<T> {
+        fn rssi_breakdown(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+            params: &EstimatorParams,
+        ) -> Vec<(T::AccountId, i16, i16, i16)> {
+            use crate::util::estimate_rssi;
+
+            // Get the location data for the account
+            let Some(location_data) = AccountData::<T>::get(account) else {
+                return Vec::new();
+            };
+
+            // Collect all RSSI errors for this account
+            let mut breakdown = Vec::new();
+
+            // Iterate only the reports about this account, at most `MaxReportsPerNode`
+            for (reporter_account, measured_rssi) in
+                RssiData::<T>::iter_prefix((block_number, account.clone()))
+            {
+                // Skip self
+                if reporter_account == *account {
+                    continue;
+                }
+
+                // Skip reporters that are no longer registered
+                let Some(reporter_location) = AccountData::<T>::get(&reporter_account) else {
+                    continue;
+                };
+
+                // Calculate estimated RSSI based on location, from the path loss if the TX power
+                // is known
+                let tx_power = TxPowerData::<T>::get((
+                    block_number,
+                    account.clone(),
+                    reporter_account.clone(),
+                ));
+                let estimated_rssi = estimate_rssi(
+                    location_data.latitude,
+                    location_data.longitude,
+                    reporter_location.latitude,
+                    reporter_location.longitude,
+                    params.reference_rssi_for(tx_power),
+                    params.path_loss_exponent,
+                );
+
+                // Calculate error
+                let error = measured_rssi.saturating_sub(estimated_rssi);
+                breakdown.push((reporter_account, measured_rssi, estimated_rssi, error));
+            }
+
+            breakdown
+        }
+
+        fn rssi_errors(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+            params: &EstimatorParams,
+        ) -> Vec<i16> {
+            Self::rssi_breakdown(block_number, account, params)
+                .into_iter()
+                .map(|(_, _, _, error)| error)
+                .collect()
+        }
+
+        fn trust_score(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+            params: &EstimatorParams,
+        ) -> Option<i16> {
+            use crate::util::trimmed_median_error_with;
+
+            let mut errors = Self::rssi_errors(block_number, account, params);
+            if errors.is_empty() {
+                return None;
+            }
+
+            Some(trimmed_median_error_with(&mut errors, params.trim_percent))
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Estimator parameters configured in the runtime.
+        pub fn estimator_params() -> EstimatorParams {
+            EstimatorParams {
+                reference_rssi: T::ReferenceRssi::get(),
+                path_loss_exponent: T::PathLossExponent::get(),
+                trim_percent: DEFAULT_TRIM_PERCENT,
+                reference_path_loss: T::ReferencePathLoss::get(),
+            }
+        }
+
+        /// Calculate trust score for a specific account at a given block number.
+        ///
+        /// Returns the trimmed median error of RSSI measurements.
+        pub fn calculate_trust_score_for_account(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+        ) -> Option<i16> {
+            Self::calculate_trust_score_for_account_with(
+                block_number,
+                account,
+                &EstimatorOverrides::default(),
+            )
+        }
+
+        /// Calculate trust score for a specific account, overriding estimator parameters.
+        pub fn calculate_trust_score_for_account_with(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+            overrides: &EstimatorOverrides,
+        ) -> Option<i16> {
+            let params = Self::estimator_params().with_overrides(overrides);
+            <Self as TrustEvaluation<T>>::trust_score(block_number, account, &params)
+        }
+
+        /// RSSI reports behind the trust score of `account` at a given block number.
+        ///
+        /// Returns `(reporter, measured, estimated, error)` for every report that contributes to
+        /// the score, showing which neighbors pull it up or down.
+        pub fn trust_score_breakdown(
+            block_number: BlockNumberFor<T>,
+            account: &T::AccountId,
+        ) -> Vec<(T::AccountId, i16, i16, i16)> {
+            let params = Self::estimator_params();
+            <Self as TrustEvaluation<T>>::rssi_breakdown(block_number, account, &params)
+        }
+
+        /// Calculate the trust score of an account over every block of an epoch.
+        ///
+        /// The RSSI errors of all blocks are pooled before taking the trimmed median.
+        pub fn calculate_epoch_trust_score(
+            epoch: BlockNumberFor<T>,
+            account: &T::AccountId,
+        ) -> Option<i16> {
+            use crate::util::trimmed_median_error_with;
+
+            let params = Self::estimator_params();
+            let epoch_length = T::EpochLength::get();
+            let first_block = epoch.saturating_mul(epoch_length);
+            let end_block = first_block.saturating_add(epoch_length);
+
+            let mut errors = Vec::new();
+            let mut block_number = first_block;
+            while block_number < end_block {
+                errors.extend(<Self as TrustEvaluation<T>>::rssi_errors(
+                    block_number,
+                    account,
+                    &params,
+                ));
+                block_number += 1u32.into();
+            }
+
+            if errors.is_empty() {
+                return None;
+            }
+
+            Some(trimmed_median_error_with(&mut errors, params.trim_percent))
+        }
+
+        /// Calculate trust scores for all accounts at a given block number.
+        ///
+        /// Returns a vector of (AccountId, trust_score) tuples.
+        pub fn calculate_all_trust_scores(
+            block_number: BlockNumberFor<T>,
+        ) -> Vec<(T::AccountId, i16)> {
+            Self::calculate_all_trust_scores_with(block_number, &EstimatorOverrides::default())
+        }
+
+        /// Calculate trust scores for all accounts, overriding estimator parameters.
+        pub fn calculate_all_trust_scores_with(
+            block_number: BlockNumberFor<T>,
+            overrides: &EstimatorOverrides,
+        ) -> Vec<(T::AccountId, i16)> {
+            let params = Self::estimator_params().with_overrides(overrides);
+            let mut results = Vec::new();
+
+            for (account, _) in AccountData::<T>::iter() {
+                if let Some(score) =
+                    <Self as TrustEvaluation<T>>::trust_score(block_number, &account, &params)
+                {
+                    results.push((account, score));
+                }
+            }
+
+            results
+        }
+    }
+}
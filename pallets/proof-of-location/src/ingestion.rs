@@ -0,0 +1,199 @@
+use frame_support::pallet_macros::*;
+
+/// A [`pallet_section`] that defines how RSSI and range reports are validated and stored.
+#[pallet_section]
+mod ingestion {
+    /// Internal API for accepting RSSI and range reports into storage.
+    ///
+    /// Every rule that decides whether a measurement may enter the chain lives behind this trait.
+    /// Dispatchables only check the origin and then hand the report over.
+    pub trait RssiIngestion<T: Config> {
+        /// Check that `who` and `neighbor` are different registered accounts whose registered
+        /// locations are within `MaxDistance` of each other.
+        ///
+        /// Unlike [`Self::validate_report`] this does not depend on the reports of the current
+        /// block, so the offchain worker uses it to leave out readings that would always fail.
+        fn validate_pair(who: &T::AccountId, neighbor: &T::AccountId) -> DispatchResult;
+
+        /// Check that `rssi` and `tx_power` are within [`crate::util::DBM_RANGE`].
+        fn validate_reading(rssi: i16, tx_power: Option<i16>) -> DispatchResult;
+
+        /// Check that `who` may report a measurement of `neighbor`.
+        ///
+        /// The pair must pass [`Self::validate_pair`]. `neighbor` may have at most
+        /// `MaxReportsPerNode` reports in the current block, a report replacing an earlier one
+        /// from `who` is always accepted.
+        ///
+        /// Returns the number of existing reports about `neighbor` that were counted, which is zero
+        /// for a replacing report. Callers use it to refund unused weight.
+        fn validate_report(
+            who: &T::AccountId,
+            neighbor: &T::AccountId,
+        ) -> Result<u32, DispatchError>;
+
+        /// Store a validated report under the current block and emit [`Event::RssiStored`].
+        ///
+        /// `tx_power` is the TX power level the neighbor advertised, if any. Also records the
+        /// block as the reporter's last activity.
+        fn store_report(
+            who: T::AccountId,
+            neighbor: T::AccountId,
+            rssi: i16,
+            tx_power: Option<i16>,
+        );
+
+        /// Check that `who` may report a UWB range of `distance_cm` to `neighbor`.
+        ///
+        /// The pair must pass [`Self::validate_pair`], and the range may differ from the distance
+        /// between their registered locations by at most `MaxRangeDeviation` meters.
+        fn validate_range(
+            who: &T::AccountId,
+            neighbor: &T::AccountId,
+            distance_cm: u32,
+        ) -> DispatchResult;
+
+        /// Store a validated range under the current block and emit [`Event::RangeStored`].
+        ///
+        /// Also records the block as the reporter's last activity.
+        fn store_range(who: T::AccountId, neighbor: T::AccountId, distance_cm: u32);
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Distance in meters between the registered locations of `who` and `neighbor`, which
+        /// must be different registered accounts.
+        fn registered_distance(
+            who: &T::AccountId,
+            neighbor: &T::AccountId,
+        ) -> Result<f64, DispatchError> {
+            // Check that the node is not reporting itself.
+            ensure!(who != neighbor, Error::<T>::SelfReportNotAllowed);
+
+            // Check that origin account is registered.
+            let reporter_location =
+                AccountData::<T>::get(who).ok_or(Error::<T>::ReporterNotRegistered)?;
+
+            // Check that neighbor account is registered.
+            let neighbor_location =
+                AccountData::<T>::get(neighbor).ok_or(Error::<T>::NeighborNotRegistered)?;
+
+            Ok(crate::util::distance_meters(
+                reporter_location.latitude,
+                reporter_location.longitude,
+                neighbor_location.latitude,
+                neighbor_location.longitude,
+            ))
+        }
+    }
+
+    impl<T: Config> RssiIngestion<T> for Pallet<T> {
+        fn validate_pair(who: &T::AccountId, neighbor: &T::AccountId) -> DispatchResult {
+            let distance = Self::registered_distance(who, neighbor)?;
+
+            // Check that distance is within allowed maximum.
+            ensure!(
+                distance <= T::MaxDistance::get() as f64,
+                Error::<T>::ExceedsMaxDistance
+            );
+
+            Ok(())
+        }
+
+        fn validate_reading(rssi: i16, tx_power: Option<i16>) -> DispatchResult {
+            let range = crate::util::DBM_RANGE;
+            ensure!(
+                range.contains(&rssi) && tx_power.is_none_or(|tx_power| range.contains(&tx_power)),
+                Error::<T>::ReadingOutOfRange
+            );
+
+            Ok(())
+        }
+
+        fn validate_report(
+            who: &T::AccountId,
+            neighbor: &T::AccountId,
+        ) -> Result<u32, DispatchError> {
+            Self::validate_pair(who, neighbor)?;
+
+            // Check that the neighbor has room for another report in this block.
+            let block_number = frame_system::Pallet::<T>::block_number();
+            if RssiData::<T>::contains_key((block_number, neighbor.clone(), who.clone())) {
+                return Ok(0);
+            }
+            let existing_reports =
+                RssiData::<T>::iter_key_prefix((block_number, neighbor.clone())).count() as u32;
+            ensure!(
+                existing_reports < T::MaxReportsPerNode::get(),
+                Error::<T>::TooManyReports
+            );
+
+            Ok(existing_reports)
+        }
+
+        fn store_report(
+            who: T::AccountId,
+            neighbor: T::AccountId,
+            rssi: i16,
+            tx_power: Option<i16>,
+        ) {
+            // Get the current block number.
+            let block_number = frame_system::Pallet::<T>::block_number();
+
+            // Update storage, a replaced report must not keep the TX power of the earlier one.
+            let key = (block_number, neighbor.clone(), who.clone());
+            RssiData::<T>::insert(&key, rssi);
+            match tx_power {
+                Some(tx_power) => TxPowerData::<T>::insert(&key, tx_power),
+                None => TxPowerData::<T>::remove(&key),
+            }
+            LastActivity::<T>::insert(&who, block_number);
+
+            // Emit an event.
+            Self::deposit_event(Event::RssiStored {
+                block_number,
+                neighbor,
+                who,
+                rssi,
+                tx_power,
+            });
+        }
+
+        fn validate_range(
+            who: &T::AccountId,
+            neighbor: &T::AccountId,
+            distance_cm: u32,
+        ) -> DispatchResult {
+            let distance = Self::registered_distance(who, neighbor)?;
+            ensure!(
+                distance <= T::MaxDistance::get() as f64,
+                Error::<T>::ExceedsMaxDistance
+            );
+
+            // A UWB range is accurate to centimeters, so it must agree with the registered
+            // locations up to their own error.
+            let deviation = (distance_cm as f64 / 100.0 - distance).abs();
+            ensure!(
+                deviation <= T::MaxRangeDeviation::get() as f64,
+                Error::<T>::RangeContradictsLocation
+            );
+
+            Ok(())
+        }
+
+        fn store_range(who: T::AccountId, neighbor: T::AccountId, distance_cm: u32) {
+            // Get the current block number.
+            let block_number = frame_system::Pallet::<T>::block_number();
+
+            // Update storage, replacing an earlier range of the pair in this block.
+            RangeData::<T>::insert((block_number, neighbor.clone(), who.clone()), distance_cm);
+            LastActivity::<T>::insert(&who, block_number);
+
+            // Emit an event.
+            Self::deposit_event(Event::RangeStored {
+                block_number,
+                neighbor,
+                who,
+                distance_cm,
+            });
+        }
+    }
+}
@@ -31,11 +31,17 @@
 //! ### Pallet Sections
 //!
 //! - **Configuration trait** ([`Config`]): Defines the types, constants (server URL, max distance), and crypto requirements
-//! - **Storage items**: RssiData, AccountData, AddressRegistrationData, ServerConfig
-//! - **Events** ([`Event`]): RssiStored, NodeRegistered, NodeUnregistered, NodeUpdated
+//! - **Storage items**: RssiData, TxPowerData, RangeData, AccountData, AddressRegistrationData, ServerConfig, LocationServerConfig, ServerKey, IdentityResolvingKey, WifiAddress, WifiAddressRegistrationData, PruneCursor, LastActivity, EpochTrustScores
+//! - **Genesis config** ([`GenesisConfig`]): Nodes pre-registered at genesis
+//! - **Events** ([`Event`]): RssiStored, RangeStored, NodeRegistered, NodeUnregistered, NodeExpired, NodeUpdated, ReportsPruned, TrustScoreRolledUp
 //! - **Errors** ([`Error`]): Address/account validation and distance verification errors
-//! - **Dispatchable functions**: set_server_config, register_node, unregister_node, update_node_info, publish_rssi_data
+//! - **View functions**: account_data, trust_score, node_count
+//! - **Dispatchable functions**: set_server_config, register_node, unregister_node, update_node_info, publish_rssi_data, prune_my_reports, publish_rssi_data_batch, report_misbehavior, set_server_key, set_location_server_config, set_identity_resolving_key, set_wifi_address, publish_range_data
+//! - **Ingestion** ([`RssiIngestion`]): Validation and storage of RSSI and range reports
+//! - **Evaluation** ([`TrustEvaluation`]): Scoring of stored RSSI reports, read-only
 //! - **Offchain worker**: Automatic location registration and RSSI data submission
+//! - **Migrations** ([`migrations`]): Counting the nodes registered before `AccountData` was counted
+//! - **Tasks** ([`Task`]): prune_rssi_report, expire_node, rollup_trust_score, prune_range_report
 //! - **RPC methods**: calculate_trust_score (for specific account), calculate_all_trust_scores (for all accounts)
 //!
 //! Run `cargo doc --package pallet-proof-of-location --open` to view this pallet's documentation.
@@ -55,15 +61,24 @@ pub mod rpc;
 // Utility module containing common structs and functions
 pub mod util;
 
-// Module containing RPC implementation functions
-mod rpc_impl;
-
 // Module containing pallet calls (dispatchable functions)
 mod pallet_calls;
 
+// Module containing RSSI report validation and storage
+mod ingestion;
+
+// Module containing trust score evaluation of stored RSSI reports
+mod evaluation;
+
 // Module containing offchain worker implementation
 mod offchain_worker;
 
+// Module containing maintenance tasks executed through `RuntimeTask`
+mod tasks;
+
+// Storage migrations, added to the `Migrations` of the runtime
+pub mod migrations;
+
 // FRAME pallets require their own "mock runtimes" to be able to run unit tests. This module
 // contains a mock runtime specific for testing this pallet's functionality.
 #[cfg(test)]
@@ -74,6 +89,10 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+// Offchain worker tests against mocked HTTP responses, keystore and transaction pool.
+#[cfg(test)]
+mod offchain_tests;
+
 // Every callable function or "dispatchable" a pallet exposes must have weight values that correctly
 // estimate a dispatchable's execution time. The benchmarking module is used to calculate weights
 // for each dispatchable and generates this pallet's weight.rs file. Learn more about benchmarking here: https://docs.substrate.io/test/benchmark/
@@ -93,9 +112,58 @@ use sp_core::crypto::KeyTypeId;
 /// The keys can be inserted manually via RPC (see `author_insertKey`).
 pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"loc!");
 
+/// Persistent offchain storage key counting the transactions the offchain worker failed to submit.
+///
+/// The node reads this counter to export it as a Prometheus metric.
+pub const SUBMISSION_FAILURES_KEY: &[u8] = b"proof-of-location::submission-failures";
+
+/// Persistent offchain storage key of the block and payload hash of the last RSSI submission.
+pub const LAST_SUBMISSION_KEY: &[u8] = b"proof-of-location::last-submission";
+
+/// Persistent offchain storage key of the bearer token sent with every request to the server.
+///
+/// Requests are sent without an `Authorization` header while it is not set.
+pub const AUTH_TOKEN_KEY: &[u8] = b"proof-of-location::auth-token";
+
+/// Persistent offchain storage key of the server URL used when the account has no `ServerConfig`.
+///
+/// Takes precedence over the runtime's `ServerUrl`, so the endpoint can be changed before the
+/// node's account is funded.
+pub const SERVER_URL_KEY: &[u8] = b"proof-of-location::server-url";
+
+/// Persistent offchain storage key of the location server URL used when the account has no
+/// `LocationServerConfig`.
+///
+/// Takes precedence over the runtime's `LocationServerUrl`.
+pub const LOCATION_SERVER_URL_KEY: &[u8] = b"proof-of-location::location-server-url";
+
+/// Persistent offchain storage key of the lock held while the offchain worker runs.
+pub const OFFCHAIN_LOCK_KEY: &[u8] = b"proof-of-location::lock";
+
+/// Prefix of the persistent offchain storage keys counting a neighbor's consecutive suspicious
+/// readings, followed by the SCALE-encoded neighbor account.
+pub const MISBEHAVIOR_STREAK_KEY: &[u8] = b"proof-of-location::misbehavior-streak";
+
+/// Prefix of the persistent offchain storage keys of the last accepted response sequence number,
+/// followed by the request URL.
+pub const RESPONSE_SEQUENCE_KEY: &[u8] = b"proof-of-location::response-sequence";
+
+/// Prefix of the persistent offchain storage keys of the offchain worker's run summaries.
+///
+/// See [`util::run_summary_key`] for the full key.
+pub const RUN_SUMMARY_KEY: &[u8] = b"proof-of-location::run-summary";
+
+/// Prefix of the persistent offchain storage keys of the block and RSSI of the last submitted
+/// reading of a neighbor, followed by the SCALE-encoded neighbor account.
+pub const LAST_RSSI_KEY: &[u8] = b"proof-of-location::last-rssi";
+
 /// Based on the above `KeyTypeId` we need to generate a pallet-specific crypto type wrappers.
 /// We can use from supported crypto kinds (`sr25519`, `ed25519` and `ecdsa`) and augment
 /// the types with this pallet-specific identifier.
+///
+/// The top-level types use `sr25519`. The [`crypto::ed25519`] and [`crypto::ecdsa`] modules
+/// provide the same wrappers for the other schemes; a runtime selects one through
+/// `Config::AuthorityId`.
 pub mod crypto {
     use super::KEY_TYPE;
     use sp_core::sr25519::Signature as Sr25519Signature;
@@ -122,26 +190,71 @@ pub mod crypto {
         type GenericSignature = sp_core::sr25519::Signature;
         type GenericPublic = sp_core::sr25519::Public;
     }
+
+    /// `ed25519` keys of the pallet's key type.
+    pub mod ed25519 {
+        use crate::KEY_TYPE;
+        use sp_runtime::{
+            app_crypto::{app_crypto, ed25519},
+            MultiSignature, MultiSigner,
+        };
+        app_crypto!(ed25519, KEY_TYPE);
+
+        pub struct AuthId;
+
+        impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for AuthId {
+            type RuntimeAppPublic = Public;
+            type GenericSignature = sp_core::ed25519::Signature;
+            type GenericPublic = sp_core::ed25519::Public;
+        }
+    }
+
+    /// `ecdsa` keys of the pallet's key type, e.g. for hardware-backed keys.
+    pub mod ecdsa {
+        use crate::KEY_TYPE;
+        use sp_runtime::{
+            app_crypto::{app_crypto, ecdsa},
+            MultiSignature, MultiSigner,
+        };
+        app_crypto!(ecdsa, KEY_TYPE);
+
+        pub struct AuthId;
+
+        impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for AuthId {
+            type RuntimeAppPublic = Public;
+            type GenericSignature = sp_core::ecdsa::Signature;
+            type GenericPublic = sp_core::ecdsa::Public;
+        }
+    }
 }
 
 use frame_support::pallet_macros::import_section;
 
 /// Import pallet sections from separate files
 #[import_section(pallet_calls::dispatches)]
+#[import_section(ingestion::ingestion)]
+#[import_section(evaluation::evaluation)]
 #[import_section(offchain_worker::offchain)]
+#[import_section(tasks::tasks)]
 // All pallet logic is defined in its own module and must be annotated by the `pallet` attribute.
 #[frame_support::pallet]
 pub mod pallet {
     // Import various useful types required by all FRAME pallets.
     use super::*;
-    use crate::util::LocationData;
+    use crate::util::{LocationData, NodeConfigBundle};
+    use alloc::vec::Vec;
     use frame_support::pallet_prelude::*;
+    use frame_support::traits::UnixTime;
     use frame_system::offchain::{AppCrypto, CreateSignedTransaction};
     use frame_system::pallet_prelude::*;
 
+    /// The in-code storage version.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
     // The `Pallet` struct serves as a placeholder to implement traits, methods and dispatchables
     // (`Call`s) in this pallet.
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     /// The pallet's configuration trait.
@@ -155,8 +268,16 @@ pub mod pallet {
         type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
         /// The overarching runtime event type.
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        /// The overarching runtime task type.
+        type RuntimeTask: frame_support::traits::Task
+            + IsType<<Self as frame_system::Config>::RuntimeTask>
+            + From<Task<Self>>;
         /// A type representing the weights required by the dispatchables of this pallet.
         type WeightInfo: WeightInfo;
+        /// Time of the current block, e.g. `pallet_timestamp::Pallet`.
+        ///
+        /// The offchain worker checks the clocks of the servers against it.
+        type UnixTime: UnixTime;
 
         /// Default server URL with port for fetching data (used if not set via set_server_config).
         ///
@@ -164,6 +285,68 @@ pub mod pallet {
         #[pallet::constant]
         type ServerUrl: Get<&'static [u8]>;
 
+        /// Server URLs the offchain worker falls back to, in order, when the configured server
+        /// cannot be reached.
+        #[pallet::constant]
+        type FallbackServerUrls: Get<&'static [&'static [u8]]>;
+
+        /// Default URL of the server providing the GPS location, if it is not the one providing
+        /// the RSSI readings (used if not set via `set_location_server_config`).
+        ///
+        /// `None` fetches `/location` from the RSSI server as well.
+        #[pallet::constant]
+        type LocationServerUrl: Get<Option<&'static [u8]>>;
+
+        /// Whether the offchain worker only contacts servers over HTTPS.
+        ///
+        /// Server URLs without a scheme then default to `https://`, and `http://` URLs are
+        /// skipped, so RSSI and location data never cross the network in cleartext.
+        #[pallet::constant]
+        type RequireHttps: Get<bool>;
+
+        /// Number of times the offchain worker retries a failed request to a server before
+        /// falling back to the next one.
+        #[pallet::constant]
+        type HttpRetries: Get<u32>;
+
+        /// Milliseconds the offchain worker waits before its first retry, doubled for every
+        /// further retry.
+        #[pallet::constant]
+        type HttpRetryBackoffMillis: Get<u64>;
+
+        /// Milliseconds the offchain worker waits for a single HTTP request to complete.
+        #[pallet::constant]
+        type HttpTimeoutMillis: Get<u64>;
+
+        /// Milliseconds all HTTP requests of one offchain worker run may take together,
+        /// including retries and fallback servers.
+        ///
+        /// Requests that would run past the budget are cut short, so a run does not hold its
+        /// lock for much longer than this.
+        #[pallet::constant]
+        type OffchainRunBudgetMillis: Get<u64>;
+
+        /// Maximum age in milliseconds of a server response the offchain worker accepts.
+        ///
+        /// Protects against stuck or replayed responses being submitted over and over.
+        #[pallet::constant]
+        type MaxResponseAgeMillis: Get<u64>;
+
+        /// Milliseconds a server's clock may deviate from the time of the current block before
+        /// the offchain worker warns about it.
+        ///
+        /// The block is produced before the worker runs, so allow for at least one block time.
+        /// A skewed clock makes fresh responses look stale, or stale ones fresh.
+        #[pallet::constant]
+        type MaxClockSkewMillis: Get<u64>;
+
+        /// Maximum size in bytes of a server response body the offchain worker reads.
+        ///
+        /// Larger responses are dropped while reading, so a misbehaving server cannot make the
+        /// worker allocate unbounded memory.
+        #[pallet::constant]
+        type MaxResponseBytes: Get<u32>;
+
         /// Reference RSSI value at 1 meter distance.
         #[pallet::constant]
         type ReferenceRssi: Get<i16>;
@@ -174,6 +357,13 @@ pub mod pallet {
         #[pallet::constant]
         type PathLossExponent: Get<u8>;
 
+        /// Path loss at 1 meter distance in dB.
+        ///
+        /// Readings published with the neighbor's advertised TX power are estimated from the path
+        /// loss (`tx_power - rssi`) instead of `ReferenceRssi`.
+        #[pallet::constant]
+        type ReferencePathLoss: Get<u8>;
+
         /// Maximum allowed distance between 2 nodes (in meters) to consider publishing RSSI data.
         #[pallet::constant]
         type MaxDistance: Get<u32>;
@@ -181,6 +371,89 @@ pub mod pallet {
         /// Minimum number of blocks that must elapse before a node can update its information again.
         #[pallet::constant]
         type UpdateCooldown: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of RSSI entries scanned by a single `prune_my_reports` call.
+        #[pallet::constant]
+        type MaxPruneReports: Get<u32>;
+
+        /// Maximum number of registered nodes, `register_node` fails beyond it.
+        ///
+        /// Storage lookups get more expensive as the node set grows, so extrinsic weights are
+        /// calculated for this bound. It should be set above the expected network size.
+        #[pallet::constant]
+        type MaxNodes: Get<u32>;
+
+        /// Maximum number of RSSI reports about a single node within one block.
+        ///
+        /// Trust score calculation reads every report of a block, so together with `MaxNodes` this
+        /// bounds the cost of the `TrustScoreApi` runtime API.
+        #[pallet::constant]
+        type MaxReportsPerNode: Get<u32>;
+
+        /// Maximum number of readings in a single `publish_rssi_data_batch` call.
+        ///
+        /// The offchain worker splits larger scans into several batches.
+        #[pallet::constant]
+        type MaxBatchSize: Get<u32>;
+
+        /// Number of blocks RSSI reports are kept before the `prune_rssi_report` task may
+        /// remove them.
+        ///
+        /// Must be longer than `EpochLength`, so reports outlive the rollup of their epoch.
+        #[pallet::constant]
+        type RssiRetention: Get<BlockNumberFor<Self>>;
+
+        /// Number of blocks without registering, updating or reporting after which a node may be
+        /// removed by the `expire_node` task.
+        #[pallet::constant]
+        type NodeExpiry: Get<BlockNumberFor<Self>>;
+
+        /// Number of blocks per epoch whose RSSI reports are rolled up into one trust score.
+        #[pallet::constant]
+        type EpochLength: Get<BlockNumberFor<Self>>;
+
+        /// Number of blocks between the offchain worker's checks of a registered node's location.
+        #[pallet::constant]
+        type LocationRefreshInterval: Get<BlockNumberFor<Self>>;
+
+        /// Distance in meters a registered node must move before the offchain worker updates its
+        /// on-chain location.
+        #[pallet::constant]
+        type LocationUpdateDelta: Get<u32>;
+
+        /// RSSI error in dB above which the offchain worker considers a neighbor's reading
+        /// suspicious.
+        #[pallet::constant]
+        type MisbehaviorThreshold: Get<u16>;
+
+        /// Number of consecutive offchain worker runs with a suspicious reading after which the
+        /// neighbor is reported with `report_misbehavior`.
+        #[pallet::constant]
+        type MisbehaviorRuns: Get<u32>;
+
+        /// Change in dB below which the offchain worker does not report a neighbor's RSSI again.
+        ///
+        /// Static nodes measure nearly the same RSSI every block, so small fluctuations are not
+        /// worth the chain space.
+        #[pallet::constant]
+        type RssiHysteresis: Get<u16>;
+
+        /// Number of blocks after which the offchain worker reports a neighbor's RSSI again, even
+        /// if it has not changed.
+        ///
+        /// Must not be longer than `EpochLength`, so every epoch gets reports to roll up.
+        #[pallet::constant]
+        type RssiResubmitInterval: Get<BlockNumberFor<Self>>;
+
+        /// Distance in meters between the location a node claims and the one trilaterated from
+        /// its registered neighbors' RSSI, above which the offchain worker refuses to register it.
+        #[pallet::constant]
+        type MaxRegistrationDeviation: Get<u32>;
+
+        /// Distance in meters a range measured by a UWB module may differ from the distance
+        /// between the registered locations of the pair before `publish_range_data` rejects it.
+        #[pallet::constant]
+        type MaxRangeDeviation: Get<u32>;
     }
 
     /// Storage for RSSI (Received Signal Strength Indicator) measurements.
@@ -197,6 +470,33 @@ pub mod pallet {
         Value = i16,
     >;
 
+    /// TX power level advertised by the neighbor of an RSSI report.
+    ///
+    /// Same keys as [`RssiData`], only present for reports published with a TX power.
+    #[pallet::storage]
+    pub type TxPowerData<T: Config> = StorageNMap<
+        Key = (
+            NMapKey<Identity, BlockNumberFor<T>>,
+            NMapKey<Blake2_128Concat, T::AccountId>, // neighbor account
+            NMapKey<Blake2_128Concat, T::AccountId>, // reporting account
+        ),
+        Value = i16,
+    >;
+
+    /// Storage for distances measured by UWB ranging, in centimeters.
+    ///
+    /// Same keys as [`RssiData`]. Ranges are checked against the registered locations when they
+    /// are published and are not part of the RSSI trust score.
+    #[pallet::storage]
+    pub type RangeData<T: Config> = StorageNMap<
+        Key = (
+            NMapKey<Identity, BlockNumberFor<T>>,
+            NMapKey<Blake2_128Concat, T::AccountId>, // neighbor account
+            NMapKey<Blake2_128Concat, T::AccountId>, // reporting account
+        ),
+        Value = u32,
+    >;
+
     /// Maps Bluetooth MAC addresses to AccountIds.
     ///
     /// Used to look up which account owns a particular Bluetooth address,
@@ -207,10 +507,11 @@ pub mod pallet {
 
     /// Maps AccountIds to their location data (Bluetooth address + GPS coordinates).
     ///
-    /// Stores the registered location information for each node in the network.
+    /// Stores the registered location information for each node in the network. Counted, so
+    /// the number of registered nodes is read without iterating them.
     #[pallet::storage]
     pub type AccountData<T: Config> =
-        StorageMap<Hasher = Blake2_128Concat, Key = T::AccountId, Value = LocationData>;
+        CountedStorageMap<Hasher = Blake2_128Concat, Key = T::AccountId, Value = LocationData>;
 
     /// Storage for server configuration per account (node)
     /// Maps AccountId -> server URL (format: "hostname:port" or "ip:port")
@@ -221,6 +522,115 @@ pub mod pallet {
         Value = BoundedVec<u8, ConstU32<256>>,
     >;
 
+    /// Storage for the location server configuration per account (node)
+    /// Maps AccountId -> URL of the GPS server, when it differs from the `ServerConfig` one
+    #[pallet::storage]
+    pub type LocationServerConfig<T: Config> = StorageMap<
+        Hasher = Blake2_128Concat,
+        Key = T::AccountId,
+        Value = BoundedVec<u8, ConstU32<256>>,
+    >;
+
+    /// sr25519 public key the server of an account (node) signs its responses with
+    /// Maps AccountId -> public key, responses must be signed while it is set
+    #[pallet::storage]
+    pub type ServerKey<T: Config> =
+        StorageMap<Hasher = Blake2_128Concat, Key = T::AccountId, Value = [u8; 32]>;
+
+    /// Identity Resolving Key an account's (node's) server rotates its BLE address with
+    /// Maps AccountId -> IRK, in the byte order BlueZ stores it
+    #[pallet::storage]
+    pub type IdentityResolvingKey<T: Config> =
+        StorageMap<Hasher = Blake2_128Concat, Key = T::AccountId, Value = [u8; 16]>;
+
+    /// BSSID of the Wi-Fi interface an account's (node's) server can be measured by
+    /// Maps AccountId -> BSSID, next to the Bluetooth address in `AccountData`
+    #[pallet::storage]
+    pub type WifiAddress<T: Config> =
+        StorageMap<Hasher = Blake2_128Concat, Key = T::AccountId, Value = [u8; 6]>;
+
+    /// Maps Wi-Fi BSSIDs to AccountIds.
+    ///
+    /// The Wi-Fi counterpart of [`AddressRegistrationData`], so RSSI measured over Wi-Fi can
+    /// reference neighbors by their BSSIDs.
+    #[pallet::storage]
+    pub type WifiAddressRegistrationData<T: Config> =
+        StorageMap<Hasher = Blake2_128Concat, Key = [u8; 6], Value = T::AccountId>;
+
+    /// Raw `RssiData` key where an account's last `prune_my_reports` call stopped scanning.
+    ///
+    /// Lets consecutive calls resume the scan instead of starting over from the first entry.
+    #[pallet::storage]
+    pub type PruneCursor<T: Config> = StorageMap<
+        Hasher = Blake2_128Concat,
+        Key = T::AccountId,
+        Value = BoundedVec<u8, ConstU32<256>>,
+    >;
+
+    /// Last block in which a node published an RSSI report.
+    ///
+    /// Used with `LocationData::last_updated` to find nodes that can be expired.
+    #[pallet::storage]
+    pub type LastActivity<T: Config> =
+        StorageMap<Hasher = Blake2_128Concat, Key = T::AccountId, Value = BlockNumberFor<T>>;
+
+    /// Trust score of a node over all RSSI reports of an epoch.
+    ///
+    /// Maps (epoch, account) -> trust score, or `None` if nobody reported the node during the
+    /// epoch. Written by the `rollup_trust_score` task once the epoch has finished.
+    #[pallet::storage]
+    pub type EpochTrustScores<T: Config> = StorageDoubleMap<
+        Hasher1 = Twox64Concat,
+        Key1 = BlockNumberFor<T>,
+        Hasher2 = Blake2_128Concat,
+        Key2 = T::AccountId,
+        Value = Option<i16>,
+    >;
+
+    /// Nodes registered at genesis.
+    ///
+    /// Allows demo and test networks to start with a known set of nodes instead of waiting
+    /// for the offchain worker to register them.
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Registered nodes as (account, bluetooth address, latitude, longitude).
+        ///
+        /// Coordinates use the same fixed-point format as `register_node` (multiplied by 1_000_000).
+        pub nodes: Vec<(T::AccountId, [u8; 6], i64, i64)>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            assert!(
+                self.nodes.len() <= T::MaxNodes::get() as usize,
+                "More nodes registered in genesis than `MaxNodes`"
+            );
+
+            for (account, address, latitude, longitude) in &self.nodes {
+                assert!(
+                    !AddressRegistrationData::<T>::contains_key(address),
+                    "Bluetooth address registered twice in genesis"
+                );
+                assert!(
+                    !AccountData::<T>::contains_key(account),
+                    "Account registered twice in genesis"
+                );
+
+                let location_data = LocationData {
+                    address: *address,
+                    latitude: *latitude,
+                    longitude: *longitude,
+                    last_updated: 0,
+                };
+
+                AccountData::<T>::insert(account, location_data);
+                AddressRegistrationData::<T>::insert(address, account);
+            }
+        }
+    }
+
     /// Events that functions in this pallet can emit.
     ///
     ///	The `generate_deposit` macro generates a function on `Pallet` called `deposit_event` which
@@ -235,6 +645,14 @@ pub mod pallet {
             neighbor: T::AccountId,
             who: T::AccountId,
             rssi: i16,
+            tx_power: Option<i16>,
+        },
+        /// A user has successfully published the UWB range of its neighbor.
+        RangeStored {
+            block_number: BlockNumberFor<T>,
+            neighbor: T::AccountId,
+            who: T::AccountId,
+            distance_cm: u32,
         },
         /// A node has successfully registered its location.
         NodeRegistered {
@@ -245,6 +663,24 @@ pub mod pallet {
         },
         /// A node has been unregistered.
         NodeUnregistered { address: [u8; 6], who: T::AccountId },
+        /// A node has been removed after being inactive for `NodeExpiry` blocks.
+        NodeExpired {
+            address: [u8; 6],
+            who: T::AccountId,
+            last_active: BlockNumberFor<T>,
+        },
+        /// The trust scores of an epoch have been rolled up for a node.
+        TrustScoreRolledUp {
+            epoch: BlockNumberFor<T>,
+            who: T::AccountId,
+            score: Option<i16>,
+        },
+        /// A node has pruned some of its own historical RSSI reports.
+        ReportsPruned {
+            who: T::AccountId,
+            up_to_block: BlockNumberFor<T>,
+            removed: u32,
+        },
         /// A node's information has been updated.
         NodeUpdated {
             who: T::AccountId,
@@ -255,6 +691,12 @@ pub mod pallet {
             old_longitude: i64,
             new_longitude: i64,
         },
+        /// A node has reported a neighbor whose signal contradicts its registered location.
+        MisbehaviorReported {
+            reporter: T::AccountId,
+            offender: T::AccountId,
+            rssi_error: i16,
+        },
     }
 
     /// Errors that can be returned by this pallet.
@@ -275,5 +717,166 @@ pub mod pallet {
         ExceedsMaxDistance,
         /// Node update cooldown period has not elapsed yet
         NodeUpdateCooldownNotElapsed,
+        /// Neighbor already has `MaxReportsPerNode` reports in this block
+        TooManyReports,
+        /// Reporting account is not registered as a node
+        ReporterNotRegistered,
+        /// Neighbor account is not registered as a node
+        NeighborNotRegistered,
+        /// Node tried to report a measurement of itself
+        SelfReportNotAllowed,
+        /// Wi-Fi BSSID is already taken by another node's Bluetooth address or BSSID
+        WifiAddressAlreadyTaken,
+        /// Range differs from the distance between the registered locations by more than
+        /// `MaxRangeDeviation`
+        RangeContradictsLocation,
+        /// RSSI or TX power is outside of the -127 to 20 dBm a radio can report
+        ReadingOutOfRange,
+        /// `MaxNodes` nodes are already registered
+        TooManyNodes,
+    }
+
+    #[cfg(any(feature = "try-runtime", test))]
+    impl<T: Config> Pallet<T> {
+        /// Check the invariants between the pallet's storage items.
+        ///
+        /// - The counter of `AccountData` matches the number of registered nodes.
+        /// - The Bluetooth address of every registered node maps back to that node.
+        /// - Every registered Bluetooth address belongs to a registered node.
+        /// - The BSSID of every node maps back to that node, and every registered BSSID belongs
+        ///   to a node.
+        /// - No node has more than `MaxReportsPerNode` reports in the current block.
+        pub fn do_try_state() -> Result<(), sp_runtime::TryRuntimeError> {
+            let mut node_count = 0usize;
+            for (account, location_data) in AccountData::<T>::iter() {
+                ensure!(
+                    AddressRegistrationData::<T>::get(location_data.address).as_ref()
+                        == Some(&account),
+                    "Bluetooth address of a registered node does not map back to it"
+                );
+                node_count += 1;
+            }
+
+            ensure!(
+                AccountData::<T>::count() as usize == node_count,
+                "Node counter does not match the registered nodes"
+            );
+
+            ensure!(
+                AddressRegistrationData::<T>::iter_keys().count() == node_count,
+                "Bluetooth address is registered without a node"
+            );
+
+            let mut wifi_count = 0usize;
+            for (account, bssid) in WifiAddress::<T>::iter() {
+                ensure!(
+                    AccountData::<T>::contains_key(&account),
+                    "BSSID is set for an account that is not registered"
+                );
+                ensure!(
+                    WifiAddressRegistrationData::<T>::get(bssid).as_ref() == Some(&account),
+                    "BSSID of a node does not map back to it"
+                );
+                wifi_count += 1;
+            }
+
+            ensure!(
+                WifiAddressRegistrationData::<T>::iter_keys().count() == wifi_count,
+                "BSSID is registered without a node"
+            );
+
+            let block_number = frame_system::Pallet::<T>::block_number();
+            for account in AccountData::<T>::iter_keys() {
+                ensure!(
+                    RssiData::<T>::iter_key_prefix((block_number, account)).count()
+                        <= T::MaxReportsPerNode::get() as usize,
+                    "Node has more than `MaxReportsPerNode` reports in the current block"
+                );
+            }
+
+            for account in LastActivity::<T>::iter_keys() {
+                ensure!(
+                    AccountData::<T>::contains_key(&account),
+                    "Activity is recorded for an account that is not registered"
+                );
+            }
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Server URL configured by `account`, falling back to the default `ServerUrl`.
+        pub fn server_url(account: &T::AccountId) -> alloc::vec::Vec<u8> {
+            ServerConfig::<T>::get(account)
+                .map(|server_url| server_url.into_inner())
+                .unwrap_or_else(|| T::ServerUrl::get().to_vec())
+        }
+
+        /// Location server URL configured by `account`, falling back to the default
+        /// `LocationServerUrl` and then to the account's RSSI server URL.
+        pub fn location_server_url(account: &T::AccountId) -> alloc::vec::Vec<u8> {
+            LocationServerConfig::<T>::get(account)
+                .map(|server_url| server_url.into_inner())
+                .or_else(|| T::LocationServerUrl::get().map(|server_url| server_url.to_vec()))
+                .unwrap_or_else(|| Self::server_url(account))
+        }
+
+        /// Account of the node measured at `address`, its Bluetooth address or Wi-Fi BSSID.
+        pub fn account_of_address(address: [u8; 6]) -> Option<T::AccountId> {
+            AddressRegistrationData::<T>::get(address)
+                .or_else(|| WifiAddressRegistrationData::<T>::get(address))
+        }
+
+        /// Remove a node and all of its per-account storage, returning its Bluetooth address.
+        pub(crate) fn remove_node(who: &T::AccountId) -> Option<[u8; 6]> {
+            let location_data = AccountData::<T>::take(who)?;
+
+            AddressRegistrationData::<T>::remove(location_data.address);
+            ServerConfig::<T>::remove(who);
+            LocationServerConfig::<T>::remove(who);
+            ServerKey::<T>::remove(who);
+            IdentityResolvingKey::<T>::remove(who);
+            if let Some(bssid) = WifiAddress::<T>::take(who) {
+                WifiAddressRegistrationData::<T>::remove(bssid);
+            }
+            PruneCursor::<T>::remove(who);
+            LastActivity::<T>::remove(who);
+
+            Some(location_data.address)
+        }
+
+        /// Registration and configuration of `account`'s node, read in one go.
+        pub fn node_config(account: &T::AccountId) -> NodeConfigBundle {
+            NodeConfigBundle {
+                location: AccountData::<T>::get(account),
+                server_url: Self::server_url(account),
+                max_distance: T::MaxDistance::get(),
+                reference_rssi: T::ReferenceRssi::get(),
+                path_loss_exponent: T::PathLossExponent::get(),
+                location_server_url: Self::location_server_url(account),
+            }
+        }
+    }
+
+    /// Read-only queries that light clients and frontends can run through the
+    /// `RuntimeViewFunction` runtime API without custom RPC plumbing.
+    #[pallet::view_functions]
+    impl<T: Config> Pallet<T> {
+        /// Registered location data of `account`, if any.
+        pub fn account_data(account: T::AccountId) -> Option<LocationData> {
+            AccountData::<T>::get(account)
+        }
+
+        /// Trust score of `account` for the RSSI reports stored in the current block.
+        pub fn trust_score(account: T::AccountId) -> Option<i16> {
+            let block_number = frame_system::Pallet::<T>::block_number();
+            Self::calculate_trust_score_for_account(block_number, &account)
+        }
+
+        /// Number of registered nodes.
+        pub fn node_count() -> u32 {
+            AccountData::<T>::count()
+        }
     }
 }
@@ -0,0 +1,28 @@
+//! Storage migrations of the proof of location pallet.
+
+use crate::{AccountData, Config, Pallet};
+use core::marker::PhantomData;
+use frame_support::migrations::VersionedMigration;
+use frame_support::traits::{Get, UncheckedOnRuntimeUpgrade};
+use frame_support::weights::Weight;
+
+/// Count the nodes registered before `AccountData` became a counted map.
+pub struct InitializeNodeCounterUnchecked<T>(PhantomData<T>);
+
+impl<T: Config> UncheckedOnRuntimeUpgrade for InitializeNodeCounterUnchecked<T> {
+    fn on_runtime_upgrade() -> Weight {
+        let node_count = AccountData::<T>::initialize_counter();
+        log::info!("Counted {} registered nodes", node_count);
+
+        T::DbWeight::get().reads_writes(node_count as u64, 1)
+    }
+}
+
+/// Migrates the storage from version 0 to 1 by initializing the counter of `AccountData`.
+pub type InitializeNodeCounter<T> = VersionedMigration<
+    0,
+    1,
+    InitializeNodeCounterUnchecked<T>,
+    Pallet<T>,
+    <T as frame_system::Config>::DbWeight,
+>;
@@ -41,21 +41,85 @@ impl frame_system::Config for Test {
 // Server configuration constants
 parameter_types! {
     pub const ServerUrl: &'static [u8] = b"localhost:3000";
+    pub static FallbackServerUrls: &'static [&'static [u8]] = &[];
+    pub const LocationServerUrl: Option<&'static [u8]> = None;
+    pub static RequireHttps: bool = false;
+    pub const HttpRetries: u32 = 2;
+    pub const HttpRetryBackoffMillis: u64 = 500;
+    pub const HttpTimeoutMillis: u64 = 30_000;
+    pub const OffchainRunBudgetMillis: u64 = 60_000;
+    pub const MaxResponseAgeMillis: u64 = 60_000;
+    pub const MaxClockSkewMillis: u64 = 30_000;
+    pub const MaxResponseBytes: u32 = 1_024;
     pub const ReferenceRssi: i16 = -48;
     pub const PathLossExponent: u8 = 40;
+    pub const ReferencePathLoss: u8 = 48;
     pub const MaxDistance: u32 = 10;
     pub const UpdateCooldown: u64 = 5; // 5 blocks cooldown for testing
+    pub const MaxPruneReports: u32 = 3;
+    pub const MaxNodes: u32 = 100;
+    pub const MaxReportsPerNode: u32 = 5;
+    pub const MaxBatchSize: u32 = 3;
+    pub const RssiRetention: u64 = 10;
+    pub const NodeExpiry: u64 = 20;
+    pub const EpochLength: u64 = 5;
+    pub const LocationRefreshInterval: u64 = 10;
+    pub const LocationUpdateDelta: u32 = 5;
+    pub const MisbehaviorThreshold: u16 = 20;
+    pub const MisbehaviorRuns: u32 = 3;
+    pub const RssiHysteresis: u16 = 2;
+    pub const RssiResubmitInterval: u64 = 3;
+    pub const MaxRegistrationDeviation: u32 = 100;
+    pub const MaxRangeDeviation: u32 = 10;
+    pub static BlockTimeMillis: u64 = 1_700_000_000_000;
+}
+
+/// Time of the current block, as `pallet_timestamp` would report it
+pub struct BlockTime;
+
+impl frame_support::traits::UnixTime for BlockTime {
+    fn now() -> core::time::Duration {
+        core::time::Duration::from_millis(BlockTimeMillis::get())
+    }
 }
 
 impl pallet_proof_of_location::Config for Test {
     type AuthorityId = pallet_proof_of_location::crypto::TestAuthId;
     type RuntimeEvent = RuntimeEvent;
+    type RuntimeTask = RuntimeTask;
     type WeightInfo = ();
+    type UnixTime = BlockTime;
     type ServerUrl = ServerUrl;
+    type FallbackServerUrls = FallbackServerUrls;
+    type LocationServerUrl = LocationServerUrl;
+    type RequireHttps = RequireHttps;
+    type HttpRetries = HttpRetries;
+    type HttpRetryBackoffMillis = HttpRetryBackoffMillis;
+    type HttpTimeoutMillis = HttpTimeoutMillis;
+    type OffchainRunBudgetMillis = OffchainRunBudgetMillis;
+    type MaxResponseAgeMillis = MaxResponseAgeMillis;
+    type MaxClockSkewMillis = MaxClockSkewMillis;
+    type MaxResponseBytes = MaxResponseBytes;
     type ReferenceRssi = ReferenceRssi;
     type PathLossExponent = PathLossExponent;
+    type ReferencePathLoss = ReferencePathLoss;
     type MaxDistance = MaxDistance;
     type UpdateCooldown = UpdateCooldown;
+    type MaxPruneReports = MaxPruneReports;
+    type MaxNodes = MaxNodes;
+    type MaxReportsPerNode = MaxReportsPerNode;
+    type MaxBatchSize = MaxBatchSize;
+    type RssiRetention = RssiRetention;
+    type NodeExpiry = NodeExpiry;
+    type EpochLength = EpochLength;
+    type LocationRefreshInterval = LocationRefreshInterval;
+    type LocationUpdateDelta = LocationUpdateDelta;
+    type MisbehaviorThreshold = MisbehaviorThreshold;
+    type MisbehaviorRuns = MisbehaviorRuns;
+    type RssiHysteresis = RssiHysteresis;
+    type RssiResubmitInterval = RssiResubmitInterval;
+    type MaxRegistrationDeviation = MaxRegistrationDeviation;
+    type MaxRangeDeviation = MaxRangeDeviation;
 }
 
 impl frame_system::offchain::SigningTypes for Test {
@@ -0,0 +1,1064 @@
+use crate::{
+    mock::*,
+    util::{
+        run_summary_key, DeviceRssi, Location, LocationResponse, NodeStatus, RssiResponse,
+        RunSummary,
+    },
+    Call, KEY_TYPE,
+};
+use codec::{Decode, Encode};
+use frame_support::{assert_ok, traits::Hooks};
+use parking_lot::RwLock;
+use sp_core::{
+    offchain::{
+        testing::{
+            OffchainState, PendingRequest, PoolState, TestOffchainExt, TestTransactionPoolExt,
+        },
+        Duration, Externalities, HttpError, HttpRequestId, HttpRequestStatus, OffchainDbExt,
+        OffchainWorkerExt, OpaqueNetworkState, StorageKind, Timestamp, TransactionPoolExt,
+    },
+    OpaquePeerId,
+};
+use sp_keystore::{testing::MemoryKeystore, Keystore, KeystoreExt};
+use sp_runtime::{
+    testing::TestXt,
+    traits::{Dispatchable, IdentifyAccount},
+    AccountId32, MultiSigner,
+};
+use std::sync::Arc;
+
+// Default `ServerUrl` of the mock runtime
+const SERVER: &str = "http://localhost:3000";
+
+// Fallback server of the tests setting `FallbackServerUrls`
+const FALLBACK_SERVER: &str = "http://localhost:3001";
+const FALLBACK_SERVER_URLS: &[&[u8]] = &[b"localhost:3001"];
+
+// Response header carrying the status code of a mocked response, see `StatusOffchainExt`
+const STATUS_HEADER: &str = "x-test-status";
+
+// Time of the offchain worker and of every mocked response, in Unix milliseconds
+const NOW: u64 = 1_700_000_000_000;
+
+// Location served by the mocked `/location` endpoint, exact in fixed-point
+const ADDRESS: [u8; 6] = [0x10, 0x00, 0x00, 0x00, 0x00, 0x01];
+const LATITUDE: i64 = 37_500_000;
+const LONGITUDE: i64 = -122_250_000;
+
+// Neighbor ~0.11 meters away from the node
+const NEIGHBOR_ADDRESS: [u8; 6] = [0x10, 0x00, 0x00, 0x00, 0x00, 0x02];
+
+/// Mocked HTTP server and transaction pool of an offchain worker test
+struct OffchainTest {
+    offchain: Arc<RwLock<OffchainState>>,
+    pool: Arc<RwLock<PoolState>>,
+    /// Account of the worker's key, `None` without a key in the keystore
+    account: Option<AccountId32>,
+}
+
+/// `TestOffchainExt` answering with the status code in the `STATUS_HEADER` of a mocked
+/// response, since the test extension answers every request with `200 OK`
+struct StatusOffchainExt(TestOffchainExt);
+
+impl Externalities for StatusOffchainExt {
+    fn is_validator(&self) -> bool {
+        self.0.is_validator()
+    }
+
+    fn network_state(&self) -> Result<OpaqueNetworkState, ()> {
+        self.0.network_state()
+    }
+
+    fn timestamp(&mut self) -> Timestamp {
+        self.0.timestamp()
+    }
+
+    fn sleep_until(&mut self, deadline: Timestamp) {
+        self.0.sleep_until(deadline)
+    }
+
+    fn random_seed(&mut self) -> [u8; 32] {
+        self.0.random_seed()
+    }
+
+    fn http_request_start(
+        &mut self,
+        method: &str,
+        uri: &str,
+        meta: &[u8],
+    ) -> Result<HttpRequestId, ()> {
+        self.0.http_request_start(method, uri, meta)
+    }
+
+    fn http_request_add_header(
+        &mut self,
+        request_id: HttpRequestId,
+        name: &str,
+        value: &str,
+    ) -> Result<(), ()> {
+        self.0.http_request_add_header(request_id, name, value)
+    }
+
+    fn http_request_write_body(
+        &mut self,
+        request_id: HttpRequestId,
+        chunk: &[u8],
+        deadline: Option<Timestamp>,
+    ) -> Result<(), HttpError> {
+        self.0.http_request_write_body(request_id, chunk, deadline)
+    }
+
+    fn http_response_wait(
+        &mut self,
+        ids: &[HttpRequestId],
+        deadline: Option<Timestamp>,
+    ) -> Vec<HttpRequestStatus> {
+        let statuses = self.0.http_response_wait(ids, deadline);
+        ids.iter()
+            .zip(statuses)
+            .map(|(id, status)| {
+                let code = self
+                    .0
+                    .http_response_headers(*id)
+                    .into_iter()
+                    .find(|(name, _)| name == STATUS_HEADER.as_bytes())
+                    .and_then(|(_, value)| String::from_utf8(value).ok()?.parse().ok());
+                match (status, code) {
+                    (HttpRequestStatus::Finished(_), Some(code)) => {
+                        HttpRequestStatus::Finished(code)
+                    }
+                    (status, _) => status,
+                }
+            })
+            .collect()
+    }
+
+    fn http_response_headers(&mut self, request_id: HttpRequestId) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.0.http_response_headers(request_id)
+    }
+
+    fn http_response_read_body(
+        &mut self,
+        request_id: HttpRequestId,
+        buffer: &mut [u8],
+        deadline: Option<Timestamp>,
+    ) -> Result<usize, HttpError> {
+        self.0.http_response_read_body(request_id, buffer, deadline)
+    }
+
+    fn set_authorized_nodes(&mut self, nodes: Vec<OpaquePeerId>, authorized_only: bool) {
+        self.0.set_authorized_nodes(nodes, authorized_only)
+    }
+}
+
+/// Test externalities with offchain, transaction pool and keystore extensions registered.
+fn new_offchain_ext(with_key: bool) -> (sp_io::TestExternalities, OffchainTest) {
+    let (offchain, offchain_state) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    let keystore = MemoryKeystore::new();
+
+    let account = with_key.then(|| {
+        let public = keystore
+            .sr25519_generate_new(KEY_TYPE, Some("//Alice"))
+            .unwrap();
+        MultiSigner::from(public).into_account()
+    });
+    offchain_state.write().timestamp = Timestamp::from_unix_millis(NOW);
+
+    let mut ext = new_test_ext();
+    ext.register_extension(OffchainDbExt::new(offchain.clone()));
+    ext.register_extension(OffchainWorkerExt::new(StatusOffchainExt(offchain)));
+    ext.register_extension(TransactionPoolExt::new(pool));
+    ext.register_extension(KeystoreExt::new(keystore));
+
+    let state = OffchainTest {
+        offchain: offchain_state,
+        pool: pool_state,
+        account,
+    };
+    (ext, state)
+}
+
+impl OffchainTest {
+    fn account(&self) -> AccountId32 {
+        self.account.clone().expect("Key was added to the keystore")
+    }
+
+    /// `X-Node-ID` header sent with every request
+    fn node_id(&self) -> String {
+        let hex: String = self
+            .account()
+            .encode()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        format!("0x{}", hex)
+    }
+
+    /// Expect a GET request of `path` and answer it with `body`
+    fn expect_get(&self, path: &str, body: Vec<u8>) {
+        self.offchain.write().expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: format!("{}{}", SERVER, path),
+            headers: vec![("X-Node-ID".into(), self.node_id())],
+            response: Some(body),
+            sent: true,
+            ..Default::default()
+        });
+    }
+
+    /// Expect a GET request of `url` and fail it with the HTTP `status`
+    fn expect_failed_get(&self, url: &str, status: u16) {
+        self.offchain.write().expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: url.into(),
+            headers: vec![("X-Node-ID".into(), self.node_id())],
+            response_headers: vec![(STATUS_HEADER.into(), status.to_string())],
+            response: Some(Vec::new()),
+            sent: true,
+            ..Default::default()
+        });
+    }
+
+    /// Expect `status` to be posted to `/status`
+    fn expect_status(&self, status: NodeStatus) {
+        self.offchain.write().expect_request(PendingRequest {
+            method: "POST".into(),
+            uri: format!("{}/status", SERVER),
+            headers: vec![
+                ("X-Node-ID".into(), self.node_id()),
+                ("Content-Type".into(), "application/octet-stream".into()),
+            ],
+            body: status.encode(),
+            response: Some(Vec::new()),
+            sent: true,
+            ..Default::default()
+        });
+    }
+
+    /// Take the calls of all transactions submitted to the pool, in order
+    fn submitted_calls(&self) -> Vec<RuntimeCall> {
+        self.pool
+            .write()
+            .transactions
+            .drain(..)
+            .map(|tx| {
+                TestXt::<RuntimeCall, ()>::decode(&mut &tx[..])
+                    .unwrap()
+                    .function
+            })
+            .collect()
+    }
+}
+
+fn location_response() -> Vec<u8> {
+    LocationResponse {
+        address: ADDRESS,
+        location: Location {
+            latitude: LATITUDE as f64 / 1_000_000.0,
+            longitude: LONGITUDE as f64 / 1_000_000.0,
+        },
+        timestamp: NOW,
+        sequence: None,
+    }
+    .encode()
+}
+
+fn rssi_response(devices: Vec<DeviceRssi>) -> Vec<u8> {
+    RssiResponse {
+        devices,
+        timestamp: NOW,
+        sequence: None,
+    }
+    .encode()
+}
+
+/// Register a neighbor next to the location served by `/location`.
+fn register_neighbor() -> AccountId32 {
+    let neighbor = AccountId32::new([2; 32]);
+    assert_ok!(ProofOfLocation::register_node(
+        RuntimeOrigin::signed(neighbor.clone()),
+        NEIGHBOR_ADDRESS,
+        LATITUDE + 1,
+        LONGITUDE
+    ));
+    neighbor
+}
+
+/// Summary the offchain worker kept for its run at `block`.
+fn run_summary(block: u32) -> Option<RunSummary> {
+    sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &run_summary_key(block))
+        .map(|value| RunSummary::decode(&mut &value[..]).unwrap())
+}
+
+#[test]
+fn offchain_worker_registers_node_then_reports_rssi() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        let account = state.account();
+        let neighbor = register_neighbor();
+        let devices = vec![DeviceRssi {
+            address: NEIGHBOR_ADDRESS,
+            rssi: -60,
+            tx_power: Some(4),
+            timestamp: NOW,
+        }];
+
+        // First run: the node registers itself and cannot report before that is included
+        System::set_block_number(1);
+        state.expect_get("/location", location_response());
+        state.expect_get("/rssi", rssi_response(devices.clone()));
+        state.expect_status(NodeStatus {
+            block: 1,
+            account: account.encode(),
+            registered: false,
+            last_submitted_block: None,
+            trust_score: None,
+        });
+        ProofOfLocation::offchain_worker(1);
+
+        let calls = state.submitted_calls();
+        assert_eq!(
+            calls,
+            vec![RuntimeCall::ProofOfLocation(Call::register_node {
+                address: ADDRESS,
+                latitude: LATITUDE,
+                longitude: LONGITUDE,
+            })]
+        );
+        assert_eq!(
+            run_summary(1),
+            Some(RunSummary {
+                block: 1,
+                fetched: 1,
+                skipped_invalid: 1,
+                ..Default::default()
+            })
+        );
+
+        // Include the registration
+        assert_ok!(calls[0]
+            .clone()
+            .dispatch(RuntimeOrigin::signed(account.clone())));
+
+        // Second run: the same readings are reported now
+        System::set_block_number(2);
+        state.expect_get("/rssi", rssi_response(devices));
+        state.expect_status(NodeStatus {
+            block: 2,
+            account: account.encode(),
+            registered: true,
+            last_submitted_block: Some(2),
+            trust_score: None,
+        });
+        ProofOfLocation::offchain_worker(2);
+
+        assert_eq!(
+            state.submitted_calls(),
+            vec![RuntimeCall::ProofOfLocation(
+                Call::publish_rssi_data_batch {
+                    readings: vec![(neighbor, -60, Some(4))].try_into().unwrap(),
+                }
+            )]
+        );
+        assert_eq!(
+            run_summary(2),
+            Some(RunSummary {
+                block: 2,
+                fetched: 1,
+                submitted: 1,
+                ..Default::default()
+            })
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_skips_unregistered_devices() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+
+        // Nobody registered this address, so there is nothing to submit
+        state.expect_get(
+            "/rssi",
+            rssi_response(vec![DeviceRssi {
+                address: NEIGHBOR_ADDRESS,
+                rssi: -60,
+                tx_power: None,
+                timestamp: NOW,
+            }]),
+        );
+        let mut summary = RunSummary::default();
+        assert_ok!(ProofOfLocation::fetch_rssi_and_submit(
+            1,
+            deadline,
+            &mut summary
+        ));
+
+        assert!(state.submitted_calls().is_empty());
+        assert_eq!(summary.fetched, 1);
+        assert_eq!(summary.skipped_unregistered, 1);
+    });
+}
+
+#[test]
+fn offchain_worker_maps_wifi_bssids_to_accounts() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let neighbor = register_neighbor();
+        let bssid = [0x20, 0x00, 0x00, 0x00, 0x00, 0x02];
+        assert_ok!(ProofOfLocation::set_wifi_address(
+            RuntimeOrigin::signed(neighbor.clone()),
+            Some(bssid)
+        ));
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+
+        // The neighbor was measured over Wi-Fi, under its BSSID
+        state.expect_get(
+            "/rssi",
+            rssi_response(vec![DeviceRssi {
+                address: bssid,
+                rssi: -55,
+                tx_power: None,
+                timestamp: NOW,
+            }]),
+        );
+        let mut summary = RunSummary::default();
+        assert_ok!(ProofOfLocation::fetch_rssi_and_submit(
+            1,
+            deadline,
+            &mut summary
+        ));
+
+        assert_eq!(
+            state.submitted_calls(),
+            vec![RuntimeCall::ProofOfLocation(
+                Call::publish_rssi_data_batch {
+                    readings: vec![(neighbor, -55, None)].try_into().unwrap(),
+                }
+            )]
+        );
+        assert_eq!(summary.skipped_unregistered, 0);
+    });
+}
+
+#[test]
+fn offchain_worker_without_key_sends_nothing() {
+    let (mut ext, state) = new_offchain_ext(false);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+
+        // No request is expected, any request would fail the test
+        ProofOfLocation::offchain_worker(1);
+
+        assert!(state.submitted_calls().is_empty());
+        assert_eq!(
+            run_summary(1),
+            Some(RunSummary {
+                block: 1,
+                error: Some(b"No signing keys available".to_vec()),
+                ..Default::default()
+            })
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_skips_block_while_earlier_run_holds_lock() {
+    use sp_runtime::offchain::storage_lock::{BlockAndTime, StorageLock};
+
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+
+        // The run of block 1 is still fetching when block 2 is imported
+        let mut lock = StorageLock::<BlockAndTime<System>>::with_block_and_time_deadline(
+            crate::OFFCHAIN_LOCK_KEY,
+            3,
+            Duration::from_millis(60_000),
+        );
+        let _guard = lock.try_lock().expect("Lock is free");
+
+        // No request is expected, any request would fail the test
+        System::set_block_number(2);
+        ProofOfLocation::offchain_worker(2);
+
+        assert!(state.submitted_calls().is_empty());
+        assert_eq!(run_summary(2), None);
+    });
+}
+
+#[test]
+fn offchain_worker_gives_up_on_unusable_responses() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+        let mut summary = RunSummary::default();
+
+        // A body that cannot be decoded is not retried and there is no fallback server
+        state.expect_get("/rssi", b"not a response".to_vec());
+        assert_eq!(
+            ProofOfLocation::fetch_rssi_and_submit(1, deadline, &mut summary),
+            Err("Failed to fetch RSSI data from server")
+        );
+
+        // Neither is a response older than `MaxResponseAgeMillis`
+        let stale = RssiResponse {
+            devices: vec![DeviceRssi {
+                address: NEIGHBOR_ADDRESS,
+                rssi: -60,
+                tx_power: None,
+                timestamp: NOW,
+            }],
+            timestamp: NOW - MaxResponseAgeMillis::get() - 1,
+            sequence: None,
+        };
+        state.expect_get("/rssi", stale.encode());
+        assert_eq!(
+            ProofOfLocation::fetch_rssi_and_submit(1, deadline, &mut summary),
+            Err("Failed to fetch RSSI data from server")
+        );
+
+        assert!(state.submitted_calls().is_empty());
+        assert_eq!(summary, RunSummary::default());
+    });
+}
+
+#[test]
+fn offchain_worker_retries_then_falls_back_to_next_server() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        FallbackServerUrls::set(FALLBACK_SERVER_URLS);
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+
+        // The primary server fails the first attempt and all `HttpRetries` retries
+        for _ in 0..=HttpRetries::get() {
+            state.expect_failed_get(&format!("{}/rssi", SERVER), 503);
+        }
+        state.offchain.write().expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: format!("{}/rssi", FALLBACK_SERVER),
+            headers: vec![("X-Node-ID".into(), state.node_id())],
+            response: Some(rssi_response(Vec::new())),
+            sent: true,
+            ..Default::default()
+        });
+        let mut summary = RunSummary::default();
+        assert_ok!(ProofOfLocation::fetch_rssi_and_submit(
+            1,
+            deadline,
+            &mut summary
+        ));
+
+        // Backing off `HttpRetryBackoffMillis` before the first retry and twice as long before
+        // the second
+        let backoff = HttpRetryBackoffMillis::get();
+        assert_eq!(
+            state.offchain.read().timestamp,
+            Timestamp::from_unix_millis(NOW + 3 * backoff)
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_stops_retrying_at_run_deadline() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let backoff = HttpRetryBackoffMillis::get();
+        let mut summary = RunSummary::default();
+
+        // Only the first retry starts before the deadline, the second one would not
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(2 * backoff));
+        state.expect_failed_get(&format!("{}/rssi", SERVER), 503);
+        state.expect_failed_get(&format!("{}/rssi", SERVER), 503);
+        assert_eq!(
+            ProofOfLocation::fetch_rssi_and_submit(1, deadline, &mut summary),
+            Err("Failed to fetch RSSI data from server")
+        );
+        assert_eq!(
+            state.offchain.read().timestamp,
+            Timestamp::from_unix_millis(NOW + backoff)
+        );
+
+        // Nothing is sent once the deadline has passed, any request would fail the test
+        let deadline = sp_io::offchain::timestamp();
+        assert_eq!(
+            ProofOfLocation::fetch_rssi_and_submit(1, deadline, &mut summary),
+            Err("Failed to fetch RSSI data from server")
+        );
+
+        assert!(state.submitted_calls().is_empty());
+        assert_eq!(summary, RunSummary::default());
+    });
+}
+
+#[test]
+fn offchain_worker_rejects_oversized_responses() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+        let mut summary = RunSummary::default();
+
+        // A body larger than `MaxResponseBytes` is dropped while reading and not retried
+        let devices = vec![
+            DeviceRssi {
+                address: NEIGHBOR_ADDRESS,
+                rssi: -60,
+                tx_power: None,
+                timestamp: NOW,
+            };
+            200
+        ];
+        let body = rssi_response(devices);
+        assert!(body.len() > MaxResponseBytes::get() as usize);
+        state.expect_get("/rssi", body);
+        assert_eq!(
+            ProofOfLocation::fetch_rssi_and_submit(1, deadline, &mut summary),
+            Err("Failed to fetch RSSI data from server")
+        );
+
+        // So is a body announced to be too large, without reading it
+        state.offchain.write().expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: format!("{}/rssi", SERVER),
+            headers: vec![("X-Node-ID".into(), state.node_id())],
+            response_headers: vec![("content-length".into(), "1000000".into())],
+            response: Some(rssi_response(Vec::new())),
+            sent: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            ProofOfLocation::fetch_rssi_and_submit(1, deadline, &mut summary),
+            Err("Failed to fetch RSSI data from server")
+        );
+
+        assert!(state.submitted_calls().is_empty());
+        assert_eq!(summary, RunSummary::default());
+    });
+}
+
+#[test]
+fn offchain_worker_skips_readings_within_hysteresis() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let neighbor = register_neighbor();
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+
+        // Close to the RSSI estimated from the registered locations, so no misbehavior is reported
+        let run = |block: u64, rssi: i16| {
+            let mut summary = RunSummary::default();
+            state.expect_get(
+                "/rssi",
+                rssi_response(vec![DeviceRssi {
+                    address: NEIGHBOR_ADDRESS,
+                    rssi,
+                    tx_power: None,
+                    timestamp: NOW,
+                }]),
+            );
+            assert_ok!(ProofOfLocation::fetch_rssi_and_submit(
+                block,
+                deadline,
+                &mut summary
+            ));
+            (state.submitted_calls(), summary)
+        };
+        let batch = |rssi: i16| {
+            vec![RuntimeCall::ProofOfLocation(
+                Call::publish_rssi_data_batch {
+                    readings: vec![(neighbor.clone(), rssi, None)].try_into().unwrap(),
+                },
+            )]
+        };
+
+        let (calls, _) = run(1, -10);
+        assert_eq!(calls, batch(-10));
+
+        // Within `RssiHysteresis` of the submitted reading
+        let (calls, summary) = run(2, -12);
+        assert!(calls.is_empty());
+        assert_eq!(summary.skipped_unchanged, 1);
+
+        // Changed by more than `RssiHysteresis`
+        let (calls, _) = run(3, -13);
+        assert_eq!(calls, batch(-13));
+
+        // Unchanged, but due again after `RssiResubmitInterval`
+        let (calls, summary) = run(3 + RssiResubmitInterval::get(), -13);
+        assert_eq!(calls, batch(-13));
+        assert_eq!(summary.skipped_unchanged, 0);
+    });
+}
+
+#[test]
+fn offchain_worker_reports_neighbor_contradicting_its_location() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let neighbor = register_neighbor();
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+        let streak_key = [crate::MISBEHAVIOR_STREAK_KEY, &neighbor.encode()].concat();
+
+        // Far weaker than the RSSI estimated for a neighbor ~0.11 meters away
+        let run = |block: u64, rssi: i16| {
+            let mut summary = RunSummary::default();
+            state.expect_get(
+                "/rssi",
+                rssi_response(vec![DeviceRssi {
+                    address: NEIGHBOR_ADDRESS,
+                    rssi,
+                    tx_power: None,
+                    timestamp: NOW,
+                }]),
+            );
+            assert_ok!(ProofOfLocation::fetch_rssi_and_submit(
+                block,
+                deadline,
+                &mut summary
+            ));
+            state.submitted_calls()
+        };
+        let batch = |rssi: i16| {
+            RuntimeCall::ProofOfLocation(Call::publish_rssi_data_batch {
+                readings: vec![(neighbor.clone(), rssi, None)].try_into().unwrap(),
+            })
+        };
+
+        // Suspicious, but not yet in `MisbehaviorRuns` consecutive runs
+        let runs = MisbehaviorRuns::get() as u64;
+        for block in 1..runs {
+            let rssi = -60 - 5 * block as i16;
+            assert_eq!(run(block, rssi), vec![batch(rssi)]);
+        }
+
+        // Reported along with the reading once the streak is long enough
+        let rssi = -60 - 5 * runs as i16;
+        let calls = run(runs, rssi);
+        assert_eq!(calls.len(), 2);
+        assert!(matches!(
+            &calls[0],
+            RuntimeCall::ProofOfLocation(Call::report_misbehavior { offender, rssi_error })
+                if *offender == neighbor
+                    && rssi_error.unsigned_abs() > MisbehaviorThreshold::get()
+        ));
+        assert_eq!(calls[1], batch(rssi));
+
+        // The streak starts over after the report
+        assert_eq!(
+            sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &streak_key),
+            None
+        );
+        let rssi = -60 - 5 * (runs + 1) as i16;
+        assert_eq!(run(runs + 1, rssi), vec![batch(rssi)]);
+        assert_eq!(
+            sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, &streak_key),
+            Some(1u32.encode())
+        );
+    });
+}
+
+#[test]
+fn offchain_worker_skips_unchanged_readings_until_resubmit_interval() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        let neighbor = register_neighbor();
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+
+        let run = |block: u64| {
+            let mut summary = RunSummary::default();
+            state.expect_get(
+                "/rssi",
+                rssi_response(vec![DeviceRssi {
+                    address: NEIGHBOR_ADDRESS,
+                    rssi: -10,
+                    tx_power: None,
+                    timestamp: NOW,
+                }]),
+            );
+            assert_ok!(ProofOfLocation::fetch_rssi_and_submit(
+                block,
+                deadline,
+                &mut summary
+            ));
+            (state.submitted_calls(), summary)
+        };
+        let batch = vec![RuntimeCall::ProofOfLocation(
+            Call::publish_rssi_data_batch {
+                readings: vec![(neighbor, -10, None)].try_into().unwrap(),
+            },
+        )];
+
+        let (calls, summary) = run(1);
+        assert_eq!(calls, batch);
+        assert!(!summary.unchanged);
+
+        // Same readings before `RssiResubmitInterval` has passed
+        let (calls, summary) = run(RssiResubmitInterval::get());
+        assert!(calls.is_empty());
+        assert!(summary.unchanged);
+        assert_eq!(summary.submitted, 0);
+
+        // Same readings, but due again
+        let (calls, summary) = run(1 + RssiResubmitInterval::get());
+        assert_eq!(calls, batch);
+        assert!(!summary.unchanged);
+        assert_eq!(summary.submitted, 1);
+    });
+}
+
+#[test]
+fn offchain_worker_skips_readings_differing_only_in_timestamp() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(state.account()),
+            ADDRESS,
+            LATITUDE,
+            LONGITUDE
+        ));
+        register_neighbor();
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+
+        let run = |block: u64, timestamp: u64| {
+            let mut summary = RunSummary::default();
+            state.expect_get(
+                "/rssi",
+                rssi_response(vec![DeviceRssi {
+                    address: NEIGHBOR_ADDRESS,
+                    rssi: -10,
+                    tx_power: Some(4),
+                    timestamp,
+                }]),
+            );
+            assert_ok!(ProofOfLocation::fetch_rssi_and_submit(
+                block,
+                deadline,
+                &mut summary
+            ));
+            (state.submitted_calls(), summary)
+        };
+
+        let (calls, summary) = run(1, NOW - 2_000);
+        assert_eq!(calls.len(), 1);
+        assert!(!summary.unchanged);
+
+        // The same reading sampled again is not a change
+        let (calls, summary) = run(2, NOW - 1_000);
+        assert!(calls.is_empty());
+        assert!(summary.unchanged);
+    });
+}
+
+#[test]
+fn offchain_worker_refuses_location_contradicting_neighbors() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+
+        // Three neighbors about a kilometer north of the location the server claims
+        let mut devices = Vec::new();
+        for (i, (latitude, longitude)) in [
+            (LATITUDE + 9_045, LONGITUDE),
+            (LATITUDE + 9_000, LONGITUDE + 57),
+            (LATITUDE + 8_970, LONGITUDE - 40),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let address = [0x20, 0x00, 0x00, 0x00, 0x00, i as u8];
+            assert_ok!(ProofOfLocation::register_node(
+                RuntimeOrigin::signed(AccountId32::new([3 + i as u8; 32])),
+                address,
+                latitude,
+                longitude
+            ));
+            devices.push(DeviceRssi {
+                address,
+                rssi: -70,
+                tx_power: None,
+                timestamp: NOW,
+            });
+        }
+
+        state.expect_get("/location", location_response());
+        state.expect_get("/rssi", rssi_response(devices));
+        let mut summary = RunSummary::default();
+        assert_eq!(
+            ProofOfLocation::fetch_rssi_and_submit(1, deadline, &mut summary),
+            Err("Claimed location contradicts the RSSI of registered neighbors")
+        );
+
+        // Neither the registration nor any reading is submitted
+        assert!(state.submitted_calls().is_empty());
+        assert_eq!(summary.fetched, 3);
+    });
+}
+
+#[test]
+fn offchain_worker_fetches_location_from_location_server() {
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(ProofOfLocation::set_location_server_config(
+            RuntimeOrigin::signed(state.account()),
+            Some(b"gps.local:4000".to_vec())
+        ));
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(60_000));
+
+        // Only `/location` goes to the location server
+        state.offchain.write().expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: "http://gps.local:4000/location".into(),
+            headers: vec![("X-Node-ID".into(), state.node_id())],
+            response: Some(location_response()),
+            sent: true,
+            ..Default::default()
+        });
+        state.expect_get("/rssi", rssi_response(Vec::new()));
+        let mut summary = RunSummary::default();
+        assert_ok!(ProofOfLocation::fetch_rssi_and_submit(
+            1,
+            deadline,
+            &mut summary
+        ));
+
+        assert_eq!(
+            state.submitted_calls(),
+            vec![RuntimeCall::ProofOfLocation(Call::register_node {
+                address: ADDRESS,
+                latitude: LATITUDE,
+                longitude: LONGITUDE,
+            })]
+        );
+    });
+}
+
+#[test]
+fn sign_challenge_verifies_with_the_node_key() {
+    use sp_core::{sr25519, Pair};
+
+    let (mut ext, state) = new_offchain_ext(true);
+    ext.execute_with(|| {
+        let nonce = "4e6f6e6365";
+        let signature = ProofOfLocation::sign_challenge(nonce).expect("Key is in the keystore");
+
+        let bytes: Vec<u8> = (2..signature.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&signature[i..i + 2], 16).unwrap())
+            .collect();
+        let signature = sr25519::Signature::decode(&mut &bytes[..]).unwrap();
+        let public = sr25519::Public::from_raw(state.account().into());
+
+        assert!(sr25519::Pair::verify(
+            &signature,
+            crate::util::challenge_message(nonce.as_bytes()),
+            &public
+        ));
+        assert!(!sr25519::Pair::verify(
+            &signature,
+            crate::util::challenge_message(b"another nonce"),
+            &public
+        ));
+    });
+}
+
+#[test]
+fn sign_challenge_without_key_gives_nothing() {
+    let (mut ext, _state) = new_offchain_ext(false);
+    ext.execute_with(|| {
+        assert_eq!(ProofOfLocation::sign_challenge("4e6f6e6365"), None);
+    });
+}
+
+#[test]
+fn require_https_upgrades_bare_urls_and_skips_plain_http() {
+    let (mut ext, _state) = new_offchain_ext(false);
+    ext.execute_with(|| {
+        assert_eq!(
+            ProofOfLocation::with_fallback_urls(b"localhost:3000".to_vec()),
+            vec![SERVER.to_string()]
+        );
+
+        RequireHttps::set(true);
+        assert_eq!(
+            ProofOfLocation::with_fallback_urls(b"localhost:3000".to_vec()),
+            vec!["https://localhost:3000".to_string()]
+        );
+        assert_eq!(
+            ProofOfLocation::with_fallback_urls(b"https://gateway.example.com/".to_vec()),
+            vec!["https://gateway.example.com".to_string()]
+        );
+        assert!(ProofOfLocation::with_fallback_urls(SERVER.as_bytes().to_vec()).is_empty());
+    });
+}
@@ -3,11 +3,18 @@ use frame_support::pallet_macros::*;
 /// A [`pallet_section`] that defines the offchain worker for the pallet.
 #[pallet_section]
 mod offchain {
-    use crate::util::{LocationResponse, RssiResponse};
+    use crate::util::{LocationResponse, RssiResponse, ServerResponse, PATH_LOSS_EXPONENT_RANGE};
 
     extern crate alloc;
     use alloc::string::String;
-    use alloc::vec::Vec;
+
+    /// Blocks after which the lock of a stalled offchain worker run expires.
+    const LOCK_BLOCK_EXPIRATION: u32 = 3;
+
+    /// Milliseconds the lock of a stalled offchain worker run outlives `OffchainRunBudgetMillis`.
+    ///
+    /// Leaves time to sign and submit transactions after the last HTTP request.
+    const LOCK_TIMEOUT_MARGIN: u64 = 5_000;
 
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
@@ -16,26 +23,235 @@ mod offchain {
         /// This function will be called when the node is fully synced and a new best block is
         /// successfully imported.
         fn offchain_worker(block_number: BlockNumberFor<T>) {
+            use crate::util::RunSummary;
+            use sp_runtime::offchain::{
+                storage_lock::{BlockAndTime, StorageLock},
+                Duration,
+            };
+
+            let run_budget = T::OffchainRunBudgetMillis::get();
+
             log::info!("Offchain worker started at block: {:?}", block_number);
 
+            // Skip this block while the run of an earlier block is still fetching or submitting,
+            // so the same measurements are not submitted twice
+            let mut lock =
+                StorageLock::<BlockAndTime<frame_system::Pallet<T>>>::with_block_and_time_deadline(
+                    crate::OFFCHAIN_LOCK_KEY,
+                    LOCK_BLOCK_EXPIRATION,
+                    Duration::from_millis(run_budget.saturating_add(LOCK_TIMEOUT_MARGIN)),
+                );
+            let Ok(_guard) = lock.try_lock() else {
+                log::info!("Offchain worker of an earlier block is still running, skipping");
+                return;
+            };
+
+            // HTTP requests of this run must finish within the run budget
+            let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(run_budget));
+
             // Call the function that fetches RSSI data and submits transactions
-            if let Err(e) = Self::fetch_rssi_and_submit(block_number) {
+            let mut summary = RunSummary {
+                block: block_number.saturated_into(),
+                ..Default::default()
+            };
+            if let Err(e) = Self::fetch_rssi_and_submit(block_number, deadline, &mut summary) {
                 log::error!("Error in offchain worker: {:?}", e);
+                summary.error = Some(e.as_bytes().to_vec());
+            }
+            Self::record_run_summary(&summary);
+
+            // Let the server know whether its data reached the chain
+            if let Err(e) = Self::post_status(block_number, deadline) {
+                log::warn!("Failed to post node status: {:?}", e);
             }
         }
+
+        /// Validate the runtime's configuration constants.
+        ///
+        /// A misconfigured runtime would otherwise silently reject every RSSI report or produce
+        /// meaningless trust scores, so it is caught when the runtime's tests are run instead.
+        fn integrity_test() {
+            // A zero distance would reject every report
+            assert!(
+                T::MaxDistance::get() > 0,
+                "`MaxDistance` must be greater than zero"
+            );
+
+            let path_loss_exponent = T::PathLossExponent::get();
+            assert!(
+                PATH_LOSS_EXPONENT_RANGE.contains(&path_loss_exponent),
+                "`PathLossExponent` must be between 10 and 60 (1.0 to 6.0)"
+            );
+
+            // The received power at 1 meter is always below 0 dBm
+            assert!(
+                T::ReferenceRssi::get() < 0,
+                "`ReferenceRssi` must be negative"
+            );
+
+            assert!(
+                T::MaxPruneReports::get() > 0,
+                "`MaxPruneReports` must be greater than zero"
+            );
+            assert!(
+                T::MaxNodes::get() >= 2,
+                "`MaxNodes` must allow at least two nodes"
+            );
+            assert!(
+                T::MaxReportsPerNode::get() > 0,
+                "`MaxReportsPerNode` must be greater than zero"
+            );
+
+            // Scoring a full block must fit in the execution time of a block
+            let scoring_weight = T::WeightInfo::calculate_all_trust_scores(
+                T::MaxNodes::get(),
+                T::MaxReportsPerNode::get(),
+            );
+            let max_block = <T as frame_system::Config>::BlockWeights::get().max_block;
+            assert!(
+                scoring_weight.ref_time() <= max_block.ref_time(),
+                "Trust score calculation for `MaxNodes` and `MaxReportsPerNode` exceeds the block weight"
+            );
+
+            assert!(
+                T::MaxBatchSize::get() > 0,
+                "`MaxBatchSize` must be greater than zero"
+            );
+            assert!(
+                T::MaxNodes::get() > T::MaxBatchSize::get(),
+                "`MaxNodes` must be greater than `MaxBatchSize`"
+            );
+
+            // A full batch must fit in the execution time of a block
+            let batch_weight = T::WeightInfo::publish_rssi_data_batch(
+                T::MaxNodes::get(),
+                T::MaxBatchSize::get(),
+                T::MaxReportsPerNode::get() - 1,
+            );
+            assert!(
+                batch_weight.ref_time() <= max_block.ref_time(),
+                "A batch of `MaxBatchSize` RSSI readings exceeds the block weight"
+            );
+
+            assert!(
+                T::EpochLength::get() > 0u32.into(),
+                "`EpochLength` must be greater than zero"
+            );
+            assert!(
+                T::NodeExpiry::get() > 0u32.into(),
+                "`NodeExpiry` must be greater than zero"
+            );
+            assert!(
+                T::MisbehaviorRuns::get() > 0,
+                "`MisbehaviorRuns` must be greater than zero"
+            );
+            assert!(
+                T::HttpTimeoutMillis::get() > 0,
+                "`HttpTimeoutMillis` must be greater than zero"
+            );
+            assert!(
+                T::OffchainRunBudgetMillis::get() >= T::HttpTimeoutMillis::get(),
+                "`OffchainRunBudgetMillis` must cover at least one `HttpTimeoutMillis` request"
+            );
+            assert!(
+                T::MaxResponseBytes::get() > 0,
+                "`MaxResponseBytes` must be greater than zero"
+            );
+            assert!(
+                T::RssiResubmitInterval::get() > 0u32.into(),
+                "`RssiResubmitInterval` must be greater than zero"
+            );
+            assert!(
+                T::LocationRefreshInterval::get() > 0u32.into(),
+                "`LocationRefreshInterval` must be greater than zero"
+            );
+
+            // Reports of an epoch must outlive the epoch so it can be rolled up
+            assert!(
+                T::RssiRetention::get() > T::EpochLength::get(),
+                "`RssiRetention` must be longer than `EpochLength`"
+            );
+
+            // A node must be able to correct its location while the reports contradicting it are
+            // still kept
+            assert!(
+                T::UpdateCooldown::get() < T::RssiRetention::get(),
+                "`UpdateCooldown` must be shorter than `RssiRetention`"
+            );
+
+            // Unchanged readings must still be reported in every epoch
+            assert!(
+                T::RssiResubmitInterval::get() <= T::EpochLength::get(),
+                "`RssiResubmitInterval` must not be longer than `EpochLength`"
+            );
+
+            // Rolling up an epoch must fit in the execution time of a block
+            let rollup_weight = T::WeightInfo::rollup_trust_score(Self::max_epoch_reports());
+            assert!(
+                rollup_weight.ref_time() <= max_block.ref_time(),
+                "Trust score rollup for `EpochLength` and `MaxReportsPerNode` exceeds the block weight"
+            );
+        }
+
+        /// Record the number of registered nodes before a runtime upgrade.
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            Ok((AccountData::<T>::iter_keys().count() as u32).encode())
+        }
+
+        /// Check that no registered node was lost and the storage is still consistent after a
+        /// runtime upgrade.
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            let node_count = u32::decode(&mut &state[..])
+                .map_err(|_| "Failed to decode the node count recorded before the upgrade")?;
+            ensure!(
+                AccountData::<T>::count() == node_count,
+                "Registered nodes were lost during the upgrade"
+            );
+
+            Self::do_try_state()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn try_state(_n: BlockNumberFor<T>) -> Result<(), sp_runtime::TryRuntimeError> {
+            Self::do_try_state()
+        }
     }
 
     impl<T: Config> Pallet<T> {
+        /// Get the account of the first `AuthorityId` key in the keystore
+        ///
+        /// The key scheme (`sr25519`, `ed25519` or `ecdsa`) is the one selected by
+        /// `Config::AuthorityId`.
+        fn local_account() -> Option<T::AccountId> {
+            use frame_system::offchain::AppCrypto;
+            use frame_system::offchain::SigningTypes;
+            use sp_runtime::{traits::IdentifyAccount, RuntimeAppPublic};
+
+            type RuntimeAppPublicOf<T> = <<T as Config>::AuthorityId as AppCrypto<
+                <T as SigningTypes>::Public,
+                <T as SigningTypes>::Signature,
+            >>::RuntimeAppPublic;
+            type GenericPublicOf<T> = <<T as Config>::AuthorityId as AppCrypto<
+                <T as SigningTypes>::Public,
+                <T as SigningTypes>::Signature,
+            >>::GenericPublic;
+
+            // Get the public keys from the keystore using the KEY_TYPE
+            let key = RuntimeAppPublicOf::<T>::all().into_iter().next()?;
+
+            // Convert the public key to an account the same way the transaction signer does
+            let public: T::Public = GenericPublicOf::<T>::from(key).into();
+            Some(public.into_account())
+        }
+
         /// Get the node identifier (account ID) as a hex string
         /// This retrieves the public key from the keystore
         fn get_node_identifier() -> Result<String, &'static str> {
-            // Get the public keys from the keystore using the KEY_TYPE
-            let keys = sp_io::crypto::sr25519_public_keys(crate::KEY_TYPE);
-
-            if let Some(key) = keys.first() {
-                // Convert the public key to hex string
-                let key_bytes: &[u8] = key.as_ref();
-                let hex_string = Self::bytes_to_hex(key_bytes);
+            if let Some(account_id) = Self::local_account() {
+                // Convert the account ID to hex string
+                let hex_string = Self::bytes_to_hex(&account_id.encode());
                 Ok(hex_string)
             } else {
                 log::warn!("No signing keys available, using default node identifier");
@@ -65,44 +281,140 @@ mod offchain {
             }
         }
 
-        /// Get the server base URL for the current account
-        /// Returns the configured URL or falls back to default configuration
-        fn get_server_base_url() -> Result<String, sp_runtime::offchain::http::Error> {
-            use codec::Decode;
+        /// Get the server base URLs for the current account, in the order they are tried
+        ///
+        /// The account's `ServerConfig` (or the default `ServerUrl`) comes first, followed by
+        /// the `FallbackServerUrls`.
+        fn get_server_base_urls() -> Result<Vec<String>, sp_runtime::offchain::http::Error> {
             use sp_runtime::offchain::http;
 
-            // Get signing keys to determine account ID
-            let keys = sp_io::crypto::sr25519_public_keys(crate::KEY_TYPE);
-
-            if let Some(key) = keys.first() {
-                // Convert public key to AccountId
-                let account_id = T::AccountId::decode(&mut &key.encode()[..])
-                    .map_err(|_| http::Error::Unknown)?;
-
-                // Try to get account-specific configuration from on-chain storage
-                if let Some(server_url_bounded) = ServerConfig::<T>::get(&account_id) {
-                    let server_url = server_url_bounded.to_vec();
-                    let url_str =
-                        alloc::str::from_utf8(&server_url).map_err(|_| http::Error::Unknown)?;
-                    log::info!("Using account-specific server config: {}", url_str);
-                    Ok(alloc::format!("http://{}", url_str))
-                } else {
-                    // Fall back to default configuration
-                    let default_url = T::ServerUrl::get();
-                    let url_str =
-                        alloc::str::from_utf8(default_url).map_err(|_| http::Error::Unknown)?;
-                    log::info!("Using default server config: {}", url_str);
-                    Ok(alloc::format!("http://{}", url_str))
+            // Get signing key to determine account ID
+            let Some(account_id) = Self::local_account() else {
+                log::error!("No signing account available");
+                return Err(http::Error::Unknown);
+            };
+
+            Ok(Self::with_fallback_urls(Self::rssi_server_url(&account_id)))
+        }
+
+        /// Get the base URLs of the server providing the GPS location, in the order they are
+        /// tried
+        ///
+        /// The account's `LocationServerConfig` (or the default `LocationServerUrl`) comes
+        /// first, followed by the `FallbackServerUrls`. Without either, the location is fetched
+        /// from the same servers as the RSSI readings.
+        fn get_location_server_base_urls() -> Result<Vec<String>, sp_runtime::offchain::http::Error>
+        {
+            use sp_runtime::offchain::http;
+
+            let Some(account_id) = Self::local_account() else {
+                log::error!("No signing account available");
+                return Err(http::Error::Unknown);
+            };
+
+            // Use the account-specific configuration, then the node's local one, then the default
+            let server_url = LocationServerConfig::<T>::get(&account_id)
+                .map(|server_url| server_url.into_inner())
+                .or_else(|| {
+                    sp_io::offchain::local_storage_get(
+                        sp_core::offchain::StorageKind::PERSISTENT,
+                        crate::LOCATION_SERVER_URL_KEY,
+                    )
+                })
+                .or_else(|| T::LocationServerUrl::get().map(|server_url| server_url.to_vec()))
+                .unwrap_or_else(|| Self::rssi_server_url(&account_id));
+
+            Ok(Self::with_fallback_urls(server_url))
+        }
+
+        /// URL of the server providing the RSSI readings of `account_id`
+        fn rssi_server_url(account_id: &T::AccountId) -> Vec<u8> {
+            // Use the account-specific configuration, then the node's local one, then the default
+            match ServerConfig::<T>::get(account_id) {
+                Some(server_url) => server_url.into_inner(),
+                None => sp_io::offchain::local_storage_get(
+                    sp_core::offchain::StorageKind::PERSISTENT,
+                    crate::SERVER_URL_KEY,
+                )
+                .unwrap_or_else(|| T::ServerUrl::get().to_vec()),
+            }
+        }
+
+        /// `server_url` followed by the `FallbackServerUrls`, as base URLs with a scheme and
+        /// without duplicates
+        pub(crate) fn with_fallback_urls(server_url: Vec<u8>) -> Vec<String> {
+            let mut base_urls: Vec<String> = Vec::new();
+            let fallback_urls = T::FallbackServerUrls::get().iter().copied();
+            for url in core::iter::once(&server_url[..]).chain(fallback_urls) {
+                let Ok(url_str) = alloc::str::from_utf8(url) else {
+                    log::warn!("Skipping server URL that is not valid UTF-8");
+                    continue;
+                };
+                let Some(base_url) = Self::with_scheme(url_str) else {
+                    log::warn!(
+                        "Skipping plain HTTP server URL {}, HTTPS is required",
+                        url_str
+                    );
+                    continue;
+                };
+                if !base_urls.contains(&base_url) {
+                    base_urls.push(base_url);
                 }
+            }
+
+            log::info!("Using server config: {:?}", base_urls);
+            base_urls
+        }
+
+        /// Keep an explicit scheme, default to plain HTTP for `host:port`, or to HTTPS if
+        /// `RequireHttps` is set
+        ///
+        /// Returns `None` for an `http://` URL while `RequireHttps` is set.
+        fn with_scheme(url_str: &str) -> Option<String> {
+            let url_str = url_str.trim_end_matches('/');
+            let require_https = T::RequireHttps::get();
+            if url_str.starts_with("https://") {
+                Some(String::from(url_str))
+            } else if url_str.starts_with("http://") {
+                (!require_https).then(|| String::from(url_str))
+            } else if require_https {
+                Some(alloc::format!("https://{}", url_str))
             } else {
-                log::error!("No signing account available");
-                Err(http::Error::Unknown)
+                Some(alloc::format!("http://{}", url_str))
+            }
+        }
+
+        /// Add an `Authorization: Bearer` header if a token is kept in persistent offchain
+        /// storage under `AUTH_TOKEN_KEY`
+        fn add_auth_header<B>(
+            request: sp_runtime::offchain::http::Request<'_, B>,
+        ) -> sp_runtime::offchain::http::Request<'_, B> {
+            let token = sp_io::offchain::local_storage_get(
+                sp_core::offchain::StorageKind::PERSISTENT,
+                crate::AUTH_TOKEN_KEY,
+            );
+
+            match token.as_deref().map(alloc::str::from_utf8) {
+                Some(Ok(token)) => {
+                    request.add_header("Authorization", &alloc::format!("Bearer {}", token))
+                }
+                Some(Err(_)) => {
+                    log::warn!("Auth token is not valid UTF-8, sending request without it");
+                    request
+                }
+                None => request,
             }
         }
 
         /// Fetch RSSI data from the bluetooth server and submit signed transactions
-        pub fn fetch_rssi_and_submit(_block_number: BlockNumberFor<T>) -> Result<(), &'static str> {
-            use codec::{Decode, Encode};
+        ///
+        /// No HTTP request is sent or waited for after `deadline`. What happened to the readings
+        /// is counted in `summary`.
+        pub fn fetch_rssi_and_submit(
+            block_number: BlockNumberFor<T>,
+            deadline: sp_runtime::offchain::Timestamp,
+            summary: &mut crate::util::RunSummary,
+        ) -> Result<(), &'static str> {
             use frame_system::offchain::{SendSignedTransaction, Signer};
 
             // Get the signer
@@ -113,21 +425,34 @@ mod offchain {
             }
 
             // Get the account ID from the signing key to check registration status
-            let keys = sp_io::crypto::sr25519_public_keys(crate::KEY_TYPE);
-            let account_id = if let Some(key) = keys.first() {
-                T::AccountId::decode(&mut &key.encode()[..])
-                    .map_err(|_| "Failed to decode account ID")?
-            } else {
-                return Err("No signing keys available");
-            };
+            let account_id = Self::local_account().ok_or("No signing keys available")?;
 
             // Check if this node has already registered by checking AccountData storage
             let is_registered = AccountData::<T>::contains_key(&account_id);
 
-            // If the node is not registered, first register it
-            if !is_registered {
-                let location_response = Self::fetch_location_from_server()
+            // If the node is not registered, fetch the location to register it with
+            let location_response = if !is_registered {
+                let location_response = Self::fetch_location_from_server(deadline)
                     .map_err(|_| "Failed to fetch location data from server")?;
+                Some(location_response)
+            } else {
+                if block_number % T::LocationRefreshInterval::get() == 0u32.into() {
+                    // A failed refresh must not hold back the RSSI reports
+                    if let Err(e) = Self::refresh_location(&account_id, block_number, deadline) {
+                        log::warn!("Failed to refresh location: {:?}", e);
+                    }
+                }
+                None
+            };
+
+            // Fetch RSSI data from the server
+            let rssi_response = Self::fetch_rssi_from_server(deadline)
+                .map_err(|_| "Failed to fetch RSSI data from server")?;
+            summary.fetched = rssi_response.devices.len() as u32;
+
+            // Register once the claimed location agrees with the neighbors in radio range
+            if let Some(location_response) = location_response {
+                Self::check_claimed_location(&location_response, &rssi_response)?;
 
                 // Submit location data
                 Self::submit_location_data(location_response)?;
@@ -135,119 +460,706 @@ mod offchain {
                 log::info!("Node registration complete");
             }
 
-            // Fetch RSSI data from the server
-            let rssi_response = Self::fetch_rssi_from_server()
-                .map_err(|_| "Failed to fetch RSSI data from server")?;
+            // Skip the submission if the readings have not changed since the last one, unless
+            // they are due to be reported again. Only the readings are hashed, the timestamps of
+            // the samples change with every response.
+            let payload: Vec<([u8; 6], i16, Option<i16>)> = rssi_response
+                .devices
+                .iter()
+                .map(|device| (device.address, device.rssi, device.tx_power))
+                .collect();
+            let payload_hash = sp_io::hashing::blake2_256(&payload.encode());
+            if let Some((last_block, last_hash)) = Self::last_submission() {
+                let resubmit_at = last_block.saturating_add(T::RssiResubmitInterval::get());
+                if last_hash == payload_hash && block_number < resubmit_at {
+                    log::info!(
+                        "RSSI data unchanged since block {:?}, skipping submission",
+                        last_block
+                    );
+                    summary.unchanged = true;
+                    return Ok(());
+                }
+            }
 
-            // Submit a signed transaction for each device
+            // Map Bluetooth addresses and Wi-Fi BSSIDs to accounts
+            let mut readings: Vec<(T::AccountId, i16, Option<i16>)> = Vec::new();
             for device in rssi_response.devices.iter() {
-                // Map bluetooth address to account
-                let account = match AddressRegistrationData::<T>::get(device.address) {
-                    Some(account_id) => account_id,
+                let neighbor = match Self::account_of_address(device.address) {
+                    Some(neighbor) => neighbor,
                     None => {
-                        log::warn!(
-                            "Bluetooth address {:?} not registered, skipping",
-                            device.address
-                        );
+                        log::warn!("Address {:?} not registered, skipping", device.address);
+                        summary.skipped_unregistered += 1;
                         continue;
                     }
                 };
 
-                let call = Call::publish_rssi_data {
-                    neighbor: account,
-                    rssi: device.rssi,
+                // Leave out readings the chain rejects, e.g. of neighbors beyond `MaxDistance`,
+                // since a single invalid reading would reject the whole batch and still cost
+                // fees. The report limit depends on the block the transaction ends up in, so it
+                // is left to the chain.
+                let valid =
+                    <Self as RssiIngestion<T>>::validate_reading(device.rssi, device.tx_power)
+                        .and_then(|()| {
+                            <Self as RssiIngestion<T>>::validate_pair(&account_id, &neighbor)
+                        });
+                if let Err(e) = valid {
+                    log::warn!("RSSI reading of {:?} rejected, skipping: {:?}", neighbor, e);
+                    summary.skipped_invalid += 1;
+                    continue;
+                }
+
+                readings.push((neighbor, device.rssi, device.tx_power));
+            }
+
+            // Report neighbors whose readings keep contradicting their registered locations
+            for (offender, rssi_error) in Self::audit_readings(&account_id, &readings) {
+                let call = Call::report_misbehavior {
+                    offender,
+                    rssi_error,
                 };
+                let results = signer.send_signed_transaction(|_account| call.clone());
+                for (_, result) in &results {
+                    if let Err(e) = result {
+                        log::error!("Failed to submit misbehavior report: {:?}", e);
+                    }
+                }
+            }
+
+            // Leave out readings that barely changed since they were last submitted
+            readings.retain(|(neighbor, rssi, _)| {
+                let unchanged = Self::is_unchanged_reading(neighbor, *rssi, block_number);
+                if unchanged {
+                    summary.skipped_unchanged += 1;
+                }
+                !unchanged
+            });
+
+            let mut all_submitted = true;
+
+            // Submit one signed transaction per batch of `MaxBatchSize` readings
+            for chunk in readings.chunks(T::MaxBatchSize::get() as usize) {
+                let Ok(readings) = BoundedVec::try_from(chunk.to_vec()) else {
+                    continue;
+                };
+                let call = Call::publish_rssi_data_batch { readings };
 
                 // Send the signed transaction
                 let results = signer.send_signed_transaction(|_account| call.clone());
 
                 // Check results
+                let mut chunk_submitted = true;
                 for (_, result) in &results {
                     if let Err(e) = result {
                         log::error!("Failed to submit RSSI transaction: {:?}", e);
+                        Self::record_submission_failure();
+                        chunk_submitted = false;
                     }
                 }
+
+                if chunk_submitted {
+                    for (neighbor, rssi, _) in chunk {
+                        Self::record_submitted_reading(neighbor, *rssi, block_number);
+                    }
+                    summary.submitted += chunk.len() as u32;
+                } else {
+                    summary.failed += chunk.len() as u32;
+                    all_submitted = false;
+                }
+            }
+
+            // Only a complete submission is remembered, so failed reports are retried. Nothing is
+            // remembered while every reading was left out, e.g. until the node's own registration
+            // is included, so the same readings are submitted once they become valid.
+            if all_submitted && !readings.is_empty() {
+                Self::record_last_submission(block_number, payload_hash);
             }
 
             Ok(())
         }
 
+        /// Refuse a claimed location that is more than `MaxRegistrationDeviation` meters from the
+        /// position trilaterated from the RSSI of registered neighbors
+        ///
+        /// A server lying about the location during the initial registration is caught this way.
+        /// The check passes while fewer than `MIN_TRILATERATION_NEIGHBORS` registered neighbors
+        /// are in range, since there is nothing to compare with.
+        fn check_claimed_location(
+            location_response: &LocationResponse,
+            rssi_response: &RssiResponse,
+        ) -> Result<(), &'static str> {
+            use crate::util::trilateration_deviation;
+
+            let (latitude, longitude) = Self::fixed_point_location(location_response);
+            let params = Self::estimator_params();
+
+            // Readings with a TX power are shifted to the reference TX power, so a single
+            // reference RSSI applies to all of them
+            let neighbors: Vec<(i64, i64, i16)> = rssi_response
+                .devices
+                .iter()
+                .filter_map(|device| {
+                    let neighbor = Self::account_of_address(device.address)?;
+                    let location = AccountData::<T>::get(&neighbor)?;
+                    Some((
+                        location.latitude,
+                        location.longitude,
+                        params.normalize_rssi(device.rssi, device.tx_power),
+                    ))
+                })
+                .collect();
+
+            let Some(deviation) = trilateration_deviation(
+                latitude,
+                longitude,
+                &neighbors,
+                params.reference_rssi,
+                params.path_loss_exponent,
+            ) else {
+                log::info!(
+                    "Not enough registered neighbors in range to check the claimed location"
+                );
+                return Ok(());
+            };
+
+            if deviation > T::MaxRegistrationDeviation::get() {
+                log::error!(
+                    "Claimed location is {} meters from the position trilaterated from {} neighbors, refusing to register",
+                    deviation,
+                    neighbors.len()
+                );
+                return Err("Claimed location contradicts the RSSI of registered neighbors");
+            }
+
+            Ok(())
+        }
+
+        /// Neighbors whose RSSI error exceeded `MisbehaviorThreshold` in `MisbehaviorRuns`
+        /// consecutive runs, with the error of the latest reading
+        ///
+        /// The readings are compared with the estimate from both registered locations. The number
+        /// of consecutive suspicious readings of each neighbor is kept in persistent offchain
+        /// storage and reset once the neighbor is reported or a reading is plausible again.
+        fn audit_readings(
+            account_id: &T::AccountId,
+            readings: &[(T::AccountId, i16, Option<i16>)],
+        ) -> Vec<(T::AccountId, i16)> {
+            use crate::util::estimate_rssi;
+            use sp_runtime::offchain::storage::StorageValueRef;
+
+            let Some(own_location) = AccountData::<T>::get(account_id) else {
+                return Vec::new();
+            };
+            let params = Self::estimator_params();
+
+            let mut offenders = Vec::new();
+            for (neighbor, measured_rssi, tx_power) in readings {
+                let Some(neighbor_location) = AccountData::<T>::get(neighbor) else {
+                    continue;
+                };
+
+                // Same estimate as the trust score, seen from this node
+                let estimated_rssi = estimate_rssi(
+                    neighbor_location.latitude,
+                    neighbor_location.longitude,
+                    own_location.latitude,
+                    own_location.longitude,
+                    params.reference_rssi_for(*tx_power),
+                    params.path_loss_exponent,
+                );
+                let error = measured_rssi.saturating_sub(estimated_rssi);
+
+                let key = [crate::MISBEHAVIOR_STREAK_KEY, &neighbor.encode()].concat();
+                let streak_ref = StorageValueRef::persistent(&key);
+                let streak = if error.unsigned_abs() > T::MisbehaviorThreshold::get() {
+                    streak_ref
+                        .get::<u32>()
+                        .ok()
+                        .flatten()
+                        .unwrap_or(0)
+                        .saturating_add(1)
+                } else {
+                    0
+                };
+
+                if streak >= T::MisbehaviorRuns::get() {
+                    log::warn!(
+                        "RSSI of {:?} off by {} dB in {} consecutive runs, reporting misbehavior",
+                        neighbor,
+                        error,
+                        streak
+                    );
+                    offenders.push((neighbor.clone(), error));
+                    streak_ref.clear();
+                } else {
+                    streak_ref.set(&streak);
+                }
+            }
+
+            offenders
+        }
+
+        /// Whether `rssi` is within `RssiHysteresis` of the last submitted reading of `neighbor`,
+        /// and that reading is more recent than `RssiResubmitInterval`
+        fn is_unchanged_reading(
+            neighbor: &T::AccountId,
+            rssi: i16,
+            block_number: BlockNumberFor<T>,
+        ) -> bool {
+            use sp_runtime::offchain::storage::StorageValueRef;
+
+            let key = [crate::LAST_RSSI_KEY, &neighbor.encode()].concat();
+            let Some((last_block, last_rssi)) = StorageValueRef::persistent(&key)
+                .get::<(BlockNumberFor<T>, i16)>()
+                .ok()
+                .flatten()
+            else {
+                return false;
+            };
+
+            let resubmit_at = last_block.saturating_add(T::RssiResubmitInterval::get());
+            rssi.abs_diff(last_rssi) <= T::RssiHysteresis::get() && block_number < resubmit_at
+        }
+
+        /// Remember the block and RSSI of a submitted reading of `neighbor`
+        fn record_submitted_reading(
+            neighbor: &T::AccountId,
+            rssi: i16,
+            block_number: BlockNumberFor<T>,
+        ) {
+            use sp_runtime::offchain::storage::StorageValueRef;
+
+            let key = [crate::LAST_RSSI_KEY, &neighbor.encode()].concat();
+            StorageValueRef::persistent(&key).set(&(block_number, rssi));
+        }
+
+        /// Block and payload hash of the last complete RSSI submission
+        fn last_submission() -> Option<(BlockNumberFor<T>, [u8; 32])> {
+            use sp_runtime::offchain::storage::StorageValueRef;
+
+            StorageValueRef::persistent(crate::LAST_SUBMISSION_KEY)
+                .get()
+                .ok()
+                .flatten()
+        }
+
+        /// Remember the block and payload hash of a complete RSSI submission
+        fn record_last_submission(block_number: BlockNumberFor<T>, payload_hash: [u8; 32]) {
+            use sp_runtime::offchain::storage::StorageValueRef;
+
+            StorageValueRef::persistent(crate::LAST_SUBMISSION_KEY)
+                .set(&(block_number, payload_hash));
+        }
+
+        /// Keep the summary of a run in persistent offchain storage for the node's RPC
+        fn record_run_summary(summary: &crate::util::RunSummary) {
+            use crate::util::run_summary_key;
+            use sp_runtime::offchain::storage::StorageValueRef;
+
+            StorageValueRef::persistent(&run_summary_key(summary.block)).set(summary);
+        }
+
         /// Fetch RSSI data from the bluetooth server
-        fn fetch_rssi_from_server() -> Result<RssiResponse, sp_runtime::offchain::http::Error> {
+        fn fetch_rssi_from_server(
+            deadline: sp_runtime::offchain::Timestamp,
+        ) -> Result<RssiResponse, crate::util::FetchError> {
             use codec::Decode;
-            use sp_runtime::offchain::{http, Duration};
 
-            // Get the server base URL
-            let base_url = Self::get_server_base_url()?;
-            let url = alloc::format!("{}/rssi", base_url);
+            let base_urls = Self::get_server_base_urls()?;
+            Self::fetch_from_servers(base_urls, "/rssi", deadline, |is_json, body| {
+                if is_json {
+                    crate::util::rssi_response_from_json(body)
+                } else {
+                    RssiResponse::decode(&mut &body[..]).ok()
+                }
+            })
+        }
 
-            log::info!("Fetching RSSI data from: {}", url);
+        /// Fetch location data from the server
+        fn fetch_location_from_server(
+            deadline: sp_runtime::offchain::Timestamp,
+        ) -> Result<LocationResponse, crate::util::FetchError> {
+            use codec::Decode;
+
+            let base_urls = Self::get_location_server_base_urls()?;
+            Self::fetch_from_servers(base_urls, "/location", deadline, |is_json, body| {
+                if is_json {
+                    crate::util::location_response_from_json(body)
+                } else {
+                    LocationResponse::decode(&mut &body[..]).ok()
+                }
+            })
+        }
+
+        /// Fetch `path` from `base_urls` and decode the response body
+        ///
+        /// A failed request is retried `HttpRetries` times, waiting `HttpRetryBackoffMillis`
+        /// before the first retry and twice as long before every further one. When all attempts
+        /// fail, or the response is too large, cannot be decoded or is stale, the next server URL
+        /// is tried. Nothing is retried once `deadline` has passed.
+        fn fetch_from_servers<R: ServerResponse>(
+            base_urls: Vec<String>,
+            path: &str,
+            deadline: sp_runtime::offchain::Timestamp,
+            decode: fn(bool, &[u8]) -> Option<R>,
+        ) -> Result<R, crate::util::FetchError> {
+            use crate::util::FetchError;
+            use sp_runtime::offchain::{http, Duration};
 
             // Get node identifier for the header
             let node_id = Self::get_node_identifier().map_err(|_| http::Error::Unknown)?;
 
             log::info!("Request from node: {}", node_id);
 
-            // Prepare the HTTP request with custom header
-            let request = http::Request::get(&url);
-            let request = request.add_header("X-Node-ID", &node_id);
+            // Once a server key is registered, only responses signed with it are trusted
+            let server_key = Self::local_account().and_then(ServerKey::<T>::get);
 
-            // Set a deadline for the request (30 seconds timeout)
-            let timeout = sp_io::offchain::timestamp().add(Duration::from_millis(30_000));
+            let mut last_error = FetchError::Http(http::Error::Unknown);
+            for base_url in base_urls {
+                let url = alloc::format!("{}{}", base_url, path);
+                let mut backoff = T::HttpRetryBackoffMillis::get();
 
-            // Send the request
-            let pending = request
-                .deadline(timeout)
-                .send()
-                .map_err(|_| http::Error::IoError)?;
+                for attempt in 0..=T::HttpRetries::get() {
+                    // Back off before retrying
+                    if attempt > 0 {
+                        let retry_at =
+                            sp_io::offchain::timestamp().add(Duration::from_millis(backoff));
+                        if retry_at >= deadline {
+                            break;
+                        }
+                        log::info!("Retrying {} in {} ms", url, backoff);
+                        sp_io::offchain::sleep_until(retry_at);
+                        backoff = backoff.saturating_mul(2);
+                    }
 
-            // Wait for the response
-            let response = pending
-                .try_wait(timeout)
-                .map_err(|_| http::Error::DeadlineReached)?
-                .map_err(|_| http::Error::IoError)?;
+                    // Stop once the run budget is used up
+                    if sp_io::offchain::timestamp() >= deadline {
+                        log::warn!(
+                            "Offchain worker run budget exhausted, giving up on {}",
+                            path
+                        );
+                        return Err(http::Error::DeadlineReached.into());
+                    }
+
+                    match Self::fetch_once(&url, &node_id, deadline) {
+                        Ok((is_json, body)) => {
+                            match Self::open_response(server_key.as_ref(), is_json, body)
+                                .and_then(|(is_json, body)| decode(is_json, &body))
+                            {
+                                Some(response) if Self::accept_response(&url, &response) => {
+                                    return Ok(response)
+                                }
+                                Some(_) => {
+                                    // A stuck server keeps sending the same old response
+                                    log::error!("Stale response from {}", url);
+                                    last_error = http::Error::Unknown.into();
+                                    break;
+                                }
+                                None => {
+                                    // A server sending an unreadable or forged response will keep
+                                    // doing so
+                                    log::error!("Failed to decode or verify response from {}", url);
+                                    last_error = http::Error::Unknown.into();
+                                    break;
+                                }
+                            }
+                        }
+                        Err(FetchError::ResponseTooLarge) => {
+                            // A server sending too much data will keep doing so
+                            log::error!("Response from {} is too large", url);
+                            last_error = FetchError::ResponseTooLarge;
+                            break;
+                        }
+                        Err(e) => {
+                            log::warn!("Request to {} failed: {:?}", url, e);
+                            last_error = e;
+                        }
+                    }
+                }
+            }
+
+            Err(last_error)
+        }
+
+        /// Unwrap the body of a response signed by `server_key`
+        ///
+        /// Without a server key the body is returned as it is. Signed payloads are always
+        /// SCALE-encoded, whatever the `Content-Type` of the response.
+        fn open_response(
+            server_key: Option<&[u8; 32]>,
+            is_json: bool,
+            body: Vec<u8>,
+        ) -> Option<(bool, Vec<u8>)> {
+            use crate::util::verify_signed_payload;
+
+            match server_key {
+                Some(server_key) => {
+                    verify_signed_payload(&body, server_key).map(|payload| (false, payload))
+                }
+                None => Some((is_json, body)),
+            }
+        }
+
+        /// Check that a response from `url` is fresh and remember its sequence number
+        ///
+        /// Responses older than `MaxResponseAgeMillis` are rejected, as are responses whose
+        /// sequence number is not greater than the last accepted one from the same URL. A server
+        /// whose clock is more than `MaxClockSkewMillis` off the block time is warned about.
+        fn accept_response(url: &str, response: &impl ServerResponse) -> bool {
+            use crate::util::{clock_skew, is_fresh_response};
+            use sp_runtime::offchain::storage::StorageValueRef;
+
+            let block_time = T::UnixTime::now().as_millis() as u64;
+            if let Some(skew) = clock_skew(
+                response.timestamp(),
+                block_time,
+                T::MaxClockSkewMillis::get(),
+            ) {
+                log::warn!(
+                    "Clock of {} is {} ms {} the block time",
+                    url,
+                    skew,
+                    if response.timestamp() > block_time {
+                        "ahead of"
+                    } else {
+                        "behind"
+                    }
+                );
+            }
+
+            let key = [crate::RESPONSE_SEQUENCE_KEY, url.as_bytes()].concat();
+            let last_sequence_ref = StorageValueRef::persistent(&key);
+            let last_sequence = last_sequence_ref.get::<u64>().ok().flatten();
+
+            let fresh = is_fresh_response(
+                response.timestamp(),
+                response.sequence(),
+                sp_io::offchain::timestamp().unix_millis(),
+                T::MaxResponseAgeMillis::get(),
+                last_sequence,
+            );
+
+            if let (true, Some(sequence)) = (fresh, response.sequence()) {
+                last_sequence_ref.set(&sequence);
+            }
+
+            fresh
+        }
+
+        /// Send a single GET request and return whether the body is JSON, and the body
+        ///
+        /// The request times out after `HttpTimeoutMillis`, or at `deadline` if that is earlier.
+        fn fetch_once(
+            url: &str,
+            node_id: &str,
+            deadline: sp_runtime::offchain::Timestamp,
+        ) -> Result<(bool, Vec<u8>), crate::util::FetchError> {
+            use sp_runtime::offchain::{http, Duration};
+
+            log::info!("Fetching data from: {}", url);
+
+            // Set a deadline for the request
+            let timeout = sp_io::offchain::timestamp()
+                .add(Duration::from_millis(T::HttpTimeoutMillis::get()))
+                .min(deadline);
+
+            // Send the request and wait for the response
+            let mut response = Self::send_request(http::Method::Get, url, node_id, None, timeout)?;
 
             // Check the response status
             if response.code != 200 {
                 log::error!("HTTP request failed with status code: {}", response.code);
-                return Err(http::Error::Unknown);
+                return Err(http::Error::Unknown.into());
             }
 
             // Read the response body
-            let body = response.body().collect::<Vec<u8>>();
+            let is_json = Self::is_json_response(&mut response);
+            let body = Self::read_body(&mut response, timeout)?;
 
-            // Decode the SCALE-encoded response
-            let rssi_response = RssiResponse::decode(&mut &body[..]).map_err(|_| {
-                log::error!("Failed to decode RSSI response");
-                http::Error::Unknown
-            })?;
+            Ok((is_json, body))
+        }
+
+        /// Read a response body of at most `MaxResponseBytes` in chunks
+        ///
+        /// Fails with `ResponseTooLarge` as soon as the server announces or sends more than that,
+        /// without reading the rest.
+        fn read_body(
+            response: &mut sp_runtime::offchain::http::Response,
+            deadline: sp_runtime::offchain::Timestamp,
+        ) -> Result<Vec<u8>, crate::util::FetchError> {
+            use crate::util::FetchError;
+            use sp_core::offchain::HttpError;
+            use sp_runtime::offchain::http;
+
+            let max_bytes = T::MaxResponseBytes::get() as usize;
+
+            // Refuse a body that is announced to be too large before reading any of it
+            let content_length = response
+                .headers()
+                .find("content-length")
+                .and_then(|length| length.trim().parse::<usize>().ok());
+            if content_length.is_some_and(|length| length > max_bytes) {
+                return Err(FetchError::ResponseTooLarge);
+            }
+
+            let mut body = Vec::with_capacity(content_length.unwrap_or(0));
+            let mut chunk = [0u8; 4096];
+            loop {
+                let read = sp_io::offchain::http_response_read_body(
+                    response.id,
+                    &mut chunk,
+                    Some(deadline),
+                )
+                .map_err(|e| match e {
+                    HttpError::DeadlineReached => http::Error::DeadlineReached,
+                    HttpError::IoError => http::Error::IoError,
+                    HttpError::Invalid => http::Error::Unknown,
+                })? as usize;
+
+                // The whole body has been read
+                if read == 0 {
+                    return Ok(body);
+                }
 
-            Ok(rssi_response)
+                if body.len() + read > max_bytes {
+                    return Err(FetchError::ResponseTooLarge);
+                }
+                body.extend_from_slice(&chunk[..read]);
+            }
         }
 
-        /// Fetch location data from the server
-        fn fetch_location_from_server(
-        ) -> Result<LocationResponse, sp_runtime::offchain::http::Error> {
-            use codec::Decode;
+        /// Post the node's status to the `/status` endpoint of the first server that accepts it
+        ///
+        /// Sent once per run without retries, since the next block sends a fresh status anyway.
+        fn post_status(
+            block_number: BlockNumberFor<T>,
+            deadline: sp_runtime::offchain::Timestamp,
+        ) -> Result<(), sp_runtime::offchain::http::Error> {
+            use crate::util::NodeStatus;
+            use sp_runtime::offchain::http;
+
+            let Some(account_id) = Self::local_account() else {
+                log::error!("No signing account available");
+                return Err(http::Error::Unknown);
+            };
+
+            let status = NodeStatus {
+                block: block_number.saturated_into(),
+                account: account_id.encode(),
+                registered: AccountData::<T>::contains_key(&account_id),
+                last_submitted_block: Self::last_submission()
+                    .map(|(block, _)| block.saturated_into()),
+                trust_score: Self::last_finished_epoch()
+                    .and_then(|epoch| EpochTrustScores::<T>::get(epoch, &account_id))
+                    .flatten(),
+            };
+            let body = status.encode();
+            let node_id = Self::get_node_identifier().map_err(|_| http::Error::Unknown)?;
+
+            let mut last_error = http::Error::Unknown;
+            for base_url in Self::get_server_base_urls()? {
+                if sp_io::offchain::timestamp() >= deadline {
+                    return Err(http::Error::DeadlineReached);
+                }
+
+                let url = alloc::format!("{}/status", base_url);
+                match Self::post_once(&url, &node_id, &body, deadline) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => {
+                        log::warn!("Posting status to {} failed: {:?}", url, e);
+                        last_error = e;
+                    }
+                }
+            }
+
+            Err(last_error)
+        }
+
+        /// Send a single SCALE-encoded POST request, expecting a successful status code
+        fn post_once(
+            url: &str,
+            node_id: &str,
+            body: &[u8],
+            deadline: sp_runtime::offchain::Timestamp,
+        ) -> Result<(), sp_runtime::offchain::http::Error> {
             use sp_runtime::offchain::{http, Duration};
 
-            // Get the server base URL
-            let base_url = Self::get_server_base_url()?;
-            let url = alloc::format!("{}/location", base_url);
+            let timeout = sp_io::offchain::timestamp()
+                .add(Duration::from_millis(T::HttpTimeoutMillis::get()))
+                .min(deadline);
 
-            log::info!("Fetching location data from: {}", url);
+            let response =
+                Self::send_request(http::Method::Post, url, node_id, Some(body), timeout)?;
 
-            // Get node identifier for the header
-            let node_id = Self::get_node_identifier().map_err(|_| http::Error::Unknown)?;
+            if !(200..300).contains(&response.code) {
+                log::error!("HTTP request failed with status code: {}", response.code);
+                return Err(http::Error::Unknown);
+            }
 
-            log::info!("Request from node: {}", node_id);
+            Ok(())
+        }
 
-            // Prepare the HTTP request with custom header
-            let request = http::Request::get(&url);
-            let request = request.add_header("X-Node-ID", &node_id);
+        /// Send a request with the node's headers and wait for the response until `timeout`
+        ///
+        /// A server that only serves known nodes answers with `401 Unauthorized` and a nonce in
+        /// the `X-Challenge` header. The request is then sent once more with the nonce signed by
+        /// the node's key in `X-Signature`.
+        fn send_request(
+            method: sp_runtime::offchain::http::Method,
+            url: &str,
+            node_id: &str,
+            body: Option<&[u8]>,
+            timeout: sp_runtime::offchain::Timestamp,
+        ) -> Result<sp_runtime::offchain::http::Response, sp_runtime::offchain::http::Error>
+        {
+            let mut response = Self::send_once(method.clone(), url, node_id, body, None, timeout)?;
+            if response.code != 401 {
+                return Ok(response);
+            }
 
-            // Set a deadline for the request (30 seconds timeout)
-            let timeout = sp_io::offchain::timestamp().add(Duration::from_millis(30_000));
+            let Some(nonce) = response.headers().find("x-challenge").map(String::from) else {
+                return Ok(response);
+            };
+            let Some(signature) = Self::sign_challenge(&nonce) else {
+                log::warn!("No signing key to answer the challenge of {}", url);
+                return Ok(response);
+            };
+
+            log::info!("Answering the challenge of {}", url);
+            Self::send_once(
+                method,
+                url,
+                node_id,
+                body,
+                Some((&nonce, &signature)),
+                timeout,
+            )
+        }
+
+        /// Send a single request, with the answer to a challenge if there is one
+        fn send_once(
+            method: sp_runtime::offchain::http::Method,
+            url: &str,
+            node_id: &str,
+            body: Option<&[u8]>,
+            challenge: Option<(&str, &str)>,
+            timeout: sp_runtime::offchain::Timestamp,
+        ) -> Result<sp_runtime::offchain::http::Response, sp_runtime::offchain::http::Error>
+        {
+            use sp_runtime::offchain::http;
+
+            // Prepare the HTTP request with custom headers
+            let mut request = http::Request::post(url, body.into_iter().collect::<Vec<_>>())
+                .method(method)
+                .add_header("X-Node-ID", node_id);
+            if body.is_some() {
+                request = request.add_header("Content-Type", "application/octet-stream");
+            }
+            let mut request = Self::add_auth_header(request);
+            if let Some((nonce, signature)) = challenge {
+                request = request
+                    .add_header("X-Challenge", nonce)
+                    .add_header("X-Signature", signature);
+            }
 
             // Send the request
             let pending = request
@@ -256,43 +1168,121 @@ mod offchain {
                 .map_err(|_| http::Error::IoError)?;
 
             // Wait for the response
-            let response = pending
+            pending
                 .try_wait(timeout)
                 .map_err(|_| http::Error::DeadlineReached)?
-                .map_err(|_| http::Error::IoError)?;
+                .map_err(|_| http::Error::IoError)
+        }
 
-            // Check the response status
-            if response.code != 200 {
-                log::error!("HTTP request failed with status code: {}", response.code);
-                return Err(http::Error::Unknown);
-            }
+        /// Sign a server's challenge with the node's key, hex-encoded for the `X-Signature` header
+        pub(crate) fn sign_challenge(nonce: &str) -> Option<String> {
+            use crate::util::challenge_message;
+            use frame_system::offchain::{AppCrypto, SigningTypes};
+            use sp_runtime::RuntimeAppPublic;
 
-            // Read the response body
-            let body = response.body().collect::<Vec<u8>>();
+            type RuntimeAppPublicOf<T> = <<T as Config>::AuthorityId as AppCrypto<
+                <T as SigningTypes>::Public,
+                <T as SigningTypes>::Signature,
+            >>::RuntimeAppPublic;
 
-            // Decode the SCALE-encoded response
-            let location_response = LocationResponse::decode(&mut &body[..]).map_err(|_| {
-                log::error!("Failed to decode location response");
-                http::Error::Unknown
-            })?;
+            // The same key the account is derived from
+            let key = RuntimeAppPublicOf::<T>::all().into_iter().next()?;
+            let signature = key.sign(&challenge_message(nonce.as_bytes()))?;
 
-            Ok(location_response)
+            Some(Self::bytes_to_hex(&signature.encode()))
         }
 
-        /// Submit location data as a signed transaction
-        fn submit_location_data(location_data: LocationResponse) -> Result<(), &'static str> {
-            use frame_system::offchain::{SendSignedTransaction, Signer};
+        /// Whether the server sent a JSON body, otherwise it is SCALE-encoded
+        fn is_json_response(response: &mut sp_runtime::offchain::http::Response) -> bool {
+            response
+                .headers()
+                .find("content-type")
+                .is_some_and(|content_type| content_type.starts_with("application/json"))
+        }
+
+        /// Increment the submission failure counter kept in persistent offchain storage
+        fn record_submission_failure() {
+            use sp_runtime::offchain::storage::{StorageRetrievalError, StorageValueRef};
+
+            let counter = StorageValueRef::persistent(crate::SUBMISSION_FAILURES_KEY);
+            let result = counter.mutate(
+                |failures: Result<Option<u32>, StorageRetrievalError>| -> Result<u32, ()> {
+                    Ok(failures.ok().flatten().unwrap_or(0).saturating_add(1))
+                },
+            );
+
+            if result.is_err() {
+                log::warn!("Failed to update submission failure counter");
+            }
+        }
+
+        /// Re-fetch the location of a registered node and update it on-chain if it has moved
+        /// more than `LocationUpdateDelta` meters or its Bluetooth address has changed
+        fn refresh_location(
+            account_id: &T::AccountId,
+            block_number: BlockNumberFor<T>,
+            deadline: sp_runtime::offchain::Timestamp,
+        ) -> Result<(), &'static str> {
+            use crate::util::distance_meters;
+
+            let location_data =
+                AccountData::<T>::get(account_id).ok_or("Node is not registered")?;
+
+            // Updates are rejected until the cooldown has elapsed
+            let last_updated: BlockNumberFor<T> = location_data.last_updated.into();
+            if block_number.saturating_sub(last_updated) < T::UpdateCooldown::get() {
+                return Ok(());
+            }
 
+            let location_response = Self::fetch_location_from_server(deadline)
+                .map_err(|_| "Failed to fetch location data from server")?;
+            let (latitude, longitude) = Self::fixed_point_location(&location_response);
+
+            // Check whether the device has moved far enough
+            let distance = distance_meters(
+                location_data.latitude,
+                location_data.longitude,
+                latitude,
+                longitude,
+            );
+            if distance <= T::LocationUpdateDelta::get() as f64
+                && location_response.address == location_data.address
+            {
+                return Ok(());
+            }
+
+            log::info!("Node moved {} meters, updating its location", distance);
+
+            Self::send_location_call(Call::update_node_info {
+                address: location_response.address,
+                latitude,
+                longitude,
+            })
+        }
+
+        /// Convert the location returned by the server to fixed-point coordinates
+        fn fixed_point_location(location_data: &LocationResponse) -> (i64, i64) {
             // Convert f64 to i64 with fixed-point precision (multiply by 1_000_000)
             let latitude_fixed = (location_data.location.latitude * 1_000_000.0) as i64;
             let longitude_fixed = (location_data.location.longitude * 1_000_000.0) as i64;
+            (latitude_fixed, longitude_fixed)
+        }
+
+        /// Submit location data as a signed transaction
+        fn submit_location_data(location_data: LocationResponse) -> Result<(), &'static str> {
+            let (latitude, longitude) = Self::fixed_point_location(&location_data);
 
             // Create the call
-            let call = Call::register_node {
+            Self::send_location_call(Call::register_node {
                 address: location_data.address,
-                latitude: latitude_fixed,
-                longitude: longitude_fixed,
-            };
+                latitude,
+                longitude,
+            })
+        }
+
+        /// Send a `register_node` or `update_node_info` call as a signed transaction
+        fn send_location_call(call: Call<T>) -> Result<(), &'static str> {
+            use frame_system::offchain::{SendSignedTransaction, Signer};
 
             // Get signer and send the transaction
             let signer = Signer::<T, T::AuthorityId>::any_account();
@@ -306,6 +1296,7 @@ mod offchain {
                 }
                 Some((_account, Err(e))) => {
                     log::error!("Failed to submit location transaction: {:?}", e);
+                    Self::record_submission_failure();
                     Err("Transaction submission failed")
                 }
                 None => {
@@ -15,7 +15,8 @@ mod dispatches {
         ///
         /// ## Parameters
         /// - `origin`: Must be signed by the account
-        /// - `server_url`: The full server URL with port (e.g., "localhost:3000", "192.168.1.100:8080")
+        /// - `server_url`: The full server URL with port (e.g., "localhost:3000", "192.168.1.100:8080").
+        ///   May start with `http://` or `https://`, plain HTTP is used otherwise
         #[pallet::call_index(0)]
         #[pallet::weight(T::WeightInfo::set_server_config())]
         pub fn set_server_config(origin: OriginFor<T>, server_url: Vec<u8>) -> DispatchResult {
@@ -54,9 +55,10 @@ mod dispatches {
             // Check that the extrinsic was signed and get the signer.
             let who = ensure_signed(origin)?;
 
-            // Confirm if the bluetooth address is not already taken
+            // Confirm if the bluetooth address is not already taken, as an address or a BSSID
             ensure!(
-                !AddressRegistrationData::<T>::contains_key(address),
+                !AddressRegistrationData::<T>::contains_key(address)
+                    && !WifiAddressRegistrationData::<T>::contains_key(address),
                 Error::<T>::BluetoothAddressAlreadyTaken
             );
 
@@ -66,6 +68,12 @@ mod dispatches {
                 Error::<T>::AccountAlreadyRegistered
             );
 
+            // Trust score calculation scores every node, so the node set is bounded
+            ensure!(
+                AccountData::<T>::count() < T::MaxNodes::get(),
+                Error::<T>::TooManyNodes
+            );
+
             // Get the current block number
             let current_block = frame_system::Pallet::<T>::block_number();
 
@@ -101,25 +109,14 @@ mod dispatches {
         /// ## Parameters
         /// - `origin`: Must be signed by the account that registered the node
         #[pallet::call_index(2)]
-        #[pallet::weight(T::WeightInfo::unregister_node())]
+        #[pallet::weight(T::WeightInfo::unregister_node(T::MaxNodes::get()))]
         pub fn unregister_node(origin: OriginFor<T>) -> DispatchResult {
             // Check that the extrinsic was signed and get the signer
             let who = ensure_signed(origin)?;
 
-            // Check that the account is registered
-            ensure!(
-                AccountData::<T>::contains_key(&who),
-                Error::<T>::AccountNotRegistered
-            );
-
-            // Get the location data to retrieve the Bluetooth address
-            let location_data = AccountData::<T>::get(&who).unwrap();
-            let bluetooth_address = location_data.address;
-
-            // Remove from all storage items
-            AccountData::<T>::remove(&who);
-            AddressRegistrationData::<T>::remove(bluetooth_address);
-            ServerConfig::<T>::remove(&who);
+            // Remove from all storage items, checking that the account is registered
+            let bluetooth_address =
+                Self::remove_node(&who).ok_or(Error::<T>::AccountNotRegistered)?;
 
             // Emit an event
             Self::deposit_event(Event::NodeUnregistered {
@@ -140,6 +137,9 @@ mod dispatches {
         /// This allows a registered node to update its location coordinates and/or Bluetooth address.
         /// The node must already be registered.
         ///
+        /// Weight is charged for an address change and refunded when the address stays the same,
+        /// since the Bluetooth address mappings are left untouched.
+        ///
         /// ## Parameters
         /// - `origin`: Must be signed by the account that registered the node
         /// - `address`: New Bluetooth address (6 bytes)
@@ -152,7 +152,7 @@ mod dispatches {
             address: [u8; 6],
             latitude: i64,
             longitude: i64,
-        ) -> DispatchResult {
+        ) -> DispatchResultWithPostInfo {
             // Check that the extrinsic was signed and get the signer
             let who = ensure_signed(origin)?;
 
@@ -179,8 +179,10 @@ mod dispatches {
 
             // If the address is changing, ensure the new address is not already taken
             if old_address != address {
+                let bssid_owner = WifiAddressRegistrationData::<T>::get(address);
                 ensure!(
-                    !AddressRegistrationData::<T>::contains_key(address),
+                    !AddressRegistrationData::<T>::contains_key(address)
+                        && bssid_owner.is_none_or(|owner| owner == who),
                     Error::<T>::BluetoothAddressAlreadyTaken
                 );
 
@@ -216,77 +218,376 @@ mod dispatches {
                 address
             );
 
-            Ok(())
+            // Refund the address mapping updates if the address did not change
+            let actual_weight = if old_address == address {
+                T::WeightInfo::update_node_info_same_address()
+            } else {
+                T::WeightInfo::update_node_info()
+            };
+            Ok(Some(actual_weight).into())
         }
 
         /// Publish RSSI (signal strength) data for a neighboring node.
         ///
         /// This function stores RSSI measurements between nodes, validating that:
-        /// - Both the reporting node and neighbor are registered
+        /// - `rssi` and `tx_power` are within -127 to 20 dBm
+        /// - Both the reporting node and neighbor are registered, and they are different nodes
         /// - The distance between nodes is within the configured maximum
+        /// - The neighbor has fewer than `MaxReportsPerNode` reports in the current block
         ///
         /// ## Parameters
         /// - `origin`: Must be signed by the reporting node's account
         /// - `neighbor`: The AccountId of the neighboring node being measured
         /// - `rssi`: The signal strength measurement (i16, typically negative dBm values)
+        /// - `tx_power`: The TX power level the neighbor advertises in dBm, if any. The trust
+        ///   score then compares the path loss (`tx_power - rssi`) instead of the raw RSSI
         #[pallet::call_index(4)]
-        #[pallet::weight(T::WeightInfo::publish_rssi_data())]
+        #[pallet::weight(T::WeightInfo::publish_rssi_data(
+            T::MaxNodes::get(),
+            T::MaxReportsPerNode::get()
+        ))]
         pub fn publish_rssi_data(
             origin: OriginFor<T>,
             neighbor: T::AccountId,
             rssi: i16,
+            tx_power: Option<i16>,
         ) -> DispatchResult {
             // Check that the extrinsic was signed and get the signer.
             let who = ensure_signed(origin)?;
 
-            // Check that origin account is registered.
+            // Validate the reading and the report against both registered locations
+            <Self as RssiIngestion<T>>::validate_reading(rssi, tx_power)?;
+            <Self as RssiIngestion<T>>::validate_report(&who, &neighbor)?;
+
+            // Store the report and emit an event
+            <Self as RssiIngestion<T>>::store_report(who, neighbor, rssi, tx_power);
+
+            // Return a successful `DispatchResult`
+            Ok(())
+        }
+
+        /// Prune the caller's own RSSI reports up to and including `up_to_block`.
+        ///
+        /// At most `MaxPruneReports` entries are scanned per call. If the limit is reached, the
+        /// scan position is remembered so the next call continues where this one stopped.
+        /// Weight is charged for the worst case and refunded for the entries actually scanned.
+        ///
+        /// ## Parameters
+        /// - `origin`: Must be signed by the reporting node's account
+        /// - `up_to_block`: The last block whose reports should be removed
+        #[pallet::call_index(5)]
+        #[pallet::weight(T::WeightInfo::prune_my_reports(T::MaxPruneReports::get()))]
+        pub fn prune_my_reports(
+            origin: OriginFor<T>,
+            up_to_block: BlockNumberFor<T>,
+        ) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            let limit = T::MaxPruneReports::get();
+
+            // Resume from the previous scan position, if any
+            let mut keys = match PruneCursor::<T>::take(&who) {
+                Some(cursor) => RssiData::<T>::iter_keys_from(cursor.into_inner()),
+                None => RssiData::<T>::iter_keys(),
+            };
+
+            let mut scanned: u32 = 0;
+            let mut removed: u32 = 0;
+            while scanned < limit {
+                let Some((block_number, neighbor, reporter)) = keys.next() else {
+                    break;
+                };
+                scanned += 1;
+
+                if reporter == who && block_number <= up_to_block {
+                    let key = (block_number, neighbor, reporter);
+                    RssiData::<T>::remove(&key);
+                    TxPowerData::<T>::remove(&key);
+                    removed += 1;
+                }
+            }
+
+            // Remember where we stopped so the next call can continue the scan
+            if scanned == limit {
+                if let Ok(cursor) = keys.last_raw_key().to_vec().try_into() {
+                    PruneCursor::<T>::insert(&who, cursor);
+                }
+            }
+
+            // Emit an event
+            Self::deposit_event(Event::ReportsPruned {
+                who,
+                up_to_block,
+                removed,
+            });
+
+            // Refund the weight of entries that were not scanned
+            Ok(Some(T::WeightInfo::prune_my_reports(scanned)).into())
+        }
+
+        /// Publish RSSI data for several neighboring nodes in one transaction.
+        ///
+        /// Every reading is validated like a `publish_rssi_data` call. If any reading is invalid
+        /// the whole batch is rejected and nothing is stored. Weight is charged for neighbors
+        /// with `MaxReportsPerNode - 1` existing reports and refunded for the reports actually
+        /// counted.
+        ///
+        /// ## Parameters
+        /// - `origin`: Must be signed by the reporting node's account
+        /// - `readings`: `(neighbor, rssi, tx_power)` tuples, at most `MaxBatchSize`
+        #[pallet::call_index(6)]
+        #[pallet::weight(T::WeightInfo::publish_rssi_data_batch(
+            T::MaxNodes::get(),
+            readings.len() as u32,
+            T::MaxReportsPerNode::get().saturating_sub(1),
+        ))]
+        pub fn publish_rssi_data_batch(
+            origin: OriginFor<T>,
+            readings: BoundedVec<(T::AccountId, i16, Option<i16>), T::MaxBatchSize>,
+        ) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            let count = readings.len() as u32;
+            let mut max_existing_reports: u32 = 0;
+
+            for (neighbor, rssi, tx_power) in readings {
+                // Validate each reading and report against both registered locations
+                <Self as RssiIngestion<T>>::validate_reading(rssi, tx_power)?;
+                let existing_reports =
+                    <Self as RssiIngestion<T>>::validate_report(&who, &neighbor)?;
+                max_existing_reports = max_existing_reports.max(existing_reports);
+
+                // Store the report and emit an event
+                <Self as RssiIngestion<T>>::store_report(who.clone(), neighbor, rssi, tx_power);
+            }
+
+            // Refund the weight of reports that did not have to be counted
+            Ok(Some(T::WeightInfo::publish_rssi_data_batch(
+                T::MaxNodes::get(),
+                count,
+                max_existing_reports,
+            ))
+            .into())
+        }
+
+        /// Report a neighbor whose measured signal strength contradicts its registered location.
+        ///
+        /// Submitted by the offchain worker after `MisbehaviorRuns` consecutive readings were off
+        /// by more than `MisbehaviorThreshold` dB. Nothing is stored, the
+        /// [`Event::MisbehaviorReported`] event is left to monitors and governance.
+        ///
+        /// ## Parameters
+        /// - `origin`: Must be signed by the reporting node's account
+        /// - `offender`: The AccountId of the suspicious neighbor
+        /// - `rssi_error`: Measured minus estimated RSSI of the latest reading
+        #[pallet::call_index(7)]
+        #[pallet::weight(T::WeightInfo::report_misbehavior())]
+        pub fn report_misbehavior(
+            origin: OriginFor<T>,
+            offender: T::AccountId,
+            rssi_error: i16,
+        ) -> DispatchResult {
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            // Check that the node is not reporting itself
+            ensure!(who != offender, Error::<T>::SelfReportNotAllowed);
+
+            // Check that both accounts are registered
             ensure!(
                 AccountData::<T>::contains_key(&who),
-                Error::<T>::AccountNotRegistered
+                Error::<T>::ReporterNotRegistered
             );
-
-            // Check that neighbor account is registered.
             ensure!(
-                AccountData::<T>::contains_key(&neighbor),
-                Error::<T>::AccountNotRegistered
+                AccountData::<T>::contains_key(&offender),
+                Error::<T>::NeighborNotRegistered
             );
 
-            // Get account locations
-            let reporter_location = AccountData::<T>::get(&who).unwrap();
-            let neighbor_location = AccountData::<T>::get(&neighbor).unwrap();
+            // Emit an event
+            Self::deposit_event(Event::MisbehaviorReported {
+                reporter: who,
+                offender,
+                rssi_error,
+            });
 
-            // Convert them to normal units
-            let reporter_latitude = reporter_location.latitude as f64 / 1_000_000.0;
-            let reporter_longitude = reporter_location.longitude as f64 / 1_000_000.0;
-            let neighbor_latitude = neighbor_location.latitude as f64 / 1_000_000.0;
-            let neighbor_longitude = neighbor_location.longitude as f64 / 1_000_000.0;
+            Ok(())
+        }
 
-            use haversine_redux::Location;
-            let a = Location::new(reporter_latitude, reporter_longitude);
-            let b = Location::new(neighbor_latitude, neighbor_longitude);
-            let distance = a.kilometers_to(&b) * 1000.0; // convert km to meters
+        /// Set the sr25519 public key the account's server signs its responses with.
+        ///
+        /// Once set, the offchain worker only accepts `/rssi` and `/location` responses wrapped in
+        /// a `SignedPayload` signed by this key, so readings cannot be forged on the way from the
+        /// server to the node.
+        ///
+        /// ## Parameters
+        /// - `origin`: Must be signed by the account
+        /// - `server_key`: The server's public key, or `None` to accept unsigned responses again
+        #[pallet::call_index(8)]
+        #[pallet::weight(T::WeightInfo::set_server_key())]
+        pub fn set_server_key(
+            origin: OriginFor<T>,
+            server_key: Option<[u8; 32]>,
+        ) -> DispatchResult {
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
 
-            // Check that distance is within allowed maximum.
+            // Store in on-chain storage
+            match server_key {
+                Some(key) => ServerKey::<T>::insert(&who, key),
+                None => ServerKey::<T>::remove(&who),
+            }
+
+            log::info!("Server key updated for account {:?}: {:?}", who, server_key);
+
+            Ok(())
+        }
+
+        /// Set the URL of the server the account's offchain worker fetches its GPS location from.
+        ///
+        /// The BLE scanner and the GNSS receiver are often different services on different hosts.
+        /// Without a location server, `/location` is fetched from the `set_server_config` server.
+        ///
+        /// ## Parameters
+        /// - `origin`: Must be signed by the account
+        /// - `server_url`: The location server URL with port, in the same format as for
+        ///   `set_server_config`, or `None` to fetch the location from the RSSI server again
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::set_location_server_config())]
+        pub fn set_location_server_config(
+            origin: OriginFor<T>,
+            server_url: Option<Vec<u8>>,
+        ) -> DispatchResult {
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            // Store in on-chain storage
+            match &server_url {
+                Some(server_url) => {
+                    let bounded_url: BoundedVec<u8, ConstU32<256>> = server_url
+                        .clone()
+                        .try_into()
+                        .map_err(|_| "Server URL too long (max 256 bytes)")?;
+                    LocationServerConfig::<T>::insert(&who, bounded_url);
+                }
+                None => LocationServerConfig::<T>::remove(&who),
+            }
+
+            log::info!(
+                "Location server configuration updated for account {:?}: {:?}",
+                who,
+                server_url
+                    .as_deref()
+                    .map(|url| core::str::from_utf8(url).unwrap_or("Invalid UTF-8"))
+            );
+
+            Ok(())
+        }
+
+        /// Set the Identity Resolving Key the account's server rotates its BLE address with.
+        ///
+        /// A node advertising from a resolvable private address cannot be tracked by its MAC, nor
+        /// impersonated by cloning one. Neighbors resolve its current address with this key
+        /// before recording its RSSI. Anyone reading the chain can resolve it too.
+        ///
+        /// ## Parameters
+        /// - `origin`: Must be signed by the account
+        /// - `irk`: The 16-byte IRK in the byte order BlueZ stores it, or `None` when the node
+        ///   advertises its identity address again
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::set_identity_resolving_key())]
+        pub fn set_identity_resolving_key(
+            origin: OriginFor<T>,
+            irk: Option<[u8; 16]>,
+        ) -> DispatchResult {
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            // Store in on-chain storage
+            match irk {
+                Some(irk) => IdentityResolvingKey::<T>::insert(&who, irk),
+                None => IdentityResolvingKey::<T>::remove(&who),
+            }
+
+            log::info!(
+                "Identity resolving key {} for account {:?}",
+                if irk.is_some() { "set" } else { "cleared" },
+                who
+            );
+
+            Ok(())
+        }
+
+        /// Set the BSSID of the Wi-Fi interface the account's server can be measured by.
+        ///
+        /// Neighbors scanning Wi-Fi instead of, or next to, Bluetooth report the node's RSSI under
+        /// this BSSID, which maps back to the account like its Bluetooth address does.
+        ///
+        /// ## Parameters
+        /// - `origin`: Must be signed by the account that registered the node
+        /// - `bssid`: The BSSID (6 bytes), or `None` when the node is no longer measured over Wi-Fi
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::set_wifi_address())]
+        pub fn set_wifi_address(origin: OriginFor<T>, bssid: Option<[u8; 6]>) -> DispatchResult {
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            // Check that the account is registered
             ensure!(
-                distance <= T::MaxDistance::get() as f64,
-                Error::<T>::ExceedsMaxDistance
+                AccountData::<T>::contains_key(&who),
+                Error::<T>::AccountNotRegistered
             );
 
-            // Get the current block number.
-            let block_number = frame_system::Pallet::<T>::block_number();
+            // A BSSID must map to a single account, whichever map it is looked up in
+            if let Some(bssid) = bssid {
+                let taken = |owner: Option<T::AccountId>| owner.is_some_and(|owner| owner != who);
+                ensure!(
+                    !taken(AddressRegistrationData::<T>::get(bssid))
+                        && !taken(WifiAddressRegistrationData::<T>::get(bssid)),
+                    Error::<T>::WifiAddressAlreadyTaken
+                );
+            }
 
-            // Update storage.
-            RssiData::<T>::insert((block_number, neighbor.clone(), who.clone()), rssi);
+            // Replace the old mapping with the new one
+            if let Some(old_bssid) = WifiAddress::<T>::take(&who) {
+                WifiAddressRegistrationData::<T>::remove(old_bssid);
+            }
+            if let Some(bssid) = bssid {
+                WifiAddress::<T>::insert(&who, bssid);
+                WifiAddressRegistrationData::<T>::insert(bssid, who.clone());
+            }
 
-            // Emit an event.
-            Self::deposit_event(Event::RssiStored {
-                block_number,
-                neighbor,
-                who,
-                rssi,
-            });
+            log::info!("Wi-Fi BSSID updated for account {:?}: {:?}", who, bssid);
+
+            Ok(())
+        }
+
+        /// Publish the distance to a neighboring node measured by UWB ranging.
+        ///
+        /// Unlike RSSI, a UWB range is an actual distance, so it is checked against the registered
+        /// locations right away:
+        /// - Both the reporting node and neighbor are registered, and they are different nodes
+        /// - The distance between the registered locations is within the configured maximum
+        /// - The range differs from that distance by at most `MaxRangeDeviation` meters
+        ///
+        /// ## Parameters
+        /// - `origin`: Must be signed by the reporting node's account
+        /// - `neighbor`: The AccountId of the neighboring node being measured
+        /// - `distance_cm`: The measured distance in centimeters
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::publish_range_data())]
+        pub fn publish_range_data(
+            origin: OriginFor<T>,
+            neighbor: T::AccountId,
+            distance_cm: u32,
+        ) -> DispatchResult {
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            Self::validate_range(&who, &neighbor, distance_cm)?;
+            Self::store_range(who, neighbor, distance_cm);
 
-            // Return a successful `DispatchResult`
             Ok(())
         }
     }
@@ -1,10 +1,12 @@
-//! Runtime API definition for trust score calculation
+//! Runtime APIs for trust score calculation and node configuration
 
+use crate::util::{EstimatorOverrides, NodeConfigBundle};
 use alloc::vec::Vec;
 use codec::Codec;
 
 sp_api::decl_runtime_apis! {
     /// Runtime API for trust score calculations
+    #[api_version(3)]
     pub trait TrustScoreApi<AccountId> where
         AccountId: Codec,
     {
@@ -26,5 +28,63 @@ sp_api::decl_runtime_apis! {
         /// # Returns
         /// The trust score error value, or None if the account has no data
         fn calculate_trust_score(target_block: u32, account: AccountId) -> Option<i16>;
+
+        /// Calculate trust scores for all accounts, overriding estimator parameters
+        ///
+        /// # Parameters
+        /// - `target_block`: The block number to calculate trust scores for
+        /// - `overrides`: Estimator parameters replacing the runtime constants
+        ///
+        /// # Returns
+        /// A vector of trust score data for each account
+        #[api_version(2)]
+        fn calculate_trust_scores_with(
+            target_block: u32,
+            overrides: EstimatorOverrides,
+        ) -> Vec<(AccountId, i16)>;
+
+        /// Calculate trust score for a specific account, overriding estimator parameters
+        ///
+        /// # Parameters
+        /// - `target_block`: The block number to calculate trust score for
+        /// - `account`: The account to calculate trust score for
+        /// - `overrides`: Estimator parameters replacing the runtime constants
+        ///
+        /// # Returns
+        /// The trust score error value, or None if the account has no data
+        #[api_version(2)]
+        fn calculate_trust_score_with(
+            target_block: u32,
+            account: AccountId,
+            overrides: EstimatorOverrides,
+        ) -> Option<i16>;
+
+        /// Get the RSSI reports behind the trust score of a specific account
+        ///
+        /// # Parameters
+        /// - `target_block`: The block number to break the trust score down for
+        /// - `account`: The account to break the trust score down for
+        ///
+        /// # Returns
+        /// `(reporter, measured, estimated, error)` for every report about the account
+        #[api_version(3)]
+        fn trust_score_breakdown(
+            target_block: u32,
+            account: AccountId,
+        ) -> Vec<(AccountId, i16, i16, i16)>;
+    }
+
+    /// Runtime API for nodes configuring themselves
+    pub trait NodeConfigApi<AccountId> where
+        AccountId: Codec,
+    {
+        /// Get the registration and configuration of a node in a single call
+        ///
+        /// # Parameters
+        /// - `account`: The account of the node
+        ///
+        /// # Returns
+        /// The node's location data, effective server URL and estimator constants
+        fn node_config(account: AccountId) -> NodeConfigBundle;
     }
 }
@@ -0,0 +1,134 @@
+use frame_support::pallet_macros::*;
+
+/// A [`pallet_section`] that defines the maintenance tasks of the pallet.
+///
+/// Tasks are executed through `frame_system::Call::do_task` by task-aware block builders, each
+/// with an explicit weight, so no maintenance work happens in block hooks.
+#[pallet_section]
+mod tasks {
+    use sp_runtime::traits::CheckedSub;
+
+    #[pallet::tasks_experimental]
+    impl<T: Config> Pallet<T> {
+        /// Remove an RSSI report older than `RssiRetention` blocks.
+        #[pallet::task_list(RssiData::<T>::iter_keys())]
+        #[pallet::task_condition(|block_number, neighbor, reporter| {
+            Self::is_report_expired(block_number)
+                && RssiData::<T>::contains_key((block_number, neighbor, reporter))
+        })]
+        #[pallet::task_weight(T::WeightInfo::prune_rssi_report())]
+        #[pallet::task_index(0)]
+        pub fn prune_rssi_report(
+            block_number: BlockNumberFor<T>,
+            neighbor: T::AccountId,
+            reporter: T::AccountId,
+        ) -> DispatchResult {
+            let key = (block_number, neighbor, reporter);
+            RssiData::<T>::remove(&key);
+            TxPowerData::<T>::remove(&key);
+
+            Ok(())
+        }
+
+        /// Remove a node that has been inactive for more than `NodeExpiry` blocks.
+        #[pallet::task_list(AccountData::<T>::iter_keys())]
+        #[pallet::task_condition(|who| Self::last_active(&who)
+            .is_some_and(|last_active| Self::is_node_expired(last_active)))]
+        #[pallet::task_weight(T::WeightInfo::expire_node())]
+        #[pallet::task_index(1)]
+        pub fn expire_node(who: T::AccountId) -> DispatchResult {
+            // Get the last activity before the node's data is removed
+            let last_active = Self::last_active(&who).ok_or(Error::<T>::AccountNotRegistered)?;
+
+            let address = Self::remove_node(&who).ok_or(Error::<T>::AccountNotRegistered)?;
+
+            // Emit an event
+            Self::deposit_event(Event::NodeExpired {
+                address,
+                who,
+                last_active,
+            });
+
+            Ok(())
+        }
+
+        /// Store the trust score of a registered node over a finished epoch.
+        ///
+        /// Only the last finished epoch is listed, but earlier epochs can still be rolled up as
+        /// long as their reports have not been pruned.
+        #[pallet::task_list(Self::last_finished_epoch().into_iter().flat_map(|epoch| {
+            AccountData::<T>::iter_keys().map(move |who| (epoch, who))
+        }))]
+        #[pallet::task_condition(|epoch, who| Self::is_epoch_finished(epoch)
+            && AccountData::<T>::contains_key(&who)
+            && !EpochTrustScores::<T>::contains_key(epoch, &who))]
+        #[pallet::task_weight(T::WeightInfo::rollup_trust_score(Self::max_epoch_reports()))]
+        #[pallet::task_index(2)]
+        pub fn rollup_trust_score(epoch: BlockNumberFor<T>, who: T::AccountId) -> DispatchResult {
+            let score = Self::calculate_epoch_trust_score(epoch, &who);
+            EpochTrustScores::<T>::insert(epoch, &who, score);
+
+            // Emit an event
+            Self::deposit_event(Event::TrustScoreRolledUp { epoch, who, score });
+
+            Ok(())
+        }
+
+        /// Remove a UWB range older than `RssiRetention` blocks.
+        #[pallet::task_list(RangeData::<T>::iter_keys())]
+        #[pallet::task_condition(|block_number, neighbor, reporter| {
+            Self::is_report_expired(block_number)
+                && RangeData::<T>::contains_key((block_number, neighbor, reporter))
+        })]
+        #[pallet::task_weight(T::WeightInfo::prune_range_report())]
+        #[pallet::task_index(3)]
+        pub fn prune_range_report(
+            block_number: BlockNumberFor<T>,
+            neighbor: T::AccountId,
+            reporter: T::AccountId,
+        ) -> DispatchResult {
+            RangeData::<T>::remove((block_number, neighbor, reporter));
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Whether reports of `block_number` are older than `RssiRetention`.
+        fn is_report_expired(block_number: BlockNumberFor<T>) -> bool {
+            let now = frame_system::Pallet::<T>::block_number();
+            now.saturating_sub(block_number) > T::RssiRetention::get()
+        }
+
+        /// Last block in which `who` registered, updated its node or reported RSSI data.
+        fn last_active(who: &T::AccountId) -> Option<BlockNumberFor<T>> {
+            let location_data = AccountData::<T>::get(who)?;
+            let last_updated: BlockNumberFor<T> = location_data.last_updated.into();
+            Some(LastActivity::<T>::get(who).map_or(last_updated, |last| last.max(last_updated)))
+        }
+
+        /// Whether a node last active at `last_active` is older than `NodeExpiry`.
+        fn is_node_expired(last_active: BlockNumberFor<T>) -> bool {
+            let now = frame_system::Pallet::<T>::block_number();
+            now.saturating_sub(last_active) > T::NodeExpiry::get()
+        }
+
+        /// Whether every block of `epoch` lies before the current block.
+        fn is_epoch_finished(epoch: BlockNumberFor<T>) -> bool {
+            let now = frame_system::Pallet::<T>::block_number();
+            epoch < now / T::EpochLength::get()
+        }
+
+        /// Epoch that finished most recently, if any.
+        fn last_finished_epoch() -> Option<BlockNumberFor<T>> {
+            let now = frame_system::Pallet::<T>::block_number();
+            (now / T::EpochLength::get()).checked_sub(&1u32.into())
+        }
+
+        /// Maximum number of reports about a node within one epoch.
+        pub(crate) fn max_epoch_reports() -> u32 {
+            let epoch_length: u32 = T::EpochLength::get().saturated_into();
+            epoch_length.saturating_mul(T::MaxReportsPerNode::get())
+        }
+    }
+}
@@ -1,12 +1,49 @@
-use crate::{mock::*, AccountData, AddressRegistrationData, Error, Event, ServerConfig};
-use frame_support::{assert_noop, assert_ok};
-use sp_runtime::AccountId32;
+use crate::{
+    migrations::InitializeNodeCounter, mock::*, weights::WeightInfo, AccountData,
+    AddressRegistrationData, EpochTrustScores, Error, Event, IdentityResolvingKey, LastActivity,
+    LocationServerConfig, PruneCursor, RangeData, RssiData, ServerConfig, ServerKey, Task,
+    TxPowerData, WifiAddress, WifiAddressRegistrationData,
+};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::{OnRuntimeUpgrade, StorageVersion, Task as _},
+};
+use sp_runtime::{traits::Dispatchable, AccountId32, BuildStorage};
 
 // Helper function to create AccountId32 from u32
 fn account(id: u32) -> AccountId32 {
     AccountId32::new([id as u8; 32])
 }
 
+#[test]
+fn genesis_config_registers_nodes() {
+    let account = account(1);
+    let address = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+    let latitude = 37_774_929;
+    let longitude = -122_419_415;
+
+    let mut storage = frame_system::GenesisConfig::<Test>::default()
+        .build_storage()
+        .unwrap();
+    crate::GenesisConfig::<Test> {
+        nodes: vec![(account.clone(), address, latitude, longitude)],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    sp_io::TestExternalities::from(storage).execute_with(|| {
+        // Verify storage was populated
+        let location_data = AccountData::<Test>::get(&account).unwrap();
+        assert_eq!(location_data.address, address);
+        assert_eq!(location_data.latitude, latitude);
+        assert_eq!(location_data.longitude, longitude);
+        assert_eq!(location_data.last_updated, 0);
+
+        // Verify address mapping
+        assert_eq!(AddressRegistrationData::<Test>::get(address), Some(account));
+    });
+}
+
 #[test]
 fn set_server_config_works() {
     new_test_ext().execute_with(|| {
@@ -125,6 +162,36 @@ fn register_node_fails_with_duplicate_account() {
     });
 }
 
+#[test]
+fn register_node_fails_beyond_max_nodes() {
+    new_test_ext().execute_with(|| {
+        let max_nodes = MaxNodes::get();
+        register_nearby_nodes(max_nodes);
+        assert_eq!(ProofOfLocation::node_count(), max_nodes);
+
+        assert_noop!(
+            ProofOfLocation::register_node(
+                RuntimeOrigin::signed(account(max_nodes)),
+                [0x20, 0x00, 0x00, 0x00, 0x00, 0x00],
+                37_774_929,
+                -122_419_415
+            ),
+            Error::<Test>::TooManyNodes
+        );
+
+        // A node leaving makes room for another one
+        assert_ok!(ProofOfLocation::unregister_node(RuntimeOrigin::signed(
+            account(0)
+        )));
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account(max_nodes)),
+            [0x20, 0x00, 0x00, 0x00, 0x00, 0x00],
+            37_774_929,
+            -122_419_415
+        ));
+    });
+}
+
 #[test]
 fn unregister_node_works() {
     new_test_ext().execute_with(|| {
@@ -146,6 +213,19 @@ fn unregister_node_works() {
             RuntimeOrigin::signed(account.clone()),
             server_url
         ));
+        assert_ok!(ProofOfLocation::set_location_server_config(
+            RuntimeOrigin::signed(account.clone()),
+            Some(b"localhost:4000".to_vec())
+        ));
+        assert_ok!(ProofOfLocation::set_identity_resolving_key(
+            RuntimeOrigin::signed(account.clone()),
+            Some([0x42; 16])
+        ));
+        let bssid = [0x20, 0x22, 0x33, 0x44, 0x55, 0x66];
+        assert_ok!(ProofOfLocation::set_wifi_address(
+            RuntimeOrigin::signed(account.clone()),
+            Some(bssid)
+        ));
 
         // Unregister node
         assert_ok!(ProofOfLocation::unregister_node(RuntimeOrigin::signed(
@@ -156,6 +236,10 @@ fn unregister_node_works() {
         assert_eq!(AccountData::<Test>::get(&account), None);
         assert_eq!(AddressRegistrationData::<Test>::get(address), None);
         assert_eq!(ServerConfig::<Test>::get(&account), None);
+        assert_eq!(LocationServerConfig::<Test>::get(&account), None);
+        assert_eq!(IdentityResolvingKey::<Test>::get(&account), None);
+        assert_eq!(WifiAddress::<Test>::get(&account), None);
+        assert_eq!(WifiAddressRegistrationData::<Test>::get(bssid), None);
 
         // Verify event was emitted
         System::assert_last_event(
@@ -241,6 +325,51 @@ fn update_node_info_works() {
     });
 }
 
+#[test]
+fn update_node_info_refunds_weight_if_address_unchanged() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account = account(1);
+        let address = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+
+        // Register node
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account.clone()),
+            address,
+            37_774_929,
+            -122_419_415
+        ));
+
+        // Only move the node, keeping its Bluetooth address
+        System::set_block_number(7);
+        let post_info = ProofOfLocation::update_node_info(
+            RuntimeOrigin::signed(account.clone()),
+            address,
+            40_712_776,
+            -74_005_974,
+        )
+        .unwrap();
+        assert_eq!(
+            post_info.actual_weight,
+            Some(<() as WeightInfo>::update_node_info_same_address())
+        );
+
+        // Changing the address is charged in full
+        System::set_block_number(13);
+        let post_info = ProofOfLocation::update_node_info(
+            RuntimeOrigin::signed(account.clone()),
+            [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF],
+            40_712_776,
+            -74_005_974,
+        )
+        .unwrap();
+        assert_eq!(
+            post_info.actual_weight,
+            Some(<() as WeightInfo>::update_node_info())
+        );
+    });
+}
+
 #[test]
 fn update_node_info_fails_if_not_registered() {
     new_test_ext().execute_with(|| {
@@ -389,7 +518,8 @@ fn publish_rssi_data_works() {
         assert_ok!(ProofOfLocation::publish_rssi_data(
             RuntimeOrigin::signed(account1.clone()),
             account2.clone(),
-            rssi
+            rssi,
+            None
         ));
 
         // Verify event was emitted
@@ -399,6 +529,7 @@ fn publish_rssi_data_works() {
                 neighbor: account2,
                 who: account1,
                 rssi,
+                tx_power: None,
             }
             .into(),
         );
@@ -425,9 +556,10 @@ fn publish_rssi_data_fails_if_reporter_not_registered() {
             ProofOfLocation::publish_rssi_data(
                 RuntimeOrigin::signed(account1.clone()),
                 account2,
-                -65
+                -65,
+                None
             ),
-            Error::<Test>::AccountNotRegistered
+            Error::<Test>::ReporterNotRegistered
         );
     });
 }
@@ -452,9 +584,36 @@ fn publish_rssi_data_fails_if_neighbor_not_registered() {
             ProofOfLocation::publish_rssi_data(
                 RuntimeOrigin::signed(account1.clone()),
                 account2,
-                -65
+                -65,
+                None
             ),
-            Error::<Test>::AccountNotRegistered
+            Error::<Test>::NeighborNotRegistered
+        );
+    });
+}
+
+#[test]
+fn publish_rssi_data_fails_if_reporting_itself() {
+    new_test_ext().execute_with(|| {
+        let account1 = account(1);
+        let address1 = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account1.clone()),
+            address1,
+            37_774_929,
+            -122_419_415
+        ));
+
+        // Try to publish RSSI about itself
+        assert_noop!(
+            ProofOfLocation::publish_rssi_data(
+                RuntimeOrigin::signed(account1.clone()),
+                account1,
+                -65,
+                None
+            ),
+            Error::<Test>::SelfReportNotAllowed
         );
     });
 }
@@ -492,9 +651,1326 @@ fn publish_rssi_data_fails_if_distance_exceeds_maximum() {
             ProofOfLocation::publish_rssi_data(
                 RuntimeOrigin::signed(account1.clone()),
                 account2,
-                -65
+                -65,
+                None
             ),
             Error::<Test>::ExceedsMaxDistance
         );
     });
 }
+
+#[test]
+fn publish_rssi_data_fails_if_reading_out_of_range() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+        register_two_nodes(&account1, &account2);
+
+        for (rssi, tx_power) in [(-128, None), (21, None), (-65, Some(-128)), (-65, Some(21))] {
+            assert_noop!(
+                ProofOfLocation::publish_rssi_data(
+                    RuntimeOrigin::signed(account1.clone()),
+                    account2.clone(),
+                    rssi,
+                    tx_power
+                ),
+                Error::<Test>::ReadingOutOfRange
+            );
+        }
+
+        // One reading out of range rejects the whole batch
+        assert_noop!(
+            ProofOfLocation::publish_rssi_data_batch(
+                RuntimeOrigin::signed(account1.clone()),
+                vec![
+                    (account2.clone(), -65, None),
+                    (account2.clone(), i16::MIN, None)
+                ]
+                .try_into()
+                .unwrap()
+            ),
+            Error::<Test>::ReadingOutOfRange
+        );
+
+        // The bounds themselves are accepted
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account1),
+            account2,
+            -127,
+            Some(20)
+        ));
+    });
+}
+
+#[test]
+fn publish_rssi_data_fails_if_neighbor_has_max_reports() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let neighbor = account(0);
+
+        // Register the neighbor and one more reporter than `MaxReportsPerNode` allows,
+        // each ~0.11 meters apart
+        let max_reports = MaxReportsPerNode::get();
+        for id in 0..=max_reports + 1 {
+            assert_ok!(ProofOfLocation::register_node(
+                RuntimeOrigin::signed(account(id)),
+                [0x10, 0x00, 0x00, 0x00, 0x00, id as u8],
+                37_774_929 + id as i64,
+                -122_419_415
+            ));
+        }
+
+        // The first `MaxReportsPerNode` reporters fill up the neighbor's reports
+        for id in 1..=max_reports {
+            assert_ok!(ProofOfLocation::publish_rssi_data(
+                RuntimeOrigin::signed(account(id)),
+                neighbor.clone(),
+                -65,
+                None
+            ));
+        }
+
+        // The next reporter is rejected
+        assert_noop!(
+            ProofOfLocation::publish_rssi_data(
+                RuntimeOrigin::signed(account(max_reports + 1)),
+                neighbor.clone(),
+                -65,
+                None
+            ),
+            Error::<Test>::TooManyReports
+        );
+
+        // An existing reporter can still replace its report
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account(1)),
+            neighbor.clone(),
+            -70,
+            None
+        ));
+        assert_eq!(RssiData::<Test>::get((1, neighbor, account(1))), Some(-70));
+    });
+}
+
+/// Register `account(0)` to `account(count - 1)`, each ~0.11 meters apart.
+fn register_nearby_nodes(count: u32) {
+    for id in 0..count {
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account(id)),
+            [0x10, 0x00, 0x00, 0x00, 0x00, id as u8],
+            37_774_929 + id as i64,
+            -122_419_415
+        ));
+    }
+}
+
+#[test]
+fn validate_pair_ignores_report_limit() {
+    use crate::RssiIngestion;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let max_reports = MaxReportsPerNode::get();
+        register_nearby_nodes(max_reports + 2);
+        let neighbor = account(0);
+        let reporter = account(max_reports + 1);
+
+        // Fill up the neighbor's reports of this block
+        for id in 1..=max_reports {
+            RssiData::<Test>::insert((1, neighbor.clone(), account(id)), -65);
+        }
+
+        // The report limit depends on the block, so only `validate_report` checks it
+        assert_ok!(<ProofOfLocation as RssiIngestion<Test>>::validate_pair(
+            &reporter, &neighbor
+        ));
+        assert_eq!(
+            <ProofOfLocation as RssiIngestion<Test>>::validate_report(&reporter, &neighbor),
+            Err(Error::<Test>::TooManyReports.into())
+        );
+
+        // A distant neighbor is rejected
+        let distant = account(50);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(distant.clone()),
+            [0x20, 0x00, 0x00, 0x00, 0x00, 0x00],
+            40_712_776,
+            -74_005_974
+        ));
+        assert_eq!(
+            <ProofOfLocation as RssiIngestion<Test>>::validate_pair(&reporter, &distant),
+            Err(Error::<Test>::ExceedsMaxDistance.into())
+        );
+    });
+}
+
+#[test]
+fn publish_rssi_data_batch_stores_every_reading() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_nearby_nodes(3);
+        let reporter = account(0);
+
+        // One earlier report about the first neighbor from another node
+        RssiData::<Test>::insert((1, account(1), account(9)), -70);
+
+        let post_info = ProofOfLocation::publish_rssi_data_batch(
+            RuntimeOrigin::signed(reporter.clone()),
+            vec![(account(1), -60, None), (account(2), -62, None)]
+                .try_into()
+                .unwrap(),
+        )
+        .unwrap();
+
+        // Verify both readings were stored
+        assert_eq!(
+            RssiData::<Test>::get((1, account(1), reporter.clone())),
+            Some(-60)
+        );
+        assert_eq!(
+            RssiData::<Test>::get((1, account(2), reporter.clone())),
+            Some(-62)
+        );
+        System::assert_last_event(
+            Event::RssiStored {
+                block_number: 1,
+                neighbor: account(2),
+                who: reporter,
+                rssi: -62,
+                tx_power: None,
+            }
+            .into(),
+        );
+
+        // Only the one counted report is charged
+        assert_eq!(
+            post_info.actual_weight,
+            Some(<() as WeightInfo>::publish_rssi_data_batch(
+                MaxNodes::get(),
+                2,
+                1
+            ))
+        );
+    });
+}
+
+#[test]
+fn publish_rssi_data_batch_fails_if_any_reading_is_invalid() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_nearby_nodes(2);
+        let reporter = account(0);
+
+        // The second reading is a self report
+        let call = RuntimeCall::ProofOfLocation(crate::Call::publish_rssi_data_batch {
+            readings: vec![(account(1), -60, None), (reporter.clone(), -61, None)]
+                .try_into()
+                .unwrap(),
+        });
+        let result = call.dispatch(RuntimeOrigin::signed(reporter.clone()));
+        assert_eq!(
+            result.map_err(|e| e.error),
+            Err(Error::<Test>::SelfReportNotAllowed.into())
+        );
+
+        // The valid reading was rolled back as well
+        assert_eq!(RssiData::<Test>::get((1, account(1), reporter)), None);
+    });
+}
+
+#[test]
+fn try_state_detects_inconsistent_address_registrations() {
+    new_test_ext().execute_with(|| {
+        let account1 = account(1);
+        let address1 = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account1.clone()),
+            address1,
+            37_774_929,
+            -122_419_415
+        ));
+        assert_ok!(ProofOfLocation::do_try_state());
+
+        // Address that doesn't belong to any node
+        AddressRegistrationData::<Test>::insert([0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF], account(2));
+        assert!(ProofOfLocation::do_try_state().is_err());
+        AddressRegistrationData::<Test>::remove([0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]);
+
+        // Node whose address is missing
+        AddressRegistrationData::<Test>::remove(address1);
+        assert!(ProofOfLocation::do_try_state().is_err());
+    });
+}
+
+#[test]
+fn prune_my_reports_removes_only_own_old_reports() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+
+        // Reports from account1 at blocks 1 and 2, and from account2 at block 1
+        RssiData::<Test>::insert((1, account2.clone(), account1.clone()), -60);
+        RssiData::<Test>::insert((2, account2.clone(), account1.clone()), -61);
+        RssiData::<Test>::insert((1, account1.clone(), account2.clone()), -62);
+
+        // Prune account1's reports up to block 1
+        assert_ok!(ProofOfLocation::prune_my_reports(
+            RuntimeOrigin::signed(account1.clone()),
+            1
+        ));
+
+        // Only account1's report at block 1 was removed
+        assert_eq!(
+            RssiData::<Test>::get((1, account2.clone(), account1.clone())),
+            None
+        );
+        assert_eq!(
+            RssiData::<Test>::get((2, account2.clone(), account1.clone())),
+            Some(-61)
+        );
+        assert_eq!(
+            RssiData::<Test>::get((1, account1.clone(), account2.clone())),
+            Some(-62)
+        );
+
+        // Verify event was emitted
+        System::assert_last_event(
+            Event::ReportsPruned {
+                who: account1,
+                up_to_block: 1,
+                removed: 1,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn prune_my_reports_resumes_from_cursor() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+
+        // More reports than MaxPruneReports (3 in tests)
+        for block in 1..=5 {
+            RssiData::<Test>::insert((block, account2.clone(), account1.clone()), -60);
+        }
+
+        // First call stops at the limit and remembers its position
+        assert_ok!(ProofOfLocation::prune_my_reports(
+            RuntimeOrigin::signed(account1.clone()),
+            5
+        ));
+        assert_eq!(RssiData::<Test>::iter_keys().count(), 2);
+        assert!(PruneCursor::<Test>::get(&account1).is_some());
+
+        // Second call continues the scan and finishes it
+        assert_ok!(ProofOfLocation::prune_my_reports(
+            RuntimeOrigin::signed(account1.clone()),
+            5
+        ));
+        assert_eq!(RssiData::<Test>::iter_keys().count(), 0);
+        assert!(PruneCursor::<Test>::get(&account1).is_none());
+    });
+}
+
+// Helper function to register two nodes ~0.11 meters apart
+fn register_two_nodes(account1: &AccountId32, account2: &AccountId32) {
+    assert_ok!(ProofOfLocation::register_node(
+        RuntimeOrigin::signed(account1.clone()),
+        [0x11, 0x22, 0x33, 0x44, 0x55, 0x66],
+        37_774_929,
+        -122_419_415
+    ));
+    assert_ok!(ProofOfLocation::register_node(
+        RuntimeOrigin::signed(account2.clone()),
+        [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF],
+        37_774_930,
+        -122_419_416
+    ));
+}
+
+#[test]
+fn report_misbehavior_emits_event() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let reporter = account(1);
+        let offender = account(2);
+        register_two_nodes(&reporter, &offender);
+
+        assert_ok!(ProofOfLocation::report_misbehavior(
+            RuntimeOrigin::signed(reporter.clone()),
+            offender.clone(),
+            35
+        ));
+        System::assert_last_event(
+            Event::MisbehaviorReported {
+                reporter: reporter.clone(),
+                offender,
+                rssi_error: 35,
+            }
+            .into(),
+        );
+
+        // Nodes cannot report themselves or unregistered accounts
+        assert_noop!(
+            ProofOfLocation::report_misbehavior(
+                RuntimeOrigin::signed(reporter.clone()),
+                reporter.clone(),
+                35
+            ),
+            Error::<Test>::SelfReportNotAllowed
+        );
+        assert_noop!(
+            ProofOfLocation::report_misbehavior(
+                RuntimeOrigin::signed(reporter.clone()),
+                account(3),
+                35
+            ),
+            Error::<Test>::NeighborNotRegistered
+        );
+        assert_noop!(
+            ProofOfLocation::report_misbehavior(RuntimeOrigin::signed(account(3)), reporter, 35),
+            Error::<Test>::ReporterNotRegistered
+        );
+    });
+}
+
+#[test]
+fn prune_rssi_report_task_removes_only_expired_reports() {
+    new_test_ext().execute_with(|| {
+        let account1 = account(1);
+        let account2 = account(2);
+
+        // Reports from account1 at blocks 1 and 5, the first with a TX power
+        RssiData::<Test>::insert((1, account2.clone(), account1.clone()), -60);
+        TxPowerData::<Test>::insert((1, account2.clone(), account1.clone()), 0);
+        RssiData::<Test>::insert((5, account2.clone(), account1.clone()), -61);
+
+        // Block 1 is older than RssiRetention (10 in tests), block 5 is not
+        System::set_block_number(12);
+        let tasks: Vec<_> = Task::<Test>::iter()
+            .filter(|task| task.is_valid())
+            .collect();
+        assert_eq!(
+            tasks,
+            vec![Task::prune_rssi_report {
+                block_number: 1,
+                neighbor: account2.clone(),
+                reporter: account1.clone(),
+            }]
+        );
+
+        for task in tasks {
+            assert_ok!(task.run());
+        }
+
+        assert_eq!(
+            RssiData::<Test>::get((1, account2.clone(), account1.clone())),
+            None
+        );
+        assert_eq!(
+            TxPowerData::<Test>::get((1, account2.clone(), account1.clone())),
+            None
+        );
+        assert_eq!(RssiData::<Test>::get((5, account2, account1)), Some(-61));
+    });
+}
+
+#[test]
+fn publish_range_data_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+        register_two_nodes(&account1, &account2);
+
+        assert_ok!(ProofOfLocation::publish_range_data(
+            RuntimeOrigin::signed(account1.clone()),
+            account2.clone(),
+            12
+        ));
+
+        assert_eq!(
+            RangeData::<Test>::get((1, account2.clone(), account1.clone())),
+            Some(12)
+        );
+        assert_eq!(LastActivity::<Test>::get(&account1), Some(1));
+        // Ranges are kept apart from the RSSI reports
+        assert_eq!(
+            RssiData::<Test>::get((1, account2.clone(), account1.clone())),
+            None
+        );
+        System::assert_last_event(
+            Event::RangeStored {
+                block_number: 1,
+                neighbor: account2,
+                who: account1,
+                distance_cm: 12,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn publish_range_data_fails_if_range_contradicts_location() {
+    new_test_ext().execute_with(|| {
+        let account1 = account(1);
+        let account2 = account(2);
+        register_two_nodes(&account1, &account2);
+
+        // The nodes are registered ~0.11 meters apart, MaxRangeDeviation is 10 meters
+        assert_noop!(
+            ProofOfLocation::publish_range_data(
+                RuntimeOrigin::signed(account1.clone()),
+                account2.clone(),
+                1_100
+            ),
+            Error::<Test>::RangeContradictsLocation
+        );
+        assert_ok!(ProofOfLocation::publish_range_data(
+            RuntimeOrigin::signed(account1.clone()),
+            account2.clone(),
+            900
+        ));
+
+        // The pair is checked like an RSSI report
+        assert_noop!(
+            ProofOfLocation::publish_range_data(
+                RuntimeOrigin::signed(account1.clone()),
+                account1.clone(),
+                10
+            ),
+            Error::<Test>::SelfReportNotAllowed
+        );
+        assert_noop!(
+            ProofOfLocation::publish_range_data(RuntimeOrigin::signed(account(3)), account1, 10),
+            Error::<Test>::ReporterNotRegistered
+        );
+    });
+}
+
+#[test]
+fn prune_range_report_task_removes_only_expired_ranges() {
+    new_test_ext().execute_with(|| {
+        let account1 = account(1);
+        let account2 = account(2);
+
+        RangeData::<Test>::insert((1, account2.clone(), account1.clone()), 12);
+        RangeData::<Test>::insert((5, account2.clone(), account1.clone()), 13);
+
+        // Block 1 is older than RssiRetention (10 in tests), block 5 is not
+        System::set_block_number(12);
+        let tasks: Vec<_> = Task::<Test>::iter()
+            .filter(|task| task.is_valid())
+            .collect();
+        assert_eq!(
+            tasks,
+            vec![Task::prune_range_report {
+                block_number: 1,
+                neighbor: account2.clone(),
+                reporter: account1.clone(),
+            }]
+        );
+
+        for task in tasks {
+            assert_ok!(task.run());
+        }
+
+        assert_eq!(
+            RangeData::<Test>::get((1, account2.clone(), account1.clone())),
+            None
+        );
+        assert_eq!(RangeData::<Test>::get((5, account2, account1)), Some(13));
+    });
+}
+
+#[test]
+fn expire_node_task_removes_inactive_nodes() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+        register_two_nodes(&account1, &account2);
+
+        // Only account2 stays active by reporting RSSI data
+        System::set_block_number(15);
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account2.clone()),
+            account1.clone(),
+            -65,
+            None
+        ));
+        assert_eq!(LastActivity::<Test>::get(&account2), Some(15));
+
+        // account1 has been inactive for longer than NodeExpiry (20 in tests)
+        System::set_block_number(22);
+        let tasks: Vec<_> = Task::<Test>::iter()
+            .filter(|task| matches!(task, Task::expire_node { .. }) && task.is_valid())
+            .collect();
+        assert_eq!(
+            tasks,
+            vec![Task::expire_node {
+                who: account1.clone()
+            }]
+        );
+
+        for task in tasks {
+            assert_ok!(task.run());
+        }
+
+        assert_eq!(AccountData::<Test>::get(&account1), None);
+        assert_eq!(
+            AddressRegistrationData::<Test>::get([0x11, 0x22, 0x33, 0x44, 0x55, 0x66]),
+            None
+        );
+        assert!(AccountData::<Test>::get(&account2).is_some());
+        assert_ok!(ProofOfLocation::do_try_state());
+
+        // Verify event was emitted
+        System::assert_last_event(
+            Event::NodeExpired {
+                address: [0x11, 0x22, 0x33, 0x44, 0x55, 0x66],
+                who: account1,
+                last_active: 1,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn rollup_trust_score_task_stores_scores_of_finished_epoch() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+        register_two_nodes(&account1, &account2);
+
+        // account2 reports account1 within epoch 0 (blocks 0 to 4 in tests)
+        System::set_block_number(2);
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account2.clone()),
+            account1.clone(),
+            -65,
+            None
+        ));
+        let expected_score = ProofOfLocation::calculate_trust_score_for_account(2, &account1);
+        assert!(expected_score.is_some());
+
+        // Epoch 0 is not finished until block 5
+        System::set_block_number(4);
+        assert!(!Task::<Test>::rollup_trust_score {
+            epoch: 0,
+            who: account1.clone()
+        }
+        .is_valid());
+
+        System::set_block_number(5);
+        let tasks: Vec<_> = Task::<Test>::iter()
+            .filter(|task| matches!(task, Task::rollup_trust_score { .. }) && task.is_valid())
+            .collect();
+        assert_eq!(tasks.len(), 2);
+
+        for task in tasks {
+            assert_ok!(task.run());
+        }
+
+        // Nodes without reports are rolled up without a score
+        assert_eq!(
+            EpochTrustScores::<Test>::get(0, &account1),
+            Some(expected_score)
+        );
+        assert_eq!(EpochTrustScores::<Test>::get(0, &account2), Some(None));
+
+        // Each epoch is rolled up only once
+        assert!(!Task::<Test>::iter().any(|task| task.is_valid()));
+    });
+}
+
+#[test]
+fn view_functions_return_node_data_and_trust_scores() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+        let address1 = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+        let address2 = [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF];
+
+        assert_eq!(ProofOfLocation::node_count(), 0);
+        assert_eq!(ProofOfLocation::account_data(account1.clone()), None);
+
+        // Register both nodes
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account1.clone()),
+            address1,
+            37_774_929,
+            -122_419_415
+        ));
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account2.clone()),
+            address2,
+            37_774_930,
+            -122_419_416
+        ));
+
+        assert_eq!(ProofOfLocation::node_count(), 2);
+        let location_data = ProofOfLocation::account_data(account1.clone()).unwrap();
+        assert_eq!(location_data.address, address1);
+        assert_eq!(location_data.latitude, 37_774_929);
+
+        // No reports yet, so no trust score
+        assert_eq!(ProofOfLocation::trust_score(account2.clone()), None);
+
+        // A report about account2 in the current block gives it a score
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account1.clone()),
+            account2.clone(),
+            -65,
+            None
+        ));
+        assert_eq!(
+            ProofOfLocation::trust_score(account2.clone()),
+            ProofOfLocation::calculate_trust_score_for_account(1, &account2)
+        );
+        assert!(ProofOfLocation::trust_score(account2).is_some());
+    });
+}
+
+#[test]
+fn node_counter_migration_counts_registered_nodes() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_two_nodes(&account(1), &account(2));
+
+        // Storage as left by version 0, before `AccountData` was counted
+        frame_support::storage::unhashed::kill(&AccountData::<Test>::counter_storage_final_key());
+        StorageVersion::new(0).put::<ProofOfLocation>();
+        assert_eq!(ProofOfLocation::node_count(), 0);
+
+        InitializeNodeCounter::<Test>::on_runtime_upgrade();
+
+        assert_eq!(ProofOfLocation::node_count(), 2);
+        assert_eq!(StorageVersion::get::<ProofOfLocation>(), 1);
+
+        // Already migrated, so a second run leaves the counter alone
+        assert_ok!(ProofOfLocation::unregister_node(RuntimeOrigin::signed(
+            account(1)
+        )));
+        InitializeNodeCounter::<Test>::on_runtime_upgrade();
+        assert_eq!(ProofOfLocation::node_count(), 1);
+    });
+}
+
+#[test]
+fn trust_score_breakdown_lists_each_report() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+        let account3 = account(3);
+        register_two_nodes(&account1, &account2);
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account3.clone()),
+            [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            37_774_931,
+            -122_419_415
+        ));
+
+        // account1 and account3 both report account2
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account1.clone()),
+            account2.clone(),
+            -50,
+            None
+        ));
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account3.clone()),
+            account2.clone(),
+            -80,
+            None
+        ));
+
+        let mut breakdown = ProofOfLocation::trust_score_breakdown(1, &account2);
+        breakdown.sort_by_key(|(_, measured, _, _)| *measured);
+
+        // One entry per reporter, with the error between measured and estimated RSSI
+        assert_eq!(breakdown.len(), 2);
+        assert_eq!(breakdown[0].0, account3);
+        assert_eq!(breakdown[0].1, -80);
+        assert_eq!(breakdown[1].0, account1);
+        assert_eq!(breakdown[1].1, -50);
+        for (_, measured, estimated, error) in &breakdown {
+            assert_eq!(*error, measured - estimated);
+        }
+
+        // Nodes without reports have an empty breakdown
+        assert!(ProofOfLocation::trust_score_breakdown(1, &account1).is_empty());
+    });
+}
+
+#[test]
+fn tx_power_estimates_from_path_loss() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let account1 = account(1);
+        let account2 = account(2);
+        register_two_nodes(&account1, &account2);
+
+        // account2 transmits 8 dB stronger than the reference
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account1.clone()),
+            account2.clone(),
+            -50,
+            Some(8)
+        ));
+        assert_eq!(
+            TxPowerData::<Test>::get((1, account2.clone(), account1.clone())),
+            Some(8)
+        );
+        System::assert_last_event(
+            Event::RssiStored {
+                block_number: 1,
+                neighbor: account2.clone(),
+                who: account1.clone(),
+                rssi: -50,
+                tx_power: Some(8),
+            }
+            .into(),
+        );
+
+        // The estimate is 8 dB higher than without the TX power
+        let with_tx_power = ProofOfLocation::trust_score_breakdown(1, &account2);
+        assert_ok!(ProofOfLocation::publish_rssi_data(
+            RuntimeOrigin::signed(account1.clone()),
+            account2.clone(),
+            -50,
+            None
+        ));
+        let without_tx_power = ProofOfLocation::trust_score_breakdown(1, &account2);
+        assert_eq!(with_tx_power[0].2, without_tx_power[0].2 + 8);
+        assert_eq!(with_tx_power[0].3, without_tx_power[0].3 - 8);
+
+        // Replacing the report without a TX power removes the earlier one
+        assert_eq!(TxPowerData::<Test>::get((1, account2, account1)), None);
+    });
+}
+
+#[test]
+fn distance_meters_between_fixed_point_locations() {
+    use crate::util::distance_meters;
+
+    // Same location
+    assert_eq!(
+        distance_meters(37_774_929, -122_419_415, 37_774_929, -122_419_415),
+        0.0
+    );
+
+    // 0.0001 degrees of latitude are ~11.1 meters
+    let distance = distance_meters(37_774_929, -122_419_415, 37_775_029, -122_419_415);
+    assert!((distance - 11.1).abs() < 0.1);
+}
+
+#[test]
+fn json_responses_are_parsed() {
+    use crate::util::{location_response_from_json, rssi_response_from_json};
+
+    let location = location_response_from_json(
+        br#"{"address": "AA:BB:CC:DD:EE:FF", "location": {"latitude": 37.774929, "longitude": -122.419415}, "timestamp": 1700000000000, "sequence": 42}"#,
+    )
+    .unwrap();
+    assert_eq!(location.address, [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]);
+    assert!((location.location.latitude - 37.774929).abs() < 1e-9);
+    assert!((location.location.longitude + 122.419415).abs() < 1e-9);
+    assert_eq!(location.timestamp, 1_700_000_000_000);
+    assert_eq!(location.sequence, Some(42));
+
+    let rssi = rssi_response_from_json(
+        br#"{"devices": [{"address": "11:22:33:44:55:66", "rssi": -61}, {"address": "aa:bb:cc:dd:ee:ff", "rssi": -70}], "timestamp": 1700000000000}"#,
+    )
+    .unwrap();
+    assert_eq!(rssi.devices.len(), 2);
+    assert_eq!(
+        rssi.devices[0].address,
+        [0x11, 0x22, 0x33, 0x44, 0x55, 0x66]
+    );
+    assert_eq!(rssi.devices[0].rssi, -61);
+    assert_eq!(
+        rssi.devices[1].address,
+        [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF]
+    );
+    assert_eq!(rssi.devices[1].rssi, -70);
+    assert_eq!(rssi.devices[0].tx_power, None);
+    assert_eq!(rssi.sequence, None);
+
+    // The TX power is optional and may be null
+    let rssi = rssi_response_from_json(
+        br#"{"devices": [{"address": "11:22:33:44:55:66", "rssi": -61, "tx_power": 4}, {"address": "aa:bb:cc:dd:ee:ff", "rssi": -70, "tx_power": null}], "timestamp": 1700000000000}"#,
+    )
+    .unwrap();
+    assert_eq!(rssi.devices[0].tx_power, Some(4));
+    assert_eq!(rssi.devices[1].tx_power, None);
+
+    // Devices without a timestamp are taken to be measured when the response was produced
+    let rssi = rssi_response_from_json(
+        br#"{"devices": [{"address": "11:22:33:44:55:66", "rssi": -61, "timestamp": 1699999998500}, {"address": "aa:bb:cc:dd:ee:ff", "rssi": -70}], "timestamp": 1700000000000}"#,
+    )
+    .unwrap();
+    assert_eq!(rssi.devices[0].timestamp, 1_699_999_998_500);
+    assert_eq!(rssi.devices[1].timestamp, 1_700_000_000_000);
+
+    // Malformed addresses and missing fields are rejected
+    assert!(rssi_response_from_json(
+        br#"{"devices": [{"address": "11:22:33", "rssi": -61}], "timestamp": 1700000000000}"#
+    )
+    .is_none());
+    assert!(rssi_response_from_json(br#"{"devices": []}"#).is_none());
+    assert!(location_response_from_json(br#"{"address": "AA:BB:CC:DD:EE:FF"}"#).is_none());
+}
+
+#[test]
+fn set_server_key_works() {
+    new_test_ext().execute_with(|| {
+        let account = account(1);
+
+        // Set the server key
+        assert_ok!(ProofOfLocation::set_server_key(
+            RuntimeOrigin::signed(account.clone()),
+            Some([0x42; 32])
+        ));
+        assert_eq!(ServerKey::<Test>::get(&account), Some([0x42; 32]));
+
+        // Clearing it accepts unsigned responses again
+        assert_ok!(ProofOfLocation::set_server_key(
+            RuntimeOrigin::signed(account.clone()),
+            None
+        ));
+        assert_eq!(ServerKey::<Test>::get(&account), None);
+    });
+}
+
+#[test]
+fn set_identity_resolving_key_works() {
+    new_test_ext().execute_with(|| {
+        let account = account(1);
+
+        assert_ok!(ProofOfLocation::set_identity_resolving_key(
+            RuntimeOrigin::signed(account.clone()),
+            Some([0x42; 16])
+        ));
+        assert_eq!(
+            IdentityResolvingKey::<Test>::get(&account),
+            Some([0x42; 16])
+        );
+
+        // Clearing it when the node advertises its identity address again
+        assert_ok!(ProofOfLocation::set_identity_resolving_key(
+            RuntimeOrigin::signed(account.clone()),
+            None
+        ));
+        assert_eq!(IdentityResolvingKey::<Test>::get(&account), None);
+    });
+}
+
+#[test]
+fn set_wifi_address_works() {
+    new_test_ext().execute_with(|| {
+        let account = account(1);
+        let bssid = [0x20, 0x22, 0x33, 0x44, 0x55, 0x66];
+
+        // Only registered nodes have a BSSID
+        assert_noop!(
+            ProofOfLocation::set_wifi_address(RuntimeOrigin::signed(account.clone()), Some(bssid)),
+            Error::<Test>::AccountNotRegistered
+        );
+
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account.clone()),
+            [0x11, 0x22, 0x33, 0x44, 0x55, 0x66],
+            37_774_929,
+            -122_419_415
+        ));
+        assert_ok!(ProofOfLocation::set_wifi_address(
+            RuntimeOrigin::signed(account.clone()),
+            Some(bssid)
+        ));
+        assert_eq!(WifiAddress::<Test>::get(&account), Some(bssid));
+        assert_eq!(
+            ProofOfLocation::account_of_address(bssid),
+            Some(account.clone())
+        );
+
+        // A new BSSID replaces the old one
+        let new_bssid = [0x20, 0x22, 0x33, 0x44, 0x55, 0x67];
+        assert_ok!(ProofOfLocation::set_wifi_address(
+            RuntimeOrigin::signed(account.clone()),
+            Some(new_bssid)
+        ));
+        assert_eq!(WifiAddressRegistrationData::<Test>::get(bssid), None);
+        assert_eq!(
+            WifiAddressRegistrationData::<Test>::get(new_bssid),
+            Some(account.clone())
+        );
+
+        // Clearing it when the node is no longer measured over Wi-Fi
+        assert_ok!(ProofOfLocation::set_wifi_address(
+            RuntimeOrigin::signed(account.clone()),
+            None
+        ));
+        assert_eq!(WifiAddress::<Test>::get(&account), None);
+        assert_eq!(WifiAddressRegistrationData::<Test>::get(new_bssid), None);
+    });
+}
+
+#[test]
+fn set_wifi_address_rejects_taken_addresses() {
+    new_test_ext().execute_with(|| {
+        let account1 = account(1);
+        let account2 = account(2);
+        let address1 = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+        let address2 = [0x11, 0x22, 0x33, 0x44, 0x55, 0x77];
+        let bssid = [0x20, 0x22, 0x33, 0x44, 0x55, 0x66];
+
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account1.clone()),
+            address1,
+            37_774_929,
+            -122_419_415
+        ));
+        assert_ok!(ProofOfLocation::register_node(
+            RuntimeOrigin::signed(account2.clone()),
+            address2,
+            37_774_929,
+            -122_419_415
+        ));
+        assert_ok!(ProofOfLocation::set_wifi_address(
+            RuntimeOrigin::signed(account1.clone()),
+            Some(bssid)
+        ));
+
+        // Another node's BSSID
+        assert_noop!(
+            ProofOfLocation::set_wifi_address(RuntimeOrigin::signed(account2.clone()), Some(bssid)),
+            Error::<Test>::WifiAddressAlreadyTaken
+        );
+
+        // Another node's Bluetooth address
+        assert_noop!(
+            ProofOfLocation::set_wifi_address(
+                RuntimeOrigin::signed(account2.clone()),
+                Some(address1)
+            ),
+            Error::<Test>::WifiAddressAlreadyTaken
+        );
+
+        // Nor can a new node register another node's BSSID as its Bluetooth address
+        assert_noop!(
+            ProofOfLocation::register_node(
+                RuntimeOrigin::signed(account(3)),
+                bssid,
+                37_774_929,
+                -122_419_415
+            ),
+            Error::<Test>::BluetoothAddressAlreadyTaken
+        );
+
+        // A node may be measured over both radios at the same address
+        assert_ok!(ProofOfLocation::set_wifi_address(
+            RuntimeOrigin::signed(account2.clone()),
+            Some(address2)
+        ));
+    });
+}
+
+#[test]
+fn set_location_server_config_works() {
+    new_test_ext().execute_with(|| {
+        let account = account(1);
+
+        // The location comes from the RSSI server by default
+        assert_eq!(
+            ProofOfLocation::location_server_url(&account),
+            b"localhost:3000".to_vec()
+        );
+
+        assert_ok!(ProofOfLocation::set_location_server_config(
+            RuntimeOrigin::signed(account.clone()),
+            Some(b"192.168.1.101:8080".to_vec())
+        ));
+        assert_eq!(
+            ProofOfLocation::location_server_url(&account),
+            b"192.168.1.101:8080".to_vec()
+        );
+        assert_eq!(
+            ProofOfLocation::node_config(&account).server_url,
+            b"localhost:3000".to_vec()
+        );
+
+        // Clearing it fetches the location from the RSSI server again
+        assert_ok!(ProofOfLocation::set_location_server_config(
+            RuntimeOrigin::signed(account.clone()),
+            None
+        ));
+        assert_eq!(LocationServerConfig::<Test>::get(&account), None);
+
+        // URLs are bounded like the RSSI server's
+        assert_noop!(
+            ProofOfLocation::set_location_server_config(
+                RuntimeOrigin::signed(account),
+                Some(vec![b'a'; 257])
+            ),
+            "Server URL too long (max 256 bytes)"
+        );
+    });
+}
+
+#[test]
+fn only_payloads_signed_by_server_key_are_accepted() {
+    use crate::util::{verify_signed_payload, SignedPayload};
+    use codec::Encode;
+    use sp_core::{sr25519, Pair};
+
+    let server = sr25519::Pair::from_string("//Server", None).unwrap();
+    let attacker = sr25519::Pair::from_string("//Attacker", None).unwrap();
+    let server_key = server.public().0;
+    let payload = b"readings".to_vec();
+
+    let signed_by = |pair: &sr25519::Pair, payload: &[u8]| {
+        SignedPayload {
+            payload: payload.to_vec(),
+            signature: pair.sign(payload).0,
+        }
+        .encode()
+    };
+
+    // A payload signed by the registered key is unwrapped
+    assert_eq!(
+        verify_signed_payload(&signed_by(&server, &payload), &server_key),
+        Some(payload.clone())
+    );
+
+    // Payloads signed by another key, tampered with or not signed at all are rejected
+    assert_eq!(
+        verify_signed_payload(&signed_by(&attacker, &payload), &server_key),
+        None
+    );
+    let mut tampered = SignedPayload {
+        payload: payload.clone(),
+        signature: server.sign(&payload).0,
+    };
+    tampered.payload[0] ^= 1;
+    assert_eq!(verify_signed_payload(&tampered.encode(), &server_key), None);
+    assert_eq!(verify_signed_payload(&payload, &server_key), None);
+}
+
+#[test]
+fn stale_responses_are_rejected() {
+    use crate::util::is_fresh_response;
+
+    let now = 1_700_000_060_000;
+    let max_age = 60_000;
+
+    // Exactly `max_age` old is still fresh, one millisecond more is not
+    let oldest = now - max_age;
+    assert!(is_fresh_response(oldest, None, now, max_age, None));
+    assert!(!is_fresh_response(oldest - 1, None, now, max_age, None));
+
+    // Clocks are not synchronized, so timestamps ahead of the node are accepted
+    assert!(is_fresh_response(now + 5_000, None, now, max_age, None));
+
+    // Sequence numbers must increase
+    assert!(is_fresh_response(now, Some(8), now, max_age, Some(7)));
+    assert!(!is_fresh_response(now, Some(7), now, max_age, Some(7)));
+    assert!(is_fresh_response(now, None, now, max_age, Some(7)));
+}
+
+#[test]
+fn skewed_server_clocks_are_detected() {
+    use crate::util::clock_skew;
+
+    let block_time = 1_700_000_000_000;
+    let max_skew = 30_000;
+
+    // Up to `max_skew` either way is tolerated
+    assert_eq!(
+        clock_skew(block_time + max_skew, block_time, max_skew),
+        None
+    );
+    assert_eq!(
+        clock_skew(block_time - max_skew, block_time, max_skew),
+        None
+    );
+
+    // Beyond that, ahead or behind
+    assert_eq!(
+        clock_skew(block_time + max_skew + 1, block_time, max_skew),
+        Some(max_skew + 1)
+    );
+    assert_eq!(
+        clock_skew(block_time - 3_600_000, block_time, max_skew),
+        Some(3_600_000)
+    );
+
+    // Nothing to compare with before the first block
+    assert_eq!(clock_skew(block_time, 0, max_skew), None);
+}
+
+#[test]
+fn run_summary_keys_wrap_around_history() {
+    use crate::util::{run_summary_key, RUN_SUMMARY_HISTORY};
+
+    // Runs `RUN_SUMMARY_HISTORY` blocks apart overwrite each other's summary
+    assert_eq!(run_summary_key(7), run_summary_key(7 + RUN_SUMMARY_HISTORY));
+    assert_ne!(run_summary_key(7), run_summary_key(8));
+    assert!(run_summary_key(7).starts_with(crate::RUN_SUMMARY_KEY));
+}
+
+#[test]
+fn trilateration_finds_claimed_position() {
+    use crate::util::{estimate_rssi, trilateration_deviation};
+
+    let (latitude, longitude) = (37_500_000, -122_250_000);
+    // About 5 meters north, east and south-west, with the RSSI the model expects there
+    let neighbors: Vec<(i64, i64, i16)> = [
+        (latitude + 45, longitude),
+        (latitude, longitude + 57),
+        (latitude - 30, longitude - 40),
+    ]
+    .into_iter()
+    .map(|(lat, lon)| {
+        (
+            lat,
+            lon,
+            estimate_rssi(lat, lon, latitude, longitude, -48, 40),
+        )
+    })
+    .collect();
+
+    // The true position is found, a position a kilometer north is not
+    assert!(trilateration_deviation(latitude, longitude, &neighbors, -48, 40).unwrap() < 5);
+    let deviation = trilateration_deviation(latitude + 9_000, longitude, &neighbors, -48, 40);
+    assert!(deviation.unwrap() > 900);
+
+    // Two neighbors or neighbors on one line cannot pin down a position
+    assert_eq!(
+        trilateration_deviation(latitude, longitude, &neighbors[..2], -48, 40),
+        None
+    );
+    let in_line: Vec<(i64, i64, i16)> = (1..=3)
+        .map(|i| (latitude, longitude + i * 20, -60))
+        .collect();
+    assert_eq!(
+        trilateration_deviation(latitude, longitude, &in_line, -48, 40),
+        None
+    );
+}
+
+#[test]
+fn trimmed_median_error_respects_trim_percent() {
+    use crate::util::{trimmed_median_error, trimmed_median_error_with};
+
+    // Default trims the highest quarter: [1, 2, 3] remain
+    assert_eq!(trimmed_median_error(&mut [1, -2, 3, 4, 100]), 2);
+    assert_eq!(trimmed_median_error_with(&mut [1, -2, 3, 4, 100], 25), 2);
+
+    // Without trimming the outlier takes part in the median
+    assert_eq!(trimmed_median_error_with(&mut [1, -2, 3, 4, 100], 0), 3);
+
+    // Trimming everything leaves no score
+    assert_eq!(
+        trimmed_median_error_with(&mut [1, -2, 3, 4, 100], 100),
+        i16::MAX
+    );
+}
+
+#[test]
+fn estimator_overrides_replace_only_given_params() {
+    use crate::util::EstimatorOverrides;
+
+    new_test_ext().execute_with(|| {
+        let defaults = ProofOfLocation::estimator_params();
+
+        // No overrides keep the runtime constants
+        assert_eq!(
+            defaults.with_overrides(&EstimatorOverrides::default()),
+            defaults
+        );
+
+        let params = defaults.with_overrides(&EstimatorOverrides {
+            reference_rssi: Some(-40),
+            path_loss_exponent: None,
+            trim_percent: Some(10),
+        });
+        assert_eq!(params.reference_rssi, -40);
+        assert_eq!(params.path_loss_exponent, defaults.path_loss_exponent);
+        assert_eq!(params.trim_percent, 10);
+    });
+}
+
+#[test]
+fn extreme_estimator_overrides_are_rejected_and_do_not_overflow() {
+    use crate::util::EstimatorOverrides;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        register_nearby_nodes(5);
+        for id in 1..5 {
+            assert_ok!(ProofOfLocation::publish_rssi_data(
+                RuntimeOrigin::signed(account(id)),
+                account(0),
+                -60,
+                None
+            ));
+        }
+
+        for reference_rssi in [i16::MAX, i16::MIN] {
+            let overrides = EstimatorOverrides {
+                reference_rssi: Some(reference_rssi),
+                path_loss_exponent: Some(0),
+                trim_percent: Some(0),
+            };
+            assert!(overrides.validate().is_err());
+
+            // The runtime API is reachable without the RPC's validation, so it must not trap
+            let scores = ProofOfLocation::calculate_all_trust_scores_with(1, &overrides);
+            let score = scores
+                .iter()
+                .find(|(who, _)| *who == account(0))
+                .map(|(_, score)| *score);
+            assert!(score.is_some_and(|score| score > 0));
+        }
+
+        for overrides in [
+            EstimatorOverrides {
+                path_loss_exponent: Some(61),
+                ..Default::default()
+            },
+            EstimatorOverrides {
+                trim_percent: Some(101),
+                ..Default::default()
+            },
+        ] {
+            assert!(overrides.validate().is_err());
+        }
+        assert_ok!(EstimatorOverrides {
+            reference_rssi: Some(-127),
+            path_loss_exponent: Some(60),
+            trim_percent: Some(100),
+        }
+        .validate());
+    });
+}
+
+#[test]
+fn integrity_test_accepts_mock_config() {
+    use frame_support::traits::Hooks;
+
+    new_test_ext().execute_with(|| {
+        <ProofOfLocation as Hooks<u64>>::integrity_test();
+    });
+}
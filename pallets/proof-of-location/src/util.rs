@@ -4,16 +4,25 @@ use scale_info::TypeInfo;
 
 extern crate alloc;
 use alloc::vec::Vec;
+use lite_json::JsonValue;
 
 #[derive(Encode, Decode, Debug, Clone, TypeInfo)]
 pub struct DeviceRssi {
     pub address: [u8; 6],
     pub rssi: i16,
+    /// TX power level the neighbor advertises in dBm, `None` if it does not advertise one
+    pub tx_power: Option<i16>,
+    /// Unix time in milliseconds of the newest reading of the neighbor
+    pub timestamp: u64,
 }
 
 #[derive(Encode, Decode, Debug, Clone, TypeInfo)]
 pub struct RssiResponse {
     pub devices: Vec<DeviceRssi>,
+    /// Unix time in milliseconds when the server produced the response
+    pub timestamp: u64,
+    /// Optional number that increases with every response of the server
+    pub sequence: Option<u64>,
 }
 
 // Using i64 to represent latitude/longitude with fixed-point precision
@@ -36,22 +45,303 @@ pub struct Location {
 pub struct LocationResponse {
     pub address: [u8; 6],
     pub location: Location,
+    /// Unix time in milliseconds when the server produced the response
+    pub timestamp: u64,
+    /// Optional number that increases with every response of the server
+    pub sequence: Option<u64>,
 }
 
+/// Freshness information carried by every server response.
+pub trait ServerResponse {
+    /// Unix time in milliseconds when the server produced the response
+    fn timestamp(&self) -> u64;
+    /// Number that increases with every response, if the server sends one
+    fn sequence(&self) -> Option<u64>;
+}
+
+impl ServerResponse for RssiResponse {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+}
+
+impl ServerResponse for LocationResponse {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+}
+
+/// Whether a response produced at `timestamp` may still be used at `now`.
+///
+/// It must be at most `max_age` milliseconds old and, if both are known, its sequence number must
+/// be greater than the last accepted one. Timestamps slightly in the future are accepted, since
+/// the clocks of node and server are not synchronized.
+pub fn is_fresh_response(
+    timestamp: u64,
+    sequence: Option<u64>,
+    now: u64,
+    max_age: u64,
+    last_sequence: Option<u64>,
+) -> bool {
+    let recent = now.saturating_sub(timestamp) <= max_age;
+    let newer = match (sequence, last_sequence) {
+        (Some(sequence), Some(last_sequence)) => sequence > last_sequence,
+        _ => true,
+    };
+    recent && newer
+}
+
+/// Milliseconds between the clock of a server, as of a response produced at `timestamp`, and
+/// the time of the current block, `None` if they are at most `max_skew` apart.
+///
+/// The block time is `0` before the first block is produced, and nothing is compared then.
+pub fn clock_skew(timestamp: u64, block_time: u64, max_skew: u64) -> Option<u64> {
+    let skew = timestamp.abs_diff(block_time);
+    (block_time != 0 && skew > max_skew).then_some(skew)
+}
+
+/// Response body signed by the server, sent once the node has registered a `ServerKey`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct SignedPayload {
+    /// SCALE-encoded `RssiResponse` or `LocationResponse`
+    pub payload: Vec<u8>,
+    /// sr25519 signature of `payload` by the server's key
+    pub signature: [u8; 64],
+}
+
+/// Decode a [`SignedPayload`] and return its payload if it is signed by `server_key`.
+///
+/// Replaying an old signed payload is caught by the freshness check of the response inside.
+pub fn verify_signed_payload(body: &[u8], server_key: &[u8; 32]) -> Option<Vec<u8>> {
+    use sp_core::sr25519::{Public, Signature};
+
+    let signed = SignedPayload::decode(&mut &body[..]).ok()?;
+    let signature = Signature::from_raw(signed.signature);
+    let public = Public::from_raw(*server_key);
+
+    sp_io::crypto::sr25519_verify(&signature, &signed.payload, &public).then_some(signed.payload)
+}
+
+/// Message the offchain worker signs to answer a server's `X-Challenge`.
+///
+/// The prefix keeps the signature from being valid for anything else signed with the node's key.
+pub fn challenge_message(nonce: &[u8]) -> Vec<u8> {
+    [b"proof-of-location/challenge:".as_slice(), nonce].concat()
+}
+
+/// Health of a node's offchain worker, posted to the server's `/status` endpoint.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct NodeStatus {
+    /// Block the offchain worker ran for
+    pub block: u32,
+    /// SCALE-encoded account of the node
+    pub account: Vec<u8>,
+    /// Whether the account is registered on-chain
+    pub registered: bool,
+    /// Block of the last complete RSSI submission
+    pub last_submitted_block: Option<u32>,
+    /// Trust score of the node over the last rolled-up epoch
+    pub trust_score: Option<i16>,
+}
+
+/// What the offchain worker did with the RSSI readings of one run.
+///
+/// Written to persistent offchain storage under [`run_summary_key`], so operators can see why
+/// readings did not reach the chain.
+#[derive(Encode, Decode, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    /// Block the offchain worker ran for
+    pub block: u32,
+    /// Devices returned by the server
+    pub fetched: u32,
+    /// Devices whose Bluetooth address is not registered
+    pub skipped_unregistered: u32,
+    /// Readings the chain would reject, e.g. of too distant neighbors
+    pub skipped_invalid: u32,
+    /// Readings within `RssiHysteresis` of the last submitted ones
+    pub skipped_unchanged: u32,
+    /// Readings in transactions that were submitted
+    pub submitted: u32,
+    /// Readings in transactions that failed to submit
+    pub failed: u32,
+    /// Whether the readings were unchanged since the last submission and not sent again
+    pub unchanged: bool,
+    /// Error that ended the run early
+    pub error: Option<Vec<u8>>,
+}
+
+/// Number of blocks whose [`RunSummary`] is kept in offchain storage.
+pub const RUN_SUMMARY_HISTORY: u32 = 256;
+
+/// Persistent offchain storage key of the [`RunSummary`] of `block`.
+///
+/// Keys are reused every [`RUN_SUMMARY_HISTORY`] blocks, so the summary's own `block` tells
+/// whether it belongs to `block` or an older run.
+pub fn run_summary_key(block: u32) -> Vec<u8> {
+    [
+        crate::RUN_SUMMARY_KEY,
+        &(block % RUN_SUMMARY_HISTORY).encode(),
+    ]
+    .concat()
+}
+
+/// Why the offchain worker failed to fetch a response from a server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchError {
+    /// The request failed or timed out
+    Http(sp_runtime::offchain::http::Error),
+    /// The response body is larger than `MaxResponseBytes`
+    ResponseTooLarge,
+}
+
+impl From<sp_runtime::offchain::http::Error> for FetchError {
+    fn from(error: sp_runtime::offchain::http::Error) -> Self {
+        Self::Http(error)
+    }
+}
+
+/// Everything a node needs to know about its own configuration.
+#[derive(Encode, Decode, Debug, Clone, TypeInfo, PartialEq, Eq)]
+pub struct NodeConfigBundle {
+    /// Registered location data, `None` if the node is not registered
+    pub location: Option<LocationData>,
+    /// Server URL the offchain worker fetches data from ("hostname:port")
+    pub server_url: Vec<u8>,
+    /// Maximum distance between neighbors in meters
+    pub max_distance: u32,
+    /// Reference RSSI value at 1 meter distance
+    pub reference_rssi: i16,
+    /// Path loss exponent multiplied by 10
+    pub path_loss_exponent: u8,
+    /// Server URL the offchain worker fetches the node's location from ("hostname:port")
+    pub location_server_url: Vec<u8>,
+}
+
+/// RSSI and TX power in dBm a report may carry, from the lowest Bluetooth LE can represent to
+/// the highest it transmits with.
+pub const DBM_RANGE: core::ops::RangeInclusive<i16> = -127..=20;
+
+/// Path loss exponents, multiplied by 10, from ~1.6 (line of sight) to ~6 (obstructed buildings).
+pub const PATH_LOSS_EXPONENT_RANGE: core::ops::RangeInclusive<u8> = 10..=60;
+
+/// Percentage of the highest errors discarded by [`trimmed_median_error`].
+pub const DEFAULT_TRIM_PERCENT: u8 = 25;
+
+/// Parameters of the RSSI estimator and the trust score aggregation.
+#[derive(Encode, Decode, Debug, Clone, Copy, TypeInfo, PartialEq, Eq)]
+pub struct EstimatorParams {
+    /// Reference RSSI value at 1 meter distance
+    pub reference_rssi: i16,
+    /// Path loss exponent multiplied by 10
+    pub path_loss_exponent: u8,
+    /// Percentage of the highest errors discarded before taking the median
+    pub trim_percent: u8,
+    /// Path loss at 1 meter distance in dB, for readings with an advertised TX power
+    pub reference_path_loss: u8,
+}
+
+/// Per-request overrides of [`EstimatorParams`]. `None` keeps the runtime's value.
+#[derive(Encode, Decode, Debug, Clone, Default, TypeInfo, PartialEq, Eq)]
+pub struct EstimatorOverrides {
+    pub reference_rssi: Option<i16>,
+    pub path_loss_exponent: Option<u8>,
+    pub trim_percent: Option<u8>,
+}
+
+impl EstimatorOverrides {
+    /// Check that every parameter that is set is within the range the runtime accepts.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self
+            .reference_rssi
+            .is_some_and(|rssi| !DBM_RANGE.contains(&rssi))
+        {
+            return Err("`reference_rssi` must be between -127 and 20 dBm");
+        }
+        if self
+            .path_loss_exponent
+            .is_some_and(|exponent| !PATH_LOSS_EXPONENT_RANGE.contains(&exponent))
+        {
+            return Err("`path_loss_exponent` must be between 10 and 60 (1.0 to 6.0)");
+        }
+        if self.trim_percent.is_some_and(|percent| percent > 100) {
+            return Err("`trim_percent` must be at most 100");
+        }
+        Ok(())
+    }
+}
+
+impl EstimatorParams {
+    /// Replace every parameter that is set in `overrides`
+    pub fn with_overrides(self, overrides: &EstimatorOverrides) -> Self {
+        Self {
+            reference_rssi: overrides.reference_rssi.unwrap_or(self.reference_rssi),
+            path_loss_exponent: overrides
+                .path_loss_exponent
+                .unwrap_or(self.path_loss_exponent),
+            trim_percent: overrides.trim_percent.unwrap_or(self.trim_percent),
+            reference_path_loss: self.reference_path_loss,
+        }
+    }
+
+    /// Reference RSSI at 1 meter of a reading, derived from the advertised TX power if known.
+    ///
+    /// Estimating from `tx_power - reference_path_loss` compares path losses (`tx_power - rssi`)
+    /// instead of raw RSSI, so a neighbor transmitting stronger or weaker than the reference is
+    /// not mistaken for a closer or farther one.
+    pub fn reference_rssi_for(&self, tx_power: Option<i16>) -> i16 {
+        match tx_power {
+            Some(tx_power) => tx_power.saturating_sub(self.reference_path_loss as i16),
+            None => self.reference_rssi,
+        }
+    }
+
+    /// `rssi` shifted to what it would be at the reference TX power, so readings of neighbors
+    /// transmitting at different levels can be compared against [`Self::reference_rssi`].
+    pub fn normalize_rssi(&self, rssi: i16, tx_power: Option<i16>) -> i16 {
+        rssi.saturating_add(self.reference_rssi)
+            .saturating_sub(self.reference_rssi_for(tx_power))
+    }
+}
+
+/// Trust score of a node with too few reports to take the trimmed median of, the worst score.
+pub const INSUFFICIENT_REPORTS: i16 = i16::MAX;
+
 /// Calculate trimmed median error from RSSI values.
 ///
 /// Discards the highest 1/4 of values and returns the median of the remaining.
 pub fn trimmed_median_error(values: &mut [i16]) -> i16 {
+    trimmed_median_error_with(values, DEFAULT_TRIM_PERCENT)
+}
+
+/// Calculate trimmed median error from RSSI values.
+///
+/// Discards the highest `trim_percent` percent of values and returns the median of the remaining,
+/// or [`INSUFFICIENT_REPORTS`] with fewer than 4 values.
+pub fn trimmed_median_error_with(values: &mut [i16], trim_percent: u8) -> i16 {
     if values.len() < 4 {
-        return i16::MAX;
+        return INSUFFICIENT_REPORTS;
     }
 
-    // Convert to absolute values
-    values.iter_mut().for_each(|x| *x = x.abs());
+    // Convert to absolute values, `i16::MIN` has none and is clamped
+    values
+        .iter_mut()
+        .for_each(|x| *x = x.unsigned_abs().min(i16::MAX as u16) as i16);
     values.sort_unstable();
 
     let len = values.len();
-    let trim_end = (len * 3 / 4) as usize;
+    let keep_percent = 100 - trim_percent.min(100) as usize;
+    let trim_end = len * keep_percent / 100;
+    if trim_end == 0 {
+        return INSUFFICIENT_REPORTS;
+    }
     let trimmed = &values[..trim_end];
 
     if trim_end % 2 == 1 {
@@ -59,10 +349,18 @@ pub fn trimmed_median_error(values: &mut [i16]) -> i16 {
     } else {
         let mid_upper = trimmed[trim_end / 2];
         let mid_lower = trimmed[trim_end / 2 - 1];
-        (mid_upper + mid_lower) / 2
+        ((mid_upper as i32 + mid_lower as i32) / 2) as i16
     }
 }
 
+/// Distance in meters between two fixed-point locations.
+pub fn distance_meters(a_lat: i64, a_lon: i64, b_lat: i64, b_lon: i64) -> f64 {
+    use haversine_redux::Location;
+    let a = Location::new(a_lat as f64 / 1_000_000.0, a_lon as f64 / 1_000_000.0);
+    let b = Location::new(b_lat as f64 / 1_000_000.0, b_lon as f64 / 1_000_000.0);
+    a.kilometers_to(&b) * 1000.0 // convert km to meters
+}
+
 /// Estimate RSSI based on distance between two locations.
 ///
 /// Uses path loss model: RSSI = r - n * 10 * log10(d).
@@ -102,3 +400,198 @@ pub fn estimate_rssi(
     };
     rssi as i16
 }
+
+/// Distance in meters at which the path loss model expects `rssi`, the inverse of
+/// [`estimate_rssi`].
+pub fn estimate_distance(rssi: i16, reference_rssi: i16, path_loss_exponent: u8) -> f64 {
+    let path_loss_exp = path_loss_exponent as f64 / 10.0;
+    libm::pow(
+        10.0,
+        (reference_rssi as f64 - rssi as f64) / (10.0 * path_loss_exp),
+    )
+}
+
+/// Minimum number of neighbors with a known location [`trilateration_deviation`] needs.
+pub const MIN_TRILATERATION_NEIGHBORS: usize = 3;
+
+/// Distance in meters between a claimed position and the one trilaterated from neighbors' RSSI.
+///
+/// `neighbors` holds the fixed-point latitude and longitude of each neighbor and the RSSI measured
+/// from it. The position is estimated by least squares on a plane around the claimed position,
+/// which is accurate enough at Bluetooth range. Returns `None` with fewer than
+/// [`MIN_TRILATERATION_NEIGHBORS`] neighbors or when they are (nearly) on one line.
+pub fn trilateration_deviation(
+    latitude: i64,
+    longitude: i64,
+    neighbors: &[(i64, i64, i16)],
+    reference_rssi: i16,
+    path_loss_exponent: u8,
+) -> Option<u32> {
+    const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+    const RADIANS_PER_UNIT: f64 = core::f64::consts::PI / 180.0 / 1_000_000.0;
+
+    if neighbors.len() < MIN_TRILATERATION_NEIGHBORS {
+        return None;
+    }
+
+    // Neighbors in meters east and north of the claimed position, with their estimated distance
+    let meters_per_longitude =
+        EARTH_RADIUS_METERS * RADIANS_PER_UNIT * libm::cos(latitude as f64 * RADIANS_PER_UNIT);
+    let meters_per_latitude = EARTH_RADIUS_METERS * RADIANS_PER_UNIT;
+    let points: Vec<(f64, f64, f64)> = neighbors
+        .iter()
+        .map(|&(lat, lon, rssi)| {
+            (
+                (lon - longitude) as f64 * meters_per_longitude,
+                (lat - latitude) as f64 * meters_per_latitude,
+                estimate_distance(rssi, reference_rssi, path_loss_exponent),
+            )
+        })
+        .collect();
+
+    // Subtracting the first circle equation from the others leaves linear equations in x and y,
+    // solved through their normal equations
+    let (x1, y1, d1) = points[0];
+    let (mut aa, mut ab, mut bb, mut ac, mut bc) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for &(x, y, d) in &points[1..] {
+        let a = 2.0 * (x - x1);
+        let b = 2.0 * (y - y1);
+        let c = d1 * d1 - d * d + x * x - x1 * x1 + y * y - y1 * y1;
+        aa += a * a;
+        ab += a * b;
+        bb += b * b;
+        ac += a * c;
+        bc += b * c;
+    }
+
+    let determinant = aa * bb - ab * ab;
+    if determinant <= 1e-6 * aa * bb {
+        return None;
+    }
+    let x = (ac * bb - bc * ab) / determinant;
+    let y = (bc * aa - ac * ab) / determinant;
+
+    Some(libm::sqrt(x * x + y * y) as u32)
+}
+
+/// Get the value of `name` in a JSON object.
+fn json_field<'a>(value: &'a JsonValue, name: &str) -> Option<&'a JsonValue> {
+    let JsonValue::Object(fields) = value else {
+        return None;
+    };
+    fields
+        .iter()
+        .find(|(key, _)| key.iter().copied().eq(name.chars()))
+        .map(|(_, value)| value)
+}
+
+/// Convert a JSON number to `f64`.
+fn json_f64(value: &JsonValue) -> Option<f64> {
+    let JsonValue::Number(number) = value else {
+        return None;
+    };
+    let fraction = number.fraction as f64 / libm::pow(10.0, number.fraction_length as f64);
+    let magnitude = (number.integer as f64 + fraction) * libm::pow(10.0, number.exponent as f64);
+    Some(if number.negative {
+        -magnitude
+    } else {
+        magnitude
+    })
+}
+
+/// Parse a Bluetooth address written as `"AA:BB:CC:DD:EE:FF"`.
+fn json_address(value: &JsonValue) -> Option<[u8; 6]> {
+    let JsonValue::String(chars) = value else {
+        return None;
+    };
+    let text: alloc::string::String = chars.iter().collect();
+
+    let mut address = [0u8; 6];
+    let mut parts = text.split(':');
+    for byte in address.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+    parts.next().is_none().then_some(address)
+}
+
+/// Convert a JSON number to a non-negative integer.
+fn json_u64(value: &JsonValue) -> Option<u64> {
+    let JsonValue::Number(number) = value else {
+        return None;
+    };
+    (!number.negative && number.fraction == 0 && number.exponent == 0).then_some(number.integer)
+}
+
+/// Get the optional `"sequence"` field of a JSON response.
+fn json_sequence(json: &JsonValue) -> Option<Option<u64>> {
+    match json_field(json, "sequence") {
+        Some(sequence) => json_u64(sequence).map(Some),
+        None => Some(None),
+    }
+}
+
+/// Parse a location response sent as JSON.
+///
+/// ```json
+/// { "address": "AA:BB:CC:DD:EE:FF", "location": { "latitude": 37.7749, "longitude": -122.4194 },
+///   "timestamp": 1700000000000, "sequence": 42 }
+/// ```
+pub fn location_response_from_json(body: &[u8]) -> Option<LocationResponse> {
+    let json = lite_json::parse_json(core::str::from_utf8(body).ok()?).ok()?;
+    let location = json_field(&json, "location")?;
+
+    Some(LocationResponse {
+        address: json_address(json_field(&json, "address")?)?,
+        location: Location {
+            latitude: json_f64(json_field(location, "latitude")?)?,
+            longitude: json_f64(json_field(location, "longitude")?)?,
+        },
+        timestamp: json_u64(json_field(&json, "timestamp")?)?,
+        sequence: json_sequence(&json)?,
+    })
+}
+
+/// Parse an RSSI response sent as JSON.
+///
+/// ```json
+/// { "devices": [{ "address": "AA:BB:CC:DD:EE:FF", "rssi": -61, "tx_power": 0,
+///                 "timestamp": 1699999998500 }],
+///   "timestamp": 1700000000000 }
+/// ```
+///
+/// `"tx_power"` may be left out or `null` for neighbors that do not advertise it. A device
+/// without a `"timestamp"` is taken to be measured when the response was produced.
+pub fn rssi_response_from_json(body: &[u8]) -> Option<RssiResponse> {
+    let json = lite_json::parse_json(core::str::from_utf8(body).ok()?).ok()?;
+    let JsonValue::Array(devices) = json_field(&json, "devices")? else {
+        return None;
+    };
+    let timestamp = json_u64(json_field(&json, "timestamp")?)?;
+
+    let devices = devices
+        .iter()
+        .map(|device| {
+            let rssi = json_f64(json_field(device, "rssi")?)?;
+            let tx_power = match json_field(device, "tx_power") {
+                None | Some(JsonValue::Null) => None,
+                Some(tx_power) => Some(i16::try_from(json_f64(tx_power)? as i64).ok()?),
+            };
+            let device_timestamp = match json_field(device, "timestamp") {
+                None | Some(JsonValue::Null) => timestamp,
+                Some(device_timestamp) => json_u64(device_timestamp)?,
+            };
+            Some(DeviceRssi {
+                address: json_address(json_field(device, "address")?)?,
+                rssi: i16::try_from(rssi as i64).ok()?,
+                tx_power,
+                timestamp: device_timestamp,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(RssiResponse {
+        devices,
+        timestamp,
+        sequence: json_sequence(&json)?,
+    })
+}
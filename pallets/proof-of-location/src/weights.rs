@@ -1,13 +1,10 @@
 
-//! Autogenerated weights for `pallet_proof_of_location`
+//! Weights for `pallet_proof_of_location`
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 47.0.0
-//! DATE: 2025-11-09, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! WORST CASE MAP SIZE: `1000000`
-//! HOSTNAME: `arch`, CPU: `AMD Ryzen 5 7600 6-Core Processor`
-//! WASM-EXECUTION: `Compiled`, CHAIN: `Some("dev")`, DB CACHE: `1024`
+//! PLACEHOLDERS, NOT BENCHMARKED. The benchmarks in `benchmarking.rs` have not been run against
+//! the current pallet, so these weights are estimated by hand from the storage each call
+//! accesses. Replace this file with the output of:
 
-// Executed Command:
 // ./target/release/solochain-template-node
 // benchmark
 // pallet
@@ -38,9 +35,23 @@ use core::marker::PhantomData;
 pub trait WeightInfo {
 	fn set_server_config() -> Weight;
 	fn register_node() -> Weight;
-	fn unregister_node() -> Weight;
+	fn unregister_node(n: u32, ) -> Weight;
 	fn update_node_info() -> Weight;
-	fn publish_rssi_data() -> Weight;
+	fn update_node_info_same_address() -> Weight;
+	fn publish_rssi_data(n: u32, m: u32, ) -> Weight;
+	fn prune_my_reports(n: u32, ) -> Weight;
+	fn publish_rssi_data_batch(n: u32, b: u32, m: u32, ) -> Weight;
+	fn report_misbehavior() -> Weight;
+	fn set_server_key() -> Weight;
+	fn set_location_server_config() -> Weight;
+	fn set_identity_resolving_key() -> Weight;
+	fn set_wifi_address() -> Weight;
+	fn publish_range_data() -> Weight;
+	fn calculate_all_trust_scores(n: u32, r: u32, ) -> Weight;
+	fn prune_rssi_report() -> Weight;
+	fn expire_node() -> Weight;
+	fn rollup_trust_score(n: u32, ) -> Weight;
+	fn prune_range_report() -> Weight;
 }
 
 /// Weights for `pallet_proof_of_location` using the Substrate node and recommended hardware.
@@ -49,10 +60,7 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	/// Storage: `ProofOfLocation::ServerConfig` (r:0 w:1)
 	/// Proof: `ProofOfLocation::ServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
 	fn set_server_config() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `0`
-		//  Estimated: `0`
-		// Minimum execution time: 6_407_000 picoseconds.
+		// Placeholder, not benchmarked
 		Weight::from_parts(6_597_000, 0)
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
@@ -60,54 +68,260 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
 	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
 	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:1 w:0)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::CounterForAccountData` (r:1 w:1)
+	/// Proof: `ProofOfLocation::CounterForAccountData` (`max_values`: Some(1), `max_size`: Some(4), added: 499, mode: `MaxEncodedLen`)
 	fn register_node() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `6`
-		//  Estimated: `3535`
-		// Minimum execution time: 8_649_000 picoseconds.
+		// Placeholder, not benchmarked
 		Weight::from_parts(8_920_000, 3535)
-			.saturating_add(T::DbWeight::get().reads(2_u64))
-			.saturating_add(T::DbWeight::get().writes(2_u64))
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
 	}
 	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
 	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
 	/// Storage: `ProofOfLocation::ServerConfig` (r:0 w:1)
 	/// Proof: `ProofOfLocation::ServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::ServerKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::ServerKey` (`max_values`: None, `max_size`: Some(80), added: 2555, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::IdentityResolvingKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::IdentityResolvingKey` (`max_values`: None, `max_size`: Some(64), added: 2539, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddress` (r:1 w:1)
+	/// Proof: `ProofOfLocation::WifiAddress` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LocationServerConfig` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LocationServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
 	/// Storage: `ProofOfLocation::AddressRegistrationData` (r:0 w:1)
 	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
-	fn unregister_node() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `135`
-		//  Estimated: `3535`
-		// Minimum execution time: 11_893_000 picoseconds.
-		Weight::from_parts(12_174_000, 3535)
-			.saturating_add(T::DbWeight::get().reads(1_u64))
-			.saturating_add(T::DbWeight::get().writes(3_u64))
+	/// Storage: `ProofOfLocation::PruneCursor` (r:0 w:1)
+	/// Proof: `ProofOfLocation::PruneCursor` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 1000]`.
+	fn unregister_node(n: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(13_376_000, 3535)
+			.saturating_add(Weight::from_parts(9_812, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(10_u64))
+			.saturating_add(Weight::from_parts(0, 1).saturating_mul(n.into()))
 	}
 	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
 	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
 	/// Storage: `ProofOfLocation::AddressRegistrationData` (r:1 w:2)
 	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:1 w:0)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
 	fn update_node_info() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `177`
-		//  Estimated: `3535`
-		// Minimum execution time: 13_925_000 picoseconds.
+		// Placeholder, not benchmarked
 		Weight::from_parts(14_426_000, 3535)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	fn update_node_info_same_address() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(9_824_000, 3535)
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:2 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:17 w:1)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[2, 1000]`.
+	/// The range of component `m` is `[0, 15]`.
+	fn publish_rssi_data(n: u32, m: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(19_322_000, 6080)
+			.saturating_add(Weight::from_parts(12_406, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(2_391_860, 0).saturating_mul(m.into()))
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(m.into())))
+			.saturating_add(T::DbWeight::get().writes(3_u64))
+			.saturating_add(Weight::from_parts(0, 2).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(0, 2577).saturating_mul(m.into()))
+	}
+	/// Storage: `ProofOfLocation::PruneCursor` (r:1 w:1)
+	/// Proof: `ProofOfLocation::PruneCursor` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:1001 w:1000)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:0 w:1000)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 1000]`.
+	fn prune_my_reports(n: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(5_431_000, 3771)
+			.saturating_add(Weight::from_parts(2_934_512, 0).saturating_mul(n.into()))
 			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2577).saturating_mul(n.into()))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:33 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:512 w:32)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:0 w:32)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[33, 1000]`.
+	/// The range of component `b` is `[1, 32]`.
+	/// The range of component `m` is `[0, 15]`.
+	fn publish_rssi_data_batch(n: u32, b: u32, m: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(8_115_000, 3535)
+			.saturating_add(Weight::from_parts(12_406, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(13_904_000, 0).saturating_mul(b.into()))
+			.saturating_add(Weight::from_parts(76_539_520, 0).saturating_mul(m.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(b.into())))
+			.saturating_add(T::DbWeight::get().reads((32_u64).saturating_mul(m.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(b.into())))
+			.saturating_add(Weight::from_parts(0, 2).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(0, 2577).saturating_mul(b.into()))
+			.saturating_add(Weight::from_parts(0, 82464).saturating_mul(m.into()))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:2 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	fn report_misbehavior() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(12_348_000, 6080)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+	}
+	/// Storage: `ProofOfLocation::ServerKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::ServerKey` (`max_values`: None, `max_size`: Some(80), added: 2555, mode: `MaxEncodedLen`)
+	fn set_server_key() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(6_302_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `ProofOfLocation::LocationServerConfig` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LocationServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	fn set_location_server_config() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(6_541_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `ProofOfLocation::IdentityResolvingKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::IdentityResolvingKey` (`max_values`: None, `max_size`: Some(64), added: 2539, mode: `MaxEncodedLen`)
+	fn set_identity_resolving_key() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(6_275_000, 0)
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:1 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::AddressRegistrationData` (r:1 w:0)
+	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:1 w:2)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddress` (r:1 w:1)
+	/// Proof: `ProofOfLocation::WifiAddress` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	fn set_wifi_address() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(15_680_000, 3535)
+			.saturating_add(T::DbWeight::get().reads(4_u64))
 			.saturating_add(T::DbWeight::get().writes(3_u64))
 	}
 	/// Storage: `ProofOfLocation::AccountData` (r:2 w:0)
 	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RangeData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::RangeData` (`max_values`: None, `max_size`: Some(104), added: 2579, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	fn publish_range_data() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(18_402_000, 6080)
+			.saturating_add(T::DbWeight::get().reads(2_u64))
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:17001 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:17000 w:0)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:17000 w:0)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 1000]`.
+	/// The range of component `r` is `[0, 16]`.
+	fn calculate_all_trust_scores(n: u32, r: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(4_318_000, 1489)
+			.saturating_add(Weight::from_parts(9_482_315, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(5_812_430_107, 0).saturating_mul(r.into()))
+			.saturating_add(T::DbWeight::get().reads(1_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().reads((3000_u64).saturating_mul(r.into())))
+			.saturating_add(Weight::from_parts(0, 5122).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(0, 5122000).saturating_mul(r.into()))
+	}
 	/// Storage: `ProofOfLocation::RssiData` (r:0 w:1)
 	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
-	fn publish_rssi_data() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `216`
-		//  Estimated: `6080`
-		// Minimum execution time: 14_426_000 picoseconds.
-		Weight::from_parts(14_877_000, 6080)
-			.saturating_add(T::DbWeight::get().reads(2_u64))
+	/// Storage: `ProofOfLocation::TxPowerData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	fn prune_rssi_report() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(4_497_000, 0)
+			.saturating_add(T::DbWeight::get().writes(2_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:1 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::AddressRegistrationData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::ServerConfig` (r:0 w:1)
+	/// Proof: `ProofOfLocation::ServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::ServerKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::ServerKey` (`max_values`: None, `max_size`: Some(80), added: 2555, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::IdentityResolvingKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::IdentityResolvingKey` (`max_values`: None, `max_size`: Some(64), added: 2539, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddress` (r:1 w:1)
+	/// Proof: `ProofOfLocation::WifiAddress` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LocationServerConfig` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LocationServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::PruneCursor` (r:0 w:1)
+	/// Proof: `ProofOfLocation::PruneCursor` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	fn expire_node() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(17_083_000, 3535)
+			.saturating_add(T::DbWeight::get().reads(3_u64))
+			.saturating_add(T::DbWeight::get().writes(10_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:9601 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:10200 w:0)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:9600 w:0)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::EpochTrustScores` (r:0 w:1)
+	/// Proof: `ProofOfLocation::EpochTrustScores` (`max_values`: None, `max_size`: Some(63), added: 2538, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[0, 9600]`.
+	fn rollup_trust_score(n: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(3_962_480_000, 3535)
+			.saturating_add(Weight::from_parts(6_214_592, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(601_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(Weight::from_parts(0, 5122).saturating_mul(n.into()))
+	}
+	/// Storage: `ProofOfLocation::RangeData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::RangeData` (`max_values`: None, `max_size`: Some(104), added: 2579, mode: `MaxEncodedLen`)
+	fn prune_range_report() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(4_122_000, 0)
 			.saturating_add(T::DbWeight::get().writes(1_u64))
 	}
 }
@@ -117,10 +331,7 @@ impl WeightInfo for () {
 	/// Storage: `ProofOfLocation::ServerConfig` (r:0 w:1)
 	/// Proof: `ProofOfLocation::ServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
 	fn set_server_config() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `0`
-		//  Estimated: `0`
-		// Minimum execution time: 6_407_000 picoseconds.
+		// Placeholder, not benchmarked
 		Weight::from_parts(6_597_000, 0)
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
@@ -128,54 +339,260 @@ impl WeightInfo for () {
 	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
 	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
 	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:1 w:0)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::CounterForAccountData` (r:1 w:1)
+	/// Proof: `ProofOfLocation::CounterForAccountData` (`max_values`: Some(1), `max_size`: Some(4), added: 499, mode: `MaxEncodedLen`)
 	fn register_node() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `6`
-		//  Estimated: `3535`
-		// Minimum execution time: 8_649_000 picoseconds.
+		// Placeholder, not benchmarked
 		Weight::from_parts(8_920_000, 3535)
-			.saturating_add(RocksDbWeight::get().reads(2_u64))
-			.saturating_add(RocksDbWeight::get().writes(2_u64))
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
 	}
 	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
 	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
 	/// Storage: `ProofOfLocation::ServerConfig` (r:0 w:1)
 	/// Proof: `ProofOfLocation::ServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::ServerKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::ServerKey` (`max_values`: None, `max_size`: Some(80), added: 2555, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::IdentityResolvingKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::IdentityResolvingKey` (`max_values`: None, `max_size`: Some(64), added: 2539, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddress` (r:1 w:1)
+	/// Proof: `ProofOfLocation::WifiAddress` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LocationServerConfig` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LocationServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
 	/// Storage: `ProofOfLocation::AddressRegistrationData` (r:0 w:1)
 	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
-	fn unregister_node() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `135`
-		//  Estimated: `3535`
-		// Minimum execution time: 11_893_000 picoseconds.
-		Weight::from_parts(12_174_000, 3535)
-			.saturating_add(RocksDbWeight::get().reads(1_u64))
-			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	/// Storage: `ProofOfLocation::PruneCursor` (r:0 w:1)
+	/// Proof: `ProofOfLocation::PruneCursor` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 1000]`.
+	fn unregister_node(n: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(13_376_000, 3535)
+			.saturating_add(Weight::from_parts(9_812, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(10_u64))
+			.saturating_add(Weight::from_parts(0, 1).saturating_mul(n.into()))
 	}
 	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
 	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
 	/// Storage: `ProofOfLocation::AddressRegistrationData` (r:1 w:2)
 	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:1 w:0)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
 	fn update_node_info() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `177`
-		//  Estimated: `3535`
-		// Minimum execution time: 13_925_000 picoseconds.
+		// Placeholder, not benchmarked
 		Weight::from_parts(14_426_000, 3535)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	fn update_node_info_same_address() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(9_824_000, 3535)
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:2 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:17 w:1)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[2, 1000]`.
+	/// The range of component `m` is `[0, 15]`.
+	fn publish_rssi_data(n: u32, m: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(19_322_000, 6080)
+			.saturating_add(Weight::from_parts(12_406, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(2_391_860, 0).saturating_mul(m.into()))
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(m.into())))
+			.saturating_add(RocksDbWeight::get().writes(3_u64))
+			.saturating_add(Weight::from_parts(0, 2).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(0, 2577).saturating_mul(m.into()))
+	}
+	/// Storage: `ProofOfLocation::PruneCursor` (r:1 w:1)
+	/// Proof: `ProofOfLocation::PruneCursor` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:1001 w:1000)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:0 w:1000)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 1000]`.
+	fn prune_my_reports(n: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(5_431_000, 3771)
+			.saturating_add(Weight::from_parts(2_934_512, 0).saturating_mul(n.into()))
 			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2577).saturating_mul(n.into()))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:33 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:512 w:32)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:0 w:32)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[33, 1000]`.
+	/// The range of component `b` is `[1, 32]`.
+	/// The range of component `m` is `[0, 15]`.
+	fn publish_rssi_data_batch(n: u32, b: u32, m: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(8_115_000, 3535)
+			.saturating_add(Weight::from_parts(12_406, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(13_904_000, 0).saturating_mul(b.into()))
+			.saturating_add(Weight::from_parts(76_539_520, 0).saturating_mul(m.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(b.into())))
+			.saturating_add(RocksDbWeight::get().reads((32_u64).saturating_mul(m.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(b.into())))
+			.saturating_add(Weight::from_parts(0, 2).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(0, 2577).saturating_mul(b.into()))
+			.saturating_add(Weight::from_parts(0, 82464).saturating_mul(m.into()))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:2 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	fn report_misbehavior() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(12_348_000, 6080)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+	}
+	/// Storage: `ProofOfLocation::ServerKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::ServerKey` (`max_values`: None, `max_size`: Some(80), added: 2555, mode: `MaxEncodedLen`)
+	fn set_server_key() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(6_302_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `ProofOfLocation::LocationServerConfig` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LocationServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	fn set_location_server_config() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(6_541_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `ProofOfLocation::IdentityResolvingKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::IdentityResolvingKey` (`max_values`: None, `max_size`: Some(64), added: 2539, mode: `MaxEncodedLen`)
+	fn set_identity_resolving_key() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(6_275_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:1 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::AddressRegistrationData` (r:1 w:0)
+	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:1 w:2)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddress` (r:1 w:1)
+	/// Proof: `ProofOfLocation::WifiAddress` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	fn set_wifi_address() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(15_680_000, 3535)
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
 			.saturating_add(RocksDbWeight::get().writes(3_u64))
 	}
 	/// Storage: `ProofOfLocation::AccountData` (r:2 w:0)
 	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RangeData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::RangeData` (`max_values`: None, `max_size`: Some(104), added: 2579, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	fn publish_range_data() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(18_402_000, 6080)
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:17001 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:17000 w:0)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:17000 w:0)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[1, 1000]`.
+	/// The range of component `r` is `[0, 16]`.
+	fn calculate_all_trust_scores(n: u32, r: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(4_318_000, 1489)
+			.saturating_add(Weight::from_parts(9_482_315, 0).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(5_812_430_107, 0).saturating_mul(r.into()))
+			.saturating_add(RocksDbWeight::get().reads(1_u64))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().reads((3000_u64).saturating_mul(r.into())))
+			.saturating_add(Weight::from_parts(0, 5122).saturating_mul(n.into()))
+			.saturating_add(Weight::from_parts(0, 5122000).saturating_mul(r.into()))
+	}
 	/// Storage: `ProofOfLocation::RssiData` (r:0 w:1)
 	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
-	fn publish_rssi_data() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `216`
-		//  Estimated: `6080`
-		// Minimum execution time: 14_426_000 picoseconds.
-		Weight::from_parts(14_877_000, 6080)
-			.saturating_add(RocksDbWeight::get().reads(2_u64))
+	/// Storage: `ProofOfLocation::TxPowerData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	fn prune_rssi_report() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(4_497_000, 0)
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:1 w:1)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LastActivity` (r:1 w:1)
+	/// Proof: `ProofOfLocation::LastActivity` (`max_values`: None, `max_size`: Some(52), added: 2527, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::AddressRegistrationData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::AddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::ServerConfig` (r:0 w:1)
+	/// Proof: `ProofOfLocation::ServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::ServerKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::ServerKey` (`max_values`: None, `max_size`: Some(80), added: 2555, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::IdentityResolvingKey` (r:0 w:1)
+	/// Proof: `ProofOfLocation::IdentityResolvingKey` (`max_values`: None, `max_size`: Some(64), added: 2539, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddress` (r:1 w:1)
+	/// Proof: `ProofOfLocation::WifiAddress` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::WifiAddressRegistrationData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::WifiAddressRegistrationData` (`max_values`: None, `max_size`: Some(54), added: 2529, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::LocationServerConfig` (r:0 w:1)
+	/// Proof: `ProofOfLocation::LocationServerConfig` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::PruneCursor` (r:0 w:1)
+	/// Proof: `ProofOfLocation::PruneCursor` (`max_values`: None, `max_size`: Some(306), added: 2781, mode: `MaxEncodedLen`)
+	fn expire_node() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(17_083_000, 3535)
+			.saturating_add(RocksDbWeight::get().reads(3_u64))
+			.saturating_add(RocksDbWeight::get().writes(10_u64))
+	}
+	/// Storage: `ProofOfLocation::AccountData` (r:9601 w:0)
+	/// Proof: `ProofOfLocation::AccountData` (`max_values`: None, `max_size`: Some(70), added: 2545, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::RssiData` (r:10200 w:0)
+	/// Proof: `ProofOfLocation::RssiData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::TxPowerData` (r:9600 w:0)
+	/// Proof: `ProofOfLocation::TxPowerData` (`max_values`: None, `max_size`: Some(102), added: 2577, mode: `MaxEncodedLen`)
+	/// Storage: `ProofOfLocation::EpochTrustScores` (r:0 w:1)
+	/// Proof: `ProofOfLocation::EpochTrustScores` (`max_values`: None, `max_size`: Some(63), added: 2538, mode: `MaxEncodedLen`)
+	/// The range of component `n` is `[0, 9600]`.
+	fn rollup_trust_score(n: u32, ) -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(3_962_480_000, 3535)
+			.saturating_add(Weight::from_parts(6_214_592, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(601_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(Weight::from_parts(0, 5122).saturating_mul(n.into()))
+	}
+	/// Storage: `ProofOfLocation::RangeData` (r:0 w:1)
+	/// Proof: `ProofOfLocation::RangeData` (`max_values`: None, `max_size`: Some(104), added: 2579, mode: `MaxEncodedLen`)
+	fn prune_range_report() -> Weight {
+		// Placeholder, not benchmarked
+		Weight::from_parts(4_122_000, 0)
 			.saturating_add(RocksDbWeight::get().writes(1_u64))
 	}
 }
@@ -302,6 +302,7 @@ impl_runtime_apis! {
         }
     }
 
+    #[api_version(3)]
     impl pallet_proof_of_location::rpc::TrustScoreApi<Block, AccountId> for Runtime {
         fn calculate_trust_scores(target_block: u32) -> Vec<(AccountId, i16)> {
             pallet_proof_of_location::Pallet::<Runtime>::calculate_all_trust_scores(target_block.into())
@@ -310,5 +311,33 @@ impl_runtime_apis! {
         fn calculate_trust_score(target_block: u32, account: AccountId) -> Option<i16> {
             pallet_proof_of_location::Pallet::<Runtime>::calculate_trust_score_for_account(target_block.into(), &account)
         }
+
+        fn calculate_trust_scores_with(
+            target_block: u32,
+            overrides: pallet_proof_of_location::util::EstimatorOverrides,
+        ) -> Vec<(AccountId, i16)> {
+            pallet_proof_of_location::Pallet::<Runtime>::calculate_all_trust_scores_with(target_block.into(), &overrides)
+        }
+
+        fn calculate_trust_score_with(
+            target_block: u32,
+            account: AccountId,
+            overrides: pallet_proof_of_location::util::EstimatorOverrides,
+        ) -> Option<i16> {
+            pallet_proof_of_location::Pallet::<Runtime>::calculate_trust_score_for_account_with(target_block.into(), &account, &overrides)
+        }
+
+        fn trust_score_breakdown(
+            target_block: u32,
+            account: AccountId,
+        ) -> Vec<(AccountId, i16, i16, i16)> {
+            pallet_proof_of_location::Pallet::<Runtime>::trust_score_breakdown(target_block.into(), &account)
+        }
+    }
+
+    impl pallet_proof_of_location::rpc::NodeConfigApi<Block, AccountId> for Runtime {
+        fn node_config(account: AccountId) -> pallet_proof_of_location::util::NodeConfigBundle {
+            pallet_proof_of_location::Pallet::<Runtime>::node_config(&account)
+        }
     }
 }
@@ -42,7 +42,7 @@ use sp_version::RuntimeVersion;
 use super::{
     AccountId, Aura, Balance, Balances, Block, BlockNumber, Hash, Nonce, PalletInfo, Runtime,
     RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask,
-    System, EXISTENTIAL_DEPOSIT, SLOT_DURATION, VERSION,
+    System, Timestamp, DAYS, EXISTENTIAL_DEPOSIT, HOURS, MINUTES, SLOT_DURATION, VERSION,
 };
 
 const NORMAL_DISPATCH_RATIO: Perbill = Perbill::from_percent(75);
@@ -160,20 +160,74 @@ impl pallet_sudo::Config for Runtime {
 // Server configuration constants
 parameter_types! {
     pub const ServerUrl: &'static [u8] = b"localhost:3000"; // URL of the Bluetooth server
+    pub const FallbackServerUrls: &'static [&'static [u8]] = &[]; // Tried when the server fails
+    pub const LocationServerUrl: Option<&'static [u8]> = None; // URL of the GPS server, if not the Bluetooth one
+    pub const RequireHttps: bool = false; // Whether servers are only contacted over HTTPS
+    pub const HttpRetries: u32 = 2; // Retries of a failed request before the next server
+    pub const HttpRetryBackoffMillis: u64 = 500; // Wait before the first retry, doubled each time
+    pub const HttpTimeoutMillis: u64 = 30_000; // Deadline of a single HTTP request
+    pub const OffchainRunBudgetMillis: u64 = 60_000; // Deadline of all HTTP requests of a run
+    pub const MaxResponseAgeMillis: u64 = 60_000; // Oldest server response that is accepted
+    pub const MaxClockSkewMillis: u64 = 30_000; // Server clock deviation from block time that is warned about
+    pub const MaxResponseBytes: u32 = 65_536; // Largest server response body that is read
     pub const ReferenceRssi: i16 = -48; // Reference RSSI at 1 meter
     pub const PathLossExponent: u8 = 40; // Path loss exponent multiplied by 10
+    pub const ReferencePathLoss: u8 = 48; // Path loss at 1 meter, ReferenceRssi at 0 dBm TX power
     pub const MaxDistance: u32 = 10; // Maximum distance between neighbors in meters
     pub const UpdateCooldown: BlockNumber = 86400; // 1 day cooldown between updates
+    pub const MaxPruneReports: u32 = 1000; // Maximum RSSI entries scanned per prune call
+    pub const MaxNodes: u32 = 1000; // Maximum number of registered nodes
+    pub const MaxReportsPerNode: u32 = 16; // Maximum RSSI reports about a node per block
+    pub const MaxBatchSize: u32 = 32; // Maximum RSSI readings per batch call
+    pub const RssiRetention: BlockNumber = 7 * DAYS; // Blocks RSSI reports are kept before pruning
+    pub const NodeExpiry: BlockNumber = 30 * DAYS; // Inactive blocks before a node can be removed
+    pub const EpochLength: BlockNumber = HOURS; // Blocks per trust score rollup
+    pub const LocationRefreshInterval: BlockNumber = HOURS; // Blocks between location checks
+    pub const LocationUpdateDelta: u32 = 5; // Meters a node must move to update its location
+    pub const MisbehaviorThreshold: u16 = 20; // RSSI error in dB that makes a reading suspicious
+    pub const MisbehaviorRuns: u32 = 10; // Suspicious readings in a row before reporting a neighbor
+    pub const RssiHysteresis: u16 = 2; // RSSI change in dB before a reading is submitted again
+    pub const RssiResubmitInterval: BlockNumber = 10 * MINUTES; // Blocks before an unchanged reading is submitted again
+    pub const MaxRegistrationDeviation: u32 = 100; // Meters a claimed location may be off from neighbors' RSSI
+    pub const MaxRangeDeviation: u32 = 10; // Meters a UWB range may be off from the registered locations' distance
 }
 
 /// Configure the pallet-proof-of-location.
 impl pallet_proof_of_location::Config for Runtime {
     type AuthorityId = pallet_proof_of_location::crypto::TestAuthId;
     type RuntimeEvent = RuntimeEvent;
+    type RuntimeTask = RuntimeTask;
     type WeightInfo = pallet_proof_of_location::weights::SubstrateWeight<Runtime>;
+    type UnixTime = Timestamp;
     type ServerUrl = ServerUrl;
+    type FallbackServerUrls = FallbackServerUrls;
+    type LocationServerUrl = LocationServerUrl;
+    type RequireHttps = RequireHttps;
+    type HttpRetries = HttpRetries;
+    type HttpRetryBackoffMillis = HttpRetryBackoffMillis;
+    type HttpTimeoutMillis = HttpTimeoutMillis;
+    type OffchainRunBudgetMillis = OffchainRunBudgetMillis;
+    type MaxResponseAgeMillis = MaxResponseAgeMillis;
+    type MaxClockSkewMillis = MaxClockSkewMillis;
+    type MaxResponseBytes = MaxResponseBytes;
     type ReferenceRssi = ReferenceRssi;
     type PathLossExponent = PathLossExponent;
+    type ReferencePathLoss = ReferencePathLoss;
     type MaxDistance = MaxDistance;
     type UpdateCooldown = UpdateCooldown;
+    type MaxPruneReports = MaxPruneReports;
+    type MaxNodes = MaxNodes;
+    type MaxReportsPerNode = MaxReportsPerNode;
+    type MaxBatchSize = MaxBatchSize;
+    type RssiRetention = RssiRetention;
+    type NodeExpiry = NodeExpiry;
+    type EpochLength = EpochLength;
+    type LocationRefreshInterval = LocationRefreshInterval;
+    type LocationUpdateDelta = LocationUpdateDelta;
+    type MisbehaviorThreshold = MisbehaviorThreshold;
+    type MisbehaviorRuns = MisbehaviorRuns;
+    type RssiHysteresis = RssiHysteresis;
+    type RssiResubmitInterval = RssiResubmitInterval;
+    type MaxRegistrationDeviation = MaxRegistrationDeviation;
+    type MaxRangeDeviation = MaxRangeDeviation;
 }
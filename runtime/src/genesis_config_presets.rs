@@ -15,7 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{AccountId, BalancesConfig, RuntimeGenesisConfig, SudoConfig};
+use crate::{AccountId, BalancesConfig, ProofOfLocationConfig, RuntimeGenesisConfig, SudoConfig};
 use alloc::{vec, vec::Vec};
 use frame_support::build_struct_json_patch;
 use serde_json::Value;
@@ -24,11 +24,18 @@ use sp_consensus_grandpa::AuthorityId as GrandpaId;
 use sp_genesis_builder::{self, PresetId};
 use sp_keyring::Sr25519Keyring;
 
+/// Preset for a development chain with the demo nodes registered at genesis.
+pub const POL_DEV_RUNTIME_PRESET: &str = "pol-dev";
+
+/// Preset for a local testnet with the demo nodes registered at genesis.
+pub const POL_LOCAL_RUNTIME_PRESET: &str = "pol-local";
+
 // Returns the genesis config presets populated with given parameters.
 fn testnet_genesis(
     initial_authorities: Vec<(AuraId, GrandpaId)>,
     endowed_accounts: Vec<AccountId>,
     root: AccountId,
+    registered_nodes: Vec<(AccountId, [u8; 6], i64, i64)>,
 ) -> Value {
     build_struct_json_patch!(RuntimeGenesisConfig {
         balances: BalancesConfig {
@@ -51,9 +58,50 @@ fn testnet_genesis(
                 .collect::<Vec<_>>(),
         },
         sudo: SudoConfig { key: Some(root) },
+        proof_of_location: ProofOfLocationConfig {
+            nodes: registered_nodes,
+        },
     })
 }
 
+/// Demo nodes matching the simulator's mock nodes (Alice to Eve).
+///
+/// Coordinates are fixed-point values (multiplied by 1_000_000).
+fn demo_nodes() -> Vec<(AccountId, [u8; 6], i64, i64)> {
+    vec![
+        (
+            Sr25519Keyring::Alice.to_account_id(),
+            [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0x01],
+            10,
+            10,
+        ),
+        (
+            Sr25519Keyring::Bob.to_account_id(),
+            [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0x02],
+            10,
+            0,
+        ),
+        (
+            Sr25519Keyring::Charlie.to_account_id(),
+            [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0x03],
+            -10,
+            0,
+        ),
+        (
+            Sr25519Keyring::Dave.to_account_id(),
+            [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0x04],
+            0,
+            10,
+        ),
+        (
+            Sr25519Keyring::Eve.to_account_id(),
+            [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0x05],
+            0,
+            -10,
+        ),
+    ]
+}
+
 /// Return the development genesis config.
 pub fn development_config_genesis() -> Value {
     testnet_genesis(
@@ -68,6 +116,7 @@ pub fn development_config_genesis() -> Value {
             Sr25519Keyring::BobStash.to_account_id(),
         ],
         sp_keyring::Sr25519Keyring::Alice.to_account_id(),
+        vec![],
     )
 }
 
@@ -89,6 +138,48 @@ pub fn local_config_genesis() -> Value {
             .map(|v| v.to_account_id())
             .collect::<Vec<_>>(),
         Sr25519Keyring::Alice.to_account_id(),
+        vec![],
+    )
+}
+
+/// Return the development genesis config with the demo nodes registered.
+pub fn pol_development_config_genesis() -> Value {
+    testnet_genesis(
+        vec![(
+            sp_keyring::Sr25519Keyring::Alice.public().into(),
+            sp_keyring::Ed25519Keyring::Alice.public().into(),
+        )],
+        vec![
+            Sr25519Keyring::Alice.to_account_id(),
+            Sr25519Keyring::Bob.to_account_id(),
+            Sr25519Keyring::Charlie.to_account_id(),
+            Sr25519Keyring::Dave.to_account_id(),
+            Sr25519Keyring::Eve.to_account_id(),
+        ],
+        sp_keyring::Sr25519Keyring::Alice.to_account_id(),
+        demo_nodes(),
+    )
+}
+
+/// Return the local testnet genesis config with the demo nodes registered.
+pub fn pol_local_config_genesis() -> Value {
+    testnet_genesis(
+        vec![
+            (
+                sp_keyring::Sr25519Keyring::Alice.public().into(),
+                sp_keyring::Ed25519Keyring::Alice.public().into(),
+            ),
+            (
+                sp_keyring::Sr25519Keyring::Bob.public().into(),
+                sp_keyring::Ed25519Keyring::Bob.public().into(),
+            ),
+        ],
+        Sr25519Keyring::iter()
+            .filter(|v| v != &Sr25519Keyring::One && v != &Sr25519Keyring::Two)
+            .map(|v| v.to_account_id())
+            .collect::<Vec<_>>(),
+        Sr25519Keyring::Alice.to_account_id(),
+        demo_nodes(),
     )
 }
 
@@ -97,6 +188,8 @@ pub fn get_preset(id: &PresetId) -> Option<Vec<u8>> {
     let patch = match id.as_ref() {
         sp_genesis_builder::DEV_RUNTIME_PRESET => development_config_genesis(),
         sp_genesis_builder::LOCAL_TESTNET_RUNTIME_PRESET => local_config_genesis(),
+        POL_DEV_RUNTIME_PRESET => pol_development_config_genesis(),
+        POL_LOCAL_RUNTIME_PRESET => pol_local_config_genesis(),
         _ => return None,
     };
     Some(
@@ -111,5 +204,7 @@ pub fn preset_names() -> Vec<PresetId> {
     vec![
         PresetId::from(sp_genesis_builder::DEV_RUNTIME_PRESET),
         PresetId::from(sp_genesis_builder::LOCAL_TESTNET_RUNTIME_PRESET),
+        PresetId::from(POL_DEV_RUNTIME_PRESET),
+        PresetId::from(POL_LOCAL_RUNTIME_PRESET),
     ]
 }
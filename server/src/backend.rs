@@ -0,0 +1,80 @@
+use bluer::Address;
+use std::str::FromStr;
+
+/// Radios the server measures its neighbors with, given by `backends` or `RSSI_BACKENDS`
+///
+/// Only applied at startup. Without `ble` no Bluetooth session is opened, so the server runs on
+/// hosts without BlueZ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backends {
+    /// Scan and advertise over Bluetooth LE with BlueZ
+    pub ble: bool,
+    /// Scan the neighbors' Wi-Fi BSSIDs with `iw`
+    pub wifi: bool,
+}
+
+impl Default for Backends {
+    fn default() -> Self {
+        Self {
+            ble: true,
+            wifi: false,
+        }
+    }
+}
+
+impl FromStr for Backends {
+    type Err = String;
+
+    /// Parse comma-separated backends, e.g. `ble,wifi`
+    fn from_str(value: &str) -> Result<Self, String> {
+        let mut backends = Self {
+            ble: false,
+            wifi: false,
+        };
+        for backend in value.split(',').map(str::trim) {
+            match backend {
+                "ble" => backends.ble = true,
+                "wifi" => backends.wifi = true,
+                _ => return Err(format!("Invalid RSSI backend: {}", backend)),
+            }
+        }
+
+        if !backends.ble && !backends.wifi {
+            return Err("At least one RSSI backend is needed".to_string());
+        }
+        Ok(backends)
+    }
+}
+
+impl Backends {
+    /// Names of the backends in use, for the startup log and `--print-config`
+    pub fn names(&self) -> String {
+        let mut names = Vec::new();
+        if self.ble {
+            names.push("ble");
+        }
+        if self.wifi {
+            names.push("wifi");
+        }
+        names.join(", ")
+    }
+}
+
+/// Module measuring the distance to neighbors, e.g. a DW3000 doing UWB two-way ranging
+///
+/// Modules are driven over serial or SPI, so the backend is read on a blocking thread.
+pub trait RangingBackend: Send + 'static {
+    /// Name of the module, for the startup log
+    fn name(&self) -> String;
+
+    /// Block until the module measured the distance to a neighbor
+    fn next_range(&mut self) -> Result<Range, String>;
+}
+
+/// Distance to a neighbor measured by a ranging backend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    /// Registered address of the neighbor, the one its module is configured with
+    pub address: Address,
+    pub distance_cm: u32,
+}
@@ -0,0 +1,156 @@
+use codec::{Decode, Encode};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tokio::time;
+
+use crate::bluetooth::{current_rssi, DeviceRssi, RssiData};
+
+const BACKLOG_INTERVAL: Duration = Duration::from_secs(60); // 1 minute
+const OFFLINE_THRESHOLD: Duration = Duration::from_secs(120); // 2 minutes
+const MAX_BACKLOG_ENTRIES: usize = 1440; // 1 day of minutes
+const DEFAULT_BACKLOG_PATH: &str = "rssi_backlog.scale";
+
+/// Aggregated RSSI snapshot taken while the offchain worker was not polling us
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct BacklogEntry {
+    /// Unix timestamp in seconds when the snapshot was taken
+    pub timestamp: u64,
+    pub devices: Vec<DeviceRssi>,
+}
+
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct BacklogResponse {
+    pub entries: Vec<BacklogEntry>,
+}
+
+/// Measurements buffered on disk while the chain or offchain worker is unreachable
+pub struct Backlog {
+    path: PathBuf,
+    entries: Vec<BacklogEntry>,
+    last_request: Instant,
+}
+
+// Global shared state for the offline backlog
+pub type SharedBacklog = Arc<Mutex<Backlog>>;
+
+impl Backlog {
+    /// Load the backlog from the file given by `BACKLOG_PATH`, starting empty if it does not exist
+    pub fn load_from_env() -> Self {
+        let path: PathBuf = std::env::var("BACKLOG_PATH")
+            .unwrap_or_else(|_| DEFAULT_BACKLOG_PATH.to_string())
+            .into();
+
+        let entries = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| Vec::<BacklogEntry>::decode(&mut &bytes[..]).ok())
+            .unwrap_or_default();
+
+        if !entries.is_empty() {
+            info!(
+                "Loaded {} buffered measurement(s) from {}",
+                entries.len(),
+                path.display()
+            );
+        }
+
+        Self {
+            path,
+            entries,
+            last_request: Instant::now(),
+        }
+    }
+
+    /// Record that the offchain worker has just polled us, or that the direct submitter has just
+    /// submitted our readings
+    pub fn mark_request(&mut self) {
+        self.last_request = Instant::now();
+    }
+
+    /// Whether our readings have neither been requested nor submitted for longer than
+    /// OFFLINE_THRESHOLD
+    fn is_offline(&self) -> bool {
+        self.last_request.elapsed() > OFFLINE_THRESHOLD
+    }
+
+    /// Append an entry, dropping the oldest ones beyond MAX_BACKLOG_ENTRIES
+    fn push(&mut self, entry: BacklogEntry) -> std::io::Result<()> {
+        self.entries.push(entry);
+        if self.entries.len() > MAX_BACKLOG_ENTRIES {
+            let excess = self.entries.len() - MAX_BACKLOG_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.persist()
+    }
+
+    /// All buffered entries, oldest first
+    pub fn entries(&self) -> &[BacklogEntry] {
+        &self.entries
+    }
+
+    /// Remove the entries taken up to `up_to`, once they are safely stored elsewhere, returning
+    /// how many were removed
+    pub fn acknowledge(&mut self, up_to: u64) -> std::io::Result<usize> {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.timestamp > up_to);
+        let removed = before - self.entries.len();
+        if removed > 0 {
+            self.persist()?;
+        }
+        Ok(removed)
+    }
+
+    fn persist(&self) -> std::io::Result<()> {
+        std::fs::write(&self.path, self.entries.encode())
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Periodically snapshot the smoothed RSSI values into the backlog while we are offline
+pub async fn start_backlog_recorder(backlog: SharedBacklog, rssi_data: RssiData) {
+    loop {
+        time::sleep(BACKLOG_INTERVAL).await;
+
+        if !backlog.lock().await.is_offline() {
+            continue;
+        }
+
+        let devices = match current_rssi(
+            Arc::clone(&rssi_data),
+            crate::config::current().filter.as_ref(),
+        )
+        .await
+        {
+            Ok(response) => response.devices,
+            Err(e) => {
+                warn!("⚠️  Failed to snapshot RSSI for backlog: {}", e);
+                continue;
+            }
+        };
+
+        if devices.is_empty() {
+            continue;
+        }
+
+        let entry = BacklogEntry {
+            timestamp: unix_timestamp(),
+            devices,
+        };
+
+        let mut backlog = backlog.lock().await;
+        match backlog.push(entry) {
+            Ok(()) => info!(
+                "📦 No RSSI request or submission recently, buffered measurement (backlog size: {})",
+                backlog.entries.len()
+            ),
+            Err(e) => warn!("⚠️  Failed to persist RSSI backlog: {}", e),
+        }
+    }
+}
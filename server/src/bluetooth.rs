@@ -1,81 +1,208 @@
 use bluer::{
-    adv::{Advertisement, Type},
-    Adapter, AdapterEvent, Address, DeviceEvent, DeviceProperty, DiscoveryFilter,
-    DiscoveryTransport,
+    adv::{Advertisement, Feature, Type},
+    gatt::local::{
+        Application, ApplicationHandle, Characteristic, CharacteristicRead, CharacteristicWrite,
+        CharacteristicWriteMethod, ReqError, Service,
+    },
+    Adapter, AdapterEvent, Address, Device, DeviceEvent, DeviceProperty, DiscoveryFilter,
+    DiscoveryTransport, Uuid,
 };
 use codec::{Decode, Encode};
 use futures::stream::StreamExt;
-use std::collections::{HashMap, HashSet, VecDeque};
+use rand::RngCore;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::error::Error;
-use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use subxt_signer::sr25519::{verify, Keypair, PublicKey, Signature};
+use tokio::sync::{broadcast, Mutex};
 use tokio::{task, time};
 
-const MEDIAN_DURATION: Duration = Duration::from_secs(60); // 1 minute
-const BLUETOOTH_SERVICE_UUID: &str = "0000b4e7-0000-1000-8000-00805f9b34fb";
+use crate::filter::RssiFilter;
+use crate::privacy::{is_resolvable_private, resolve, CurrentAddresses, ResolvingKeys};
+
+const DEFAULT_SAMPLE_WINDOW: Duration = Duration::from_secs(60); // 1 minute
+const DEFAULT_SCAN_ACTIVE: Duration = Duration::from_secs(10);
+const DEFAULT_BLUETOOTH_SERVICE_UUID: &str = "0000b4e7-0000-1000-8000-00805f9b34fb";
+
+/// Characteristic neighbors write a nonce to and read its signature from
+const RANGING_CHARACTERISTIC_UUID: &str = "0000b4e8-0000-1000-8000-00805f9b34fb";
+/// How often every neighbor with a server key is challenged
+const RANGING_INTERVAL: Duration = Duration::from_secs(15);
+/// Fits in a single write at the default ATT MTU
+const RANGING_NONCE_LEN: usize = 16;
+/// Prefix of the signed message, so ranging signatures are of no use for anything else
+const RANGING_PREFIX: &[u8] = b"proof-of-location/ranging:";
+/// How often polling checks whether it was enabled by a reloaded configuration
+const POLL_IDLE_INTERVAL: Duration = Duration::from_secs(10);
+/// Samples buffered per stream subscriber before the oldest are skipped
+const SAMPLE_CHANNEL_CAPACITY: usize = 256;
+/// Company ID of the manufacturer data carrying the identity tag, reserved by the Bluetooth SIG
+/// for testing
+const IDENTITY_COMPANY_ID: u16 = 0xFFFF;
+/// Bytes of the account ID advertised as identity tag
+pub const IDENTITY_TAG_LEN: usize = 8;
+
+/// Scan and advertising parameters, the `[scan]` section of the configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanConfig {
+    /// Readings older than this are dropped before smoothing
+    pub sample_window: Duration,
+    /// Most readings kept per device, 0 for no limit besides the window
+    pub max_queue_size: usize,
+    /// How long discovery runs before pausing
+    pub scan_active: Duration,
+    /// How long discovery pauses, zero to scan continuously
+    pub scan_pause: Duration,
+    /// Service UUID we advertise and discover neighbors by, only applied at startup
+    pub service_uuid: Uuid,
+    /// Advertising interval, BlueZ's default if not set, only applied at startup
+    pub advertising_interval: Option<Duration>,
+    /// How often neighbors without a recent reading are connected to and polled, `None` to
+    /// only listen to their advertisements
+    pub poll_interval: Option<Duration>,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            sample_window: DEFAULT_SAMPLE_WINDOW,
+            max_queue_size: 0,
+            scan_active: DEFAULT_SCAN_ACTIVE,
+            scan_pause: Duration::ZERO,
+            service_uuid: DEFAULT_BLUETOOTH_SERVICE_UUID.parse().unwrap(),
+            advertising_interval: None,
+            poll_interval: None,
+        }
+    }
+}
+
+/// Get the scan parameters of the current configuration
+///
+/// Read again wherever they are used, so a reloaded configuration takes effect.
+pub fn scan_config() -> ScanConfig {
+    crate::config::current().scan.clone()
+}
 
 #[derive(Debug, Clone)]
 pub struct RssiReading {
-    rssi: i16,
-    timestamp: Instant,
+    pub rssi: i16,
+    /// TX power the device advertises, in dBm
+    pub tx_power: Option<i16>,
+    pub timestamp: Instant,
+    /// Unix time in milliseconds, sent along so the wall clock of the reading is known
+    pub unix_millis: u64,
+}
+
+/// A single RSSI reading of a neighbor, as it arrives from BlueZ
+#[derive(Debug, Clone)]
+pub struct RssiSample {
+    pub address: Address,
+    pub rssi: i16,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+}
+
+static SAMPLES: OnceLock<broadcast::Sender<RssiSample>> = OnceLock::new();
+
+fn samples() -> &'static broadcast::Sender<RssiSample> {
+    SAMPLES.get_or_init(|| broadcast::channel(SAMPLE_CHANNEL_CAPACITY).0)
+}
+
+/// Subscribe to every RSSI reading recorded from now on
+pub fn subscribe_samples() -> broadcast::Receiver<RssiSample> {
+    samples().subscribe()
 }
 
 #[derive(Encode, Decode, Debug, Clone)]
 pub struct DeviceRssi {
     pub address: [u8; 6],
     pub rssi: i16,
+    /// TX power the device last advertised, in dBm, so the RSSI can be read as a path loss
+    pub tx_power: Option<i16>,
+    /// Unix time in milliseconds of the newest reading
+    pub timestamp: u64,
 }
 
 #[derive(Encode, Decode, Debug, Clone)]
 pub struct RssiResponse {
     pub devices: Vec<DeviceRssi>,
+    /// Unix time in milliseconds, the offchain worker rejects stale responses
+    pub timestamp: u64,
+    /// Not sent, since a counter would restart with the server
+    pub sequence: Option<u64>,
+}
+
+/// Current Unix time in milliseconds
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
 }
 
 // Global shared state for neighbor addresses
 pub type NeighborAddresses = Arc<Mutex<HashSet<Address>>>;
 
-/// Initialize neighbor addresses from environment variable (for backwards compatibility/testing)
-pub fn init_neighbor_addresses_from_env() -> HashSet<Address> {
-    std::env::var("BLUETOOTH_ADDRESSES")
-        .unwrap_or_default()
-        .split(',')
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .filter_map(|s| s.parse().ok())
-        .collect()
+// Server keys of the neighbors that are ranged with a challenge, by Bluetooth address
+pub type RangingKeys = Arc<Mutex<HashMap<Address, [u8; 32]>>>;
+
+// Identity tags of the registered nodes, by Bluetooth address
+pub type IdentityTags = Arc<Mutex<HashMap<Address, [u8; IDENTITY_TAG_LEN]>>>;
+
+/// Identity tag advertised by the node of `account_id`
+///
+/// Account IDs are sr25519 public keys, so their first bytes are as unpredictable as a hash.
+pub fn identity_tag(account_id: &[u8; 32]) -> [u8; IDENTITY_TAG_LEN] {
+    let mut tag = [0u8; IDENTITY_TAG_LEN];
+    tag.copy_from_slice(&account_id[..IDENTITY_TAG_LEN]);
+    tag
 }
 
-pub async fn bluetooth_address(adapter: &Adapter) -> Address {
-    adapter
-        .address()
-        .await
-        .expect("Failed to get adapter address")
+/// Whether neighbors advertising no identity tag are ignored, enabled by `REQUIRE_BLE_IDENTITY`
+pub fn identity_required() -> bool {
+    std::env::var("REQUIRE_BLE_IDENTITY").is_ok_and(|v| v == "1" || v == "true")
 }
 
-fn calculate_median(values: &mut Vec<i16>) -> Option<i16> {
-    if values.is_empty() {
-        return None;
+/// Whether the identity tag a device advertises matches the account registered with its address
+///
+/// A device whose account is not known yet passes. One advertising no tag passes too, unless
+/// `REQUIRE_BLE_IDENTITY` is set, so neighbors running an older server are still measured.
+fn identity_matches(
+    manufacturer_data: Option<&HashMap<u16, Vec<u8>>>,
+    expected: Option<[u8; IDENTITY_TAG_LEN]>,
+) -> bool {
+    let Some(expected) = expected else {
+        return true;
+    };
+
+    match manufacturer_data.and_then(|data| data.get(&IDENTITY_COMPANY_ID)) {
+        Some(tag) => tag[..] == expected[..],
+        None => !identity_required(),
     }
+}
 
-    values.sort_unstable();
-    let len = values.len();
+/// Whether RSSI is only measured during a signed challenge, enabled by `BLE_RANGING`
+pub fn ranging_enabled() -> bool {
+    std::env::var("BLE_RANGING").is_ok_and(|v| v == "1" || v == "true")
+}
 
-    if len % 2 == 0 {
-        Some((values[len / 2 - 1] + values[len / 2]) / 2)
-    } else {
-        Some(values[len / 2])
-    }
+pub async fn bluetooth_address(adapter: &Adapter) -> Address {
+    adapter
+        .address()
+        .await
+        .expect("Failed to get adapter address")
 }
 
 // Global shared state for RSSI data with timestamps
 pub type RssiData = Arc<Mutex<HashMap<Address, VecDeque<RssiReading>>>>;
 
-/// Remove RSSI readings older than MEDIAN_DURATION
+/// Remove RSSI readings older than the sample window
 fn remove_old_readings(deque: &mut VecDeque<RssiReading>) {
     let now = Instant::now();
+    let window = scan_config().sample_window;
     while let Some(reading) = deque.front() {
-        if now.duration_since(reading.timestamp) > MEDIAN_DURATION {
+        if now.duration_since(reading.timestamp) > window {
             deque.pop_front();
         } else {
             break;
@@ -83,46 +210,419 @@ fn remove_old_readings(deque: &mut VecDeque<RssiReading>) {
     }
 }
 
-async fn start_advertising(adapter: &Adapter) -> Result<(), Box<dyn Error>> {
-    println!("Starting BLE advertising...");
+/// Add a reading of a device and return the number of readings kept for it
+pub async fn record_reading(
+    rssi_data: &RssiData,
+    addr: Address,
+    rssi: i16,
+    tx_power: Option<i16>,
+) -> usize {
+    let mut data = rssi_data.lock().await;
+    let deque = data.entry(addr).or_insert_with(VecDeque::new);
+
+    // Remove readings older than the sample window
+    remove_old_readings(deque);
+
+    // Add new reading with timestamp, keeping at most MAX_RSSI_QUEUE_SIZE
+    let now = unix_millis();
+    deque.push_back(RssiReading {
+        rssi,
+        tx_power,
+        timestamp: Instant::now(),
+        unix_millis: now,
+    });
+    let max_queue_size = scan_config().max_queue_size;
+    if max_queue_size > 0 && deque.len() > max_queue_size {
+        deque.drain(..deque.len() - max_queue_size);
+    }
+
+    // Push the reading to the live streams, nobody may be listening
+    let _ = samples().send(RssiSample {
+        address: addr,
+        rssi,
+        timestamp: now,
+    });
 
-    let advertisement = Advertisement {
-        // If it never connects, it should be 'Broadcast'.
-        advertisement_type: Type::Broadcast,
+    deque.len()
+}
 
-        // Add a service UUID. This is often used by apps to find specific devices.
-        service_uuids: [BLUETOOTH_SERVICE_UUID.parse().unwrap()]
-            .into_iter()
-            .collect(),
+/// Message a server signs to answer the challenge of the neighbor at `challenger`
+///
+/// Binding the challenger's address keeps a relay from passing the answer on to another node.
+fn ranging_message(challenger: Address, nonce: &[u8]) -> Vec<u8> {
+    [RANGING_PREFIX, &challenger.0, nonce].concat()
+}
 
+/// Serve the ranging characteristic, answering the nonce a neighbor writes with its signature
+///
+/// Signatures are kept per neighbor so that long reads at an offset return the same answer. A
+/// neighbor connecting from a resolvable private address is answered for its registered address.
+async fn serve_ranging(
+    adapter: &Adapter,
+    signer: Keypair,
+    resolving_keys: ResolvingKeys,
+) -> Result<ApplicationHandle, Box<dyn Error>> {
+    let answers: Arc<Mutex<HashMap<Address, Vec<u8>>>> = Arc::new(Mutex::new(HashMap::new()));
+    let answers_read = Arc::clone(&answers);
+
+    let application = Application {
+        services: vec![Service {
+            uuid: scan_config().service_uuid,
+            primary: true,
+            characteristics: vec![Characteristic {
+                uuid: RANGING_CHARACTERISTIC_UUID.parse()?,
+                write: Some(CharacteristicWrite {
+                    write: true,
+                    method: CharacteristicWriteMethod::Fun(Box::new(move |nonce, req| {
+                        let answers = Arc::clone(&answers);
+                        let signer = signer.clone();
+                        let resolving_keys = Arc::clone(&resolving_keys);
+                        Box::pin(async move {
+                            if nonce.len() != RANGING_NONCE_LEN {
+                                return Err(ReqError::InvalidValueLength);
+                            }
+                            let challenger = resolve(&resolving_keys, req.device_address)
+                                .await
+                                .unwrap_or(req.device_address);
+                            let signature = signer.sign(&ranging_message(challenger, &nonce));
+                            answers
+                                .lock()
+                                .await
+                                .insert(req.device_address, signature.0.to_vec());
+                            info!("🔏 Answered ranging challenge of {}", challenger);
+                            Ok(())
+                        })
+                    })),
+                    ..Default::default()
+                }),
+                read: Some(CharacteristicRead {
+                    read: true,
+                    fun: Box::new(move |req| {
+                        let answers = Arc::clone(&answers_read);
+                        Box::pin(async move {
+                            let answers = answers.lock().await;
+                            let answer = answers
+                                .get(&req.device_address)
+                                .ok_or(ReqError::NotPermitted)?;
+                            let offset = (req.offset as usize).min(answer.len());
+                            Ok(answer[offset..].to_vec())
+                        })
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
         ..Default::default()
     };
 
-    let _handle = adapter.advertise(advertisement).await?;
-    println!(
-        "BLE advertising started with service UUID: {}",
-        BLUETOOTH_SERVICE_UUID
-    );
+    Ok(adapter.serve_gatt_application(application).await?)
+}
+
+/// Challenge a neighbor over GATT and check its answer against the neighbor's server key
+async fn challenge_device(
+    device: &Device,
+    our_address: Address,
+    server_key: [u8; 32],
+) -> Result<(), Box<dyn Error>> {
+    let uuid: Uuid = RANGING_CHARACTERISTIC_UUID.parse()?;
+
+    for service in device.services().await? {
+        for characteristic in service.characteristics().await? {
+            if characteristic.uuid().await? != uuid {
+                continue;
+            }
+
+            let mut nonce = [0u8; RANGING_NONCE_LEN];
+            rand::thread_rng().fill_bytes(&mut nonce);
+            characteristic.write(&nonce).await?;
+
+            let signature: [u8; 64] = characteristic
+                .read()
+                .await?
+                .try_into()
+                .map_err(|_| "Ranging answer is not a signature")?;
+            let message = ranging_message(our_address, &nonce);
+            if !verify(&Signature(signature), message, &PublicKey(server_key)) {
+                return Err("Ranging answer is not signed by the neighbor's server key".into());
+            }
+            return Ok(());
+        }
+    }
+
+    Err("Neighbor does not serve the ranging characteristic".into())
+}
+
+/// Connect to a neighbor, challenge it and return the RSSI measured right after its answer,
+/// with the TX power it advertises
+async fn range_device(
+    adapter: &Adapter,
+    address: Address,
+    our_address: Address,
+    server_key: [u8; 32],
+) -> Result<Option<(i16, Option<i16>)>, Box<dyn Error>> {
+    let device = adapter.device(address)?;
+    device.connect().await?;
+
+    let result = challenge_device(&device, our_address, server_key).await;
+    let rssi = device.rssi().await;
+    let tx_power = device.tx_power().await.ok().flatten();
+    let _ = device.disconnect().await;
+
+    result?;
+    Ok(rssi?.map(|rssi| (rssi, tx_power)))
+}
+
+/// Periodically range every neighbor with a server key, recording the RSSI of the answers
+///
+/// Neighbors are connected to at the address they currently advertise from.
+async fn start_ranging(
+    adapter: Adapter,
+    rssi_data: RssiData,
+    neighbor_addresses: NeighborAddresses,
+    ranging_keys: RangingKeys,
+    current_addresses: CurrentAddresses,
+) {
+    let our_address = bluetooth_address(&adapter).await;
+
+    loop {
+        time::sleep(RANGING_INTERVAL).await;
+
+        let neighbors: Vec<Address> = neighbor_addresses.lock().await.iter().copied().collect();
+        for address in neighbors {
+            let Some(server_key) = ranging_keys.lock().await.get(&address).copied() else {
+                continue;
+            };
+
+            let current = current_addresses
+                .lock()
+                .await
+                .get(&address)
+                .copied()
+                .unwrap_or(address);
+
+            match range_device(&adapter, current, our_address, server_key).await {
+                Ok(Some((rssi, tx_power))) => {
+                    let len = record_reading(&rssi_data, address, rssi, tx_power).await;
+                    debug!("🔐 Ranged {}: {} (queue size: {})", address, rssi, len);
+                }
+                Ok(None) => info!("⚠️  Ranged {} but no RSSI is known", address),
+                Err(e) => warn!("⚠️  Ranging {} failed: {}", address, e),
+            }
+        }
+    }
+}
+
+/// A reading polled over a connection
+struct PolledReading {
+    rssi: i16,
+    tx_power: Option<i16>,
+    manufacturer_data: Option<HashMap<u16, Vec<u8>>>,
+}
+
+/// Connect to a neighbor and return the RSSI BlueZ reports while connected, with the TX power
+/// and manufacturer data it advertises
+///
+/// A connection we did not open, e.g. one of the neighbor, is left open.
+async fn poll_device(
+    adapter: &Adapter,
+    address: Address,
+) -> Result<Option<PolledReading>, Box<dyn Error>> {
+    let device = adapter.device(address)?;
+    let was_connected = device.is_connected().await?;
+    if !was_connected {
+        device.connect().await?;
+    }
+
+    let rssi = device.rssi().await;
+    let tx_power = device.tx_power().await.ok().flatten();
+    let manufacturer_data = device.manufacturer_data().await.ok().flatten();
+    if !was_connected {
+        let _ = device.disconnect().await;
+    }
+
+    Ok(rssi?.map(|rssi| PolledReading {
+        rssi,
+        tx_power,
+        manufacturer_data,
+    }))
+}
+
+/// Poll the RSSI of neighbors over a connection every `scan.poll_interval_secs`
+///
+/// Stacks with long advertising intervals rarely update the RSSI of their advertisements, so
+/// neighbors without a reading within the interval are connected to instead. Neighbors that are
+/// ranged are left to ranging, and ones that do not advertise their registered identity are
+/// skipped. Neighbors are connected to at the address they currently advertise from.
+async fn start_polling(
+    adapter: Adapter,
+    rssi_data: RssiData,
+    neighbor_addresses: NeighborAddresses,
+    ranging_keys: RangingKeys,
+    identity_tags: IdentityTags,
+    current_addresses: CurrentAddresses,
+) {
+    loop {
+        let Some(interval) = scan_config().poll_interval else {
+            time::sleep(POLL_IDLE_INTERVAL).await;
+            continue;
+        };
+        time::sleep(interval).await;
+
+        let neighbors: Vec<Address> = neighbor_addresses.lock().await.iter().copied().collect();
+        for address in neighbors {
+            if ranging_keys.lock().await.contains_key(&address) {
+                continue;
+            }
+            let recent = rssi_data
+                .lock()
+                .await
+                .get(&address)
+                .and_then(|readings| readings.back())
+                .is_some_and(|reading| reading.timestamp.elapsed() < interval);
+            if recent {
+                continue;
+            }
+
+            let current = current_addresses
+                .lock()
+                .await
+                .get(&address)
+                .copied()
+                .unwrap_or(address);
+
+            match poll_device(&adapter, current).await {
+                Ok(Some(polled)) => {
+                    // A device cloning the address of a neighbor answers connections too
+                    let expected_tag = identity_tags.lock().await.get(&address).copied();
+                    if !identity_matches(polled.manufacturer_data.as_ref(), expected_tag) {
+                        debug!(
+                            "🚫 Polled {} does not advertise its registered identity",
+                            address
+                        );
+                        continue;
+                    }
+
+                    let len =
+                        record_reading(&rssi_data, address, polled.rssi, polled.tx_power).await;
+                    debug!(
+                        "🔗 Polled {}: {} (queue size: {})",
+                        address, polled.rssi, len
+                    );
+                }
+                Ok(None) => debug!("🔗 Polled {} but no RSSI is known", address),
+                // Neighbors out of range fail on every poll
+                Err(e) => debug!("🔗 Polling {} failed: {}", address, e),
+            }
+        }
+    }
+}
+
+/// Advertise our service, with our identity tag once it is known
+///
+/// The advertisement is registered again whenever our tag changes. If it does not fit, e.g. next
+/// to a 128-bit service UUID, it is advertised without.
+async fn start_advertising(
+    adapter: &Adapter,
+    connectable: bool,
+    our_address: Address,
+    identity_tags: IdentityTags,
+) -> Result<(), Box<dyn Error>> {
+    info!("Starting BLE advertising...");
+
+    let mut advertised_tag = identity_tags.lock().await.get(&our_address).copied();
+    let mut _handle = advertise(adapter, connectable, advertised_tag).await?;
 
     // Keep advertising running indefinitely
     loop {
         time::sleep(Duration::from_secs(60)).await;
+
+        let tag = identity_tags.lock().await.get(&our_address).copied();
+        if tag != advertised_tag {
+            // Only one advertisement with our service at a time
+            drop(_handle);
+            _handle = advertise(adapter, connectable, tag).await?;
+            advertised_tag = tag;
+        }
     }
 }
 
+async fn advertise(
+    adapter: &Adapter,
+    connectable: bool,
+    tag: Option<[u8; IDENTITY_TAG_LEN]>,
+) -> Result<bluer::adv::AdvertisementHandle, Box<dyn Error>> {
+    let config = scan_config();
+    let advertisement = |tag: Option<[u8; IDENTITY_TAG_LEN]>| Advertisement {
+        // If it never connects, it should be 'Broadcast'.
+        // Neighbors connect to the ranging characteristic.
+        advertisement_type: if connectable {
+            Type::Peripheral
+        } else {
+            Type::Broadcast
+        },
+
+        // Add a service UUID. This is often used by apps to find specific devices.
+        service_uuids: [config.service_uuid].into_iter().collect(),
+
+        // Advertise at a fixed interval if one is configured
+        min_interval: config.advertising_interval,
+        max_interval: config.advertising_interval,
+
+        // Let neighbors check that our address belongs to our account
+        manufacturer_data: tag
+            .map(|tag| BTreeMap::from([(IDENTITY_COMPANY_ID, tag.to_vec())]))
+            .unwrap_or_default(),
+
+        // Let neighbors turn our RSSI into a path loss
+        system_includes: [Feature::TxPower].into_iter().collect(),
+
+        ..Default::default()
+    };
+
+    let handle = match adapter.advertise(advertisement(tag)).await {
+        Ok(handle) if tag.is_some() => {
+            info!(
+                "BLE advertising started with service UUID {} and our identity tag",
+                config.service_uuid
+            );
+            handle
+        }
+        Ok(handle) => {
+            info!(
+                "BLE advertising started with service UUID: {}",
+                config.service_uuid
+            );
+            handle
+        }
+        Err(e) if tag.is_some() => {
+            warn!("⚠️  Advertising without our identity tag: {}", e);
+            adapter.advertise(advertisement(None)).await?
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(handle)
+}
+
 async fn scan_devices(
     adapter: &Adapter,
     rssi_data: RssiData,
     neighbor_addresses: NeighborAddresses,
+    ranging_keys: RangingKeys,
+    identity_tags: IdentityTags,
+    resolving_keys: ResolvingKeys,
+    current_addresses: CurrentAddresses,
 ) -> Result<(), Box<dyn Error>> {
-    println!("Starting device scanning...");
+    info!("Starting device scanning...");
 
     // Initially check if we have any neighbors to monitor
     let initial_count = neighbor_addresses.lock().await.len();
     if initial_count == 0 {
-        println!("Warning: No neighbor addresses configured yet. Waiting for updates...");
+        info!("Warning: No neighbor addresses configured yet. Waiting for updates...");
     } else {
-        println!("Monitoring {} device(s) initially", initial_count);
+        info!("Monitoring {} device(s) initially", initial_count);
     }
 
     adapter
@@ -131,9 +631,7 @@ async fn scan_devices(
             transport: DiscoveryTransport::Le,
 
             // filter by service UUIDs.
-            uuids: vec![BLUETOOTH_SERVICE_UUID.parse().unwrap()]
-                .into_iter()
-                .collect(),
+            uuids: [scan_config().service_uuid].into_iter().collect(),
 
             // Set discoverable to true
             discoverable: true,
@@ -142,113 +640,162 @@ async fn scan_devices(
         })
         .await?;
 
-    // Start discovery
-    let discover = adapter.discover_devices().await?;
-    tokio::pin!(discover);
-
-    println!("Device scanning started...");
-
     // Track spawned tasks so we can abort them when devices are removed
     let mut device_tasks: HashMap<Address, task::JoinHandle<()>> = HashMap::new();
 
-    // Continuously scan for devices
     loop {
-        tokio::select! {
-            Some(evt) = discover.next() => {
-                match evt {
-                    AdapterEvent::DeviceAdded(addr) => {
-                        // Only process devices in our target list
-                        let is_neighbor = neighbor_addresses.lock().await.contains(&addr);
-                        if !is_neighbor {
-                            continue;
-                        }
-
-                        // Skip if we already have a task for this device
-                        if device_tasks.contains_key(&addr) {
-                            continue;
-                        }
-
-                        let device = adapter.device(addr)?;
-
-                        // Spawn a task to listen for RSSI changes on this device
-                        let rssi_data_clone = Arc::clone(&rssi_data);
-
-                        let rssi = device.rssi().await?.unwrap_or(0);
-                        println!("Device added: {} (RSSI: {})", addr, rssi);
-
-                        if rssi != 0 {
-                            let mut data = rssi_data_clone.lock().await;
-                            let deque = data.entry(addr).or_insert_with(VecDeque::new);
+        let config = scan_config();
+
+        // Start discovery, it stops when the stream is dropped
+        let discover = adapter.discover_devices().await?;
+        tokio::pin!(discover);
+
+        info!("Device scanning started...");
+
+        // Scan until it is time to pause, or continuously without a pause
+        let pause_at = (!config.scan_pause.is_zero()).then(|| Instant::now() + config.scan_active);
+        while !pause_at.is_some_and(|at| Instant::now() >= at) {
+            tokio::select! {
+                Some(evt) = discover.next() => {
+                    match evt {
+                        AdapterEvent::DeviceAdded(addr) => {
+                            // Neighbors advertising from a resolvable private address are recorded under their registered one
+                            let Some(identity) = resolve(&resolving_keys, addr).await else {
+                                continue;
+                            };
+
+                            // Only process devices in our target list
+                            let is_neighbor = neighbor_addresses.lock().await.contains(&identity);
+                            if !is_neighbor {
+                                continue;
+                            }
 
-                            // Remove readings older than MEDIAN_DURATION
-                            remove_old_readings(deque);
+                            // Skip if we already have a task for this device
+                            if device_tasks.contains_key(&addr) {
+                                continue;
+                            }
 
-                            // Add new reading with timestamp
-                            deque.push_back(RssiReading {
-                                rssi,
-                                timestamp: Instant::now(),
-                            });
-                        }
+                            let device = adapter.device(addr)?;
 
-                        let task = tokio::spawn(async move {
-                            if let Ok(events) = device.events().await {
-                                tokio::pin!(events);
+                            // Spawn a task to listen for RSSI changes on this device
+                            let rssi_data_clone = Arc::clone(&rssi_data);
+                            let ranging_keys_clone = Arc::clone(&ranging_keys);
+                            let identity_tags_clone = Arc::clone(&identity_tags);
 
-                                while let Some(event) = events.next().await {
-                                    match event {
-                                        DeviceEvent::PropertyChanged(DeviceProperty::Rssi(rssi)) => {
-                                            // RSSI changed
-                                            let mut data = rssi_data_clone.lock().await;
-                                            let deque = data.entry(addr).or_insert_with(VecDeque::new);
+                            let rssi = device.rssi().await?.unwrap_or(0);
+                            let mut tx_power = device.tx_power().await?;
+                            info!("Device added: {} (RSSI: {})", addr, rssi);
+                            if identity != addr {
+                                info!("🕵️  {} is the private address of {}", addr, identity);
+                                current_addresses.lock().await.insert(identity, addr);
+                            }
 
-                                            // Remove readings older than MEDIAN_DURATION
-                                            remove_old_readings(deque);
+                            // A device cloning the address of a neighbor advertises another identity
+                            let mut manufacturer_data = device.manufacturer_data().await?;
+                            let expected_tag = identity_tags.lock().await.get(&identity).copied();
+                            let mut identity_ok = identity_matches(manufacturer_data.as_ref(), expected_tag);
+                            if !identity_ok {
+                                info!("🚫 {} does not advertise its registered identity, ignoring its RSSI", identity);
+                            }
 
-                                            // Add new reading with timestamp
-                                            deque.push_back(RssiReading {
-                                                rssi,
-                                                timestamp: Instant::now(),
-                                            });
+                            // Neighbors with a server key are only measured while ranging
+                            let ranged = ranging_keys.lock().await.contains_key(&identity);
+                            if rssi != 0 && !ranged && identity_ok {
+                                record_reading(&rssi_data_clone, identity, rssi, tx_power).await;
+                            }
 
-                                            println!("RSSI update for {}: {} (queue size: {})", addr, rssi, deque.len());
+                            let task = tokio::spawn(async move {
+                                if let Ok(events) = device.events().await {
+                                    tokio::pin!(events);
+
+                                    while let Some(event) = events.next().await {
+                                        match event {
+                                            DeviceEvent::PropertyChanged(DeviceProperty::ManufacturerData(data)) => {
+                                                manufacturer_data = Some(data);
+                                            }
+                                            DeviceEvent::PropertyChanged(DeviceProperty::TxPower(power)) => {
+                                                tx_power = Some(power);
+                                            }
+                                            DeviceEvent::PropertyChanged(DeviceProperty::Rssi(rssi)) => {
+                                                // Passively observed advertisements can be cloned
+                                                if ranging_keys_clone.lock().await.contains_key(&identity) {
+                                                    continue;
+                                                }
+
+                                                // The tags are known once the chain is connected
+                                                let expected_tag = identity_tags_clone.lock().await.get(&identity).copied();
+                                                let matches = identity_matches(manufacturer_data.as_ref(), expected_tag);
+                                                if matches != identity_ok {
+                                                    identity_ok = matches;
+                                                    if matches {
+                                                        info!("🔎 {} advertises its registered identity again", identity);
+                                                    } else {
+                                                        info!("🚫 {} does not advertise its registered identity, ignoring its RSSI", identity);
+                                                    }
+                                                }
+                                                if !identity_ok {
+                                                    continue;
+                                                }
+
+                                                // RSSI changed
+                                                let len = record_reading(&rssi_data_clone, identity, rssi, tx_power).await;
+                                                debug!("RSSI update for {}: {} (queue size: {})", identity, rssi, len);
+                                            }
+                                            _ => {}
                                         }
-                                        _ => {}
                                     }
                                 }
-                            }
-                        });
+                            });
 
-                        device_tasks.insert(addr, task);
-                    }
-                    AdapterEvent::DeviceRemoved(addr) => {
-                        // Clean up the task for this device
-                        if let Some(task) = device_tasks.remove(&addr) {
-                            task.abort();
-                            println!("Device removed, task aborted: {}", addr);
+                            device_tasks.insert(addr, task);
                         }
+                        AdapterEvent::DeviceRemoved(addr) => {
+                            // Clean up the task for this device
+                            if let Some(task) = device_tasks.remove(&addr) {
+                                task.abort();
+                                info!("Device removed, task aborted: {}", addr);
+                            }
 
-                        // Also remove RSSI data
-                        rssi_data.lock().await.remove(&addr);
+                            // The readings of a private address continue under the next one of its neighbor
+                            current_addresses.lock().await.retain(|_, current| *current != addr);
+                            if !is_resolvable_private(addr) {
+                                // Also remove RSSI data
+                                rssi_data.lock().await.remove(&addr);
+                            }
+                        }
+                        _ => {}
                     }
-                    _ => {}
                 }
-            }
-            _ = time::sleep(Duration::from_millis(100)) => {
-                // Just continue scanning
+                _ = time::sleep(Duration::from_millis(100)) => {
+                    // Just continue scanning
+                }
             }
         }
+
+        info!("Pausing device scanning for {:?}...", config.scan_pause);
+        time::sleep(config.scan_pause).await;
     }
 }
 
+/// Scan for neighbors and advertise our service until an error occurs
+///
+/// With a `signer` the ranging characteristic is served, and neighbors with a key in
+/// `ranging_keys` are challenged every `RANGING_INTERVAL` instead of measured passively.
+/// Neighbors with a key in `resolving_keys` are recognized from their resolvable private
+/// addresses.
 pub async fn start_continuous_scan(
     adapter: Adapter,
     rssi_data: RssiData,
     neighbor_addresses: NeighborAddresses,
+    ranging_keys: RangingKeys,
+    identity_tags: IdentityTags,
+    resolving_keys: ResolvingKeys,
+    signer: Option<Keypair>,
 ) -> Result<(), Box<dyn Error>> {
-    println!("Starting continuous Bluetooth operations...");
+    info!("Starting continuous Bluetooth operations...");
 
     // Get the Bluetooth adapter
-    println!(
+    info!(
         "Using adapter: {} ({})",
         adapter.address().await?,
         adapter.name()
@@ -263,41 +810,103 @@ pub async fn start_continuous_scan(
     // Set discoverable timeout to 0 (never timeout).
     adapter.set_discoverable_timeout(0).await?;
 
+    // Answer the ranging challenges of neighbors, kept alive as long as the scan runs
+    let ranging_handle = match signer {
+        Some(signer) => {
+            let handle = serve_ranging(&adapter, signer, Arc::clone(&resolving_keys)).await?;
+            info!("Serving ranging challenges");
+            Some(handle)
+        }
+        None => None,
+    };
+    let connectable = ranging_handle.is_some();
+
     // Clone adapter for the advertising task
     let adapter_clone = adapter.clone();
+    let our_address = adapter.address().await?;
+    let identity_tags_clone = Arc::clone(&identity_tags);
 
     // Spawn advertising task
     tokio::spawn(async move {
-        if let Err(e) = start_advertising(&adapter_clone).await {
-            eprintln!("Advertising error: {}", e);
+        if let Err(e) = start_advertising(
+            &adapter_clone,
+            connectable,
+            our_address,
+            identity_tags_clone,
+        )
+        .await
+        {
+            error!("Advertising error: {}", e);
         }
     });
 
+    // Addresses neighbors advertise from, shared with ranging to connect to them
+    let current_addresses: CurrentAddresses = Arc::new(Mutex::new(HashMap::new()));
+
+    // Spawn ranging task
+    tokio::spawn(start_ranging(
+        adapter.clone(),
+        Arc::clone(&rssi_data),
+        Arc::clone(&neighbor_addresses),
+        Arc::clone(&ranging_keys),
+        Arc::clone(&current_addresses),
+    ));
+
+    // Spawn polling task, idle until a poll interval is configured
+    tokio::spawn(start_polling(
+        adapter.clone(),
+        Arc::clone(&rssi_data),
+        Arc::clone(&neighbor_addresses),
+        Arc::clone(&ranging_keys),
+        Arc::clone(&identity_tags),
+        Arc::clone(&current_addresses),
+    ));
+
     // Run device scanning (this blocks indefinitely)
-    scan_devices(&adapter, rssi_data, neighbor_addresses).await
+    scan_devices(
+        &adapter,
+        rssi_data,
+        neighbor_addresses,
+        ranging_keys,
+        identity_tags,
+        resolving_keys,
+        current_addresses,
+    )
+    .await
 }
 
-pub async fn current_rssi(rssi_data: RssiData) -> Result<RssiResponse, Box<dyn Error>> {
-    println!("Calculating median RSSI from current data...");
+/// Smooth the recent readings of every device with `filter`
+pub async fn current_rssi(
+    rssi_data: RssiData,
+    filter: &dyn RssiFilter,
+) -> Result<RssiResponse, Box<dyn Error>> {
+    debug!("Calculating {} RSSI from current data...", filter.name());
 
     let mut rssi_data_locked = rssi_data.lock().await;
 
-    // Build response with median RSSI values
+    // Build response with smoothed RSSI values
     let mut devices = Vec::new();
     for (address, rssi_deque) in rssi_data_locked.iter_mut() {
-        // Remove old readings before calculating median
+        // Remove old readings before smoothing
         remove_old_readings(rssi_deque);
 
-        if !rssi_deque.is_empty() {
-            let mut rssi_values: Vec<i16> = rssi_deque.iter().map(|r| r.rssi).collect();
-            if let Some(median_rssi) = calculate_median(&mut rssi_values) {
-                devices.push(DeviceRssi {
-                    address: address.0,
-                    rssi: median_rssi,
-                });
-            }
-        }
+        let (Some(rssi), Some(newest)) = (
+            filter.smooth(rssi_deque.make_contiguous()),
+            rssi_deque.back(),
+        ) else {
+            continue;
+        };
+        devices.push(DeviceRssi {
+            address: address.0,
+            rssi,
+            tx_power: rssi_deque.iter().rev().find_map(|reading| reading.tx_power),
+            timestamp: newest.unix_millis,
+        });
     }
 
-    Ok(RssiResponse { devices })
+    Ok(RssiResponse {
+        devices,
+        timestamp: unix_millis(),
+        sequence: None,
+    })
 }
@@ -0,0 +1,297 @@
+use bluer::Address;
+use codec::{Decode, Encode};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use subxt::backend::rpc::reconnecting_rpc_client::{ExponentialBackoff, RpcClient};
+use subxt::{OnlineClient, SubstrateConfig};
+use tokio::time;
+
+use crate::backlog::SharedBacklog;
+use crate::bluetooth::{IdentityTags, NeighborAddresses, RangingKeys, RssiData};
+use crate::clock::start_clock_check;
+use crate::health::{set_chain_connected, ChainStatus};
+use crate::neighbor::{
+    degrees, fetch_max_distance, fetch_node_config, recalculate_neighbors, registered_location,
+    set_registered_location, start_identity_refresher, start_neighbor_event_listener,
+    start_server_key_refresher, SharedMaxDistance,
+};
+use crate::peer::{start_peer_checker, PeerCheck, PeerLinks};
+use crate::privacy::ResolvingKeys;
+use crate::submitter::{start_submitter, Submitter};
+use crate::uwb::RangeData;
+use crate::wifi::WifiAddresses;
+
+/// First wait before reconnecting to the Substrate node, doubled after every failure
+pub const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Longest wait between two reconnection attempts
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const CACHE_INTERVAL: Duration = Duration::from_secs(60); // 1 minute
+const DEFAULT_CACHE_PATH: &str = "chain_cache.scale";
+
+/// Connection to the Substrate node, set once it is first connected
+pub type SharedChain = Arc<OnceLock<OnlineClient<SubstrateConfig>>>;
+
+/// Double the wait before the next attempt, up to MAX_BACKOFF
+pub fn next_backoff(backoff: Duration) -> Duration {
+    (backoff * 2).min(MAX_BACKOFF)
+}
+
+/// Connect to the Substrate node, retrying with exponential backoff until it answers
+///
+/// The client reconnects by itself when the connection drops later on. Subscriptions then end
+/// and must be made again.
+async fn connect(url: &str) -> OnlineClient<SubstrateConfig> {
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        let client = async {
+            let rpc = RpcClient::builder()
+                .retry_policy(
+                    ExponentialBackoff::from_millis(INITIAL_BACKOFF.as_millis() as u64)
+                        .max_delay(MAX_BACKOFF),
+                )
+                .build(url.to_string())
+                .await
+                .map_err(|e| e.to_string())?;
+            OnlineClient::<SubstrateConfig>::from_rpc_client(rpc)
+                .await
+                .map_err(|e| e.to_string())
+        };
+
+        match client.await {
+            Ok(api) => return api,
+            Err(e) => {
+                warn!(
+                    "⚠️  Failed to connect to the Substrate node: {}, retrying in {}s",
+                    e,
+                    backoff.as_secs()
+                );
+                time::sleep(backoff).await;
+                backoff = next_backoff(backoff);
+            }
+        }
+    }
+}
+
+/// What we last learned from the chain, kept on disk so scanning continues with the same
+/// neighbors while the Substrate node is unreachable
+#[derive(Encode, Decode, Default, PartialEq)]
+pub struct ChainCache {
+    /// Neighbors in range, without the configured overrides
+    pub neighbors: Vec<[u8; 6]>,
+    /// `0` if never fetched
+    pub max_distance: u32,
+    pub registered_location: Option<(f64, f64)>,
+}
+
+/// File of the chain cache, given by `CHAIN_CACHE_PATH`
+pub fn cache_path() -> PathBuf {
+    std::env::var("CHAIN_CACHE_PATH")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_CACHE_PATH.to_string())
+        .into()
+}
+
+impl ChainCache {
+    /// Load the cache, empty if it does not exist
+    pub fn load(path: &PathBuf) -> Self {
+        let cache = std::fs::read(path)
+            .ok()
+            .and_then(|bytes| Self::decode(&mut &bytes[..]).ok())
+            .unwrap_or_default();
+
+        if !cache.neighbors.is_empty() {
+            info!(
+                "Loaded {} cached neighbor(s) from {}",
+                cache.neighbors.len(),
+                path.display()
+            );
+        }
+
+        cache
+    }
+}
+
+/// Write the neighbors, max distance and registered location to the cache every minute when
+/// they changed
+pub async fn start_cache_writer(
+    path: PathBuf,
+    neighbor_addresses: NeighborAddresses,
+    max_distance: SharedMaxDistance,
+) {
+    let mut written = ChainCache::load(&path);
+
+    loop {
+        time::sleep(CACHE_INTERVAL).await;
+
+        let overrides = crate::config::current().neighbor_overrides.clone();
+        let cache = ChainCache {
+            neighbors: neighbor_addresses
+                .lock()
+                .await
+                .difference(&overrides)
+                .map(|address| address.0)
+                .collect(),
+            max_distance: max_distance.load(Ordering::Relaxed),
+            registered_location: registered_location(),
+        };
+        if cache == written {
+            continue;
+        }
+
+        match std::fs::write(&path, cache.encode()) {
+            Ok(()) => written = cache,
+            Err(e) => warn!("⚠️  Failed to write the chain cache: {}", e),
+        }
+    }
+}
+
+/// Neighbors to start with: the cached ones and the configured overrides
+pub fn initial_neighbors(cache: &ChainCache) -> HashSet<Address> {
+    let mut neighbors: HashSet<Address> = cache.neighbors.iter().copied().map(Address).collect();
+    neighbors.extend(crate::config::current().neighbor_overrides.iter().copied());
+    neighbors
+}
+
+/// Everything that needs the chain, started once the Substrate node is connected
+pub struct ChainTasks {
+    /// Refresh the server keys of ranged neighbors
+    pub ranging_keys: Option<RangingKeys>,
+    /// Refresh the identity tags advertised by the registered nodes
+    pub identity_tags: IdentityTags,
+    /// Refresh the keys resolving the private addresses of the registered nodes
+    pub resolving_keys: ResolvingKeys,
+    /// Refresh the Wi-Fi BSSIDs the registered nodes are measured at
+    pub wifi_addresses: WifiAddresses,
+    /// Cross-check the links with our neighbors against their servers
+    pub peer_check: Option<PeerCheck>,
+    pub peer_links: PeerLinks,
+    /// Sign and submit the node's extrinsics ourselves
+    pub submitter: Option<Submitter>,
+    /// Measurements the submitter replays once the chain is reachable again
+    pub backlog: SharedBacklog,
+    pub rssi_data: RssiData,
+    pub range_data: RangeData,
+}
+
+/// Connect to the Substrate node in the background, then fetch our configuration, calculate
+/// the neighbors and start the tasks following the chain
+///
+/// Until then the server keeps scanning the cached neighbors.
+pub async fn start_chain_client(
+    url: String,
+    our_bluetooth_address: Address,
+    max_distance: SharedMaxDistance,
+    neighbor_addresses: NeighborAddresses,
+    chain_status: ChainStatus,
+    chain: SharedChain,
+    tasks: ChainTasks,
+) {
+    tokio::spawn(async move {
+        info!("Connecting to Substrate node at: {}", url);
+        let api = connect(&url).await;
+        info!("Connected to Substrate node successfully\n");
+        set_chain_connected(&chain_status, true);
+
+        // Get our node's configuration, falling back to the MaxDistance constant
+        let fetched_max_distance = match fetch_node_config(&api, our_bluetooth_address).await {
+            Ok(config) => {
+                set_registered_location(config.location.as_ref().map(degrees));
+                if let Some((latitude, longitude)) = registered_location() {
+                    info!("Registered location: {}, {}", latitude, longitude);
+                }
+                info!(
+                    "Server URL: {}",
+                    String::from_utf8_lossy(&config.server_url)
+                );
+                if config.location_server_url != config.server_url {
+                    info!(
+                        "Location server URL: {}",
+                        String::from_utf8_lossy(&config.location_server_url)
+                    );
+                }
+                info!(
+                    "Reference RSSI: {} dBm, path loss exponent: {}",
+                    config.reference_rssi,
+                    config.path_loss_exponent as f64 / 10.0
+                );
+                config.max_distance
+            }
+            Err(e) => {
+                warn!("⚠️  Failed to fetch node configuration: {}", e);
+                fetch_max_distance(&api)
+            }
+        };
+        max_distance.store(fetched_max_distance, Ordering::Relaxed);
+        info!(
+            "Max distance for neighbors: {} meters\n",
+            fetched_max_distance
+        );
+
+        // Replace the cached neighbors with the ones in range now
+        info!("Calculating neighbor list...");
+        recalculate_neighbors(
+            &api,
+            our_bluetooth_address,
+            fetched_max_distance,
+            &neighbor_addresses,
+        )
+        .await;
+
+        // Start listening for NodeRegistered events and auto-update neighbor list
+        start_neighbor_event_listener(
+            api.clone(),
+            our_bluetooth_address,
+            Arc::clone(&max_distance),
+            Arc::clone(&neighbor_addresses),
+            Arc::clone(&chain_status),
+        )
+        .await;
+
+        // Measure neighbors with a server key only while ranging them
+        if let Some(ranging_keys) = tasks.ranging_keys {
+            start_server_key_refresher(api.clone(), ranging_keys).await;
+        }
+
+        // Check that neighbors advertise the identity of their account, and advertise ours
+        start_identity_refresher(
+            api.clone(),
+            tasks.identity_tags,
+            tasks.resolving_keys,
+            tasks.wifi_addresses,
+        )
+        .await;
+
+        // Warn while our clock is too far off the chain's for our responses to be trusted
+        tokio::spawn(start_clock_check(api.clone()));
+
+        if let Some(peer_check) = tasks.peer_check {
+            tokio::spawn(start_peer_checker(
+                peer_check,
+                api.clone(),
+                our_bluetooth_address,
+                Arc::clone(&tasks.rssi_data),
+                Arc::clone(&neighbor_addresses),
+                tasks.peer_links,
+                tasks.submitter.clone(),
+            ));
+        }
+
+        if let Some(submitter) = tasks.submitter {
+            tokio::spawn(start_submitter(
+                submitter,
+                api.clone(),
+                our_bluetooth_address,
+                tasks.backlog,
+                tasks.rssi_data,
+                tasks.range_data,
+            ));
+        }
+
+        let _ = chain.set(api);
+    });
+}
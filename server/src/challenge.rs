@@ -0,0 +1,185 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use rand::RngCore;
+use sp_core::{ecdsa, ed25519, hashing::blake2_256, sr25519, Pair};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use subxt_signer::sr25519::Keypair;
+
+/// How long a node has to answer a challenge
+const CHALLENGE_TTL: Duration = Duration::from_secs(60);
+/// Challenges waiting for an answer before the oldest one is dropped
+const MAX_PENDING_CHALLENGES: usize = 1024;
+
+/// Prefix of the message signed by the offchain worker, see `util::challenge_message` in the
+/// pallet
+const CHALLENGE_PREFIX: &[u8] = b"proof-of-location/challenge:";
+
+/// Nodes allowed to query the server and the challenges they were sent
+///
+/// Requests without a valid signature are answered with `401 Unauthorized` and a fresh nonce in
+/// the `X-Challenge` header. The node repeats the request with the nonce and its signature in
+/// `X-Challenge` and `X-Signature`. sr25519, ed25519 and ecdsa keys are accepted, whichever
+/// `--location-key-scheme` the node uses.
+pub struct ChallengeAuth {
+    nodes: HashSet<[u8; 32]>,
+    pending: Mutex<HashMap<String, Instant>>,
+}
+
+pub type SharedChallengeAuth = Arc<ChallengeAuth>;
+
+impl ChallengeAuth {
+    /// Load the allowed nodes from `NODE_ACCOUNTS`, a comma-separated list of hex account IDs
+    ///
+    /// Requests are not challenged while it is not set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(accounts) = std::env::var("NODE_ACCOUNTS")
+            .ok()
+            .filter(|v| !v.is_empty())
+        else {
+            return Ok(None);
+        };
+
+        let nodes = accounts
+            .split(',')
+            .map(str::trim)
+            .filter(|account| !account.is_empty())
+            .map(|account| {
+                decode_hex::<32>(account)
+                    .ok_or_else(|| format!("Invalid account in NODE_ACCOUNTS: {}", account))
+            })
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        Ok(Some(Self {
+            nodes,
+            pending: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    /// Issue a new nonce, forgetting the ones that expired
+    ///
+    /// Anyone can ask for a challenge, so once `MAX_PENDING_CHALLENGES` are waiting the oldest
+    /// one is dropped for the new one.
+    fn issue(&self) -> String {
+        let mut nonce = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let nonce: String = nonce.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, issued| issued.elapsed() < CHALLENGE_TTL);
+        if pending.len() >= MAX_PENDING_CHALLENGES {
+            let oldest = pending
+                .iter()
+                .min_by_key(|(_, issued)| **issued)
+                .map(|(nonce, _)| nonce.clone());
+            if let Some(oldest) = oldest {
+                pending.remove(&oldest);
+            }
+        }
+        pending.insert(nonce.clone(), Instant::now());
+
+        nonce
+    }
+
+    /// Check the answer to a challenge, each nonce can only be used once
+    fn verify(&self, node_id: &str, nonce: &str, signature: &str) -> bool {
+        let issued = self.pending.lock().unwrap().remove(nonce);
+        if !issued.is_some_and(|issued| issued.elapsed() < CHALLENGE_TTL) {
+            return false;
+        }
+
+        let Some(account) = decode_hex::<32>(node_id) else {
+            return false;
+        };
+        if !self.nodes.contains(&account) {
+            return false;
+        }
+
+        let message = [CHALLENGE_PREFIX, nonce.as_bytes()].concat();
+        verify_signature(&account, signature, &message)
+    }
+}
+
+/// Check `signature` of `message` by the key of `account`
+///
+/// sr25519 and ed25519 accounts are the public key itself, an ecdsa account is the blake2 hash
+/// of the public key recovered from the signature.
+fn verify_signature(account: &[u8; 32], signature: &str, message: &[u8]) -> bool {
+    if let Some(signature) = decode_hex::<64>(signature) {
+        return sr25519::Pair::verify(
+            &sr25519::Signature::from_raw(signature),
+            message,
+            &sr25519::Public::from_raw(*account),
+        ) || ed25519::Pair::verify(
+            &ed25519::Signature::from_raw(signature),
+            message,
+            &ed25519::Public::from_raw(*account),
+        );
+    }
+
+    decode_hex::<65>(signature)
+        .and_then(|signature| ecdsa::Signature::from_raw(signature).recover(message))
+        .is_some_and(|public| blake2_256(public.as_ref()) == *account)
+}
+
+/// Reject requests that do not answer a challenge with the key of a node in `NODE_ACCOUNTS`
+pub async fn require_node_signature(
+    State(auth): State<SharedChallengeAuth>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+
+    let authorized = match (
+        header("X-Node-ID"),
+        header("X-Challenge"),
+        header("X-Signature"),
+    ) {
+        (Some(node_id), Some(nonce), Some(signature)) => auth.verify(node_id, nonce, signature),
+        _ => false,
+    };
+
+    if !authorized {
+        info!("🔒 Challenging request without a valid node signature");
+        return Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .header("X-Challenge", auth.issue())
+            .body(Body::from("Sign the challenge with the node's key"))
+            .unwrap();
+    }
+
+    next.run(req).await
+}
+
+/// Answer a challenge of another server with our node's key, the hex signature for the
+/// `X-Signature` header
+pub fn sign_challenge(keypair: &Keypair, nonce: &str) -> String {
+    let message = [CHALLENGE_PREFIX, nonce.as_bytes()].concat();
+    let signature: String = keypair
+        .sign(&message)
+        .0
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("0x{}", signature)
+}
+
+/// Decode a `0x`-prefixed or bare hex string of exactly `N` bytes
+pub fn decode_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() != N * 2 {
+        return None;
+    }
+
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(bytes)
+}
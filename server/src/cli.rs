@@ -0,0 +1,43 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::logging::LogLevel;
+
+/// Bluetooth RSSI scanner server of a proof-of-location node
+///
+/// Every option overrides the configuration file, which overrides the environment.
+#[derive(Parser, Debug, Default, Clone)]
+#[command(version, about)]
+pub struct Cli {
+    /// Configuration file [default: CONFIG_PATH or config.toml]
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// HTTP server listening port
+    #[arg(long)]
+    pub port: Option<u16>,
+
+    /// Bluetooth adapter to use, e.g. hci1
+    #[arg(long)]
+    pub adapter: Option<String>,
+
+    /// Our latitude in degrees
+    #[arg(long, allow_negative_numbers = true)]
+    pub latitude: Option<f64>,
+
+    /// Our longitude in degrees
+    #[arg(long, allow_negative_numbers = true)]
+    pub longitude: Option<f64>,
+
+    /// Substrate node WebSocket URL
+    #[arg(long, value_name = "URL")]
+    pub rpc_url: Option<String>,
+
+    /// How much the server prints
+    #[arg(long, value_enum)]
+    pub log_level: Option<LogLevel>,
+
+    /// Print the effective configuration and where each setting came from, then exit
+    #[arg(long)]
+    pub print_config: bool,
+}
@@ -0,0 +1,80 @@
+use subxt::{OnlineClient, SubstrateConfig};
+use tokio::time;
+
+use crate::bluetooth::unix_millis;
+use crate::chain::{next_backoff, INITIAL_BACKOFF};
+use crate::neighbor::substrate;
+
+const DEFAULT_MAX_CLOCK_SKEW: u64 = 30_000; // Matches the runtime constant
+
+/// Milliseconds our clock may deviate from the block time, the runtime's `MaxClockSkewMillis`
+fn fetch_max_clock_skew(api: &OnlineClient<SubstrateConfig>) -> u64 {
+    let query = substrate::constants()
+        .proof_of_location()
+        .max_clock_skew_millis();
+    api.constants().at(&query).unwrap_or(DEFAULT_MAX_CLOCK_SKEW)
+}
+
+/// Compare our clock with the timestamp of every new best block, warning while they are further
+/// apart than `MaxClockSkewMillis`
+///
+/// The offchain worker rejects responses by their timestamp, so a skewed clock makes our
+/// measurements look stale, or lets stale ones through.
+pub async fn start_clock_check(api: OnlineClient<SubstrateConfig>) {
+    let max_skew = fetch_max_clock_skew(&api);
+    let mut skewed = false;
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        let mut blocks_sub = match api.blocks().subscribe_best().await {
+            Ok(sub) => {
+                backoff = INITIAL_BACKOFF;
+                sub
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️  Failed to subscribe to blocks for the clock check: {}, retrying in {}s",
+                    e,
+                    backoff.as_secs()
+                );
+                time::sleep(backoff).await;
+                backoff = next_backoff(backoff);
+                continue;
+            }
+        };
+
+        while let Some(block_result) = blocks_sub.next().await {
+            let block = match block_result {
+                Ok(block) => block,
+                Err(e) => {
+                    warn!("⚠️  Block subscription for the clock check failed: {}", e);
+                    break;
+                }
+            };
+
+            let query = substrate::storage().timestamp().now();
+            let block_time = match block.storage().fetch(&query).await {
+                Ok(Some(block_time)) => block_time,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("⚠️  Failed to fetch the block timestamp: {}", e);
+                    continue;
+                }
+            };
+
+            let now = unix_millis();
+            let skew = now.abs_diff(block_time);
+            if skew > max_skew && !skewed {
+                warn!(
+                    "⏰ Our clock is {} ms {} the time of block #{}, responses may be rejected as stale",
+                    skew,
+                    if now > block_time { "ahead of" } else { "behind" },
+                    block.number()
+                );
+            } else if skew <= max_skew && skewed {
+                info!("⏰ Our clock is in line with the chain again");
+            }
+            skewed = skew > max_skew;
+        }
+    }
+}
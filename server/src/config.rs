@@ -0,0 +1,787 @@
+use axum_server::tls_rustls::RustlsConfig;
+use bluer::Address;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::watch;
+
+use crate::backend::Backends;
+use crate::bluetooth::ScanConfig;
+use crate::cli::Cli;
+use crate::filter::{build_filter, SharedFilter};
+use crate::history::HistoryConfig;
+use crate::logging::{set_level, LogLevel};
+use crate::rate_limit::RateLimitConfig;
+use crate::submitter::DEFAULT_SUBMIT_INTERVAL;
+use crate::uwb::UwbConfig;
+use crate::wifi::WifiConfig;
+
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+const DEFAULT_PORT: u16 = 3000;
+const DEFAULT_RPC_URL: &str = "ws://127.0.0.1:9944";
+/// How often the configuration file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Contents of `config.toml`, every value is optional
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    port: Option<u16>,
+    rpc_url: Option<String>,
+    adapter: Option<String>,
+    log_level: Option<String>,
+    backends: Option<String>,
+    location: LocationSection,
+    smoothing: SmoothingSection,
+    scan: ScanSection,
+    neighbors: NeighborsSection,
+    tls: TlsSection,
+    history: HistorySection,
+    submit: SubmitSection,
+    rate_limit: RateLimitSection,
+    wifi: WifiSection,
+    uwb: UwbSection,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct LocationSection {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct SmoothingSection {
+    filter: Option<String>,
+    ema_alpha: Option<f64>,
+    kalman_process_noise: Option<f64>,
+    kalman_measurement_noise: Option<f64>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct ScanSection {
+    sample_window_secs: Option<u64>,
+    max_queue_size: Option<usize>,
+    active_ms: Option<u64>,
+    pause_ms: Option<u64>,
+    service_uuid: Option<String>,
+    advertising_interval_ms: Option<u64>,
+    poll_interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct NeighborsSection {
+    addresses: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct TlsSection {
+    cert: Option<PathBuf>,
+    key: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct HistorySection {
+    path: Option<PathBuf>,
+    retention_days: Option<u64>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct SubmitSection {
+    interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct RateLimitSection {
+    per_minute: Option<u32>,
+    burst: Option<u32>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct WifiSection {
+    interface: Option<String>,
+    scan_interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+struct UwbSection {
+    device: Option<String>,
+    baud_rate: Option<u32>,
+}
+
+/// Where the value of a setting came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Cli,
+    File,
+    Env,
+    Default,
+}
+
+impl Source {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cli => "command line",
+            Self::File => "configuration file",
+            Self::Env => "environment",
+            Self::Default => "default",
+        }
+    }
+}
+
+/// Configuration of the server
+///
+/// Every value is taken from the command line, the configuration file, or its environment
+/// variable, in that order.
+#[derive(Clone)]
+pub struct ServerConfig {
+    /// HTTP server listening port, only applied at startup
+    pub port: u16,
+    /// Substrate node WebSocket URL, only applied at startup
+    pub rpc_url: String,
+    /// Bluetooth adapter to use, the default one if not set, only applied at startup
+    pub adapter: Option<String>,
+    /// How much the server prints
+    pub log_level: LogLevel,
+    /// Radios the neighbors are measured with, only applied at startup
+    pub backends: Backends,
+    /// Wi-Fi interface and scan interval of the `wifi` backend, only applied at startup
+    pub wifi: WifiConfig,
+    /// Serial port of the UWB module neighbors are ranged with, only applied at startup
+    pub uwb: UwbConfig,
+    /// Our location (latitude, longitude)
+    pub location: (f64, f64),
+    /// Strategy the readings of a device are smoothed with
+    pub filter: SharedFilter,
+    /// Scan and advertising parameters
+    pub scan: ScanConfig,
+    /// Neighbors monitored whatever the chain says
+    pub neighbor_overrides: HashSet<Address>,
+    /// PEM certificate chain and private key to serve HTTPS with, plain HTTP if not set
+    ///
+    /// Turning TLS on or off is only applied at startup, the files are read again on every
+    /// reload.
+    pub tls: Option<(PathBuf, PathBuf)>,
+    /// Where the RSSI history is kept and for how long, only applied at startup
+    pub history: HistoryConfig,
+    /// How often readings are submitted while the server submits them itself, only applied at
+    /// startup
+    pub submit_interval: Duration,
+    /// Limits of the requests of each client, only applied at startup
+    pub rate_limit: RateLimitConfig,
+    /// Where each setting came from, for `--print-config`
+    sources: Vec<(&'static str, Source)>,
+}
+
+static CONFIG: RwLock<Option<Arc<ServerConfig>>> = RwLock::new(None);
+
+/// Location pushed to `POST /location`, kept over the configured one until the server restarts
+static PUSHED_LOCATION: RwLock<Option<(f64, f64)>> = RwLock::new(None);
+
+/// Notifies the listeners of every configuration that was applied
+static UPDATES: OnceLock<watch::Sender<Arc<ServerConfig>>> = OnceLock::new();
+
+/// Command line the server was started with, layered over every reload
+static CLI: OnceLock<Cli> = OnceLock::new();
+
+/// Remember the command line, before the configuration is loaded
+pub fn set_cli(cli: Cli) {
+    let _ = CLI.set(cli);
+}
+
+fn cli() -> Cli {
+    CLI.get().cloned().unwrap_or_default()
+}
+
+/// Path of the configuration file, given by `--config` or `CONFIG_PATH`
+pub fn config_path() -> PathBuf {
+    cli().config.unwrap_or_else(|| {
+        std::env::var("CONFIG_PATH")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string())
+            .into()
+    })
+}
+
+/// Get the current configuration
+///
+/// Panics if it was not loaded with [`init`] yet.
+pub fn current() -> Arc<ServerConfig> {
+    CONFIG
+        .read()
+        .unwrap()
+        .clone()
+        .expect("Configuration is loaded at startup")
+}
+
+/// Load the configuration at startup
+pub fn init(path: &Path) -> Result<Arc<ServerConfig>, String> {
+    let config = Arc::new(ServerConfig::load(path)?);
+    set_level(config.log_level);
+    *CONFIG.write().unwrap() = Some(Arc::clone(&config));
+    Ok(config)
+}
+
+/// Make `config` the current configuration and notify the listeners
+fn apply(config: Arc<ServerConfig>) {
+    set_level(config.log_level);
+    *CONFIG.write().unwrap() = Some(Arc::clone(&config));
+    if let Some(sender) = UPDATES.get() {
+        let _ = sender.send(config);
+    }
+}
+
+/// Move our location at runtime, over the configured one
+///
+/// Kept across configuration reloads, a location set in the file or environment only applies
+/// again after a restart.
+pub fn push_location(location: (f64, f64)) {
+    *PUSHED_LOCATION.write().unwrap() = Some(location);
+
+    let mut config = (*current()).clone();
+    config.location = location;
+    apply(Arc::new(config));
+}
+
+impl ServerConfig {
+    /// Load the configuration file at `path`, environment variables and defaults only if it
+    /// does not exist
+    fn load(path: &Path) -> Result<Self, String> {
+        let file: FileConfig = match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| format!("Invalid configuration file {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => FileConfig::default(),
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read configuration file {}: {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+
+        let cli = cli();
+        let mut layers = Layers::default();
+        let file_log_level = file.log_level.map(|level| level.parse()).transpose()?;
+        let file_backends = file.backends.map(|backends| backends.parse()).transpose()?;
+
+        let defaults = ScanConfig::default();
+        let scan = ScanConfig {
+            sample_window: layers
+                .get(
+                    "scan.sample_window_secs",
+                    None,
+                    file.scan.sample_window_secs,
+                    "RSSI_WINDOW_SECS",
+                )?
+                .filter(|secs| *secs > 0)
+                .map_or(defaults.sample_window, Duration::from_secs),
+            max_queue_size: layers
+                .get(
+                    "scan.max_queue_size",
+                    None,
+                    file.scan.max_queue_size,
+                    "MAX_RSSI_QUEUE_SIZE",
+                )?
+                .unwrap_or(defaults.max_queue_size),
+            scan_active: layers
+                .get(
+                    "scan.active_ms",
+                    None,
+                    file.scan.active_ms,
+                    "SCAN_ACTIVE_MS",
+                )?
+                .filter(|ms| *ms > 0)
+                .map_or(defaults.scan_active, Duration::from_millis),
+            scan_pause: layers
+                .get("scan.pause_ms", None, file.scan.pause_ms, "SCAN_PAUSE_MS")?
+                .map_or(defaults.scan_pause, Duration::from_millis),
+            service_uuid: match layers.get(
+                "scan.service_uuid",
+                None,
+                file.scan.service_uuid,
+                "BLUETOOTH_SERVICE_UUID",
+            )? {
+                Some(uuid) => uuid
+                    .parse()
+                    .map_err(|e| format!("Invalid service UUID {}: {}", uuid, e))?,
+                None => defaults.service_uuid,
+            },
+            advertising_interval: layers
+                .get(
+                    "scan.advertising_interval_ms",
+                    None,
+                    file.scan.advertising_interval_ms,
+                    "ADVERTISING_INTERVAL_MS",
+                )?
+                .filter(|ms| *ms > 0)
+                .map(Duration::from_millis),
+            poll_interval: layers
+                .get(
+                    "scan.poll_interval_secs",
+                    None,
+                    file.scan.poll_interval_secs,
+                    "BLE_POLL_INTERVAL_SECS",
+                )?
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs),
+        };
+
+        let filter = build_filter(
+            layers
+                .get(
+                    "smoothing.filter",
+                    None,
+                    file.smoothing.filter,
+                    "RSSI_FILTER",
+                )?
+                .as_deref(),
+            layers.get(
+                "smoothing.ema_alpha",
+                None,
+                file.smoothing.ema_alpha,
+                "RSSI_EMA_ALPHA",
+            )?,
+            layers.get(
+                "smoothing.kalman_process_noise",
+                None,
+                file.smoothing.kalman_process_noise,
+                "RSSI_KALMAN_PROCESS_NOISE",
+            )?,
+            layers.get(
+                "smoothing.kalman_measurement_noise",
+                None,
+                file.smoothing.kalman_measurement_noise,
+                "RSSI_KALMAN_MEASUREMENT_NOISE",
+            )?,
+        )?;
+
+        // Neighbor addresses from the environment are kept for backwards compatibility/testing
+        let addresses = match file.neighbors.addresses {
+            Some(addresses) => {
+                layers.record("neighbors.addresses", Source::File);
+                addresses
+            }
+            None => {
+                let addresses: Vec<String> = std::env::var("BLUETOOTH_ADDRESSES")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                let source = if addresses.is_empty() {
+                    Source::Default
+                } else {
+                    Source::Env
+                };
+                layers.record("neighbors.addresses", source);
+                addresses
+            }
+        };
+        let neighbor_overrides = addresses
+            .iter()
+            .map(|address| {
+                address
+                    .parse()
+                    .map_err(|_| format!("Invalid neighbor address: {}", address))
+            })
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        let tls = match (
+            layers.get("tls.cert", None, file.tls.cert, "TLS_CERT")?,
+            layers.get("tls.key", None, file.tls.key, "TLS_KEY")?,
+        ) {
+            (Some(cert), Some(key)) => Some((cert, key)),
+            (None, None) => None,
+            _ => return Err("TLS needs both a certificate and a key".to_string()),
+        };
+
+        let history_defaults = HistoryConfig::default();
+        let history = HistoryConfig {
+            path: layers
+                .get("history.path", None, file.history.path, "RSSI_HISTORY_PATH")?
+                .unwrap_or(history_defaults.path),
+            retention_days: layers
+                .get(
+                    "history.retention_days",
+                    None,
+                    file.history.retention_days,
+                    "RSSI_HISTORY_DAYS",
+                )?
+                .unwrap_or(history_defaults.retention_days),
+        };
+
+        let wifi_defaults = WifiConfig::default();
+        let wifi = WifiConfig {
+            interface: layers
+                .get(
+                    "wifi.interface",
+                    None,
+                    file.wifi.interface,
+                    "WIFI_INTERFACE",
+                )?
+                .unwrap_or(wifi_defaults.interface),
+            scan_interval: layers
+                .get(
+                    "wifi.scan_interval_secs",
+                    None,
+                    file.wifi.scan_interval_secs,
+                    "WIFI_SCAN_INTERVAL_SECS",
+                )?
+                .filter(|secs| *secs > 0)
+                .map_or(wifi_defaults.scan_interval, Duration::from_secs),
+        };
+
+        let uwb = UwbConfig {
+            device: layers
+                .get("uwb.device", None, file.uwb.device, "UWB_DEVICE")?
+                .filter(|device: &String| !device.is_empty()),
+            baud_rate: layers
+                .get("uwb.baud_rate", None, file.uwb.baud_rate, "UWB_BAUD_RATE")?
+                .unwrap_or(UwbConfig::default().baud_rate),
+        };
+
+        let rate_limit_defaults = RateLimitConfig::default();
+        let rate_limit = RateLimitConfig {
+            per_minute: layers
+                .get(
+                    "rate_limit.per_minute",
+                    None,
+                    file.rate_limit.per_minute,
+                    "RATE_LIMIT_PER_MINUTE",
+                )?
+                .unwrap_or(rate_limit_defaults.per_minute),
+            burst: layers
+                .get(
+                    "rate_limit.burst",
+                    None,
+                    file.rate_limit.burst,
+                    "RATE_LIMIT_BURST",
+                )?
+                .unwrap_or(rate_limit_defaults.burst),
+        };
+
+        Ok(Self {
+            port: layers
+                .get("port", cli.port, file.port, "PORT")?
+                .unwrap_or(DEFAULT_PORT),
+            rpc_url: layers
+                .get("rpc_url", cli.rpc_url, file.rpc_url, "RPC_URL")?
+                .unwrap_or_else(|| DEFAULT_RPC_URL.to_string()),
+            adapter: layers.get("adapter", cli.adapter, file.adapter, "BLUETOOTH_ADAPTER")?,
+            log_level: layers
+                .get("log_level", cli.log_level, file_log_level, "LOG_LEVEL")?
+                .unwrap_or(LogLevel::Info),
+            backends: layers
+                .get("backends", None, file_backends, "RSSI_BACKENDS")?
+                .unwrap_or_default(),
+            wifi,
+            uwb,
+            location: (
+                layers
+                    .get(
+                        "location.latitude",
+                        cli.latitude,
+                        file.location.latitude,
+                        "LATITUDE",
+                    )?
+                    .unwrap_or(0.0),
+                layers
+                    .get(
+                        "location.longitude",
+                        cli.longitude,
+                        file.location.longitude,
+                        "LONGITUDE",
+                    )?
+                    .unwrap_or(0.0),
+            ),
+            filter,
+            scan,
+            neighbor_overrides,
+            tls,
+            history,
+            submit_interval: layers
+                .get(
+                    "submit.interval_secs",
+                    None,
+                    file.submit.interval_secs,
+                    "SUBMIT_INTERVAL_SECS",
+                )?
+                .filter(|secs| *secs > 0)
+                .map_or(DEFAULT_SUBMIT_INTERVAL, Duration::from_secs),
+            rate_limit,
+            sources: layers.sources,
+        })
+    }
+
+    /// Every setting, its effective value and where it came from, for `--print-config`
+    pub fn describe(&self) -> String {
+        let source = |setting: &str| {
+            self.sources
+                .iter()
+                .find(|(name, _)| *name == setting)
+                .map_or(Source::Default, |(_, source)| *source)
+                .name()
+        };
+        let (cert, key) = match &self.tls {
+            Some((cert, key)) => (cert.display().to_string(), key.display().to_string()),
+            None => ("none".to_string(), "none".to_string()),
+        };
+        let mut addresses: Vec<String> = self
+            .neighbor_overrides
+            .iter()
+            .map(|address| address.to_string())
+            .collect();
+        addresses.sort();
+
+        let settings = [
+            ("port", self.port.to_string()),
+            ("rpc_url", self.rpc_url.clone()),
+            (
+                "adapter",
+                self.adapter
+                    .clone()
+                    .unwrap_or_else(|| "default".to_string()),
+            ),
+            ("log_level", self.log_level.name().to_string()),
+            ("backends", self.backends.names()),
+            ("wifi.interface", self.wifi.interface.clone()),
+            (
+                "wifi.scan_interval_secs",
+                self.wifi.scan_interval.as_secs().to_string(),
+            ),
+            (
+                "uwb.device",
+                self.uwb
+                    .device
+                    .clone()
+                    .unwrap_or_else(|| "none".to_string()),
+            ),
+            ("uwb.baud_rate", self.uwb.baud_rate.to_string()),
+            ("location.latitude", self.location.0.to_string()),
+            ("location.longitude", self.location.1.to_string()),
+            ("smoothing.filter", self.filter.name().to_string()),
+            (
+                "scan.sample_window_secs",
+                self.scan.sample_window.as_secs().to_string(),
+            ),
+            ("scan.max_queue_size", self.scan.max_queue_size.to_string()),
+            (
+                "scan.active_ms",
+                self.scan.scan_active.as_millis().to_string(),
+            ),
+            (
+                "scan.pause_ms",
+                self.scan.scan_pause.as_millis().to_string(),
+            ),
+            ("scan.service_uuid", self.scan.service_uuid.to_string()),
+            (
+                "scan.advertising_interval_ms",
+                self.scan
+                    .advertising_interval
+                    .map_or("default".to_string(), |interval| {
+                        interval.as_millis().to_string()
+                    }),
+            ),
+            (
+                "scan.poll_interval_secs",
+                self.scan
+                    .poll_interval
+                    .map_or("off".to_string(), |interval| interval.as_secs().to_string()),
+            ),
+            ("neighbors.addresses", addresses.join(",")),
+            ("tls.cert", cert),
+            ("tls.key", key),
+            ("history.path", self.history.path.display().to_string()),
+            (
+                "history.retention_days",
+                self.history.retention_days.to_string(),
+            ),
+            (
+                "submit.interval_secs",
+                self.submit_interval.as_secs().to_string(),
+            ),
+            (
+                "rate_limit.per_minute",
+                self.rate_limit.per_minute.to_string(),
+            ),
+            ("rate_limit.burst", self.rate_limit.burst.to_string()),
+        ];
+
+        settings
+            .iter()
+            .map(|(setting, value)| format!("{} = {} ({})\n", setting, value, source(setting)))
+            .collect()
+    }
+
+    /// Names of the settings that changed from `old` but are only applied at startup
+    pub fn restart_required(&self, old: &ServerConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.port != old.port {
+            changed.push("port");
+        }
+        if self.rpc_url != old.rpc_url {
+            changed.push("rpc_url");
+        }
+        if self.adapter != old.adapter {
+            changed.push("adapter");
+        }
+        if self.backends != old.backends {
+            changed.push("backends");
+        }
+        if self.wifi != old.wifi {
+            changed.push("wifi");
+        }
+        if self.uwb != old.uwb {
+            changed.push("uwb");
+        }
+        if self.tls.is_some() != old.tls.is_some() {
+            changed.push("tls");
+        }
+        if self.scan.service_uuid != old.scan.service_uuid {
+            changed.push("scan.service_uuid");
+        }
+        if self.scan.advertising_interval != old.scan.advertising_interval {
+            changed.push("scan.advertising_interval_ms");
+        }
+        if self.history != old.history {
+            changed.push("history");
+        }
+        if self.submit_interval != old.submit_interval {
+            changed.push("submit.interval_secs");
+        }
+        if self.rate_limit != old.rate_limit {
+            changed.push("rate_limit");
+        }
+        changed
+    }
+}
+
+/// Settings loaded so far and where each came from
+#[derive(Default)]
+struct Layers {
+    sources: Vec<(&'static str, Source)>,
+}
+
+impl Layers {
+    /// Take the value from the command line, the configuration file, or parse it from the
+    /// environment variable `env`, in that order
+    fn get<T: FromStr>(
+        &mut self,
+        setting: &'static str,
+        cli: Option<T>,
+        file: Option<T>,
+        env: &str,
+    ) -> Result<Option<T>, String> {
+        if cli.is_some() {
+            self.record(setting, Source::Cli);
+            return Ok(cli);
+        }
+        if file.is_some() {
+            self.record(setting, Source::File);
+            return Ok(file);
+        }
+
+        match std::env::var(env).ok().filter(|v| !v.is_empty()) {
+            Some(value) => {
+                self.record(setting, Source::Env);
+                value
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("Invalid {}: {}", env, value))
+            }
+            None => {
+                self.record(setting, Source::Default);
+                Ok(None)
+            }
+        }
+    }
+
+    fn record(&mut self, setting: &'static str, source: Source) {
+        self.sources.push((setting, source));
+    }
+}
+
+/// Last modification time of the file, `None` if it does not exist
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reload the configuration on SIGHUP or when the file at `path` changes
+///
+/// The receiver is notified of every configuration that was applied. A configuration that
+/// fails to load is reported and the current one is kept.
+pub fn start_config_watcher(
+    path: PathBuf,
+) -> Result<watch::Receiver<Arc<ServerConfig>>, Box<dyn std::error::Error>> {
+    let mut hangup = signal(SignalKind::hangup())?;
+    let (sender, receiver) = watch::channel(current());
+    let _ = UPDATES.set(sender);
+
+    tokio::spawn(async move {
+        let mut last_modified = modified(&path);
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = hangup.recv() => info!("🔁 SIGHUP received, reloading configuration"),
+                _ = interval.tick() => {
+                    let now_modified = modified(&path);
+                    if now_modified == last_modified {
+                        continue;
+                    }
+                    last_modified = now_modified;
+                    info!("🔁 {} changed, reloading configuration", path.display());
+                }
+            }
+
+            match ServerConfig::load(&path) {
+                Ok(mut config) => {
+                    if let Some(location) = *PUSHED_LOCATION.read().unwrap() {
+                        config.location = location;
+                    }
+                    let changed = config.restart_required(&current());
+                    if !changed.is_empty() {
+                        info!("⚠️  Restart the server to apply: {}", changed.join(", "));
+                    }
+
+                    apply(Arc::new(config));
+                }
+                Err(e) => warn!("⚠️  Keeping the current configuration: {}", e),
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+/// Read the TLS certificate and key again on every configuration reload, so a renewed
+/// certificate is served after a SIGHUP
+pub async fn start_tls_reloader(
+    tls: RustlsConfig,
+    mut configs: watch::Receiver<Arc<ServerConfig>>,
+) {
+    while configs.changed().await.is_ok() {
+        let Some((cert, key)) = configs.borrow_and_update().tls.clone() else {
+            continue;
+        };
+        match tls.reload_from_pem_file(&cert, &key).await {
+            Ok(()) => info!("🔐 Reloaded TLS certificate {}", cert.display()),
+            Err(e) => warn!("⚠️  Keeping the current TLS certificate: {}", e),
+        }
+    }
+}
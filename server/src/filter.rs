@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use crate::bluetooth::RssiReading;
+
+/// Default smoothing factor of the exponential moving average
+const DEFAULT_EMA_ALPHA: f64 = 0.3;
+/// Default process noise of the Kalman filter, how much the RSSI drifts between readings
+const DEFAULT_KALMAN_PROCESS_NOISE: f64 = 0.5;
+/// Default measurement noise of the Kalman filter, the variance of a single reading
+const DEFAULT_KALMAN_MEASUREMENT_NOISE: f64 = 4.0;
+
+/// Strategy that turns the recent readings of a device into the single RSSI reported for it
+pub trait RssiFilter: Send + Sync {
+    /// Name printed at startup
+    fn name(&self) -> &'static str;
+
+    /// Smooth the readings, oldest first, into one value
+    ///
+    /// Returns `None` without readings.
+    fn smooth(&self, readings: &[RssiReading]) -> Option<i16>;
+}
+
+// Filter shared by the HTTP handlers and the backlog recorder
+pub type SharedFilter = Arc<dyn RssiFilter>;
+
+/// Median of the readings, robust against outliers but slow to follow a moving node
+pub struct Median;
+
+impl RssiFilter for Median {
+    fn name(&self) -> &'static str {
+        "median"
+    }
+
+    fn smooth(&self, readings: &[RssiReading]) -> Option<i16> {
+        let mut values: Vec<i16> = readings.iter().map(|r| r.rssi).collect();
+        if values.is_empty() {
+            return None;
+        }
+
+        values.sort_unstable();
+        let len = values.len();
+
+        if len % 2 == 0 {
+            Some((values[len / 2 - 1] + values[len / 2]) / 2)
+        } else {
+            Some(values[len / 2])
+        }
+    }
+}
+
+/// Exponential moving average, weighting every reading by `alpha` over the previous estimate
+pub struct Ema {
+    pub alpha: f64,
+}
+
+impl RssiFilter for Ema {
+    fn name(&self) -> &'static str {
+        "ema"
+    }
+
+    fn smooth(&self, readings: &[RssiReading]) -> Option<i16> {
+        let (first, rest) = readings.split_first()?;
+        let estimate = rest.iter().fold(first.rssi as f64, |estimate, reading| {
+            self.alpha * reading.rssi as f64 + (1.0 - self.alpha) * estimate
+        });
+        Some(estimate.round() as i16)
+    }
+}
+
+/// One-dimensional Kalman filter assuming a constant RSSI with some drift between readings
+pub struct Kalman {
+    pub process_noise: f64,
+    pub measurement_noise: f64,
+}
+
+impl RssiFilter for Kalman {
+    fn name(&self) -> &'static str {
+        "kalman"
+    }
+
+    fn smooth(&self, readings: &[RssiReading]) -> Option<i16> {
+        let (first, rest) = readings.split_first()?;
+        let mut estimate = first.rssi as f64;
+        let mut variance = self.measurement_noise;
+
+        for reading in rest {
+            // Predict: the RSSI may have drifted since the last reading
+            variance += self.process_noise;
+
+            // Update with the new reading
+            let gain = variance / (variance + self.measurement_noise);
+            estimate += gain * (reading.rssi as f64 - estimate);
+            variance *= 1.0 - gain;
+        }
+
+        Some(estimate.round() as i16)
+    }
+}
+
+/// Build the filter named `name` (`median`, `ema` or `kalman`), median by default
+///
+/// Parameters that are not given take their default. They must be positive, and the EMA factor
+/// at most 1.
+pub fn build_filter(
+    name: Option<&str>,
+    ema_alpha: Option<f64>,
+    process_noise: Option<f64>,
+    measurement_noise: Option<f64>,
+) -> Result<SharedFilter, String> {
+    let positive = |what: &str, value: Option<f64>, default: f64| -> Result<f64, String> {
+        match value {
+            Some(value) if value > 0.0 => Ok(value),
+            Some(value) => Err(format!("Invalid {}: {}", what, value)),
+            None => Ok(default),
+        }
+    };
+
+    match name {
+        None | Some("median") => Ok(Arc::new(Median)),
+        Some("ema") => {
+            let alpha = positive("EMA alpha", ema_alpha, DEFAULT_EMA_ALPHA)?;
+            if alpha > 1.0 {
+                return Err(format!("Invalid EMA alpha: {}", alpha));
+            }
+            Ok(Arc::new(Ema { alpha }))
+        }
+        Some("kalman") => Ok(Arc::new(Kalman {
+            process_noise: positive(
+                "Kalman process noise",
+                process_noise,
+                DEFAULT_KALMAN_PROCESS_NOISE,
+            )?,
+            measurement_noise: positive(
+                "Kalman measurement noise",
+                measurement_noise,
+                DEFAULT_KALMAN_MEASUREMENT_NOISE,
+            )?,
+        })),
+        Some(other) => Err(format!("Unknown RSSI filter: {}", other)),
+    }
+}
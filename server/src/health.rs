@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::bluetooth::RssiData;
+
+// Whether the block subscription to the Substrate node is alive
+pub type ChainStatus = Arc<AtomicBool>;
+
+/// State of the server's subsystems, served on `/health`
+pub struct HealthReport {
+    /// The Bluetooth adapter is powered and the Wi-Fi interface is up, for the backends in use
+    pub radios_up: bool,
+    /// Time since the newest RSSI reading of any neighbor
+    pub last_scan: Option<Duration>,
+    pub chain_connected: bool,
+    pub neighbor_count: usize,
+    /// Our location, `None` while it is not configured
+    pub location: Option<(f64, f64)>,
+}
+
+impl HealthReport {
+    /// Whether the server can serve measurements: the radios are up and our location is known
+    ///
+    /// A lost chain connection only stops neighbor updates, and without neighbors in range
+    /// there is nothing to scan, so neither makes the server unready.
+    pub fn is_ready(&self) -> bool {
+        self.radios_up && self.location.is_some()
+    }
+
+    /// Human-readable summary of the subsystems
+    pub fn summary(&self) -> String {
+        let last_scan = self
+            .last_scan
+            .map_or("never".to_string(), |ago| format!("{}s ago", ago.as_secs()));
+        let location = self
+            .location
+            .map_or("not configured".to_string(), |(latitude, longitude)| {
+                format!("{}, {}", latitude, longitude)
+            });
+
+        format!(
+            "Ready: {}\nRadios up: {}\nLast scan: {}\nSubstrate connected: {}\nNeighbors: {}\nLocation: {}\n",
+            self.is_ready(),
+            self.radios_up,
+            last_scan,
+            self.chain_connected,
+            self.neighbor_count,
+            location
+        )
+    }
+}
+
+/// Time since the newest RSSI reading of any device
+pub async fn last_scan(rssi_data: &RssiData) -> Option<Duration> {
+    rssi_data
+        .lock()
+        .await
+        .values()
+        .filter_map(|readings| readings.back())
+        .map(|reading| reading.timestamp.elapsed())
+        .min()
+}
+
+/// Mark the chain connection as up or down
+pub fn set_chain_connected(status: &ChainStatus, connected: bool) {
+    status.store(connected, Ordering::Relaxed);
+}
+
+/// Whether the chain connection is up
+pub fn chain_connected(status: &ChainStatus) -> bool {
+    status.load(Ordering::Relaxed)
+}
@@ -0,0 +1,156 @@
+use bluer::Address;
+use codec::{Decode, Encode};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tokio::time;
+
+use crate::bluetooth::{unix_millis, RssiSample};
+
+const DEFAULT_HISTORY_PATH: &str = "rssi_history";
+const DEFAULT_RETENTION_DAYS: u64 = 30;
+/// Most samples returned by a single query, later ones are fetched with a later `from`
+pub const MAX_HISTORY_RESULTS: usize = 10_000;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600); // 1 hour
+
+/// Where the RSSI history is kept and for how long, the `[history]` section of the
+/// configuration, only applied at startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryConfig {
+    /// Directory of the database
+    pub path: PathBuf,
+    /// How many days samples are kept
+    pub retention_days: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            path: DEFAULT_HISTORY_PATH.into(),
+            retention_days: DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+/// A raw RSSI reading kept in the history
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct HistorySample {
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+    pub rssi: i16,
+}
+
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct HistoryResponse {
+    pub address: [u8; 6],
+    /// Oldest first, at most `MAX_HISTORY_RESULTS`
+    pub samples: Vec<HistorySample>,
+}
+
+/// Every raw RSSI reading, kept on disk so it can be compared with what landed on chain
+///
+/// Keys are the neighbor's address followed by the big-endian timestamp and a sequence number,
+/// so the samples of a neighbor are stored in time order and samples of the same millisecond
+/// are all kept.
+#[derive(Clone)]
+pub struct History {
+    db: sled::Db,
+    retention: Duration,
+}
+
+fn key(address: Address, timestamp: u64, sequence: u64) -> [u8; 22] {
+    let mut key = [0u8; 22];
+    key[..6].copy_from_slice(&address.0);
+    key[6..14].copy_from_slice(&timestamp.to_be_bytes());
+    key[14..].copy_from_slice(&sequence.to_be_bytes());
+    key
+}
+
+impl History {
+    /// Open the database at the configured path, keeping samples for the configured days
+    pub fn open(config: &HistoryConfig) -> Result<Self, String> {
+        let path = config.path.display();
+        let db = sled::open(&config.path)
+            .map_err(|e| format!("Failed to open RSSI history {}: {}", path, e))?;
+        info!(
+            "Keeping the RSSI history of {} day(s) in {}",
+            config.retention_days, path
+        );
+
+        Ok(Self {
+            db,
+            retention: Duration::from_secs(config.retention_days * 24 * 3600),
+        })
+    }
+
+    fn record(&self, sample: &RssiSample) -> sled::Result<()> {
+        let sequence = self.db.generate_id()?;
+        self.db.insert(
+            key(sample.address, sample.timestamp, sequence),
+            &sample.rssi.to_le_bytes()[..],
+        )?;
+        Ok(())
+    }
+
+    /// Samples of `neighbor` between `from` and `to` (inclusive, Unix milliseconds)
+    pub fn query(&self, neighbor: Address, from: u64, to: u64) -> sled::Result<Vec<HistorySample>> {
+        // Keys written without a sequence number are a prefix of the ones with it, so the
+        // bounds cover both
+        let start = &key(neighbor, from, 0)[..14];
+        let end = key(neighbor, to, u64::MAX);
+
+        let mut samples = Vec::new();
+        for entry in self.db.range(start..=&end[..]).take(MAX_HISTORY_RESULTS) {
+            let (key, value) = entry?;
+            let (Ok(timestamp), Ok(rssi)) = (key[6..14].try_into(), value[..].try_into()) else {
+                continue;
+            };
+            samples.push(HistorySample {
+                timestamp: u64::from_be_bytes(timestamp),
+                rssi: i16::from_le_bytes(rssi),
+            });
+        }
+        Ok(samples)
+    }
+
+    /// Remove the samples older than the retention period
+    fn prune(&self) -> sled::Result<usize> {
+        let cutoff = unix_millis().saturating_sub(self.retention.as_millis() as u64);
+        let mut removed = 0;
+        for entry in self.db.iter() {
+            let (key, _) = entry?;
+            let timestamp = key[6..14].try_into().map(u64::from_be_bytes).unwrap_or(0);
+            if timestamp < cutoff {
+                self.db.remove(key)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Record every RSSI reading into the history, pruning old samples every hour
+pub async fn start_history_recorder(history: History, mut samples: Receiver<RssiSample>) {
+    let mut prune_interval = time::interval(PRUNE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            sample = samples.recv() => match sample {
+                Ok(sample) => {
+                    if let Err(e) = history.record(&sample) {
+                        warn!("⚠️  Failed to record RSSI history: {}", e);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("⚠️  RSSI history missed {} reading(s)", skipped);
+                }
+                Err(RecvError::Closed) => return,
+            },
+            _ = prune_interval.tick() => match history.prune() {
+                Ok(0) => {}
+                Ok(removed) => info!("🧹 Pruned {} old RSSI history sample(s)", removed),
+                Err(e) => warn!("⚠️  Failed to prune RSSI history: {}", e),
+            },
+        }
+    }
+}
@@ -1,30 +1,80 @@
+#[macro_use]
+mod logging;
+
+mod backend;
+mod backlog;
 mod bluetooth;
+mod chain;
+mod challenge;
+mod cli;
+mod clock;
+mod config;
+mod filter;
+mod health;
+mod history;
 mod neighbor;
+mod peer;
+mod privacy;
+mod rate_limit;
+mod signing;
+mod status;
+mod submitter;
+mod uwb;
+mod wifi;
 
 use axum::{
-    body::Body,
-    extract::{Request, State},
+    body::{Body, Bytes},
+    extract::{Query, Request, State},
     http::{header, StatusCode},
-    response::{IntoResponse, Response},
-    routing::get,
-    Router,
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Json, Router,
 };
+use axum_server::tls_rustls::RustlsConfig;
 use bluer::{Adapter, Session};
 use codec::{Decode, Encode};
+use futures::stream::Stream;
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast::error::RecvError, Mutex};
 
+use backend::RangingBackend;
+use backlog::{start_backlog_recorder, Backlog, BacklogResponse, SharedBacklog};
 use bluetooth::{
-    bluetooth_address, current_rssi, init_neighbor_addresses_from_env, start_continuous_scan,
-    NeighborAddresses, RssiData,
+    bluetooth_address, current_rssi, ranging_enabled, start_continuous_scan, subscribe_samples,
+    unix_millis, IdentityTags, NeighborAddresses, RangingKeys, RssiData, RssiSample,
+};
+use chain::{
+    initial_neighbors, start_cache_writer, start_chain_client, ChainCache, ChainTasks, SharedChain,
 };
+use challenge::{require_node_signature, ChallengeAuth};
+use clap::Parser;
+use cli::Cli;
+use config::{config_path, start_config_watcher, start_tls_reloader};
+use health::{chain_connected, last_scan, ChainStatus, HealthReport};
+use history::{start_history_recorder, History, HistoryResponse};
 use neighbor::{
-    calculate_neighbors, fetch_max_distance, get_our_location, start_neighbor_event_listener,
+    describe_neighbors, get_our_location, set_registered_location, start_config_listener,
+    SharedMaxDistance,
 };
-use subxt::{OnlineClient, SubstrateConfig};
+use peer::{PeerCheck, PeerLinks};
+use privacy::{irk_hex, local_irk, ResolvingKeys};
+use rate_limit::{limit_rate, RateLimiter};
+use signing::{public_key_hex, sign_response, signer_from_env};
+use status::{NodeStatus, SharedStatus, StatusReport};
+use submitter::Submitter;
+use subxt_signer::sr25519::Keypair;
+use uwb::{current_ranges, start_ranging, RangeData, SerialRanging};
+use wifi::{interface_address, interface_up, start_wifi_scan, WifiAddresses, WifiScanner};
 
 #[derive(Encode, Decode, Debug, Clone)]
 struct Location {
@@ -36,12 +86,82 @@ struct Location {
 struct LocationResponse {
     address: [u8; 6],
     location: Location,
+    timestamp: u64,
+    sequence: Option<u64>,
 }
 
 #[derive(Clone)]
 struct AppState {
-    adapter: Adapter,
+    /// Bluetooth adapter, `None` without the `ble` backend
+    adapter: Option<Adapter>,
+    /// Wi-Fi interface, `None` without the `wifi` backend
+    wifi_interface: Option<String>,
+    /// Address our node is registered with
+    our_address: bluer::Address,
     rssi_data: RssiData,
+    range_data: RangeData,
+    backlog: SharedBacklog,
+    status: SharedStatus,
+    /// Key the `/rssi` and `/location` responses are signed with, if configured
+    signer: Option<Keypair>,
+    neighbor_addresses: NeighborAddresses,
+    wifi_addresses: WifiAddresses,
+    peer_links: PeerLinks,
+    chain_status: ChainStatus,
+    /// Connection to the Substrate node, unset until it is first connected
+    chain: SharedChain,
+    /// Submits location updates on chain, if a key is configured
+    submitter: Option<Submitter>,
+    history: History,
+}
+
+/// Body of `POST /location`, in degrees
+#[derive(serde::Deserialize)]
+struct LocationUpdate {
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Parameters of `DELETE /rssi/backlog`
+#[derive(serde::Deserialize)]
+struct BacklogAck {
+    /// Unix timestamp in seconds of the newest entry to remove
+    up_to: u64,
+}
+
+/// Parameters of `/rssi/history`
+#[derive(serde::Deserialize)]
+struct HistoryQuery {
+    /// Bluetooth address of the neighbor
+    neighbor: String,
+    /// Unix time in milliseconds, the beginning of time if not set
+    from: Option<u64>,
+    /// Unix time in milliseconds, now if not set
+    to: Option<u64>,
+}
+
+/// Reject requests that do not carry the bearer token configured in `AUTH_TOKEN`
+async fn require_auth_token(
+    State(token): State<Arc<String>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|v| v == token.as_str());
+
+    if !authorized {
+        info!("🔒 Rejected request without a valid auth token");
+        return Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::from("Missing or invalid auth token"))
+            .unwrap();
+    }
+
+    next.run(req).await
 }
 
 async fn scan_rssi(State(state): State<AppState>, req: Request) -> impl IntoResponse {
@@ -52,12 +172,15 @@ async fn scan_rssi(State(state): State<AppState>, req: Request) -> impl IntoResp
         .and_then(|v| v.to_str().ok())
         .unwrap_or("unknown");
 
-    println!("📡 RSSI request from node: {}", node_id);
+    info!("📡 RSSI request from node: {}", node_id);
+
+    // The offchain worker is reachable again, stop buffering measurements
+    state.backlog.lock().await.mark_request();
 
-    match current_rssi(state.rssi_data).await {
+    match current_rssi(state.rssi_data, config::current().filter.as_ref()).await {
         Ok(response) => {
             // Encode the response using SCALE codec
-            let encoded = response.encode();
+            let encoded = sign_response(state.signer.as_ref(), response.encode());
             Response::builder()
                 .status(StatusCode::OK)
                 .header(header::CONTENT_TYPE, "application/octet-stream")
@@ -74,6 +197,138 @@ async fn scan_rssi(State(state): State<AppState>, req: Request) -> impl IntoResp
     }
 }
 
+async fn get_range(State(state): State<AppState>, req: Request) -> impl IntoResponse {
+    // Extract and log the Node ID from the X-Node-ID header
+    let node_id = req
+        .headers()
+        .get("X-Node-ID")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown");
+
+    info!("📏 Range request from node: {}", node_id);
+
+    // Encode the response using SCALE codec
+    let response = current_ranges(&state.range_data).await;
+    let encoded = sign_response(state.signer.as_ref(), response.encode());
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .body(Body::from(encoded))
+        .unwrap()
+}
+
+/// Server-sent event of a single RSSI reading
+fn sample_event(sample: &RssiSample) -> Event {
+    Event::default().event("rssi").data(format!(
+        r#"{{"address":"{}","rssi":{},"timestamp":{}}}"#,
+        sample.address, sample.rssi, sample.timestamp
+    ))
+}
+
+/// Push every RSSI reading to the client as it arrives, as server-sent events
+///
+/// A client too slow to keep up receives a `lagged` event with the number of readings it missed.
+async fn stream_rssi() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    info!("📺 RSSI stream opened");
+
+    let stream = futures::stream::unfold(subscribe_samples(), |mut receiver| async move {
+        let event = match receiver.recv().await {
+            Ok(sample) => sample_event(&sample),
+            Err(RecvError::Lagged(skipped)) => {
+                Event::default().event("lagged").data(skipped.to_string())
+            }
+            Err(RecvError::Closed) => return None,
+        };
+        Some((Ok(event), receiver))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn get_rssi_backlog(State(state): State<AppState>) -> impl IntoResponse {
+    info!("📦 RSSI backlog request");
+
+    // Hand over a copy of the buffered measurements, they are only removed once acknowledged
+    let entries = state.backlog.lock().await.entries().to_vec();
+    info!("Returning {} buffered measurement(s)", entries.len());
+
+    // Encode the response using SCALE codec
+    let encoded = BacklogResponse { entries }.encode();
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .body(Body::from(encoded))
+        .unwrap()
+}
+
+/// Remove the buffered measurements a client has stored, up to the timestamp of the newest one
+async fn delete_rssi_backlog(
+    State(state): State<AppState>,
+    Query(query): Query<BacklogAck>,
+) -> impl IntoResponse {
+    match state.backlog.lock().await.acknowledge(query.up_to) {
+        Ok(removed) => {
+            info!(
+                "📦 Removed {} acknowledged measurement(s) from the backlog",
+                removed
+            );
+            Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .body(Body::empty())
+                .unwrap()
+        }
+        Err(e) => {
+            let error_msg = format!("Failed to update backlog: {}", e);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(error_msg))
+                .unwrap()
+        }
+    }
+}
+
+async fn get_rssi_history(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    let Ok(neighbor) = query.neighbor.parse::<bluer::Address>() else {
+        return Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(format!("Invalid neighbor: {}", query.neighbor)))
+            .unwrap();
+    };
+    let from = query.from.unwrap_or(0);
+    let to = query.to.unwrap_or_else(unix_millis);
+
+    info!(
+        "📜 RSSI history request for {} ({} - {})",
+        neighbor, from, to
+    );
+
+    match state.history.query(neighbor, from, to) {
+        Ok(samples) => {
+            // Encode the response using SCALE codec
+            let encoded = HistoryResponse {
+                address: neighbor.0,
+                samples,
+            }
+            .encode();
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/octet-stream")
+                .body(Body::from(encoded))
+                .unwrap()
+        }
+        Err(e) => {
+            let error_msg = format!("Failed to read history: {}", e);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(error_msg))
+                .unwrap()
+        }
+    }
+}
+
 async fn get_location(State(state): State<AppState>, req: Request) -> impl IntoResponse {
     // Extract and log the Node ID from the X-Node-ID header
     let node_id = req
@@ -82,21 +337,22 @@ async fn get_location(State(state): State<AppState>, req: Request) -> impl IntoR
         .and_then(|v| v.to_str().ok())
         .unwrap_or("unknown");
 
-    println!("📍 Location request from node: {}", node_id);
+    info!("📍 Location request from node: {}", node_id);
 
     let (latitude, longitude) = get_our_location();
-    let address = bluetooth_address(&state.adapter).await;
 
     let response = LocationResponse {
-        address: address.0,
+        address: state.our_address.0,
         location: Location {
             latitude,
             longitude,
         },
+        timestamp: unix_millis(),
+        sequence: None,
     };
 
     // Encode the response using SCALE codec
-    let encoded = response.encode();
+    let encoded = sign_response(state.signer.as_ref(), response.encode());
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "application/octet-stream")
@@ -104,107 +360,471 @@ async fn get_location(State(state): State<AppState>, req: Request) -> impl IntoR
         .unwrap()
 }
 
+/// Move the node at runtime, e.g. from an operator or a GNSS sidecar of a mobile unit
+///
+/// The new location is served on `/location` right away, and neighbors are measured from it.
+/// With the direct submitter the node is also moved on chain, otherwise the offchain worker
+/// picks the location up on its next check.
+async fn post_location(
+    State(state): State<AppState>,
+    Json(update): Json<LocationUpdate>,
+) -> impl IntoResponse {
+    if !(-90.0..=90.0).contains(&update.latitude) || !(-180.0..=180.0).contains(&update.longitude) {
+        return Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(format!(
+                "Invalid location: {}, {}",
+                update.latitude, update.longitude
+            )))
+            .unwrap();
+    }
+
+    let location = (update.latitude, update.longitude);
+    info!("📍 Location pushed: {}, {}", location.0, location.1);
+    config::push_location(location);
+
+    if let (Some(submitter), Some(api)) = (state.submitter, state.chain.get().cloned()) {
+        let our_address = state.our_address;
+        tokio::spawn(async move {
+            if let Err(e) = submitter.update_location(&api, our_address, location).await {
+                warn!("⚠️  Failed to update our location on chain: {}", e);
+            }
+        });
+    }
+
+    Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn post_status(State(state): State<AppState>, body: Bytes) -> impl IntoResponse {
+    // Decode the status using SCALE codec
+    let status = match NodeStatus::decode(&mut &body[..]) {
+        Ok(status) => status,
+        Err(e) => {
+            let error_msg = format!("Invalid status: {}", e);
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(error_msg))
+                .unwrap();
+        }
+    };
+
+    info!(
+        "🩺 Node status at block #{}: registered: {}, last submission: {:?}, trust score: {:?}",
+        status.block, status.registered, status.last_submitted_block, status.trust_score
+    );
+
+    state.status.lock().await.update(status);
+
+    Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn get_status(State(state): State<AppState>) -> impl IntoResponse {
+    let summary = state.status.lock().await.summary();
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(summary))
+        .unwrap()
+}
+
+async fn get_neighbors(State(state): State<AppState>) -> impl IntoResponse {
+    info!("🧭 Neighbors request");
+
+    let neighbors = describe_neighbors(
+        state.chain.get(),
+        &state.neighbor_addresses,
+        &state.rssi_data,
+        &state.wifi_addresses,
+        &state.peer_links,
+    )
+    .await;
+    Json(neighbors)
+}
+
+async fn get_health(State(state): State<AppState>) -> impl IntoResponse {
+    let (latitude, longitude) = get_our_location();
+    let adapter_powered = match &state.adapter {
+        Some(adapter) => adapter.is_powered().await.unwrap_or(false),
+        None => true,
+    };
+    let report = HealthReport {
+        radios_up: adapter_powered && state.wifi_interface.as_deref().is_none_or(interface_up),
+        last_scan: last_scan(&state.rssi_data).await,
+        chain_connected: chain_connected(&state.chain_status),
+        neighbor_count: state.neighbor_addresses.lock().await.len(),
+        // Unset coordinates default to 0, 0
+        location: (latitude != 0.0 || longitude != 0.0).then_some((latitude, longitude)),
+    };
+
+    // Orchestrators only look at the status code of a readiness probe
+    let status = if report.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(report.summary()))
+        .unwrap()
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    // Command line options take precedence over the configuration file and the environment
+    let cli = Cli::parse();
+    let print_config = cli.print_config;
+    config::set_cli(cli);
+
     // Load environment variables from .env file
     dotenvy::dotenv()?;
 
-    println!("Starting Bluetooth RSSI Scanner Server...\n");
+    // Load the configuration file, falling back to environment variables
+    let path = config_path();
+    let server_config = config::init(&path)?;
+    if print_config {
+        println!("# Configuration file: {}", path.display());
+        print!("{}", server_config.describe());
+        return Ok(());
+    }
+
+    info!("Starting Bluetooth RSSI Scanner Server...\n");
+    info!("Configuration loaded from {}", path.display());
+
+    // Measure neighbors over Bluetooth, Wi-Fi or both
+    let backends = server_config.backends;
+    info!("Measuring RSSI with: {}", backends.names());
 
     // Create Bluetooth session
-    let session = Session::new()
-        .await
-        .expect("Failed to create Bluetooth session");
-    let adapter = session
-        .default_adapter()
-        .await
-        .expect("Failed to get default adapter");
+    let adapter = if backends.ble {
+        let session = Session::new()
+            .await
+            .expect("Failed to create Bluetooth session");
+        Some(match &server_config.adapter {
+            Some(name) => session.adapter(name).expect("Failed to get adapter"),
+            None => session
+                .default_adapter()
+                .await
+                .expect("Failed to get default adapter"),
+        })
+    } else {
+        None
+    };
+    let wifi_scanner = backends.wifi.then(|| WifiScanner::new(&server_config.wifi));
 
     // Create shared state for RSSI data
     let rssi_data: RssiData = Arc::new(Mutex::new(HashMap::new()));
 
-    // Create shared state for neighbor addresses
-    // Initialize with env variable if available (for backwards compatibility)
-    let initial_neighbors = init_neighbor_addresses_from_env();
-    let neighbor_addresses: NeighborAddresses = Arc::new(Mutex::new(initial_neighbors));
-
-    // Get our Bluetooth address
-    let our_bluetooth_address = bluetooth_address(&adapter).await;
-    println!("Our Bluetooth address: {}", our_bluetooth_address);
-
-    // Connect to the Substrate node
-    let substrate_url =
-        std::env::var("RPC_URL").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string());
-    println!("Connecting to Substrate node at: {}", substrate_url);
-
-    if let Ok(api) = OnlineClient::<SubstrateConfig>::from_url(&substrate_url).await {
-        println!("Connected to Substrate node successfully\n");
-
-        // Get max distance
-        let max_distance = fetch_max_distance(&api);
-        println!("Max distance for neighbors: {} meters\n", max_distance);
-
-        // Calculate neighbors once at startup
-        println!("Calculating initial neighbor list...");
-        match calculate_neighbors(&api, our_bluetooth_address, max_distance).await {
-            Ok(neighbors) => {
-                // Merge existing neighbors with new ones
-                let mut addr_lock = neighbor_addresses.lock().await;
-                (*addr_lock).extend(neighbors);
-                println!("✅ Initial neighbor count: {}", addr_lock.len());
-            }
-            Err(e) => {
-                eprintln!("⚠️  Failed to calculate initial neighbors: {}", e);
+    // Measure the distance to neighbors too if a UWB module is attached
+    let ranging_backend: Option<Box<dyn RangingBackend>> = SerialRanging::open(&server_config.uwb)?
+        .map(|backend| Box::new(backend) as Box<dyn RangingBackend>);
+    let range_data: RangeData = Arc::new(Mutex::new(HashMap::new()));
+
+    // Start from the neighbors last learned from the chain and the configured overrides
+    let cache_path = chain::cache_path();
+    let cache = ChainCache::load(&cache_path);
+    let neighbor_addresses: NeighborAddresses = Arc::new(Mutex::new(initial_neighbors(&cache)));
+    let max_distance: SharedMaxDistance = Arc::new(AtomicU32::new(cache.max_distance));
+    set_registered_location(cache.registered_location);
+
+    // Create shared state for the server keys of neighbors that are ranged
+    let ranging_keys: RangingKeys = Arc::new(Mutex::new(HashMap::new()));
+    let ranging = ranging_enabled();
+
+    // Create shared state for the identity tags advertised by registered nodes
+    let identity_tags: IdentityTags = Arc::new(Mutex::new(HashMap::new()));
+
+    // Create shared state for the keys resolving the private addresses of registered nodes
+    let resolving_keys: ResolvingKeys = Arc::new(Mutex::new(HashMap::new()));
+
+    // Create shared state for the Wi-Fi BSSIDs of registered nodes
+    let wifi_addresses: WifiAddresses = Arc::new(Mutex::new(HashMap::new()));
+
+    // Get our Bluetooth address, or the MAC of our Wi-Fi interface without Bluetooth
+    let our_bluetooth_address = match (&adapter, &wifi_scanner) {
+        (Some(adapter), _) => bluetooth_address(adapter).await,
+        (None, Some(scanner)) => interface_address(&scanner.interface)?,
+        (None, None) => unreachable!("Backends require at least one backend"),
+    };
+    info!("Our address: {}", our_bluetooth_address);
+
+    // Neighbors only recognize our private addresses once our IRK is on chain
+    match local_irk(our_bluetooth_address) {
+        Ok(Some(irk)) if adapter.is_some() => info!(
+            "Advertising from private addresses, register the IRK with set_identity_resolving_key: {}",
+            irk_hex(&irk)
+        ),
+        Ok(_) => {}
+        Err(e) => warn!("⚠️  Failed to read our IRK: {}", e),
+    }
+
+    // Create shared state for the links cross-checked with the neighbors' servers
+    let peer_links: PeerLinks = Arc::new(Mutex::new(HashMap::new()));
+
+    // Sign and submit our extrinsics ourselves if the node's key is configured
+    let submitter = Submitter::from_env(server_config.submit_interval)?;
+
+    // Load measurements buffered during previous offline periods
+    let backlog: SharedBacklog = Arc::new(Mutex::new(Backlog::load_from_env()));
+
+    // Connect to the Substrate node in the background, scanning the cached neighbors meanwhile
+    let chain: SharedChain = Arc::default();
+    let chain_status: ChainStatus = Arc::default();
+    start_chain_client(
+        server_config.rpc_url.clone(),
+        our_bluetooth_address,
+        Arc::clone(&max_distance),
+        Arc::clone(&neighbor_addresses),
+        Arc::clone(&chain_status),
+        Arc::clone(&chain),
+        ChainTasks {
+            ranging_keys: ranging.then(|| Arc::clone(&ranging_keys)),
+            identity_tags: Arc::clone(&identity_tags),
+            resolving_keys: Arc::clone(&resolving_keys),
+            wifi_addresses: Arc::clone(&wifi_addresses),
+            peer_check: PeerCheck::from_env()?,
+            peer_links: Arc::clone(&peer_links),
+            submitter: submitter.clone(),
+            backlog: Arc::clone(&backlog),
+            rssi_data: Arc::clone(&rssi_data),
+            range_data: Arc::clone(&range_data),
+        },
+    )
+    .await;
+    tokio::spawn(start_cache_writer(
+        cache_path,
+        Arc::clone(&neighbor_addresses),
+        Arc::clone(&max_distance),
+    ));
+
+    // Load the key responses are signed with
+    let signer = signer_from_env()?;
+    if let Some(keypair) = &signer {
+        info!(
+            "Signing responses, register the key with set_server_key: {}",
+            public_key_hex(keypair)
+        );
+    }
+
+    // Spawn background task for continuous Bluetooth scanning
+    if let Some(adapter) = adapter.clone() {
+        let rssi_data_clone = Arc::clone(&rssi_data);
+        let neighbor_addresses_clone = Arc::clone(&neighbor_addresses);
+        let ranging_signer = signer.clone().filter(|_| ranging);
+        tokio::spawn(async move {
+            if let Err(e) = start_continuous_scan(
+                adapter,
+                rssi_data_clone,
+                neighbor_addresses_clone,
+                ranging_keys,
+                identity_tags,
+                resolving_keys,
+                ranging_signer,
+            )
+            .await
+            {
+                error!("Bluetooth scan error: {}", e);
             }
-        }
+        });
+    }
 
-        // Start listening for NodeRegistered events and auto-update neighbor list
-        start_neighbor_event_listener(
-            api.clone(),
-            our_bluetooth_address,
-            max_distance,
+    // Spawn background task for Wi-Fi scanning
+    let wifi_interface = wifi_scanner
+        .as_ref()
+        .map(|scanner| scanner.interface.clone());
+    if let Some(scanner) = wifi_scanner {
+        tokio::spawn(start_wifi_scan(
+            scanner,
+            Arc::clone(&rssi_data),
             Arc::clone(&neighbor_addresses),
-        )
-        .await;
-    } else {
-        println!("Connection to Substrate node refused: Neighbor list will not be updated");
+            Arc::clone(&wifi_addresses),
+        ));
     }
 
-    // Spawn background task for continuous Bluetooth scanning
-    let adapter_clone = adapter.clone();
-    let rssi_data_clone = Arc::clone(&rssi_data);
-    let neighbor_addresses_clone = Arc::clone(&neighbor_addresses);
-    tokio::spawn(async move {
-        if let Err(e) =
-            start_continuous_scan(adapter_clone, rssi_data_clone, neighbor_addresses_clone).await
-        {
-            eprintln!("Bluetooth scan error: {}", e);
-        }
-    });
+    // Spawn background task for UWB ranging
+    if let Some(backend) = ranging_backend {
+        tokio::spawn(start_ranging(
+            backend,
+            Arc::clone(&range_data),
+            Arc::clone(&neighbor_addresses),
+        ));
+    }
+
+    // Reload the configuration on SIGHUP or when the file changes
+    let configs = start_config_watcher(path)?;
+    let tls_configs = configs.clone();
+    start_config_listener(
+        configs,
+        Arc::clone(&chain),
+        max_distance,
+        our_bluetooth_address,
+        Arc::clone(&neighbor_addresses),
+    )
+    .await;
+
+    info!(
+        "Smoothing RSSI readings with the {} filter",
+        server_config.filter.name()
+    );
+
+    // Spawn background task buffering measurements while nobody polls us
+    tokio::spawn(start_backlog_recorder(
+        Arc::clone(&backlog),
+        Arc::clone(&rssi_data),
+    ));
+
+    // Keep every raw reading so auditors can compare it with what landed on chain
+    let history = History::open(&server_config.history)?;
+    tokio::spawn(start_history_recorder(history.clone(), subscribe_samples()));
 
     // Create app state
-    let app_state = AppState { adapter, rssi_data };
+    let app_state = AppState {
+        adapter,
+        wifi_interface,
+        our_address: our_bluetooth_address,
+        rssi_data,
+        range_data,
+        backlog,
+        status: Arc::new(Mutex::new(StatusReport::default())),
+        signer,
+        neighbor_addresses,
+        wifi_addresses,
+        peer_links,
+        chain_status,
+        chain,
+        submitter,
+        history,
+    };
 
-    // Build the Axum router
+    // Build the Axum router, limiting the rate of the endpoints handing out measurements
     let app = Router::new()
         .route("/rssi", get(scan_rssi))
-        .route("/location", get(get_location))
-        .with_state(app_state);
-
-    // Get the server port from environment or use default
-    let port = std::env::var("PORT").unwrap_or_else(|_| "3000".to_string());
-    let addr = format!("0.0.0.0:{}", port);
-
-    println!("Server listening on http://{}", addr);
-    println!("Access the RSSI endpoint at: http://{}/rssi", addr);
-    println!(
-        "Access the Location endpoint at: http://{}/location\n",
-        addr
+        .route("/range", get(get_range))
+        .route("/location", get(get_location));
+    let app = match RateLimiter::new(&server_config.rate_limit) {
+        Some(limiter) => {
+            info!("Rate limiting /rssi, /range and /location per client");
+            app.route_layer(middleware::from_fn_with_state(
+                Arc::new(limiter),
+                limit_rate,
+            ))
+        }
+        None => app,
+    };
+    let app = app
+        .route(
+            "/rssi/backlog",
+            get(get_rssi_backlog).delete(delete_rssi_backlog),
+        )
+        .route("/rssi/stream", get(stream_rssi))
+        .route("/rssi/history", get(get_rssi_history))
+        .route("/status", post(post_status).get(get_status))
+        .route("/neighbors", get(get_neighbors))
+        .with_state(app_state.clone());
+
+    // Require a bearer token on every endpoint if one is configured
+    let app = match std::env::var("AUTH_TOKEN").ok().filter(|t| !t.is_empty()) {
+        Some(token) => {
+            info!("Requests must carry the configured auth token");
+            app.layer(middleware::from_fn_with_state(
+                Arc::new(token),
+                require_auth_token,
+            ))
+        }
+        None => app,
+    };
+
+    // Require nodes to sign a challenge with their key if the allowed nodes are configured
+    let app = match ChallengeAuth::from_env()? {
+        Some(auth) => {
+            info!("Requests must be signed by a node in NODE_ACCOUNTS");
+            app.layer(middleware::from_fn_with_state(
+                Arc::new(auth),
+                require_node_signature,
+            ))
+        }
+        None => app,
+    };
+
+    // Location updates carry their own token, so an operator does not need the node's
+    let app = match std::env::var("LOCATION_UPDATE_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty())
+    {
+        Some(token) => {
+            info!("Accepting location updates carrying the configured token");
+            app.merge(
+                Router::new()
+                    .route("/location", post(post_location))
+                    .route_layer(middleware::from_fn_with_state(
+                        Arc::new(token),
+                        require_auth_token,
+                    ))
+                    .with_state(app_state.clone()),
+            )
+        }
+        None => app,
+    };
+
+    // Readiness probes cannot authenticate, so the health check is left open
+    let app = app.merge(
+        Router::new()
+            .route("/health", get(get_health))
+            .with_state(app_state),
     );
 
-    // Start the server
-    let listener = TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    // Get the server port from the configuration
+    let addr = format!("0.0.0.0:{}", server_config.port);
+    let scheme = if server_config.tls.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+
+    info!("Server listening on {}://{}", scheme, addr);
+    info!("Access the RSSI endpoint at: {}://{}/rssi", scheme, addr);
+    info!(
+        "Access the RSSI backlog at: {}://{}/rssi/backlog",
+        scheme, addr
+    );
+    info!(
+        "Access the live RSSI stream at: {}://{}/rssi/stream",
+        scheme, addr
+    );
+    info!(
+        "Access the RSSI history at: {}://{}/rssi/history?neighbor=",
+        scheme, addr
+    );
+    info!("Access the UWB ranges at: {}://{}/range", scheme, addr);
+    info!("Access the neighbors at: {}://{}/neighbors", scheme, addr);
+    info!("Access the node status at: {}://{}/status", scheme, addr);
+    info!("Access the health check at: {}://{}/health", scheme, addr);
+    info!(
+        "Access the Location endpoint at: {}://{}/location\n",
+        scheme, addr
+    );
+
+    // Start the server, keeping the client address for the rate limiter
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+    match &server_config.tls {
+        Some((cert, key)) => {
+            let tls = RustlsConfig::from_pem_file(cert, key).await?;
+            tokio::spawn(start_tls_reloader(tls.clone(), tls_configs));
+            axum_server::bind_rustls(addr.parse()?, tls)
+                .serve(app)
+                .await?;
+        }
+        None => {
+            let listener = TcpListener::bind(&addr).await?;
+            axum::serve(listener, app).await?;
+        }
+    }
 
     Ok(())
 }
@@ -1,33 +1,63 @@
 use bluer::Address;
 use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+use subxt::utils::AccountId32;
 use subxt::{OnlineClient, SubstrateConfig};
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
+
+use crate::bluetooth::{identity_tag, IdentityTags, NeighborAddresses, RssiData};
+use crate::chain::{next_backoff, SharedChain, INITIAL_BACKOFF};
+use crate::config::ServerConfig;
+use crate::health::{set_chain_connected, ChainStatus};
+use crate::peer::PeerLinks;
+use crate::privacy::ResolvingKeys;
+use crate::wifi::WifiAddresses;
 
 use substrate::proof_of_location::events::{NodeRegistered, NodeUnregistered, NodeUpdated};
-use substrate::runtime_types::pallet_proof_of_location::util::LocationData;
+use substrate::runtime_types::pallet_proof_of_location::util::{LocationData, NodeConfigBundle};
+use substrate::system::events::CodeUpdated;
 
 // This creates a complete, type-safe API for interacting with the runtime.
 #[subxt::subxt(runtime_metadata_path = "../metadata.scale")]
 pub mod substrate {}
 
-/// Cached location coordinates (latitude, longitude)
-/// Read once from environment variables and reused throughout the application
-static CACHED_LOCATION: OnceLock<(f64, f64)> = OnceLock::new();
+/// Max distance between neighbors, refreshed when the runtime or our registration changes
+pub type SharedMaxDistance = Arc<AtomicU32>;
+
+/// Our location as registered on chain, `None` while we are not registered
+static REGISTERED_LOCATION: RwLock<Option<(f64, f64)>> = RwLock::new(None);
 
-/// Get our location from cache or initialize from environment variables
+/// Get our location from the current configuration
 pub fn get_our_location() -> (f64, f64) {
-    *CACHED_LOCATION.get_or_init(|| {
-        let lat = std::env::var("LATITUDE")
-            .ok()
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.0);
-        let lon = std::env::var("LONGITUDE")
-            .ok()
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.0);
-        (lat, lon)
-    })
+    crate::config::current().location
+}
+
+/// Location neighbors are measured from
+///
+/// The chain checks the distance to a neighbor against our registered location, so it is used
+/// once known. Until then, and until a changed configured location is registered, it is the
+/// configured one.
+fn neighbor_origin() -> (f64, f64) {
+    registered_location().unwrap_or_else(get_our_location)
+}
+
+/// Our location as registered on chain, `None` while we are not registered
+pub fn registered_location() -> Option<(f64, f64)> {
+    *REGISTERED_LOCATION.read().unwrap()
+}
+
+/// Remember our registered location
+pub fn set_registered_location(location: Option<(f64, f64)>) {
+    *REGISTERED_LOCATION.write().unwrap() = location;
+}
+
+/// Coordinates in degrees of the chain's fixed-point location
+pub fn degrees(location: &LocationData) -> (f64, f64) {
+    (
+        location.latitude as f64 / 1_000_000.0,
+        location.longitude as f64 / 1_000_000.0,
+    )
 }
 
 /// Fetch all location data from the chain
@@ -64,6 +94,247 @@ pub async fn fetch_all_location_data(
     Ok(results)
 }
 
+/// Fetch the server keys registered with `set_server_key`, by the Bluetooth address of the node
+pub async fn fetch_server_keys(
+    api: &OnlineClient<SubstrateConfig>,
+) -> Result<HashMap<Address, [u8; 32]>, String> {
+    let locations = fetch_all_location_data(api).await?;
+    let query = substrate::storage().proof_of_location().server_key_iter();
+
+    let mut server_keys = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(|e| e.to_string())?
+        .iter(query)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut results = HashMap::new();
+    while let Some(Ok(data)) = server_keys.next().await {
+        // The account ID is the last 32 bytes of the key, as for `AccountData`
+        let len = data.key_bytes.len();
+        let account_id: [u8; 32] = data.key_bytes[len - 32..]
+            .try_into()
+            .map_err(|e| format!("Failed to convert key bytes to account ID: {:?}", e))?;
+
+        if let Some(location_data) = locations.get(&account_id) {
+            results.insert(Address(location_data.address), data.value);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Keep the neighbors' server keys up to date for ranging, refreshing them every minute
+pub async fn start_server_key_refresher(
+    api: OnlineClient<SubstrateConfig>,
+    ranging_keys: Arc<Mutex<HashMap<Address, [u8; 32]>>>,
+) {
+    tokio::spawn(async move {
+        loop {
+            match fetch_server_keys(&api).await {
+                Ok(keys) => {
+                    let mut keys_lock = ranging_keys.lock().await;
+                    if keys_lock.len() != keys.len() {
+                        info!("🔑 Ranging {} node(s) with a server key", keys.len());
+                    }
+                    *keys_lock = keys;
+                }
+                Err(e) => warn!("⚠️  Failed to fetch server keys: {}", e),
+            }
+
+            tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+        }
+    });
+}
+
+/// Keep the identity tags, resolving keys and Wi-Fi BSSIDs of the registered nodes up to date,
+/// refreshing them every minute
+///
+/// Includes our own tag, which is advertised once it is known.
+pub async fn start_identity_refresher(
+    api: OnlineClient<SubstrateConfig>,
+    identity_tags: IdentityTags,
+    resolving_keys: ResolvingKeys,
+    wifi_addresses: WifiAddresses,
+) {
+    tokio::spawn(async move {
+        loop {
+            match fetch_all_location_data(&api).await {
+                Ok(locations) => {
+                    let tags: HashMap<_, _> = locations
+                        .iter()
+                        .map(|(account_id, location_data)| {
+                            (Address(location_data.address), identity_tag(account_id))
+                        })
+                        .collect();
+                    *identity_tags.lock().await = tags;
+
+                    match fetch_resolving_keys(&api, &locations).await {
+                        Ok(keys) => {
+                            let mut keys_lock = resolving_keys.lock().await;
+                            if keys_lock.len() != keys.len() {
+                                info!(
+                                    "🕵️  Resolving the private addresses of {} node(s)",
+                                    keys.len()
+                                );
+                            }
+                            *keys_lock = keys;
+                        }
+                        Err(e) => warn!("⚠️  Failed to fetch identity resolving keys: {}", e),
+                    }
+
+                    match fetch_wifi_addresses(&api, &locations).await {
+                        Ok(bssids) => {
+                            let mut bssids_lock = wifi_addresses.lock().await;
+                            if bssids_lock.len() != bssids.len() {
+                                info!("📶 {} node(s) registered a Wi-Fi BSSID", bssids.len());
+                            }
+                            *bssids_lock = bssids;
+                        }
+                        Err(e) => warn!("⚠️  Failed to fetch Wi-Fi BSSIDs: {}", e),
+                    }
+                }
+                Err(e) => warn!("⚠️  Failed to fetch identity tags: {}", e),
+            }
+
+            tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+        }
+    });
+}
+
+/// Fetch the Identity Resolving Keys of the registered nodes, by their registered Bluetooth
+/// address
+pub async fn fetch_resolving_keys(
+    api: &OnlineClient<SubstrateConfig>,
+    locations: &HashMap<[u8; 32], LocationData>,
+) -> Result<HashMap<Address, [u8; 16]>, String> {
+    let query = substrate::storage()
+        .proof_of_location()
+        .identity_resolving_key_iter();
+
+    let mut resolving_keys = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(|e| e.to_string())?
+        .iter(query)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut results = HashMap::new();
+    while let Some(Ok(data)) = resolving_keys.next().await {
+        let len = data.key_bytes.len();
+        let account_id: [u8; 32] = data.key_bytes[len - 32..]
+            .try_into()
+            .map_err(|e| format!("Failed to convert key bytes to account ID: {:?}", e))?;
+
+        if let Some(location_data) = locations.get(&account_id) {
+            results.insert(Address(location_data.address), data.value);
+        }
+    }
+
+    Ok(results)
+}
+
+/// Fetch the Wi-Fi BSSIDs registered with `set_wifi_address`, by the registered Bluetooth
+/// address of the node
+pub async fn fetch_wifi_addresses(
+    api: &OnlineClient<SubstrateConfig>,
+    locations: &HashMap<[u8; 32], LocationData>,
+) -> Result<HashMap<Address, Address>, String> {
+    let query = substrate::storage().proof_of_location().wifi_address_iter();
+
+    let mut wifi_addresses = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(|e| e.to_string())?
+        .iter(query)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut results = HashMap::new();
+    while let Some(Ok(data)) = wifi_addresses.next().await {
+        let len = data.key_bytes.len();
+        let account_id: [u8; 32] = data.key_bytes[len - 32..]
+            .try_into()
+            .map_err(|e| format!("Failed to convert key bytes to account ID: {:?}", e))?;
+
+        if let Some(location_data) = locations.get(&account_id) {
+            results.insert(Address(location_data.address), Address(data.value));
+        }
+    }
+
+    Ok(results)
+}
+
+/// Fetch the server URLs registered with `set_server_config`, by the registered Bluetooth address
+/// of the node
+pub async fn fetch_server_urls(
+    api: &OnlineClient<SubstrateConfig>,
+    locations: &HashMap<[u8; 32], LocationData>,
+) -> Result<HashMap<Address, String>, String> {
+    let query = substrate::storage()
+        .proof_of_location()
+        .server_config_iter();
+
+    let mut server_urls = api
+        .storage()
+        .at_latest()
+        .await
+        .map_err(|e| e.to_string())?
+        .iter(query)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut results = HashMap::new();
+    while let Some(Ok(data)) = server_urls.next().await {
+        let len = data.key_bytes.len();
+        let account_id: [u8; 32] = data.key_bytes[len - 32..]
+            .try_into()
+            .map_err(|e| format!("Failed to convert key bytes to account ID: {:?}", e))?;
+
+        if let Some(location_data) = locations.get(&account_id) {
+            // The URL is stored as a `BoundedVec`, whose bytes are its only field
+            results.insert(
+                Address(location_data.address),
+                String::from_utf8_lossy(&data.value.0).into_owned(),
+            );
+        }
+    }
+
+    Ok(results)
+}
+
+/// Fetch the configuration of the node registered with our Bluetooth address.
+///
+/// All values are read from the same block in a single runtime API call.
+pub async fn fetch_node_config(
+    api: &OnlineClient<SubstrateConfig>,
+    our_bluetooth_address: Address,
+) -> Result<NodeConfigBundle, String> {
+    // Find the account our Bluetooth address is registered to
+    let account_id = fetch_all_location_data(api)
+        .await?
+        .into_iter()
+        .find(|(_, location_data)| location_data.address == our_bluetooth_address.0)
+        .map(|(account_id, _)| account_id)
+        .ok_or_else(|| "Our Bluetooth address is not registered".to_string())?;
+
+    let rpc_call = substrate::apis()
+        .node_config_api()
+        .node_config(AccountId32(account_id));
+    api.runtime_api()
+        .at_latest()
+        .await
+        .map_err(|e| e.to_string())?
+        .call(rpc_call)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Fetch the MaxDistance constant from the runtime.
 ///
 /// Falls back to default of 10 meters.
@@ -83,7 +354,7 @@ pub async fn calculate_neighbors(
     let all_location_data = fetch_all_location_data(api).await?;
 
     // Get our cached location
-    let (our_lat, our_lon) = get_our_location();
+    let (our_lat, our_lon) = neighbor_origin();
 
     // Find all neighbors within max_distance
     let mut neighbors = HashSet::new();
@@ -108,6 +379,86 @@ pub async fn calculate_neighbors(
     Ok(neighbors)
 }
 
+/// A neighbor as served by `/neighbors`, in JSON
+#[derive(serde::Serialize, Debug, Clone)]
+pub struct NeighborInfo {
+    /// Registered Bluetooth address
+    pub address: String,
+    /// SS58 account the address is registered to, `None` if it is not or the chain is unreachable
+    pub account: Option<String>,
+    /// Meters from the location neighbors are measured from
+    pub distance: Option<f64>,
+    /// Latest raw RSSI reading, in dBm
+    pub last_rssi: Option<i16>,
+    /// Unix time in milliseconds of the latest reading
+    pub last_seen: Option<u64>,
+    /// The neighbor's RSSI of us at the last cross-check with its server
+    pub peer_rssi: Option<i16>,
+    /// The last cross-check found our and the neighbor's RSSI too far apart
+    pub asymmetric: bool,
+}
+
+/// Describe every neighbor we measure, with its account and distance if `api` is connected
+///
+/// Neighbors measured over Wi-Fi are looked up under their BSSID when nothing was recorded under
+/// their registered address.
+pub async fn describe_neighbors(
+    api: Option<&OnlineClient<SubstrateConfig>>,
+    neighbor_addresses: &NeighborAddresses,
+    rssi_data: &RssiData,
+    wifi_addresses: &WifiAddresses,
+    peer_links: &PeerLinks,
+) -> Vec<NeighborInfo> {
+    // Same storage calculate_neighbors reads, keyed by address
+    let registered: HashMap<Address, ([u8; 32], LocationData)> = match api {
+        Some(api) => match fetch_all_location_data(api).await {
+            Ok(all_location_data) => all_location_data
+                .into_iter()
+                .map(|(account_id, data)| (Address(data.address), (account_id, data)))
+                .collect(),
+            Err(e) => {
+                warn!("⚠️  Failed to fetch location data: {}", e);
+                HashMap::new()
+            }
+        },
+        None => HashMap::new(),
+    };
+
+    let addresses: Vec<Address> = neighbor_addresses.lock().await.iter().copied().collect();
+    let wifi_addresses = wifi_addresses.lock().await.clone();
+    let peer_links = peer_links.lock().await.clone();
+    let rssi_data = rssi_data.lock().await;
+
+    let mut neighbors: Vec<NeighborInfo> = addresses
+        .into_iter()
+        .map(|address| {
+            let registration = registered.get(&address);
+            let peer_link = peer_links.get(&address);
+            let last_reading = rssi_data
+                .get(&address)
+                .and_then(|readings| readings.back())
+                .or_else(|| {
+                    let bssid = wifi_addresses.get(&address)?;
+                    rssi_data.get(bssid)?.back()
+                });
+
+            NeighborInfo {
+                address: address.to_string(),
+                account: registration.map(|(account_id, _)| AccountId32(*account_id).to_string()),
+                distance: registration
+                    .map(|(_, data)| calculate_distance_from_us(data.latitude, data.longitude)),
+                last_rssi: last_reading.map(|reading| reading.rssi),
+                last_seen: last_reading.map(|reading| reading.unix_millis),
+                peer_rssi: peer_link.map(|link| link.peer_rssi),
+                asymmetric: peer_link.is_some_and(|link| link.asymmetric_runs > 0),
+            }
+        })
+        .collect();
+    neighbors.sort_by(|a, b| a.address.cmp(&b.address));
+
+    neighbors
+}
+
 /// Calculate distance between two coordinates in meters
 fn distance(a_lat: f64, a_lon: f64, b_lat: f64, b_lon: f64) -> f64 {
     use haversine_redux::Location;
@@ -118,7 +469,7 @@ fn distance(a_lat: f64, a_lon: f64, b_lat: f64, b_lon: f64) -> f64 {
 
 /// Helper function to calculate distance from our cached location to a given coordinate
 fn calculate_distance_from_us(latitude: i64, longitude: i64) -> f64 {
-    let (our_lat, our_lon) = get_our_location();
+    let (our_lat, our_lon) = neighbor_origin();
     let their_lat = latitude as f64 / 1_000_000.0;
     let their_lon = longitude as f64 / 1_000_000.0;
     distance(our_lat, our_lon, their_lat, their_lon)
@@ -139,7 +490,7 @@ async fn handle_node_in_range(
     if dist <= max_distance as f64 {
         let mut addr_lock = neighbor_addresses.lock().await;
         if addr_lock.insert(node_address) {
-            println!(
+            info!(
                 "✅ {} neighbor: {} (distance: {:.2}m) - Total neighbors: {}",
                 event_type,
                 node_address,
@@ -147,13 +498,13 @@ async fn handle_node_in_range(
                 addr_lock.len()
             );
         } else if event_type == "Updated" {
-            println!(
+            info!(
                 "🔄 Updated neighbor location: {} (distance: {:.2}m)",
                 node_address, dist
             );
         }
     } else {
-        println!(
+        info!(
             "⏭️  Node {:?} is too far away ({:.2}m > {}m), not adding as neighbor",
             address, dist, max_distance
         );
@@ -174,12 +525,12 @@ async fn handle_node_out_of_range(
     if dist > max_distance as f64 {
         let mut addr_lock = neighbor_addresses.lock().await;
         if addr_lock.remove(&node_address) {
-            println!(
+            info!(
                 "❌ Removed neighbor (moved too far): {} (distance: {:.2}m > {}m) - Total neighbors: {}",
                 node_address, dist, max_distance, addr_lock.len()
             );
         } else {
-            println!(
+            info!(
                 "⏭️  Updated node is not a neighbor ({:.2}m > {}m)",
                 dist, max_distance
             );
@@ -187,28 +538,122 @@ async fn handle_node_out_of_range(
     }
 }
 
+/// Replace the neighbor list with the nodes in range, keeping the configured overrides
+pub async fn recalculate_neighbors(
+    api: &OnlineClient<SubstrateConfig>,
+    our_bluetooth_address: Address,
+    max_distance: u32,
+    neighbor_addresses: &Arc<Mutex<HashSet<Address>>>,
+) {
+    match calculate_neighbors(api, our_bluetooth_address, max_distance).await {
+        Ok(neighbors) => {
+            let mut addr_lock = neighbor_addresses.lock().await;
+            *addr_lock = neighbors;
+            addr_lock.extend(crate::config::current().neighbor_overrides.iter().copied());
+            info!("✅ Neighbor count: {}", addr_lock.len());
+        }
+        Err(e) => warn!("⚠️  Failed to recalculate neighbors: {}", e),
+    }
+}
+
+/// Refresh our registered location and max distance from the chain, then recalculate the
+/// neighbors
+///
+/// Called when our own node was registered, updated or unregistered, after a runtime upgrade,
+/// which may change `MaxDistance`, and after resubscribing, to catch up on missed blocks.
+async fn refresh_own_config(
+    api: &OnlineClient<SubstrateConfig>,
+    our_bluetooth_address: Address,
+    max_distance: &SharedMaxDistance,
+    neighbor_addresses: &Arc<Mutex<HashSet<Address>>>,
+) {
+    match fetch_node_config(api, our_bluetooth_address).await {
+        Ok(config) => {
+            set_registered_location(config.location.as_ref().map(degrees));
+            if let Some((latitude, longitude)) = registered_location() {
+                info!("📍 Registered location: {}, {}", latitude, longitude);
+            }
+            info!(
+                "🔗 Server URL: {}",
+                String::from_utf8_lossy(&config.server_url)
+            );
+
+            let old_max_distance = max_distance.swap(config.max_distance, Ordering::Relaxed);
+            if old_max_distance != config.max_distance {
+                info!(
+                    "📏 Max distance for neighbors changed from {} to {} meters",
+                    old_max_distance, config.max_distance
+                );
+            }
+        }
+        Err(e) => {
+            // Unregistered, measure from the configured location again
+            warn!("⚠️  Failed to refresh our node configuration: {}", e);
+            set_registered_location(None);
+        }
+    }
+
+    recalculate_neighbors(
+        api,
+        our_bluetooth_address,
+        max_distance.load(Ordering::Relaxed),
+        neighbor_addresses,
+    )
+    .await;
+}
+
 /// Start listening to NodeRegistered events and update the neighbor list automatically
 /// This function spawns a background task that subscribes to blockchain events
+///
+/// Events of our own node and runtime upgrades refresh our registered location and the max
+/// distance instead.
 pub async fn start_neighbor_event_listener(
     api: OnlineClient<SubstrateConfig>,
     our_bluetooth_address: Address,
-    max_distance: u32,
+    max_distance: SharedMaxDistance,
     neighbor_addresses: Arc<Mutex<HashSet<Address>>>,
+    chain_status: ChainStatus,
 ) {
     tokio::spawn(async move {
-        println!("🎧 Starting node event listener...\n");
+        info!("🎧 Starting node event listener...\n");
+
+        let mut backoff = INITIAL_BACKOFF;
+        let mut missed_blocks = false;
 
         loop {
             // Subscribe to finalized blocks
             let mut blocks_sub = match api.blocks().subscribe_finalized().await {
-                Ok(sub) => sub,
+                Ok(sub) => {
+                    set_chain_connected(&chain_status, true);
+                    backoff = INITIAL_BACKOFF;
+                    sub
+                }
                 Err(e) => {
-                    eprintln!("⚠️  Failed to subscribe to blocks: {}", e);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    set_chain_connected(&chain_status, false);
+                    warn!(
+                        "⚠️  Failed to subscribe to blocks: {}, retrying in {}s",
+                        e,
+                        backoff.as_secs()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = next_backoff(backoff);
                     continue;
                 }
             };
 
+            // Events were missed while the subscription was down
+            if missed_blocks {
+                info!("🔁 Resubscribed to blocks, refreshing neighbors");
+                refresh_own_config(
+                    &api,
+                    our_bluetooth_address,
+                    &max_distance,
+                    &neighbor_addresses,
+                )
+                .await;
+                missed_blocks = false;
+            }
+
             // Process each finalized block
             while let Some(block_result) = blocks_sub.next().await {
                 match block_result {
@@ -217,17 +662,20 @@ pub async fn start_neighbor_event_listener(
                         let events = match block.events().await {
                             Ok(events) => events,
                             Err(e) => {
-                                eprintln!("⚠️  Failed to fetch events: {}", e);
+                                warn!("⚠️  Failed to fetch events: {}", e);
                                 continue;
                             }
                         };
 
+                        // Refresh our own configuration once per block, after all its events
+                        let mut own_config_changed = false;
+
                         // Find and process node events using subxt generated API
                         for event_result in events.iter() {
                             let event = match event_result {
                                 Ok(event) => event,
                                 Err(e) => {
-                                    eprintln!("⚠️  Failed to get event: {}", e);
+                                    warn!("⚠️  Failed to get event: {}", e);
                                     continue;
                                 }
                             };
@@ -235,10 +683,12 @@ pub async fn start_neighbor_event_listener(
                             // Handle NodeRegistered event
                             if let Ok(Some(node_registered)) = event.as_event::<NodeRegistered>() {
                                 if node_registered.address == our_bluetooth_address.0 {
+                                    info!("📍 Our node was registered");
+                                    own_config_changed = true;
                                     continue;
                                 }
 
-                                println!(
+                                info!(
                                     "📍 NodeRegistered event detected for address: {:?}",
                                     node_registered.address
                                 );
@@ -248,7 +698,7 @@ pub async fn start_neighbor_event_listener(
                                     node_registered.latitude,
                                     node_registered.longitude,
                                     &neighbor_addresses,
-                                    max_distance,
+                                    max_distance.load(Ordering::Relaxed),
                                     "Added new",
                                 )
                                 .await;
@@ -261,23 +711,25 @@ pub async fn start_neighbor_event_listener(
                                 let removed_address = Address(node_unregistered.address);
 
                                 if removed_address == our_bluetooth_address {
+                                    info!("🗑️  Our node was unregistered");
+                                    own_config_changed = true;
                                     continue;
                                 }
 
-                                println!(
+                                info!(
                                     "🗑️  NodeUnregistered event detected for address: {:?}",
                                     node_unregistered.address
                                 );
 
                                 let mut addr_lock = neighbor_addresses.lock().await;
                                 if addr_lock.remove(&removed_address) {
-                                    println!(
+                                    info!(
                                         "❌ Removed neighbor: {} - Total neighbors: {}",
                                         removed_address,
                                         addr_lock.len()
                                     );
                                 } else {
-                                    println!(
+                                    info!(
                                         "⏭️  Node {:?} was not in neighbor list",
                                         node_unregistered.address
                                     );
@@ -289,11 +741,15 @@ pub async fn start_neighbor_event_listener(
                                 let old_address = Address(node_updated.old_address);
                                 let new_address = Address(node_updated.new_address);
 
-                                if new_address == our_bluetooth_address {
+                                if new_address == our_bluetooth_address
+                                    || old_address == our_bluetooth_address
+                                {
+                                    info!("🔄 Our node was updated");
+                                    own_config_changed = true;
                                     continue;
                                 }
 
-                                println!(
+                                info!(
                                     "🔄 NodeUpdated event detected - Old: {:?}, New: {:?}",
                                     node_updated.old_address, node_updated.new_address
                                 );
@@ -305,6 +761,7 @@ pub async fn start_neighbor_event_listener(
                                 }
 
                                 // Calculate distance and determine if node should be a neighbor
+                                let max_distance = max_distance.load(Ordering::Relaxed);
                                 let dist = calculate_distance_from_us(
                                     node_updated.new_latitude,
                                     node_updated.new_longitude,
@@ -331,17 +788,89 @@ pub async fn start_neighbor_event_listener(
                                     .await;
                                 }
                             }
+
+                            // A runtime upgrade may change MaxDistance
+                            if let Ok(Some(_)) = event.as_event::<CodeUpdated>() {
+                                info!("⬆️  Runtime upgraded");
+                                own_config_changed = true;
+                            }
                         }
+
+                        if own_config_changed {
+                            refresh_own_config(
+                                &api,
+                                our_bluetooth_address,
+                                &max_distance,
+                                &neighbor_addresses,
+                            )
+                            .await;
+                        }
+                    }
+                    Err(e) if e.is_disconnected_will_reconnect() => {
+                        warn!("⚠️  Connection to the Substrate node lost, reconnecting...");
+                        break;
                     }
                     Err(e) => {
-                        eprintln!("⚠️  Error processing block: {}", e);
+                        warn!("⚠️  Error processing block: {}", e);
                     }
                 }
             }
 
-            // If subscription ends, wait a bit and reconnect
-            eprintln!("⚠️  Block subscription ended, reconnecting in 5s...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            // The subscription ended, keep the cached neighbors until it is made again
+            set_chain_connected(&chain_status, false);
+            missed_blocks = true;
+            warn!("⚠️  Block subscription ended, resubscribing...");
+        }
+    });
+}
+
+/// Apply reloaded configurations to the neighbor list
+///
+/// Neighbor overrides that were removed stop being monitored and new ones are added. When our
+/// location changed the neighbors are recalculated, if the chain is connected.
+pub async fn start_config_listener(
+    mut configs: watch::Receiver<Arc<ServerConfig>>,
+    chain: SharedChain,
+    max_distance: SharedMaxDistance,
+    our_bluetooth_address: Address,
+    neighbor_addresses: Arc<Mutex<HashSet<Address>>>,
+) {
+    tokio::spawn(async move {
+        let mut old = configs.borrow_and_update().clone();
+
+        while configs.changed().await.is_ok() {
+            let new = configs.borrow_and_update().clone();
+
+            if new.location != old.location {
+                info!(
+                    "📍 Location changed to {}, {}",
+                    new.location.0, new.location.1
+                );
+
+                if let Some(api) = chain.get() {
+                    recalculate_neighbors(
+                        api,
+                        our_bluetooth_address,
+                        max_distance.load(Ordering::Relaxed),
+                        &neighbor_addresses,
+                    )
+                    .await;
+                }
+            }
+
+            if new.neighbor_overrides != old.neighbor_overrides {
+                let mut addr_lock = neighbor_addresses.lock().await;
+                for removed in old.neighbor_overrides.difference(&new.neighbor_overrides) {
+                    addr_lock.remove(removed);
+                }
+                addr_lock.extend(new.neighbor_overrides.iter().copied());
+                info!(
+                    "🔄 Neighbor overrides updated - Total neighbors: {}",
+                    addr_lock.len()
+                );
+            }
+
+            old = new;
         }
     });
 }
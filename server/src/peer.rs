@@ -0,0 +1,330 @@
+use bluer::Address;
+use codec::{Decode, DecodeAll};
+use reqwest::{header, StatusCode};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, SubstrateConfig};
+use subxt_signer::sr25519::{verify, PublicKey, Signature};
+use tokio::sync::Mutex;
+use tokio::time;
+
+use crate::bluetooth::{current_rssi, unix_millis, NeighborAddresses, RssiData, RssiResponse};
+use crate::challenge::sign_challenge;
+use crate::neighbor::{fetch_all_location_data, fetch_server_keys, fetch_server_urls};
+use crate::signing::SignedPayload;
+use crate::submitter::Submitter;
+
+const DEFAULT_ASYMMETRY_THRESHOLD: u16 = 10; // dB
+const DEFAULT_ASYMMETRY_RUNS: u32 = 3;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Last cross-check of the link with a neighbor
+#[derive(Debug, Clone)]
+pub struct PeerLink {
+    /// Our smoothed RSSI of the neighbor
+    pub our_rssi: i16,
+    /// The neighbor's smoothed RSSI of us, from its server
+    pub peer_rssi: i16,
+    /// Checks in a row that found the link asymmetric, `0` if the last one did not
+    pub asymmetric_runs: u32,
+    /// Unix time in milliseconds
+    pub checked_at: u64,
+}
+
+// Cross-checked links, by the registered Bluetooth address of the neighbor
+pub type PeerLinks = Arc<Mutex<HashMap<Address, PeerLink>>>;
+
+/// Compare our readings of the neighbors with theirs of us, fetched from their servers
+///
+/// Radio links are reciprocal: two nodes see each other at about the same RSSI, whoever
+/// transmits. A node spoofing its location, or replaying another device, cannot keep both
+/// directions in agreement.
+pub struct PeerCheck {
+    interval: Duration,
+    /// RSSI difference in dB beyond which a link is asymmetric
+    threshold: u16,
+    /// Asymmetric checks in a row before the neighbor is reported
+    runs: u32,
+    /// Bearer token the neighbors' servers require, if any
+    auth_token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl PeerCheck {
+    /// Check every `PEER_CHECK_INTERVAL_SECS`, `None` if it is not set
+    ///
+    /// Links are asymmetric beyond `PEER_ASYMMETRY_DB`, and reported after `PEER_ASYMMETRY_RUNS`
+    /// asymmetric checks in a row.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        let Some(interval) = var("PEER_CHECK_INTERVAL_SECS") else {
+            return Ok(None);
+        };
+        let interval = interval
+            .parse::<u64>()
+            .ok()
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("Invalid PEER_CHECK_INTERVAL_SECS: {}", interval))?;
+        let threshold = match var("PEER_ASYMMETRY_DB") {
+            Some(db) => db
+                .parse()
+                .map_err(|_| format!("Invalid PEER_ASYMMETRY_DB: {}", db))?,
+            None => DEFAULT_ASYMMETRY_THRESHOLD,
+        };
+        let runs = match var("PEER_ASYMMETRY_RUNS") {
+            Some(runs) => runs
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid PEER_ASYMMETRY_RUNS: {}", runs))?
+                .max(1),
+            None => DEFAULT_ASYMMETRY_RUNS,
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create the HTTP client: {}", e))?;
+
+        Ok(Some(Self {
+            interval,
+            threshold,
+            runs,
+            auth_token: var("PEER_AUTH_TOKEN"),
+            client,
+        }))
+    }
+
+    /// Fetch the smoothed RSSI the server at `server_url` measures of its neighbors
+    ///
+    /// A challenge of the server is answered with the key of `submitter`. The response must be
+    /// signed with `server_key` if the neighbor registered one.
+    async fn fetch_rssi(
+        &self,
+        server_url: &str,
+        server_key: Option<[u8; 32]>,
+        submitter: Option<&Submitter>,
+    ) -> Result<RssiResponse, String> {
+        let url = format!("{}/rssi", with_scheme(server_url));
+        let node_id = submitter.map_or("server".to_string(), |submitter| {
+            format!("0x{}", hex(&submitter.account().0))
+        });
+
+        let request = |challenge: Option<(&str, String)>| {
+            let mut request = self.client.get(&url).header("X-Node-ID", &node_id);
+            if let Some(token) = &self.auth_token {
+                request = request.bearer_auth(token);
+            }
+            if let Some((nonce, signature)) = challenge {
+                request = request
+                    .header("X-Challenge", nonce)
+                    .header("X-Signature", signature);
+            }
+            request.send()
+        };
+
+        let mut response = request(None).await.map_err(|e| e.to_string())?;
+
+        // Servers restricted to NODE_ACCOUNTS challenge us first
+        if response.status() == StatusCode::UNAUTHORIZED {
+            let nonce = response
+                .headers()
+                .get("X-Challenge")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            if let (Some(nonce), Some(submitter)) = (nonce, submitter) {
+                let signature = sign_challenge(submitter.keypair(), &nonce);
+                response = request(Some((&nonce, signature)))
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        if !response.status().is_success() {
+            return Err(format!("{} answered {}", url, response.status()));
+        }
+        let is_scale = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .is_some_and(|v| v == "application/octet-stream");
+        if !is_scale {
+            return Err(format!("{} did not answer with SCALE", url));
+        }
+        let body = response.bytes().await.map_err(|e| e.to_string())?;
+
+        // Checked like the offchain worker does, once the neighbor registered a server key
+        let payload = match server_key {
+            Some(server_key) => {
+                let signed = SignedPayload::decode_all(&mut &body[..])
+                    .map_err(|e| format!("Invalid signed response: {}", e))?;
+                if !verify(
+                    &Signature(signed.signature),
+                    &signed.payload,
+                    &PublicKey(server_key),
+                ) {
+                    return Err("Invalid response signature".to_string());
+                }
+                signed.payload
+            }
+            None => body.to_vec(),
+        };
+
+        RssiResponse::decode(&mut &payload[..]).map_err(|e| format!("Invalid response: {}", e))
+    }
+}
+
+/// Keep an explicit scheme, default to plain HTTP for `host:port` as the offchain worker does
+fn with_scheme(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else {
+        format!("http://{}", url)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Cross-check the links with our neighbors every `PEER_CHECK_INTERVAL_SECS`
+///
+/// Only neighbors we measure and that registered a server URL are checked. With a `submitter`,
+/// a neighbor whose link stays asymmetric is reported with `report_misbehavior`, the RSSI error
+/// being its reading minus ours.
+pub async fn start_peer_checker(
+    check: PeerCheck,
+    api: OnlineClient<SubstrateConfig>,
+    our_bluetooth_address: Address,
+    rssi_data: RssiData,
+    neighbor_addresses: NeighborAddresses,
+    peer_links: PeerLinks,
+    submitter: Option<Submitter>,
+) {
+    info!(
+        "🤝 Cross-checking neighbors' servers every {}s",
+        check.interval.as_secs()
+    );
+
+    loop {
+        time::sleep(check.interval).await;
+
+        let locations = match fetch_all_location_data(&api).await {
+            Ok(locations) => locations,
+            Err(e) => {
+                warn!("⚠️  Failed to fetch location data: {}", e);
+                continue;
+            }
+        };
+        let server_urls = match fetch_server_urls(&api, &locations).await {
+            Ok(server_urls) => server_urls,
+            Err(e) => {
+                warn!("⚠️  Failed to fetch server URLs: {}", e);
+                continue;
+            }
+        };
+        let server_keys = fetch_server_keys(&api).await.unwrap_or_else(|e| {
+            warn!("⚠️  Failed to fetch server keys: {}", e);
+            HashMap::new()
+        });
+
+        let filter = crate::config::current().filter.clone();
+        let ours: HashMap<Address, i16> =
+            match current_rssi(rssi_data.clone(), filter.as_ref()).await {
+                Ok(response) => response
+                    .devices
+                    .into_iter()
+                    .map(|device| (Address(device.address), device.rssi))
+                    .collect(),
+                Err(e) => {
+                    warn!("⚠️  Failed to calculate our RSSI: {}", e);
+                    continue;
+                }
+            };
+
+        let neighbors: Vec<Address> = neighbor_addresses.lock().await.iter().copied().collect();
+        for neighbor in neighbors {
+            let (Some(server_url), Some(&our_rssi)) =
+                (server_urls.get(&neighbor), ours.get(&neighbor))
+            else {
+                continue;
+            };
+
+            let response = match check
+                .fetch_rssi(
+                    server_url,
+                    server_keys.get(&neighbor).copied(),
+                    submitter.as_ref(),
+                )
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => {
+                    warn!("⚠️  Failed to cross-check {}: {}", neighbor, e);
+                    continue;
+                }
+            };
+            let Some(peer_rssi) = response
+                .devices
+                .iter()
+                .find(|device| device.address == our_bluetooth_address.0)
+                .map(|device| device.rssi)
+            else {
+                info!("⏭️  {} does not measure us, skipping", neighbor);
+                continue;
+            };
+
+            let rssi_error = peer_rssi.saturating_sub(our_rssi);
+            let asymmetric = rssi_error.unsigned_abs() > check.threshold;
+
+            let asymmetric_runs = {
+                let mut links = peer_links.lock().await;
+                let runs = match links.get(&neighbor) {
+                    Some(link) if asymmetric => link.asymmetric_runs + 1,
+                    None if asymmetric => 1,
+                    _ => 0,
+                };
+                links.insert(
+                    neighbor,
+                    PeerLink {
+                        our_rssi,
+                        peer_rssi,
+                        asymmetric_runs: runs,
+                        checked_at: unix_millis(),
+                    },
+                );
+                runs
+            };
+            if !asymmetric {
+                continue;
+            }
+
+            info!(
+                "⚖️  Asymmetric link with {}: we measure {} dBm, it measures {} dBm ({} in a row)",
+                neighbor, our_rssi, peer_rssi, asymmetric_runs
+            );
+
+            // Report once per run of asymmetric checks
+            if asymmetric_runs != check.runs {
+                continue;
+            }
+            let (Some(submitter), Some(offender)) = (
+                submitter.as_ref(),
+                locations
+                    .iter()
+                    .find(|(_, location_data)| location_data.address == neighbor.0)
+                    .map(|(account_id, _)| AccountId32(*account_id)),
+            ) else {
+                continue;
+            };
+            if let Err(e) = submitter
+                .report_misbehavior(&api, offender, rssi_error)
+                .await
+            {
+                warn!("⚠️  Failed to report {}: {}", neighbor, e);
+            }
+        }
+    }
+}
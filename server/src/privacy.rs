@@ -0,0 +1,91 @@
+use aes::cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit};
+use aes::Aes128;
+use bluer::Address;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::challenge::decode_hex;
+
+/// Directory BlueZ keeps the keys of each adapter in
+const BLUEZ_STORAGE: &str = "/var/lib/bluetooth";
+
+// Identity Resolving Keys of the registered nodes, by their registered Bluetooth address
+pub type ResolvingKeys = Arc<Mutex<HashMap<Address, [u8; 16]>>>;
+
+// Address each neighbor currently advertises from, by its registered Bluetooth address
+pub type CurrentAddresses = Arc<Mutex<HashMap<Address, Address>>>;
+
+/// Whether `address` is a resolvable private address, its two most significant bits are `01`
+pub fn is_resolvable_private(address: Address) -> bool {
+    address.0[0] >> 6 == 0b01
+}
+
+/// Hash of the random part of an address under `irk`, the `ah` function of the Bluetooth Core
+/// specification
+fn ah(irk: &[u8; 16], prand: &[u8]) -> [u8; 3] {
+    // BlueZ stores keys least significant byte first, AES takes them most significant first
+    let mut key = *irk;
+    key.reverse();
+
+    let mut block = [0u8; 16];
+    block[13..].copy_from_slice(prand);
+    let mut block = GenericArray::from(block);
+    Aes128::new(&GenericArray::from(key)).encrypt_block(&mut block);
+
+    [block[13], block[14], block[15]]
+}
+
+/// Whether the resolvable private `address` was generated with `irk`
+fn resolves(irk: &[u8; 16], address: Address) -> bool {
+    ah(irk, &address.0[..3]) == address.0[3..]
+}
+
+/// Registered address of the node advertising from `address`
+///
+/// Addresses that are not resolvable private ones are already registered addresses.
+pub async fn resolve(resolving_keys: &ResolvingKeys, address: Address) -> Option<Address> {
+    if !is_resolvable_private(address) {
+        return Some(address);
+    }
+
+    resolving_keys
+        .lock()
+        .await
+        .iter()
+        .find(|(_, irk)| resolves(irk, address))
+        .map(|(identity, _)| *identity)
+}
+
+/// Our own IRK, from `BLE_IRK` or the identity BlueZ generated for `adapter_address`
+///
+/// BlueZ only rotates the advertising address with `Privacy = device` in its `main.conf`, the
+/// identity file does not exist without.
+pub fn local_irk(adapter_address: Address) -> Result<Option<[u8; 16]>, String> {
+    if let Some(irk) = std::env::var("BLE_IRK").ok().filter(|v| !v.is_empty()) {
+        return decode_hex::<16>(&irk)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid BLE_IRK: {}", irk));
+    }
+
+    let path = format!("{}/{}/identity", BLUEZ_STORAGE, adapter_address);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path, e)),
+    };
+
+    contents
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("IdentityResolvingKey="))
+        .map(|irk| decode_hex::<16>(irk).ok_or_else(|| format!("Invalid IRK in {}", path)))
+        .transpose()
+}
+
+/// Hex-encoded IRK to register on-chain with `set_identity_resolving_key`
+pub fn irk_hex(irk: &[u8; 16]) -> String {
+    format!(
+        "0x{}",
+        irk.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+    )
+}
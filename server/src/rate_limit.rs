@@ -0,0 +1,119 @@
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const DEFAULT_PER_MINUTE: u32 = 120;
+const DEFAULT_BURST: u32 = 20;
+/// Clients tracked before the idle ones are forgotten
+const MAX_TRACKED_CLIENTS: usize = 1024;
+
+/// Limits of the requests of each client, the `[rate_limit]` section of the configuration,
+/// only applied at startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    /// Requests per minute, `0` for no limit
+    pub per_minute: u32,
+    /// Requests at once before being limited
+    pub burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_minute: DEFAULT_PER_MINUTE,
+            burst: DEFAULT_BURST,
+        }
+    }
+}
+
+/// Requests a client can still make, refilled continuously
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket per client IP address
+///
+/// Every client may make `burst` requests at once, then `per_minute` requests per minute.
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    clients: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+pub type SharedRateLimiter = Arc<RateLimiter>;
+
+impl RateLimiter {
+    /// Limit the requests to the configured rate
+    ///
+    /// Requests are not limited if `per_minute` is `0`.
+    pub fn new(config: &RateLimitConfig) -> Option<Self> {
+        if config.per_minute == 0 {
+            return None;
+        }
+
+        Some(Self {
+            per_second: config.per_minute as f64 / 60.0,
+            burst: config.burst.max(1) as f64,
+            clients: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Take a token from the bucket of `client`
+    ///
+    /// Returns the seconds until the next token if the bucket is empty.
+    fn check(&self, client: IpAddr) -> Result<(), u64> {
+        let now = Instant::now();
+        let mut clients = self.clients.lock().unwrap();
+
+        // Idle clients have a full bucket again, forgetting them changes nothing
+        if clients.len() >= MAX_TRACKED_CLIENTS {
+            clients.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * self.per_second
+                    < self.burst
+            });
+        }
+
+        let bucket = clients.entry(client).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let refilled = now.duration_since(bucket.updated).as_secs_f64() * self.per_second;
+        bucket.tokens = (bucket.tokens + refilled).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / self.per_second).ceil() as u64)
+        }
+    }
+}
+
+/// Answer `429 Too Many Requests` to clients over their rate limit
+pub async fn limit_rate(
+    State(limiter): State<SharedRateLimiter>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if let Err(retry_after) = limiter.check(client.ip()) {
+        info!("🐢 Rate limited {}", client.ip());
+        return Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header(header::RETRY_AFTER, retry_after)
+            .body(Body::from("Too many requests"))
+            .unwrap();
+    }
+
+    next.run(req).await
+}
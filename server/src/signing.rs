@@ -0,0 +1,78 @@
+use codec::{Decode, Encode};
+use std::str::FromStr;
+use subxt_signer::{sr25519::Keypair, SecretUri};
+
+/// Response body signed with the server's key, checked by the offchain worker once the node has
+/// registered the key with `set_server_key`
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct SignedPayload {
+    /// SCALE-encoded response
+    pub payload: Vec<u8>,
+    /// sr25519 signature of `payload`
+    pub signature: [u8; 64],
+}
+
+/// Load the signing key from the secret URI in `SIGNING_KEY`, e.g. a mnemonic or `//Alice`, or
+/// from the file named by `SIGNING_KEY_FILE`
+///
+/// Responses are sent unsigned while neither is set.
+pub fn signer_from_env() -> Result<Option<Keypair>, String> {
+    keypair_from_env("SIGNING_KEY", "SIGNING_KEY_FILE")
+}
+
+/// Load a key from the secret URI in the variable `uri_var`, or from the file named by
+/// `file_var`
+pub fn keypair_from_env(uri_var: &str, file_var: &str) -> Result<Option<Keypair>, String> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+    let (uri, source) = if let Some(uri) = var(uri_var) {
+        (uri, uri_var)
+    } else if let Some(path) = var(file_var) {
+        (read_key_file(file_var, &path)?, file_var)
+    } else {
+        return Ok(None);
+    };
+
+    let uri = SecretUri::from_str(&uri).map_err(|e| format!("Invalid {}: {}", source, e))?;
+    let keypair = Keypair::from_uri(&uri).map_err(|e| format!("Invalid {}: {}", source, e))?;
+
+    Ok(Some(keypair))
+}
+
+/// Read a secret URI from a file
+///
+/// Files of a Substrate keystore hold the phrase as a JSON string, so surrounding quotes are
+/// removed as well as whitespace.
+fn read_key_file(file_var: &str, path: &str) -> Result<String, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {} {}: {}", file_var, path, e))?;
+    let contents = contents.trim();
+
+    Ok(contents
+        .strip_prefix('"')
+        .and_then(|uri| uri.strip_suffix('"'))
+        .unwrap_or(contents)
+        .to_string())
+}
+
+/// Hex-encoded public key to register on-chain with `set_server_key`
+pub fn public_key_hex(keypair: &Keypair) -> String {
+    let hex: String = keypair
+        .public_key()
+        .0
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("0x{}", hex)
+}
+
+/// Wrap a SCALE-encoded response in a [`SignedPayload`] if a signing key is configured
+pub fn sign_response(signer: Option<&Keypair>, payload: Vec<u8>) -> Vec<u8> {
+    match signer {
+        Some(keypair) => {
+            let signature = keypair.sign(&payload).0;
+            SignedPayload { payload, signature }.encode()
+        }
+        None => payload,
+    }
+}
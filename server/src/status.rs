@@ -0,0 +1,64 @@
+use codec::{Decode, Encode};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+/// Health of the node's offchain worker, posted to `/status` after every run
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct NodeStatus {
+    /// Block the offchain worker ran for
+    pub block: u32,
+    /// SCALE-encoded account of the node
+    pub account: Vec<u8>,
+    pub registered: bool,
+    /// Block of the last complete RSSI submission
+    pub last_submitted_block: Option<u32>,
+    /// Trust score of the node over the last rolled-up epoch
+    pub trust_score: Option<i16>,
+}
+
+/// Last status received from the offchain worker
+#[derive(Default)]
+pub struct StatusReport {
+    status: Option<NodeStatus>,
+    /// Unix timestamp in seconds when the status was received
+    received_at: u64,
+}
+
+// Global shared state for the offchain worker status
+pub type SharedStatus = Arc<Mutex<StatusReport>>;
+
+impl StatusReport {
+    /// Remember a status posted by the offchain worker
+    pub fn update(&mut self, status: NodeStatus) {
+        self.status = Some(status);
+        self.received_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+    }
+
+    /// Human-readable summary of the last status
+    pub fn summary(&self) -> String {
+        let Some(status) = &self.status else {
+            return "No status received from the offchain worker yet\n".to_string();
+        };
+
+        let account: String = status
+            .account
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let last_submitted = status
+            .last_submitted_block
+            .map_or("never".to_string(), |block| format!("#{}", block));
+        let trust_score = status
+            .trust_score
+            .map_or("none".to_string(), |score| score.to_string());
+
+        format!(
+            "Account: 0x{}\nRegistered: {}\nLast run: block #{}\nLast RSSI submission: {}\nTrust score: {}\nReceived at: {}\n",
+            account, status.registered, status.block, last_submitted, trust_score, self.received_at
+        )
+    }
+}
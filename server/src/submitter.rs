@@ -0,0 +1,304 @@
+use bluer::Address;
+use std::time::Duration;
+use subxt::storage::Storage;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, SubstrateConfig};
+use subxt_signer::sr25519::Keypair;
+use tokio::time;
+
+use crate::backlog::SharedBacklog;
+use crate::bluetooth::{current_rssi, RssiData};
+use crate::neighbor::{get_our_location, substrate};
+use crate::signing::keypair_from_env;
+use crate::uwb::{current_ranges, RangeData};
+
+use substrate::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+
+pub const DEFAULT_SUBMIT_INTERVAL: Duration = Duration::from_secs(60); // 1 minute
+const DEFAULT_MAX_BATCH_SIZE: u32 = 32; // Matches the runtime constant
+
+/// Signs and submits the node's extrinsics itself, instead of the node's offchain worker
+#[derive(Clone)]
+pub struct Submitter {
+    keypair: Keypair,
+    interval: Duration,
+}
+
+impl Submitter {
+    /// Load the node's account key from `SUBMIT_KEY` or `SUBMIT_KEY_FILE`, submitting every
+    /// `interval`
+    ///
+    /// Submission is left to the offchain worker while no key is set.
+    pub fn from_env(interval: Duration) -> Result<Option<Self>, String> {
+        let Some(keypair) = keypair_from_env("SUBMIT_KEY", "SUBMIT_KEY_FILE")? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self { keypair, interval }))
+    }
+
+    /// Account the extrinsics are signed with
+    pub fn account(&self) -> AccountId32 {
+        AccountId32(self.keypair.public_key().0)
+    }
+
+    /// Key of the node's account, to answer the challenges of other servers
+    pub fn keypair(&self) -> &Keypair {
+        &self.keypair
+    }
+
+    /// Report a neighbor whose measurements contradict ours
+    pub async fn report_misbehavior(
+        &self,
+        api: &OnlineClient<SubstrateConfig>,
+        offender: AccountId32,
+        rssi_error: i16,
+    ) -> Result<(), subxt::Error> {
+        let tx = substrate::tx()
+            .proof_of_location()
+            .report_misbehavior(offender.clone(), rssi_error);
+        let hash = api.tx().sign_and_submit_default(&tx, &self.keypair).await?;
+        info!(
+            "🚨 Submitted report_misbehavior against {}: {:?}",
+            offender, hash
+        );
+
+        Ok(())
+    }
+
+    /// Register the node with our Bluetooth address and location unless it already is
+    async fn ensure_registered(
+        &self,
+        api: &OnlineClient<SubstrateConfig>,
+        our_bluetooth_address: Address,
+    ) -> Result<(), subxt::Error> {
+        let query = substrate::storage()
+            .proof_of_location()
+            .account_data(self.account());
+        if api
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&query)
+            .await?
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        let (latitude, longitude) = fixed_point(get_our_location());
+        let tx = substrate::tx().proof_of_location().register_node(
+            our_bluetooth_address.0,
+            latitude,
+            longitude,
+        );
+        let hash = api.tx().sign_and_submit_default(&tx, &self.keypair).await?;
+        info!("📝 Submitted register_node: {:?}", hash);
+
+        Ok(())
+    }
+
+    /// Move our registered node to `location`
+    pub async fn update_location(
+        &self,
+        api: &OnlineClient<SubstrateConfig>,
+        our_bluetooth_address: Address,
+        location: (f64, f64),
+    ) -> Result<(), subxt::Error> {
+        let (latitude, longitude) = fixed_point(location);
+        let tx = substrate::tx().proof_of_location().update_node_info(
+            our_bluetooth_address.0,
+            latitude,
+            longitude,
+        );
+        let hash = api.tx().sign_and_submit_default(&tx, &self.keypair).await?;
+        info!("📝 Submitted update_node_info: {:?}", hash);
+
+        Ok(())
+    }
+
+    /// Submit the current RSSI of every registered neighbor, returning how many were submitted
+    async fn submit_rssi(
+        &self,
+        api: &OnlineClient<SubstrateConfig>,
+        rssi_data: &RssiData,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let filter = crate::config::current().filter.clone();
+        let response = current_rssi(rssi_data.clone(), filter.as_ref()).await?;
+
+        let storage = api.storage().at_latest().await?;
+        let mut submitted = 0;
+        for device in response.devices {
+            let Some(neighbor) = neighbor_account(&storage, device.address).await? else {
+                info!(
+                    "⏭️  {} is not registered, skipping",
+                    Address(device.address)
+                );
+                continue;
+            };
+
+            let tx = substrate::tx().proof_of_location().publish_rssi_data(
+                neighbor,
+                device.rssi,
+                device.tx_power,
+            );
+            api.tx().sign_and_submit_default(&tx, &self.keypair).await?;
+            submitted += 1;
+        }
+
+        Ok(submitted)
+    }
+
+    /// Submit the oldest measurement buffered while we were offline and remove it from the
+    /// backlog, returning how many readings were replayed
+    ///
+    /// Each batch is waited for until it is finalized, so the replayed readings land in an earlier
+    /// block than the current ones and the backlog only forgets what is on chain. Batches the
+    /// runtime rejects are dropped, they would be rejected again.
+    async fn replay_backlog(
+        &self,
+        api: &OnlineClient<SubstrateConfig>,
+        backlog: &SharedBacklog,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let Some(entry) = backlog.lock().await.entries().first().cloned() else {
+            return Ok(0);
+        };
+
+        let storage = api.storage().at_latest().await?;
+        let mut readings = Vec::new();
+        for device in entry.devices {
+            if let Some(neighbor) = neighbor_account(&storage, device.address).await? {
+                readings.push((neighbor, device.rssi, device.tx_power));
+            }
+        }
+
+        let query = substrate::constants().proof_of_location().max_batch_size();
+        let max_batch_size = api.constants().at(&query).unwrap_or(DEFAULT_MAX_BATCH_SIZE);
+        for batch in readings.chunks(max_batch_size.max(1) as usize) {
+            let tx = substrate::tx()
+                .proof_of_location()
+                .publish_rssi_data_batch(BoundedVec(batch.to_vec()));
+            let result = api
+                .tx()
+                .sign_and_submit_then_watch_default(&tx, &self.keypair)
+                .await?
+                .wait_for_finalized_success()
+                .await;
+            match result {
+                Ok(_) => {}
+                Err(subxt::Error::Runtime(e)) => warn!(
+                    "⚠️  Dropping {} buffered reading(s) the runtime rejected: {}",
+                    batch.len(),
+                    e
+                ),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        backlog.lock().await.acknowledge(entry.timestamp)?;
+        Ok(readings.len())
+    }
+
+    /// Submit the current UWB range of every registered neighbor, returning how many were
+    /// submitted
+    async fn submit_ranges(
+        &self,
+        api: &OnlineClient<SubstrateConfig>,
+        range_data: &RangeData,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let response = current_ranges(range_data).await;
+
+        let storage = api.storage().at_latest().await?;
+        let mut submitted = 0;
+        for device in response.devices {
+            let Some(neighbor) = neighbor_account(&storage, device.address).await? else {
+                continue;
+            };
+
+            let tx = substrate::tx()
+                .proof_of_location()
+                .publish_range_data(neighbor, device.distance_cm);
+            api.tx().sign_and_submit_default(&tx, &self.keypair).await?;
+            submitted += 1;
+        }
+
+        Ok(submitted)
+    }
+}
+
+/// Coordinates in the fixed-point format the offchain worker registers with
+fn fixed_point((latitude, longitude): (f64, f64)) -> (i64, i64) {
+    (
+        (latitude * 1_000_000.0).round() as i64,
+        (longitude * 1_000_000.0).round() as i64,
+    )
+}
+
+/// Account of the neighbor registered with `address`, as its Bluetooth address or Wi-Fi BSSID
+async fn neighbor_account(
+    storage: &Storage<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    address: [u8; 6],
+) -> Result<Option<AccountId32>, Box<dyn std::error::Error>> {
+    let query = substrate::storage()
+        .proof_of_location()
+        .address_registration_data(address);
+    if let Some(neighbor) = storage.fetch(&query).await? {
+        return Ok(Some(neighbor));
+    }
+
+    let query = substrate::storage()
+        .proof_of_location()
+        .wifi_address_registration_data(address);
+    Ok(storage.fetch(&query).await?)
+}
+
+/// Register the node, then periodically submit the RSSI and UWB ranges of its neighbors
+///
+/// Measurements buffered while the chain was unreachable are replayed one per round, before the
+/// current ones.
+pub async fn start_submitter(
+    submitter: Submitter,
+    api: OnlineClient<SubstrateConfig>,
+    our_bluetooth_address: Address,
+    backlog: SharedBacklog,
+    rssi_data: RssiData,
+    range_data: RangeData,
+) {
+    info!(
+        "📤 Submitting extrinsics directly as {}",
+        submitter.account()
+    );
+
+    loop {
+        time::sleep(submitter.interval).await;
+
+        if let Err(e) = submitter
+            .ensure_registered(&api, our_bluetooth_address)
+            .await
+        {
+            warn!("⚠️  Failed to register node: {}", e);
+            continue;
+        }
+
+        match submitter.replay_backlog(&api, &backlog).await {
+            Ok(0) => {}
+            Ok(replayed) => info!("📦 Replayed {} buffered RSSI reading(s)", replayed),
+            Err(e) => warn!("⚠️  Failed to replay buffered RSSI readings: {}", e),
+        }
+
+        match submitter.submit_rssi(&api, &rssi_data).await {
+            Ok(submitted) => {
+                info!("📤 Submitted {} RSSI reading(s)", submitted);
+                // Our readings reach the chain, stop buffering measurements
+                backlog.lock().await.mark_request();
+            }
+            Err(e) => warn!("⚠️  Failed to submit RSSI readings: {}", e),
+        }
+
+        match submitter.submit_ranges(&api, &range_data).await {
+            Ok(0) => {}
+            Ok(submitted) => info!("📤 Submitted {} UWB range(s)", submitted),
+            Err(e) => warn!("⚠️  Failed to submit UWB ranges: {}", e),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use bluer::Address;
+use codec::{Decode, Encode};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+
+use crate::backend::{Range, RangingBackend};
+use crate::bluetooth::{scan_config, unix_millis, NeighborAddresses};
+
+const DEFAULT_BAUD_RATE: u32 = 115_200;
+
+/// Serial port of the UWB module, the `[uwb]` section of the configuration, only applied at
+/// startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct UwbConfig {
+    /// Serial port, no ranging if not set
+    pub device: Option<String>,
+    pub baud_rate: u32,
+}
+
+impl Default for UwbConfig {
+    fn default() -> Self {
+        Self {
+            device: None,
+            baud_rate: DEFAULT_BAUD_RATE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RangeReading {
+    pub distance_cm: u32,
+    pub timestamp: Instant,
+}
+
+// Global shared state for UWB ranges with timestamps
+pub type RangeData = Arc<Mutex<HashMap<Address, VecDeque<RangeReading>>>>;
+
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct DeviceRange {
+    pub address: [u8; 6],
+    pub distance_cm: u32,
+}
+
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct RangeResponse {
+    pub devices: Vec<DeviceRange>,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+}
+
+/// UWB module on a serial port, e.g. a DW3000 board running two-way ranging firmware
+///
+/// The firmware prints a line per measurement with the registered address of the neighbor and
+/// the distance in centimeters, e.g. `aa:bb:cc:dd:ee:ff 245`. Other lines are ignored.
+pub struct SerialRanging {
+    device: String,
+    reader: BufReader<File>,
+}
+
+impl SerialRanging {
+    /// Open the configured serial port at its baud rate, `None` if no device is set
+    pub fn open(config: &UwbConfig) -> Result<Option<Self>, String> {
+        let Some(device) = config.device.clone() else {
+            return Ok(None);
+        };
+        let baud_rate = config.baud_rate;
+
+        // Raw mode, the firmware's output must not be altered by the line discipline
+        let status = Command::new("stty")
+            .args(["-F", &device, &baud_rate.to_string(), "raw", "-echo"])
+            .status()
+            .map_err(|e| format!("Failed to run stty: {}", e))?;
+        if !status.success() {
+            return Err(format!("Failed to configure {}", device));
+        }
+
+        let file = File::open(&device).map_err(|e| format!("Failed to open {}: {}", device, e))?;
+        Ok(Some(Self {
+            device,
+            reader: BufReader::new(file),
+        }))
+    }
+}
+
+impl RangingBackend for SerialRanging {
+    fn name(&self) -> String {
+        format!("UWB module on {}", self.device)
+    }
+
+    fn next_range(&mut self) -> Result<Range, String> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| format!("Failed to read {}: {}", self.device, e))?;
+            if read == 0 {
+                return Err(format!("{} was closed", self.device));
+            }
+
+            if let Some(range) = parse_range(&line) {
+                return Ok(range);
+            }
+        }
+    }
+}
+
+/// Range in a line of the firmware's output
+fn parse_range(line: &str) -> Option<Range> {
+    let mut fields = line.split_whitespace();
+    let address = fields.next()?.parse().ok()?;
+    let distance_cm = fields.next()?.parse().ok()?;
+    Some(Range {
+        address,
+        distance_cm,
+    })
+}
+
+/// Remove ranges older than the sample window
+fn remove_old_ranges(deque: &mut VecDeque<RangeReading>) {
+    let now = Instant::now();
+    let window = scan_config().sample_window;
+    while let Some(reading) = deque.front() {
+        if now.duration_since(reading.timestamp) > window {
+            deque.pop_front();
+        } else {
+            break;
+        }
+    }
+}
+
+/// Record the ranges of our neighbors as the backend measures them
+///
+/// The backend is read on a blocking thread and handed back after every range. Ranging stops if
+/// the backend fails, e.g. when the module is unplugged.
+pub async fn start_ranging(
+    mut backend: Box<dyn RangingBackend>,
+    range_data: RangeData,
+    neighbor_addresses: NeighborAddresses,
+) {
+    info!("Starting ranging with the {}...", backend.name());
+
+    loop {
+        let (returned, result) = match tokio::task::spawn_blocking(move || {
+            let result = backend.next_range();
+            (backend, result)
+        })
+        .await
+        {
+            Ok(next) => next,
+            Err(e) => {
+                warn!("⚠️  Ranging thread failed: {}", e);
+                return;
+            }
+        };
+        backend = returned;
+
+        let range = match result {
+            Ok(range) => range,
+            Err(e) => {
+                error!("⚠️  Ranging stopped: {}", e);
+                return;
+            }
+        };
+        if !neighbor_addresses.lock().await.contains(&range.address) {
+            continue;
+        }
+
+        let mut data = range_data.lock().await;
+        let deque = data.entry(range.address).or_default();
+        remove_old_ranges(deque);
+        deque.push_back(RangeReading {
+            distance_cm: range.distance_cm,
+            timestamp: Instant::now(),
+        });
+        debug!(
+            "📏 Range to {}: {} cm (queue size: {})",
+            range.address,
+            range.distance_cm,
+            deque.len()
+        );
+    }
+}
+
+/// Median of the recent ranges of every neighbor
+///
+/// A reflected signal only ever arrives later, so outliers are long and the median ignores them.
+pub async fn current_ranges(range_data: &RangeData) -> RangeResponse {
+    let mut data = range_data.lock().await;
+
+    let mut devices = Vec::new();
+    for (address, deque) in data.iter_mut() {
+        remove_old_ranges(deque);
+
+        let mut distances: Vec<u32> = deque.iter().map(|reading| reading.distance_cm).collect();
+        if distances.is_empty() {
+            continue;
+        }
+        distances.sort_unstable();
+        devices.push(DeviceRange {
+            address: address.0,
+            distance_cm: distances[distances.len() / 2],
+        });
+    }
+
+    RangeResponse {
+        devices,
+        timestamp: unix_millis(),
+    }
+}
@@ -0,0 +1,166 @@
+use bluer::Address;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio::time;
+
+use crate::bluetooth::{record_reading, NeighborAddresses, RssiData};
+
+const DEFAULT_WIFI_INTERFACE: &str = "wlan0";
+const DEFAULT_SCAN_INTERVAL: Duration = Duration::from_secs(10);
+/// `IFF_UP` in the flags the kernel reports for a network interface
+const IFF_UP: u32 = 0x1;
+
+// BSSIDs registered with `set_wifi_address`, by the registered Bluetooth address of the node
+pub type WifiAddresses = Arc<Mutex<HashMap<Address, Address>>>;
+
+/// Wi-Fi scan parameters, the `[wifi]` section of the configuration, only applied at startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct WifiConfig {
+    /// Interface the `wifi` backend scans with
+    pub interface: String,
+    /// How often the interface is scanned
+    pub scan_interval: Duration,
+}
+
+impl Default for WifiConfig {
+    fn default() -> Self {
+        Self {
+            interface: DEFAULT_WIFI_INTERFACE.to_string(),
+            scan_interval: DEFAULT_SCAN_INTERVAL,
+        }
+    }
+}
+
+/// Wi-Fi interface the neighbors' BSSIDs are scanned with
+pub struct WifiScanner {
+    pub interface: String,
+    interval: Duration,
+}
+
+impl WifiScanner {
+    /// Scan with the configured interface at the configured interval
+    pub fn new(config: &WifiConfig) -> Self {
+        Self {
+            interface: config.interface.clone(),
+            interval: config.scan_interval,
+        }
+    }
+
+    /// Output of a fresh scan, or of the last scan of the interface if it cannot scan now
+    ///
+    /// Triggering a scan needs `CAP_NET_ADMIN`. Without it, the results of the scans that e.g.
+    /// NetworkManager runs are read instead.
+    async fn scan(&self) -> Result<String, String> {
+        match iw(&["dev", &self.interface, "scan"]).await {
+            Ok(output) => Ok(output),
+            Err(e) => {
+                warn!("⚠️  Wi-Fi scan failed, reading the last results: {}", e);
+                iw(&["dev", &self.interface, "scan", "dump"]).await
+            }
+        }
+    }
+}
+
+async fn iw(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("iw")
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run iw: {}", e))?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// BSSID and RSSI of every network in the output of `iw dev <interface> scan`
+fn parse_scan(output: &str) -> Vec<(Address, i16)> {
+    let mut networks = Vec::new();
+    let mut bssid = None;
+
+    for line in output.lines() {
+        // Every network starts with e.g. `BSS aa:bb:cc:dd:ee:ff(on wlan0)`
+        if let Some(rest) = line.strip_prefix("BSS ") {
+            bssid = rest.get(..17).and_then(|address| address.parse().ok());
+        } else if let Some(signal) = line.trim().strip_prefix("signal: ") {
+            // e.g. `signal: -47.00 dBm`
+            let dbm = signal
+                .split_whitespace()
+                .next()
+                .and_then(|dbm| dbm.parse::<f64>().ok());
+            if let (Some(bssid), Some(dbm)) = (bssid, dbm) {
+                networks.push((bssid, dbm.round() as i16));
+            }
+        }
+    }
+
+    networks
+}
+
+/// MAC address of `interface`, our address when the server runs without Bluetooth
+pub fn interface_address(interface: &str) -> Result<Address, String> {
+    let path = format!("/sys/class/net/{}/address", interface);
+    let address =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    address
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid address in {}: {}", path, address.trim()))
+}
+
+/// Whether `interface` is up
+pub fn interface_up(interface: &str) -> bool {
+    std::fs::read_to_string(format!("/sys/class/net/{}/flags", interface))
+        .ok()
+        .and_then(|flags| u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).ok())
+        .is_some_and(|flags| flags & IFF_UP != 0)
+}
+
+/// Scan the BSSIDs of our neighbors every `WIFI_SCAN_INTERVAL_SECS`, recording their RSSI
+///
+/// A neighbor is measured at its BSSID from `wifi_addresses`, or at its registered address if it
+/// has none, as a node without Bluetooth registers with the MAC of its Wi-Fi interface. Readings
+/// are recorded under the BSSID, which the chain maps back to the neighbor.
+pub async fn start_wifi_scan(
+    scanner: WifiScanner,
+    rssi_data: RssiData,
+    neighbor_addresses: NeighborAddresses,
+    wifi_addresses: WifiAddresses,
+) {
+    info!("Starting Wi-Fi scanning on {}...", scanner.interface);
+
+    loop {
+        match scanner.scan().await {
+            Ok(output) => {
+                let neighbors: Vec<Address> =
+                    neighbor_addresses.lock().await.iter().copied().collect();
+                let bssids: HashSet<Address> = {
+                    let wifi_addresses = wifi_addresses.lock().await;
+                    neighbors
+                        .iter()
+                        .map(|address| wifi_addresses.get(address).copied().unwrap_or(*address))
+                        .collect()
+                };
+
+                for (bssid, rssi) in parse_scan(&output) {
+                    if !bssids.contains(&bssid) {
+                        continue;
+                    }
+
+                    let len = record_reading(&rssi_data, bssid, rssi, None).await;
+                    debug!(
+                        "📶 Wi-Fi RSSI for {}: {} (queue size: {})",
+                        bssid, rssi, len
+                    );
+                }
+            }
+            Err(e) => warn!("⚠️  Wi-Fi scan on {} failed: {}", scanner.interface, e),
+        }
+
+        time::sleep(scanner.interval).await;
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, SubstrateConfig};
+use subxt_signer::{sr25519::Keypair, SecretUri};
+use tokio::time;
+
+use crate::recording::decode_hex;
+use crate::scenario::Node;
+use crate::{distance_meters, reported_location, simulate_scan, AppState};
+
+use substrate::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+
+// This creates a complete, type-safe API for interacting with the runtime.
+#[subxt::subxt(runtime_metadata_path = "../metadata.scale")]
+pub mod substrate {}
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(6); // One round per block
+const DEFAULT_MAX_DISTANCE: u32 = 10; // Matches the runtime constant
+const DEFAULT_MAX_BATCH_SIZE: u32 = 32; // Matches the runtime constant
+
+/// Substrate node to drive from `CHAIN_RPC_URL`, and how often from `CHAIN_SUBMIT_INTERVAL_MS`
+pub fn driver_from_env() -> Result<Option<(String, Duration)>, String> {
+    let Some(url) = std::env::var("CHAIN_RPC_URL")
+        .ok()
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    let interval = crate::millis_from_env("CHAIN_SUBMIT_INTERVAL_MS", DEFAULT_INTERVAL)?;
+    Ok(Some((url, interval)))
+}
+
+/// Coordinates in the fixed-point format the pallet registers with
+pub fn fixed_point((latitude, longitude): (f64, f64)) -> (i64, i64) {
+    (
+        (latitude * 1_000_000.0).round() as i64,
+        (longitude * 1_000_000.0).round() as i64,
+    )
+}
+
+/// Account ID bytes of a node
+pub fn account(node: &Node) -> Result<[u8; 32], String> {
+    decode_hex(node.node_id.trim_start_matches("0x"))?
+        .try_into()
+        .map_err(|_| format!("Invalid node ID of {}: {}", node.name, node.node_id))
+}
+
+/// Drives the chain with the simulated nodes' own keys
+struct ChainDriver {
+    api: OnlineClient<SubstrateConfig>,
+    /// Keys of the nodes given by `suri`, by name
+    keys: HashMap<String, Keypair>,
+    /// Address and fixed-point location each node was last registered with, by name
+    registered: HashMap<String, ([u8; 6], (i64, i64))>,
+    max_distance: f64,
+    max_batch_size: usize,
+}
+
+impl ChainDriver {
+    /// Whether an account is registered
+    async fn is_registered(&self, account: [u8; 32]) -> Result<bool, subxt::Error> {
+        let query = substrate::storage()
+            .proof_of_location()
+            .account_data(AccountId32(account));
+        Ok(self
+            .api
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&query)
+            .await?
+            .is_some())
+    }
+
+    /// Register the node with its current address at its reported location, or update it if it
+    /// was registered with another address or elsewhere
+    async fn sync_registration(
+        &mut self,
+        node: &Node,
+        keypair: &Keypair,
+        address: [u8; 6],
+        location: (i64, i64),
+    ) -> Result<(), String> {
+        if self.registered.get(&node.name) == Some(&(address, location)) {
+            return Ok(());
+        }
+
+        let update = self.registered.contains_key(&node.name)
+            || self
+                .is_registered(keypair.public_key().0)
+                .await
+                .map_err(|e| e.to_string())?;
+        let calls = substrate::tx().proof_of_location();
+        let (call, submitted) = if update {
+            let tx = calls.update_node_info(address, location.0, location.1);
+            (
+                "update_node_info",
+                self.api.tx().sign_and_submit_default(&tx, keypair).await,
+            )
+        } else {
+            let tx = calls.register_node(address, location.0, location.1);
+            (
+                "register_node",
+                self.api.tx().sign_and_submit_default(&tx, keypair).await,
+            )
+        };
+        submitted.map_err(|e| e.to_string())?;
+        println!("⛓️  Submitted {} for {}", call, node.name);
+        self.registered
+            .insert(node.name.clone(), (address, location));
+        Ok(())
+    }
+
+    /// Publish the RSSI a node reports for its neighbors, in batches of at most `MaxBatchSize`
+    ///
+    /// Neighbors the chain would reject, unregistered or registered more than `MaxDistance`
+    /// away, are left out so they do not fail the batch.
+    async fn publish_rssi(
+        &self,
+        state: &AppState,
+        node: &Node,
+        keypair: &Keypair,
+        location: (f64, f64),
+    ) -> Result<usize, String> {
+        let mut readings = Vec::new();
+        for (neighbor, rssi) in simulate_scan(state, node).await {
+            let neighbor_location = reported_location(state, &neighbor);
+            if distance_meters(
+                location.0,
+                location.1,
+                neighbor_location.0,
+                neighbor_location.1,
+            ) > self.max_distance
+            {
+                continue;
+            }
+            let neighbor_account = account(&neighbor)?;
+            if !self.registered.contains_key(&neighbor.name)
+                && !self
+                    .is_registered(neighbor_account)
+                    .await
+                    .map_err(|e| e.to_string())?
+            {
+                continue;
+            }
+            // Simulated nodes all transmit at the reference power, so no TX power is advertised
+            readings.push((AccountId32(neighbor_account), rssi, None));
+        }
+
+        let published = readings.len();
+        for batch in readings.chunks(self.max_batch_size) {
+            let tx = substrate::tx()
+                .proof_of_location()
+                .publish_rssi_data_batch(BoundedVec(batch.to_vec()));
+            self.api
+                .tx()
+                .sign_and_submit_default(&tx, keypair)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(published)
+    }
+}
+
+/// Register the simulated nodes and publish their RSSI every `interval`, in place of one
+/// Substrate node and offchain worker per simulated device
+///
+/// Only nodes given by `suri` can sign; the others are still reported by them as neighbors when
+/// they are registered on chain.
+pub async fn start_chain_driver(
+    state: AppState,
+    url: String,
+    interval: Duration,
+) -> Result<(), String> {
+    let api = OnlineClient::<SubstrateConfig>::from_insecure_url(&url)
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+
+    let mut keys = HashMap::new();
+    for node in state.nodes.read().await.iter() {
+        let Some(suri) = &node.suri else {
+            println!(
+                "⛓️  {} has no suri, the chain driver cannot sign for it",
+                node.name
+            );
+            continue;
+        };
+        let uri = SecretUri::from_str(suri)
+            .map_err(|e| format!("Invalid suri of {}: {}", node.name, e))?;
+        let keypair =
+            Keypair::from_uri(&uri).map_err(|e| format!("Invalid suri of {}: {}", node.name, e))?;
+        keys.insert(node.name.clone(), keypair);
+    }
+
+    let mut driver = ChainDriver {
+        max_distance: api
+            .constants()
+            .at(&substrate::constants().proof_of_location().max_distance())
+            .unwrap_or(DEFAULT_MAX_DISTANCE) as f64,
+        max_batch_size: api
+            .constants()
+            .at(&substrate::constants().proof_of_location().max_batch_size())
+            .unwrap_or(DEFAULT_MAX_BATCH_SIZE)
+            .max(1) as usize,
+        api,
+        keys,
+        registered: HashMap::new(),
+    };
+    println!("⛓️  Driving {} with {} node(s)", url, driver.keys.len());
+
+    let mut ticker = time::interval(interval);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    loop {
+        ticker.tick().await;
+
+        let nodes = state.nodes.read().await.clone();
+        for node in &nodes {
+            let Some(keypair) = driver.keys.get(&node.name).cloned() else {
+                continue;
+            };
+            let location = reported_location(&state, node);
+            let address = node.address_at(state.clock.elapsed_secs());
+            if let Err(e) = driver
+                .sync_registration(node, &keypair, address, fixed_point(location))
+                .await
+            {
+                eprintln!("⚠️  Failed to register {}: {}", node.name, e);
+                continue;
+            }
+        }
+
+        // Registrations made in this round are only usable once they are in a block
+        for node in &nodes {
+            let Some(keypair) = driver.keys.get(&node.name) else {
+                continue;
+            };
+            let location = reported_location(&state, node);
+            match driver.publish_rssi(&state, node, keypair, location).await {
+                Ok(published) => println!(
+                    "⛓️  Published {} RSSI reading(s) of {}",
+                    published, node.name
+                ),
+                Err(e) => eprintln!("⚠️  Failed to publish the RSSI of {}: {}", node.name, e),
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Simulated Bluetooth RSSI server, serving the offchain workers of virtual nodes
+///
+/// Everything else is configured through the environment and the scenario file.
+#[derive(Parser, Debug, Clone)]
+#[command(version, about)]
+pub struct Cli {
+    /// Run without the web UI and exit after `--ticks` ticks, printing a summary of the run
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Ticks to run for when headless, each one MOBILITY_INTERVAL_MS of scenario time
+    #[arg(long, requires = "headless", default_value_t = 60)]
+    pub ticks: u64,
+
+    /// Score the scenario across the parameter grid of this sweep file instead of serving it
+    #[arg(long, value_name = "PATH", conflicts_with = "headless")]
+    pub sweep: Option<PathBuf>,
+}
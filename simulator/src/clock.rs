@@ -0,0 +1,173 @@
+use axum::{
+    body::Body,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::scenario::positive;
+use crate::AppState;
+
+/// Scenario time, running at `speed` times wall time unless paused
+pub struct SimulationClock {
+    state: Mutex<ClockState>,
+}
+
+struct ClockState {
+    paused: bool,
+    speed: f64,
+    /// Scenario seconds elapsed until `since`
+    elapsed: f64,
+    since: Instant,
+}
+
+impl ClockState {
+    fn elapsed(&self) -> f64 {
+        if self.paused {
+            self.elapsed
+        } else {
+            self.elapsed + self.since.elapsed().as_secs_f64() * self.speed
+        }
+    }
+
+    /// Fold the time run so far into `elapsed`, before changing how it runs
+    fn settle(&mut self) {
+        self.elapsed = self.elapsed();
+        self.since = Instant::now();
+    }
+}
+
+/// State of the clock, as returned by the clock API and pushed to the web UI
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClockStatus {
+    pub paused: bool,
+    pub speed: f64,
+    pub elapsed_secs: f64,
+}
+
+/// Body of `POST /api/clock`, fields left out stay as they are
+#[derive(Debug, Deserialize)]
+pub struct ClockUpdate {
+    paused: Option<bool>,
+    speed: Option<f64>,
+}
+
+/// Body of `POST /api/clock/step`
+#[derive(Debug, Deserialize)]
+pub struct ClockStep {
+    seconds: f64,
+}
+
+impl SimulationClock {
+    pub fn new(speed: f64) -> Self {
+        Self {
+            state: Mutex::new(ClockState {
+                paused: false,
+                speed,
+                elapsed: 0.0,
+                since: Instant::now(),
+            }),
+        }
+    }
+
+    /// Scenario seconds since the simulation started
+    pub fn elapsed_secs(&self) -> f64 {
+        self.state.lock().unwrap().elapsed()
+    }
+
+    pub fn status(&self) -> ClockStatus {
+        let state = self.state.lock().unwrap();
+        ClockStatus {
+            paused: state.paused,
+            speed: state.speed,
+            elapsed_secs: state.elapsed(),
+        }
+    }
+
+    pub fn update(&self, update: &ClockUpdate) -> Result<ClockStatus, String> {
+        let speed = update
+            .speed
+            .map(|speed| positive(speed, "speed"))
+            .transpose()?;
+        {
+            let mut state = self.state.lock().unwrap();
+            state.settle();
+            if let Some(paused) = update.paused {
+                state.paused = paused;
+            }
+            if let Some(speed) = speed {
+                state.speed = speed;
+            }
+        }
+        Ok(self.status())
+    }
+
+    /// Move scenario time on by `seconds` at once
+    pub fn step(&self, seconds: f64) -> Result<ClockStatus, String> {
+        let seconds = positive(seconds, "seconds")?;
+        {
+            let mut state = self.state.lock().unwrap();
+            state.settle();
+            state.elapsed += seconds;
+        }
+        Ok(self.status())
+    }
+}
+
+/// Initial speed of the clock, given by `SIMULATION_SPEED`
+pub fn speed_from_env() -> Result<f64, String> {
+    match std::env::var("SIMULATION_SPEED")
+        .ok()
+        .filter(|v| !v.is_empty())
+    {
+        Some(speed) => speed
+            .parse::<f64>()
+            .map_err(|e| e.to_string())
+            .and_then(|speed| positive(speed, "speed"))
+            .map_err(|e| format!("Invalid SIMULATION_SPEED {}: {}", speed, e)),
+        None => Ok(1.0),
+    }
+}
+
+fn clock_response(result: Result<ClockStatus, String>) -> Response {
+    match result {
+        Ok(status) => Json(status).into_response(),
+        Err(e) => Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from(e))
+            .unwrap(),
+    }
+}
+
+pub async fn get_clock(State(state): State<AppState>) -> Json<ClockStatus> {
+    Json(state.clock.status())
+}
+
+/// Pause, resume or change the speed of the clock
+pub async fn update_clock(
+    State(state): State<AppState>,
+    Json(update): Json<ClockUpdate>,
+) -> Response {
+    let result = state.clock.update(&update);
+    if let Ok(status) = &result {
+        println!(
+            "⏱️  Clock {} at {}x",
+            if status.paused { "paused" } else { "running" },
+            status.speed
+        );
+    }
+    clock_response(result)
+}
+
+/// Step the clock, usually while it is paused
+pub async fn step_clock(State(state): State<AppState>, Json(step): Json<ClockStep>) -> Response {
+    let result = state.clock.step(step.seconds);
+    if result.is_ok() {
+        println!("⏭️  Clock stepped {} s", step.seconds);
+    }
+    clock_response(result)
+}
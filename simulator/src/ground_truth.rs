@@ -0,0 +1,256 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Duration;
+use tokio::time;
+
+use crate::scenario::{format_address, Attack, Node};
+use crate::{floor_loss, millis_from_env, node_distance, unix_millis, wall_loss, AppState};
+
+/// One snapshot per block at the default block time
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(6);
+
+const CSV_HEADER: &str = "timestamp_ms,scenario_secs,from,from_node_id,from_latitude,from_longitude,from_altitude,from_lying,to,to_node_id,to_latitude,to_longitude,to_altitude,to_lying,distance_m,rssi,dropout,outage";
+
+/// What a node really is, next to what it claims
+#[derive(Debug, Clone, Serialize)]
+struct NodeTruth {
+    name: String,
+    node_id: String,
+    /// Address the node advertises right now, one of its rotation if it rotates
+    address: String,
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+    /// Location the node reports, differing from its position for a GPS liar
+    reported_latitude: f64,
+    reported_longitude: f64,
+    attack: Option<&'static str>,
+    colluding: bool,
+    /// Whether the trust scores should flag the node, by an attack or collusion
+    lying: bool,
+}
+
+/// True distance and noiseless RSSI between two nodes
+#[derive(Debug, Clone, Serialize)]
+struct LinkTruth {
+    from: String,
+    to: String,
+    /// Straight-line distance, the height difference included
+    distance_m: f64,
+    /// Noiseless RSSI over the true distance and through the walls and floors, none when either
+    /// node has no radio
+    rssi: Option<f64>,
+    /// Probability that a scan of either node misses the other
+    dropout: f64,
+    /// Whether the nodes cannot hear each other at all right now
+    outage: bool,
+}
+
+/// Ground truth of the simulation at one instant
+#[derive(Debug, Clone, Serialize)]
+struct Snapshot {
+    /// Unix time in milliseconds, to line the snapshot up with block timestamps
+    timestamp_ms: u64,
+    scenario_secs: f64,
+    nodes: Vec<NodeTruth>,
+    links: Vec<LinkTruth>,
+}
+
+/// Format of the ground truth, picked with `/api/ground-truth?format=`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GroundTruthQuery {
+    #[serde(default)]
+    format: Format,
+}
+
+fn snapshot(state: &AppState, nodes: &[Node]) -> Snapshot {
+    let scenario_secs = state.clock.elapsed_secs();
+    let truths: Vec<NodeTruth> = nodes
+        .iter()
+        .map(|node| {
+            let colluding = state
+                .collusions
+                .iter()
+                .any(|c| c.members.contains(&node.name));
+            let (reported_latitude, reported_longitude) = match &node.attack {
+                Some(Attack::GpsLiar {
+                    latitude,
+                    longitude,
+                }) => (*latitude, *longitude),
+                _ => (node.latitude, node.longitude),
+            };
+            NodeTruth {
+                name: node.name.clone(),
+                node_id: node.node_id.clone(),
+                address: format_address(node.address_at(scenario_secs)),
+                latitude: node.latitude,
+                longitude: node.longitude,
+                altitude: node.altitude,
+                reported_latitude,
+                reported_longitude,
+                attack: node.attack.as_ref().map(Attack::kind),
+                colluding,
+                lying: node.attack.is_some() || colluding,
+            }
+        })
+        .collect();
+
+    let mut links = Vec::new();
+    for (i, from) in nodes.iter().enumerate() {
+        for to in &nodes[i + 1..] {
+            let distance_m = node_distance(from, to);
+            let silent = [from, to]
+                .iter()
+                .any(|node| node.attack == Some(Attack::Ghost));
+            let lossy = state
+                .links
+                .iter()
+                .find(|link| link.connects(&from.name, &to.name));
+            links.push(LinkTruth {
+                from: from.name.clone(),
+                to: to.name.clone(),
+                distance_m,
+                rssi: (!silent).then(|| {
+                    state.propagation.mean_rssi(distance_m)
+                        - wall_loss(from, to, None, &state.walls)
+                        - floor_loss(from, to, None, state.floors)
+                }),
+                dropout: lossy.map_or(0.0, |link| link.dropout),
+                outage: lossy.is_some_and(|link| link.in_outage(scenario_secs)),
+            });
+        }
+    }
+
+    Snapshot {
+        timestamp_ms: unix_millis(),
+        scenario_secs,
+        nodes: truths,
+        links,
+    }
+}
+
+/// Quote a CSV field if it needs to be
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One CSV row per pair of nodes, each with both nodes' truth
+fn csv_rows(snapshot: &Snapshot) -> Vec<String> {
+    snapshot
+        .links
+        .iter()
+        .filter_map(|link| {
+            let from = snapshot.nodes.iter().find(|n| n.name == link.from)?;
+            let to = snapshot.nodes.iter().find(|n| n.name == link.to)?;
+            Some(format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                snapshot.timestamp_ms,
+                snapshot.scenario_secs,
+                csv_field(&from.name),
+                from.node_id,
+                from.latitude,
+                from.longitude,
+                from.altitude,
+                from.lying,
+                csv_field(&to.name),
+                to.node_id,
+                to.latitude,
+                to.longitude,
+                to.altitude,
+                to.lying,
+                link.distance_m,
+                link.rssi.map(|rssi| rssi.to_string()).unwrap_or_default(),
+                link.dropout,
+                link.outage
+            ))
+        })
+        .collect()
+}
+
+/// Current ground truth, as JSON or CSV
+pub async fn get_ground_truth(
+    State(state): State<AppState>,
+    Query(query): Query<GroundTruthQuery>,
+) -> Response {
+    let truth = snapshot(&state, &state.nodes.read().await);
+    match query.format {
+        Format::Json => Json(truth).into_response(),
+        Format::Csv => {
+            let mut csv = vec![CSV_HEADER.to_string()];
+            csv.extend(csv_rows(&truth));
+            ([(header::CONTENT_TYPE, "text/csv")], csv.join("\n") + "\n").into_response()
+        }
+    }
+}
+
+/// File to export the ground truth to from `GROUND_TRUTH_PATH`, CSV if it ends in `.csv` and JSON
+/// lines otherwise
+pub fn export_from_env() -> Result<Option<(String, Format, Duration)>, String> {
+    let Some(path) = std::env::var("GROUND_TRUTH_PATH")
+        .ok()
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    let format = if path.ends_with(".csv") {
+        Format::Csv
+    } else {
+        Format::Json
+    };
+    let interval = millis_from_env("GROUND_TRUTH_INTERVAL_MS", DEFAULT_INTERVAL)?;
+    Ok(Some((path, format, interval)))
+}
+
+/// Append a ground truth snapshot to `path` every `interval`
+pub async fn start_export(
+    state: AppState,
+    path: String,
+    format: Format,
+    interval: Duration,
+) -> Result<(), String> {
+    let file = File::create(&path)
+        .map_err(|e| format!("Failed to create ground truth {}: {}", path, e))?;
+    let mut file = BufWriter::new(file);
+    if format == Format::Csv {
+        writeln!(file, "{}", CSV_HEADER).map_err(|e| e.to_string())?;
+    }
+
+    let mut ticker = time::interval(interval);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    loop {
+        ticker.tick().await;
+
+        let truth = snapshot(&state, &state.nodes.read().await);
+        let lines = match format {
+            Format::Json => vec![serde_json::to_string(&truth).map_err(|e| e.to_string())?],
+            Format::Csv => csv_rows(&truth),
+        };
+        // Flushed snapshot by snapshot, so the file can be scored while the run goes on
+        let written = lines
+            .iter()
+            .try_for_each(|line| writeln!(file, "{}", line))
+            .and_then(|_| file.flush());
+        if let Err(e) = written {
+            eprintln!("⚠️  Failed to export the ground truth: {}", e);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+use tokio::time::{self, Instant};
+
+use crate::mobility::MAX_WALL_TICK;
+use crate::AppState;
+
+/// Wait until `ticks` steps of `interval` scenario time have passed on the clock
+pub async fn run_ticks(state: &AppState, ticks: u64, interval: Duration) {
+    let end = ticks as f64 * interval.as_secs_f64();
+    let mut ticker = time::interval(interval.min(MAX_WALL_TICK));
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    while state.clock.elapsed_secs() < end {
+        ticker.tick().await;
+    }
+}
+
+/// Print where the nodes ended up and what was served, for batch jobs to read from the log
+pub async fn print_summary(state: &AppState, ticks: u64, started: Instant) {
+    println!(
+        "\n📋 Summary of {} tick(s): {:.1} s of scenario time in {:.1} s",
+        ticks,
+        state.clock.elapsed_secs(),
+        started.elapsed().as_secs_f64()
+    );
+    for node in state.nodes.read().await.iter() {
+        println!(
+            "  {}: lat={}, lon={}, served {} RSSI and {} location request(s)",
+            node.name,
+            node.latitude,
+            node.longitude,
+            state.metrics.requests(&node.name, "rssi"),
+            state.metrics.requests(&node.name, "location")
+        );
+    }
+    let (samples, average_distance) = state.metrics.distances();
+    println!(
+        "  📡 {} RSSI sample(s) at {:.2} m on average",
+        samples, average_distance
+    );
+    let faults = state.metrics.faults();
+    if faults.is_empty() {
+        println!("  💉 No injected faults");
+    }
+    for (kind, count) in faults {
+        println!("  💉 {} {} fault(s)", count, kind);
+    }
+}
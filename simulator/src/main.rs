@@ -1,59 +1,108 @@
 use axum::{
     body::Body,
-    extract::{Request, State},
+    extract::{Query, Request, State},
     http::{header, StatusCode},
     response::{Html, IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use codec::{Decode, Encode};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::sync::RwLock;
 
-const ALICE_NODE_ID: &str = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
-const ALICE_BLUETOOTH_ADDRESS: &str = "AA:BB:CC:DD:EE:01";
-
-const BOB_NODE_ID: &str = "0x8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48";
-const BOB_BLUETOOTH_ADDRESS: &str = "AA:BB:CC:DD:EE:02";
-const BOB_LATITUDE: f64 = 0.00001;
-const BOB_LONGITUDE: f64 = 0.0;
-
-const CHARLIE_NODE_ID: &str = "0x90b5ab205c6974c9ea841be688864633dc9ca8a357843eeacf2314649965fe22";
-const CHARLIE_BLUETOOTH_ADDRESS: &str = "AA:BB:CC:DD:EE:03";
-const CHARLIE_LATITUDE: f64 = -0.00001;
-const CHARLIE_LONGITUDE: f64 = 0.0;
+mod chain;
+mod cli;
+mod clock;
+mod ground_truth;
+mod headless;
+mod map;
+mod metrics;
+mod mobility;
+mod propagation;
+mod recording;
+mod scenario;
+mod server_faults;
+mod stats;
+mod stream;
+mod sweep;
+mod validation;
+use clap::Parser;
+use cli::Cli;
+use clock::SimulationClock;
+use map::{MapTiles, PositionsFormat, PositionsQuery};
+use metrics::{Metrics, NOISE_OUTLIER_SIGMAS};
+use propagation::PropagationModel;
+use recording::{Endpoint, Event, Recorder, Replay};
+use scenario::{
+    format_address, Attack, Collusion, Floors, LossyLink, Mobility, Node, Propagation, Scenario,
+    ServerFaults, Wall, Wormhole,
+};
+use validation::Validation;
 
-const DAVE_NODE_ID: &str = "0x306721211d5404bd9da88e0204360a1a9ab8b87c66c1bc2fcdd37f3c2222cc20";
-const DAVE_BLUETOOTH_ADDRESS: &str = "AA:BB:CC:DD:EE:04";
-const DAVE_LATITUDE: f64 = 0.0;
-const DAVE_LONGITUDE: f64 = 0.00001;
+/// The simulated nodes, moved by the web UI and their mobility models
+type SharedNodes = Arc<RwLock<Vec<Node>>>;
 
-const EVE_NODE_ID: &str = "0xe659a7a1628cdd93febc04a4e0646ea20e9f5f0ce097d9a05290d4a9e054df4e";
-const EVE_BLUETOOTH_ADDRESS: &str = "AA:BB:CC:DD:EE:05";
-const EVE_LATITUDE: f64 = 0.0;
-const EVE_LONGITUDE: f64 = -0.00001;
+/// Offsets of the seed of each random stream, so the mobility models and the radio noise do not
+/// disturb each other's sequence
+const MOBILITY_STREAM: u64 = 0;
+const PROPAGATION_STREAM: u64 = 1;
 
-const REFERENCE_RSSI: f64 = -48.0;
-const PATH_LOSS_EXPONENT: f64 = 4.0;
+#[derive(Clone)]
+struct AppState {
+    nodes: SharedNodes,
+    collusions: Arc<Vec<Collusion>>,
+    wormholes: Arc<Vec<Wormhole>>,
+    links: Arc<Vec<LossyLink>>,
+    walls: Arc<Vec<Wall>>,
+    floors: Option<Floors>,
+    propagation: Arc<dyn PropagationModel>,
+    /// Source of the radio noise, seeded for reproducible runs
+    rng: Arc<Mutex<StdRng>>,
+    /// Records the run to `RECORD_PATH`
+    recorder: Option<Arc<Recorder>>,
+    /// Recorded run replayed from `REPLAY_PATH`, served instead of simulated responses
+    replay: Option<Arc<Replay>>,
+    /// How often positions are pushed to the web UI
+    stream_interval: Duration,
+    /// Map tiles drawn under the nodes in the web UI
+    map_tiles: Option<Arc<MapTiles>>,
+    /// Server errors injected into the responses
+    server_faults: Arc<ServerFaults>,
+    /// Scenario time the mobility models move in
+    clock: Arc<SimulationClock>,
+    /// Scenario seconds a headless run stops at
+    end_secs: Option<f64>,
+    metrics: Arc<Metrics>,
+    /// Latest trust scores of the chain compared with the ground truth
+    validation: Arc<Mutex<Option<Validation>>>,
+}
 
+/// Body of `/api/update-position`
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct AlicePosition {
+struct PositionUpdate {
+    name: String,
     latitude: f64,
     longitude: f64,
 }
-type SharedState = Arc<RwLock<AlicePosition>>;
 
 #[derive(Encode, Decode, Debug, Clone)]
 struct DeviceRssi {
     address: [u8; 6],
     rssi: i16,
+    tx_power: Option<i16>,
+    timestamp: u64,
 }
 #[derive(Encode, Decode, Debug, Clone)]
 struct RssiResponse {
     devices: Vec<DeviceRssi>,
+    timestamp: u64,
+    sequence: Option<u64>,
 }
 #[derive(Encode, Decode, Debug, Clone)]
 struct Location {
@@ -64,129 +113,244 @@ struct Location {
 struct LocationResponse {
     address: [u8; 6],
     location: Location,
+    timestamp: u64,
+    sequence: Option<u64>,
 }
 
-fn estimate_rssi(a_lat: f64, a_lon: f64, b_lat: f64, b_lon: f64) -> i16 {
+/// Positive number of milliseconds in the environment variable `name`, `default` if it is not set
+fn millis_from_env(name: &str, default: Duration) -> Result<Duration, String> {
+    match std::env::var(name).ok().filter(|v| !v.is_empty()) {
+        Some(ms) => ms
+            .parse::<u64>()
+            .ok()
+            .filter(|ms| *ms > 0)
+            .map(Duration::from_millis)
+            .ok_or_else(|| format!("Invalid {}: {}", name, ms)),
+        None => Ok(default),
+    }
+}
+
+/// Current Unix time in milliseconds, sent so the offchain worker can reject stale responses
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn distance_meters(a_lat: f64, a_lon: f64, b_lat: f64, b_lon: f64) -> f64 {
     use haversine_redux::Location;
-    use rand::{thread_rng, Rng};
-    use rand_distr::Normal;
 
     let a = Location::new(a_lat, a_lon);
     let b = Location::new(b_lat, b_lon);
-    let dist = a.kilometers_to(&b) * 1000.0; // convert kilometers to meters
-    let rssi = if dist != 0.0 {
-        REFERENCE_RSSI - PATH_LOSS_EXPONENT * 10.0 * dist.log10()
-    } else {
-        0.0
+    a.kilometers_to(&b) * 1000.0 // convert kilometers to meters
+}
+
+/// Distance between two nodes in meters, their height difference included
+fn node_distance(a: &Node, b: &Node) -> f64 {
+    let horizontal = distance_meters(a.latitude, a.longitude, b.latitude, b.longitude);
+    horizontal.hypot(a.altitude - b.altitude)
+}
+
+/// Ends of a wormhole, the one near the node hearing through it first
+type Tunnel = ([f64; 2], [f64; 2]);
+
+/// Distance `requester` hears `other` from, and the wormhole it is tunneled through if it is
+///
+/// A wormhole replays `other` at its end near `requester`, which only counts when that is closer
+/// than `other` itself. Wormhole ends are at the height of the node next to them.
+fn heard_distance(requester: &Node, other: &Node, wormholes: &[Wormhole]) -> (f64, Option<Tunnel>) {
+    let mut heard = (node_distance(requester, other), None);
+    for wormhole in wormholes {
+        for (near, far) in [
+            (wormhole.ends[0], wormhole.ends[1]),
+            (wormhole.ends[1], wormhole.ends[0]),
+        ] {
+            let to_near =
+                distance_meters(requester.latitude, requester.longitude, near[0], near[1]);
+            let to_far = distance_meters(other.latitude, other.longitude, far[0], far[1]);
+            if to_near <= wormhole.range_m && to_far <= wormhole.range_m && to_near < heard.0 {
+                heard = (to_near, Some((near, far)));
+            }
+        }
+    }
+    heard
+}
+
+/// dB the walls take off the signal of `other` on its way to `requester`, through `tunnel` if it
+/// is tunneled
+fn wall_loss(requester: &Node, other: &Node, tunnel: Option<Tunnel>, walls: &[Wall]) -> f64 {
+    let requester_at = [requester.latitude, requester.longitude];
+    let other_at = [other.latitude, other.longitude];
+    let paths = match tunnel {
+        Some((near, far)) => vec![(requester_at, near), (far, other_at)],
+        None => vec![(requester_at, other_at)],
     };
-    let noise = thread_rng().sample(Normal::new(0.0, 2.0).unwrap());
-    (rssi + noise) as i16
+    paths
+        .iter()
+        .flat_map(|(a, b)| walls.iter().filter(|wall| wall.blocks(*a, *b)))
+        .map(|wall| wall.attenuation_db)
+        .sum()
+}
+
+/// dB the floors take off the signal between two nodes at different heights
+///
+/// A tunneled signal is replayed on the floor of `requester`, so it crosses no floors.
+fn floor_loss(
+    requester: &Node,
+    other: &Node,
+    tunnel: Option<Tunnel>,
+    floors: Option<Floors>,
+) -> f64 {
+    match (floors, tunnel) {
+        (Some(floors), None) => {
+            floors.between(requester.altitude, other.altitude) * floors.attenuation_db
+        }
+        _ => 0.0,
+    }
+}
+
+/// RSSI `requester` and `other` made up for each other, if they collude
+fn colluding_rssi(requester: &Node, other: &Node, collusions: &[Collusion]) -> Option<i16> {
+    collusions
+        .iter()
+        .find(|c| c.members.contains(&requester.name) && c.members.contains(&other.name))
+        .map(|c| c.rssi)
 }
 
-fn parse_bluetooth_address(addr_str: &str) -> Result<[u8; 6], Box<dyn Error>> {
-    let parts: Vec<&str> = addr_str.split(':').collect();
-    if parts.len() != 6 {
-        return Err("Invalid Bluetooth address format".into());
+/// Whether a scan of `requester` misses `other`, and if so the `kind` of fault it counts as
+///
+/// Only links with a dropout draw from `rng`, so scenarios without any keep their noise sequence.
+fn missed(
+    requester: &Node,
+    other: &Node,
+    links: &[LossyLink],
+    secs: f64,
+    rng: &mut StdRng,
+) -> Option<&'static str> {
+    let link = links
+        .iter()
+        .find(|link| link.connects(&requester.name, &other.name))?;
+    if link.in_outage(secs) {
+        Some("outage")
+    } else if link.dropout > 0.0 && rng.gen_bool(link.dropout) {
+        Some("dropout")
+    } else {
+        None
     }
-    let mut address = [0u8; 6];
-    for (i, part) in parts.iter().enumerate() {
-        address[i] = u8::from_str_radix(part, 16)?;
+}
+
+/// Next recorded response of `endpoint` to the node
+async fn replay_response(replay: &Replay, endpoint: Endpoint, node: &Node) -> Response {
+    match replay.respond(endpoint, &node.node_id).await {
+        Some(body) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .body(Body::from(body))
+            .unwrap(),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from(format!(
+                "No more recorded responses for {}",
+                node.name
+            )))
+            .unwrap(),
     }
-    Ok(address)
 }
 
-async fn get_node_info(
-    node_id: &str,
-    state: &SharedState,
-) -> Option<(String, &'static str, f64, f64)> {
-    match node_id {
-        ALICE_NODE_ID => {
-            let alice_pos = state.read().await;
-            Some((
-                "Alice".to_string(),
-                ALICE_BLUETOOTH_ADDRESS,
-                alice_pos.latitude,
-                alice_pos.longitude,
-            ))
+/// Node whose account sent `X-Node-ID`
+async fn find_node(node_id: &str, nodes: &SharedNodes) -> Option<Node> {
+    nodes
+        .read()
+        .await
+        .iter()
+        .find(|node| node.node_id.eq_ignore_ascii_case(node_id))
+        .cloned()
+}
+
+/// RSSI `requester` reports for each of the other nodes it hears, with dropouts, noise, attacks
+/// and collusion applied
+async fn simulate_scan(state: &AppState, requester: &Node) -> Vec<(Node, i16)> {
+    // Ghosts have no radio to be heard with
+    let others: Vec<Node> = state
+        .nodes
+        .read()
+        .await
+        .iter()
+        .filter(|node| node.node_id != requester.node_id && node.attack != Some(Attack::Ghost))
+        .cloned()
+        .collect();
+    let secs = state.clock.elapsed_secs();
+    let mut readings = Vec::new();
+    for other in others {
+        let lost = missed(
+            requester,
+            &other,
+            &state.links,
+            secs,
+            &mut state.rng.lock().unwrap(),
+        );
+        if let Some(kind) = lost {
+            state.metrics.record_fault(kind);
+            continue;
+        }
+        let (dist, tunnel) = heard_distance(requester, &other, &state.wormholes);
+        let (rssi, noise) = state.propagation.rssi(dist, &mut state.rng.lock().unwrap());
+        let mut rssi = (rssi
+            - wall_loss(requester, &other, tunnel, &state.walls)
+            - floor_loss(requester, &other, tunnel, state.floors)) as i16;
+        state.metrics.record_distance(dist);
+        if state.propagation.sigma() > 0.0
+            && noise.abs() > NOISE_OUTLIER_SIGMAS * state.propagation.sigma()
+        {
+            state.metrics.record_fault("noise_outlier");
+        }
+        if tunnel.is_some() {
+            state.metrics.record_fault("wormhole");
+        }
+        // A ghost makes up what it would hear at its claimed position, an inflater boosts it
+        match &requester.attack {
+            Some(attack @ Attack::RssiInflater { boost_db }) => {
+                rssi = rssi.saturating_add(*boost_db);
+                state.metrics.record_fault(attack.kind());
+            }
+            Some(attack @ Attack::Ghost) => state.metrics.record_fault(attack.kind()),
+            _ => {}
         }
-        BOB_NODE_ID => Some((
-            "Bob".to_string(),
-            BOB_BLUETOOTH_ADDRESS,
-            BOB_LATITUDE,
-            BOB_LONGITUDE,
-        )),
-        CHARLIE_NODE_ID => Some((
-            "Charlie".to_string(),
-            CHARLIE_BLUETOOTH_ADDRESS,
-            CHARLIE_LATITUDE,
-            CHARLIE_LONGITUDE,
-        )),
-        DAVE_NODE_ID => Some((
-            "Dave".to_string(),
-            DAVE_BLUETOOTH_ADDRESS,
-            DAVE_LATITUDE,
-            DAVE_LONGITUDE,
-        )),
-        EVE_NODE_ID => Some((
-            "Eve".to_string(),
-            EVE_BLUETOOTH_ADDRESS,
-            EVE_LATITUDE,
-            EVE_LONGITUDE,
-        )),
-        _ => None,
+        if let Some(fabricated) = colluding_rssi(requester, &other, &state.collusions) {
+            rssi = fabricated;
+            state.metrics.record_fault("collusion");
+        }
+        readings.push((other, rssi));
     }
+    readings
 }
 
-async fn get_all_nodes(state: &SharedState) -> Vec<(&'static str, String, &'static str, f64, f64)> {
-    let alice_pos = state.read().await;
-    vec![
-        (
-            ALICE_NODE_ID,
-            "Alice".to_string(),
-            ALICE_BLUETOOTH_ADDRESS,
-            alice_pos.latitude,
-            alice_pos.longitude,
-        ),
-        (
-            BOB_NODE_ID,
-            "Bob".to_string(),
-            BOB_BLUETOOTH_ADDRESS,
-            BOB_LATITUDE,
-            BOB_LONGITUDE,
-        ),
-        (
-            CHARLIE_NODE_ID,
-            "Charlie".to_string(),
-            CHARLIE_BLUETOOTH_ADDRESS,
-            CHARLIE_LATITUDE,
-            CHARLIE_LONGITUDE,
-        ),
-        (
-            DAVE_NODE_ID,
-            "Dave".to_string(),
-            DAVE_BLUETOOTH_ADDRESS,
-            DAVE_LATITUDE,
-            DAVE_LONGITUDE,
-        ),
-        (
-            EVE_NODE_ID,
-            "Eve".to_string(),
-            EVE_BLUETOOTH_ADDRESS,
-            EVE_LATITUDE,
-            EVE_LONGITUDE,
-        ),
-    ]
+/// Location `node` reports, a false one for a GPS liar
+fn reported_location(state: &AppState, node: &Node) -> (f64, f64) {
+    match &node.attack {
+        Some(
+            attack @ Attack::GpsLiar {
+                latitude,
+                longitude,
+            },
+        ) => {
+            state.metrics.record_fault(attack.kind());
+            (*latitude, *longitude)
+        }
+        _ => (node.latitude, node.longitude),
+    }
 }
 
-async fn scan_rssi(State(state): State<SharedState>, req: Request) -> impl IntoResponse {
+async fn scan_rssi(State(state): State<AppState>, req: Request) -> impl IntoResponse {
     let node_id = req
         .headers()
         .get("X-Node-ID")
         .and_then(|v| v.to_str().ok())
         .unwrap_or("unknown");
     println!("📡 RSSI request from node: {}", node_id);
-    let (_, _, requester_lat, requester_lon) = match get_node_info(node_id, &state).await {
-        Some(info) => info,
+    let requester = match find_node(node_id, &state.nodes).await {
+        Some(node) => node,
         None => {
             let error_msg = format!("Unknown node ID: {}", node_id);
             return Response::builder()
@@ -195,27 +359,47 @@ async fn scan_rssi(State(state): State<SharedState>, req: Request) -> impl IntoR
                 .unwrap();
         }
     };
+    state.metrics.record_request(&requester.name, "rssi");
+    if let Some(replay) = &state.replay {
+        return replay_response(replay, Endpoint::Rssi, &requester).await;
+    }
+    let injected = match server_faults::inject(&state, Endpoint::Rssi).await {
+        Ok(injected) => injected,
+        Err(response) => return response,
+    };
+    let secs = state.clock.elapsed_secs();
     let mut devices = Vec::new();
-    for (other_node_id, name, bluetooth_addr_str, other_lat, other_lon) in
-        get_all_nodes(&state).await
-    {
-        if other_node_id == node_id {
-            continue;
-        }
-        let address = match parse_bluetooth_address(bluetooth_addr_str) {
-            Ok(addr) => addr,
-            Err(e) => {
-                eprintln!("Failed to parse Bluetooth address for {}: {}", name, e);
-                continue;
-            }
-        };
-        let rssi = estimate_rssi(requester_lat, requester_lon, other_lat, other_lon);
-        devices.push(DeviceRssi { address, rssi });
-        println!("  {} ({}): RSSI = {} dBm", name, bluetooth_addr_str, rssi);
+    for (other, rssi) in simulate_scan(&state, &requester).await {
+        // Simulated nodes all transmit at the reference power, so no TX power is advertised
+        let address = other.address_at(secs);
+        state
+            .metrics
+            .record_rssi(&requester.name, &other.name, rssi);
+        devices.push(DeviceRssi {
+            address,
+            rssi,
+            tx_power: None,
+            timestamp: injected.timestamp(),
+        });
+        println!(
+            "  {} ({}): RSSI = {} dBm",
+            other.name,
+            format_address(address),
+            rssi
+        );
     }
     println!("Returning RSSI data for {} devices\n", devices.len());
-    let response = RssiResponse { devices };
-    let encoded = response.encode();
+    let response = RssiResponse {
+        devices,
+        timestamp: injected.timestamp(),
+        sequence: None,
+    };
+    let encoded = injected.body(response.encode());
+    if let Some(recorder) = &state.recorder {
+        recorder.record_response(Endpoint::Rssi, &requester.node_id, &encoded);
+    }
+    // Sent after the latency, so the timestamps are as old as they would be over a slow network
+    server_faults::delay(&state, &requester).await;
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "application/octet-stream")
@@ -223,16 +407,15 @@ async fn scan_rssi(State(state): State<SharedState>, req: Request) -> impl IntoR
         .unwrap()
 }
 
-async fn get_location(State(state): State<SharedState>, req: Request) -> impl IntoResponse {
+async fn get_location(State(state): State<AppState>, req: Request) -> impl IntoResponse {
     let node_id = req
         .headers()
         .get("X-Node-ID")
         .and_then(|v| v.to_str().ok())
         .unwrap_or("unknown");
     println!("📍 Location request from node: {}", node_id);
-    let (_, bluetooth_address_str, latitude, longitude) = match get_node_info(node_id, &state).await
-    {
-        Some(info) => info,
+    let node = match find_node(node_id, &state.nodes).await {
+        Some(node) => node,
         None => {
             let error_msg = format!("Unknown node ID: {}", node_id);
             return Response::builder()
@@ -241,28 +424,33 @@ async fn get_location(State(state): State<SharedState>, req: Request) -> impl In
                 .unwrap();
         }
     };
-    let address = match parse_bluetooth_address(bluetooth_address_str) {
-        Ok(addr) => addr,
-        Err(e) => {
-            let error_msg = format!("Failed to parse Bluetooth address: {}", e);
-            return Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .body(Body::from(error_msg))
-                .unwrap();
-        }
+    state.metrics.record_request(&node.name, "location");
+    if let Some(replay) = &state.replay {
+        return replay_response(replay, Endpoint::Location, &node).await;
+    }
+    let injected = match server_faults::inject(&state, Endpoint::Location).await {
+        Ok(injected) => injected,
+        Err(response) => return response,
     };
+    let (latitude, longitude) = reported_location(&state, &node);
     let response = LocationResponse {
-        address,
+        address: node.address_at(state.clock.elapsed_secs()),
         location: Location {
             latitude,
             longitude,
         },
+        timestamp: injected.timestamp(),
+        sequence: None,
     };
     println!(
         "Returning location for node {}: lat={}, lon={}",
         node_id, latitude, longitude
     );
-    let encoded = response.encode();
+    let encoded = injected.body(response.encode());
+    if let Some(recorder) = &state.recorder {
+        recorder.record_response(Endpoint::Location, &node.node_id, &encoded);
+    }
+    server_faults::delay(&state, &node).await;
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "application/octet-stream")
@@ -270,61 +458,103 @@ async fn get_location(State(state): State<SharedState>, req: Request) -> impl In
         .unwrap()
 }
 
-async fn update_alice_position(
-    State(state): State<SharedState>,
-    Json(new_pos): Json<AlicePosition>,
-) -> impl IntoResponse {
-    let mut alice_pos = state.write().await;
-    *alice_pos = new_pos.clone();
+/// Move a node with manual mobility
+async fn update_position(
+    State(state): State<AppState>,
+    Json(update): Json<PositionUpdate>,
+) -> Response {
+    let mut nodes = state.nodes.write().await;
+    let Some(node) = nodes.iter_mut().find(|node| node.name == update.name) else {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from(format!("Unknown node: {}", update.name)))
+            .unwrap();
+    };
+    if node.mobility != Mobility::Manual {
+        return Response::builder()
+            .status(StatusCode::CONFLICT)
+            .body(Body::from(format!(
+                "{} cannot be moved manually",
+                update.name
+            )))
+            .unwrap();
+    }
+    node.latitude = update.latitude;
+    node.longitude = update.longitude;
+    if let Some(recorder) = &state.recorder {
+        recorder.record(Event::Position {
+            name: update.name.clone(),
+            latitude: update.latitude,
+            longitude: update.longitude,
+        });
+    }
     println!(
-        "🔄 Updated Alice's position to: lat={}, lon={}",
-        new_pos.latitude, new_pos.longitude
+        "🔄 Updated {}'s position to: lat={}, lon={}",
+        update.name, update.latitude, update.longitude
     );
-    Json(new_pos)
+    Json(update).into_response()
 }
 
-async fn get_positions(State(state): State<SharedState>) -> impl IntoResponse {
-    let alice_pos = state.read().await;
-    #[derive(Serialize)]
-    struct NodePosition {
-        name: String,
-        latitude: f64,
-        longitude: f64,
-        color: String,
-    }
-    let positions = vec![
-        NodePosition {
-            name: "Alice".to_string(),
-            latitude: alice_pos.latitude,
-            longitude: alice_pos.longitude,
-            color: "#e74c3c".to_string(),
-        },
-        NodePosition {
-            name: "Bob".to_string(),
-            latitude: BOB_LATITUDE,
-            longitude: BOB_LONGITUDE,
-            color: "#3498db".to_string(),
-        },
-        NodePosition {
-            name: "Charlie".to_string(),
-            latitude: CHARLIE_LATITUDE,
-            longitude: CHARLIE_LONGITUDE,
-            color: "#2ecc71".to_string(),
-        },
-        NodePosition {
-            name: "Dave".to_string(),
-            latitude: DAVE_LATITUDE,
-            longitude: DAVE_LONGITUDE,
-            color: "#f39c12".to_string(),
-        },
-        NodePosition {
-            name: "Eve".to_string(),
-            latitude: EVE_LATITUDE,
-            longitude: EVE_LONGITUDE,
-            color: "#9b59b6".to_string(),
-        },
-    ];
-    Json(positions)
+/// A node as shown in the web UI
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct NodePosition {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    altitude: f64,
+    color: String,
+    /// Whether the UI lets the node be dragged
+    draggable: bool,
+    /// Kind of attack of a malicious node
+    attack: Option<&'static str>,
+}
+
+fn node_positions(nodes: &[Node]) -> Vec<NodePosition> {
+    nodes
+        .iter()
+        .map(|node| NodePosition {
+            name: node.name.clone(),
+            latitude: node.latitude,
+            longitude: node.longitude,
+            altitude: node.altitude,
+            color: node.color.clone(),
+            draggable: node.mobility == Mobility::Manual,
+            attack: node.attack.as_ref().map(Attack::kind),
+        })
+        .collect()
+}
+
+/// Positions of the nodes, as GeoJSON with `?format=geojson`
+async fn get_positions(
+    State(state): State<AppState>,
+    Query(query): Query<PositionsQuery>,
+) -> Response {
+    let positions = node_positions(&state.nodes.read().await);
+    match query.format {
+        PositionsFormat::Json => Json(positions).into_response(),
+        PositionsFormat::GeoJson => (
+            [(header::CONTENT_TYPE, "application/geo+json")],
+            Json(map::positions_geojson(&positions, &state.walls)),
+        )
+            .into_response(),
+    }
+}
+
+async fn get_metrics(State(state): State<AppState>) -> Response {
+    state
+        .metrics
+        .record_progress(state.clock.elapsed_secs(), state.end_secs);
+    match state.metrics.encode() {
+        Ok(body) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(Body::from(body))
+            .unwrap(),
+        Err(e) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from(format!("Failed to encode metrics: {}", e)))
+            .unwrap(),
+    }
 }
 
 async fn serve_ui() -> Html<&'static str> {
@@ -333,28 +563,268 @@ async fn serve_ui() -> Html<&'static str> {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
     println!("Starting Location Simulator Server...\n");
-    let state = Arc::new(RwLock::new(AlicePosition {
-        latitude: 0.00001,
-        longitude: 0.00001,
-    }));
-    let app = Router::new()
-        .route("/", get(serve_ui))
+    let recorder = recording::recorder_from_env()?.map(Arc::new);
+    let replay = recording::replay_from_env()?.map(Arc::new);
+    if recorder.is_some() && replay.is_some() {
+        return Err("RECORD_PATH and REPLAY_PATH cannot be set together".into());
+    }
+    let (source, scenario) = match &replay {
+        // The recorded responses already carry the effects of the scenario
+        Some(replay) => (
+            "the recording in REPLAY_PATH".to_string(),
+            Scenario {
+                seed: Some(replay.seed),
+                nodes: replay.nodes.clone(),
+                collusions: Vec::new(),
+                wormholes: Vec::new(),
+                links: Vec::new(),
+                walls: Vec::new(),
+                propagation: Propagation::default(),
+                server_faults: ServerFaults::default(),
+                floors: None,
+            },
+        ),
+        None => scenario::load()?,
+    };
+    if let Some(path) = &cli.sweep {
+        if recorder.is_some() || replay.is_some() {
+            return Err("--sweep cannot be combined with RECORD_PATH or REPLAY_PATH".into());
+        }
+        let seed = scenario.seed()?;
+        println!("  🎲 Seed {}", seed);
+        return Ok(sweep::run(path, scenario, seed).await?);
+    }
+    let nodes = scenario.nodes;
+    println!("Simulating {} node(s) from {}", nodes.len(), source);
+    for node in &nodes {
+        println!(
+            "  {} ({}, {}): lat={}, lon={}, alt={} m",
+            node.name,
+            node.address_string(),
+            node.node_id,
+            node.latitude,
+            node.longitude,
+            node.altitude
+        );
+        if let Some(attack) = &node.attack {
+            println!("    😈 attack: {}", attack.kind());
+        }
+        if let Some(rotation) = &node.rotation {
+            println!(
+                "    🔀 rotates through {} more address(es) every {} s",
+                rotation.addresses.len(),
+                rotation.period_secs
+            );
+        }
+        if let Some(latency) = &node.latency {
+            println!("    🐢 latency: {} ± {} ms", latency.ms, latency.jitter_ms);
+        }
+    }
+    for collusion in &scenario.collusions {
+        println!(
+            "  🤝 {} collude at {} dBm",
+            collusion.members.join(", "),
+            collusion.rssi
+        );
+    }
+    for wormhole in &scenario.wormholes {
+        println!(
+            "  🕳️  Wormhole between {:?} and {:?} within {} m",
+            wormhole.ends[0], wormhole.ends[1], wormhole.range_m
+        );
+    }
+    for link in &scenario.links {
+        println!(
+            "  📉 {} and {} drop {:.0}% of scans with {} outage(s)",
+            link.nodes[0],
+            link.nodes[1],
+            link.dropout * 100.0,
+            link.outages.len()
+        );
+    }
+    for wall in &scenario.walls {
+        println!(
+            "  🧱 Wall between {:?} and {:?} taking off {} dB",
+            wall.ends[0], wall.ends[1], wall.attenuation_db
+        );
+    }
+    if let Some(floors) = &scenario.floors {
+        println!(
+            "  🏢 Floors of {} m taking off {} dB each",
+            floors.height_m, floors.attenuation_db
+        );
+    }
+    let propagation: Arc<dyn PropagationModel> = Arc::from(scenario.propagation.model()?);
+    println!(
+        "  📶 {} propagation (σ = {:.1} dB)",
+        propagation.name(),
+        propagation.sigma()
+    );
+    let seed = match &replay {
+        Some(replay) => replay.seed,
+        None => scenario.seed()?,
+    };
+    println!(
+        "  🎲 Seed {} (set SIMULATION_SEED={} to repeat this run)",
+        seed, seed
+    );
+    println!();
+    if let Some(recorder) = &recorder {
+        recorder.record(Event::Started {
+            seed,
+            nodes: nodes.clone(),
+        });
+    }
+    let nodes: SharedNodes = Arc::new(RwLock::new(nodes));
+    let interval = mobility::interval_from_env()?;
+    let moving = nodes.clone();
+    let mobility_rng = StdRng::seed_from_u64(seed.wrapping_add(MOBILITY_STREAM));
+    let mobility_recorder = recorder.clone();
+    let clock = Arc::new(SimulationClock::new(clock::speed_from_env()?));
+    let mobility_clock = clock.clone();
+    tokio::spawn(async move {
+        if let Err(e) = mobility::start_mobility(
+            moving,
+            interval,
+            mobility_rng,
+            mobility_recorder,
+            mobility_clock,
+        )
+        .await
+        {
+            eprintln!("❌ {}", e);
+        }
+    });
+    let state = AppState {
+        nodes: nodes.clone(),
+        collusions: Arc::new(scenario.collusions),
+        wormholes: Arc::new(scenario.wormholes),
+        links: Arc::new(scenario.links),
+        walls: Arc::new(scenario.walls),
+        floors: scenario.floors,
+        propagation,
+        rng: Arc::new(Mutex::new(StdRng::seed_from_u64(
+            seed.wrapping_add(PROPAGATION_STREAM),
+        ))),
+        recorder: recorder.clone(),
+        replay: replay.clone(),
+        stream_interval: stream::interval_from_env()?,
+        map_tiles: map::tiles_from_env(),
+        server_faults: Arc::new(scenario.server_faults),
+        clock,
+        end_secs: cli
+            .headless
+            .then(|| cli.ticks as f64 * interval.as_secs_f64()),
+        metrics: Arc::new(Metrics::new()?),
+        validation: Arc::new(Mutex::new(None)),
+    };
+    if let Some((path, format, interval)) = ground_truth::export_from_env()? {
+        println!("📋 Exporting the ground truth to {}\n", path);
+        let exported = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = ground_truth::start_export(exported, path, format, interval).await {
+                eprintln!("❌ {}", e);
+            }
+        });
+    }
+    if let Some((url, interval)) = chain::driver_from_env()? {
+        if replay.is_some() {
+            return Err("CHAIN_RPC_URL and REPLAY_PATH cannot be set together".into());
+        }
+        println!("⛓️  Driving the chain at {}\n", url);
+        let driving = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = chain::start_chain_driver(driving, url, interval).await {
+                eprintln!("❌ {}", e);
+            }
+        });
+    }
+    if let Some((url, threshold)) = validation::validator_from_env()? {
+        println!(
+            "🔎 Validating the trust scores at {} against the ground truth\n",
+            url
+        );
+        let validated = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = validation::start_validation(validated, url, threshold).await {
+                eprintln!("❌ {}", e);
+            }
+        });
+    }
+    // Headless runs serve everything but the web UI and its position stream
+    let mut app = Router::new()
         .route("/rssi", get(scan_rssi))
         .route("/location", get(get_location))
-        .route("/api/update-alice", post(update_alice_position))
+        .route("/api/update-position", post(update_position))
         .route("/api/positions", get(get_positions))
-        .with_state(state);
+        .route(
+            "/api/clock",
+            get(clock::get_clock).post(clock::update_clock),
+        )
+        .route("/api/clock/step", post(clock::step_clock))
+        .route("/api/ground-truth", get(ground_truth::get_ground_truth))
+        .route("/api/validation", get(validation::get_validation))
+        .route("/api/stats", get(stats::get_stats))
+        .route("/metrics", get(get_metrics));
+    if !cli.headless {
+        app = app
+            .route("/", get(serve_ui))
+            .route("/api/stream", get(stream::stream_positions))
+            .route("/api/map-tiles", get(map::get_map_tiles));
+    }
+    let app = app.with_state(state.clone());
     let port = std::env::var("PORT").unwrap_or_else(|_| "3000".to_string());
     let addr = format!("0.0.0.0:{}", port);
     println!("Server listening on http://{}", addr);
-    println!(
-        "🌐 Open http://{} in your browser to access the interactive map",
-        addr
-    );
+    if cli.headless {
+        println!(
+            "🤖 Running headless for {} tick(s) of {} ms",
+            cli.ticks,
+            interval.as_millis()
+        );
+    } else {
+        println!(
+            "🌐 Open http://{} in your browser to access the interactive map",
+            addr
+        );
+    }
     println!("📡 RSSI endpoint: http://{}/rssi", addr);
-    println!("📍 Location endpoint: http://{}/location\n", addr);
+    println!("📍 Location endpoint: http://{}/location", addr);
+    println!("📊 Metrics endpoint: http://{}/metrics", addr);
+    println!("📈 Stats endpoint: http://{}/api/stats\n", addr);
+    if let Some(replay) = &replay {
+        println!("⏯️  Replaying the recording in REPLAY_PATH\n");
+        let replay = replay.clone();
+        let replayed = nodes.clone();
+        tokio::spawn(async move { replay.start_positions(replayed).await });
+    }
+    if recorder.is_some() {
+        println!("⏺️  Recording the run to RECORD_PATH\n");
+    }
     let listener = TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    let started = tokio::time::Instant::now();
+    let headless = cli.headless.then_some(cli.ticks);
+    let running = state.clone();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            match headless {
+                Some(ticks) => tokio::select! {
+                    _ = headless::run_ticks(&running, ticks, interval) => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                },
+                None => {
+                    let _ = tokio::signal::ctrl_c().await;
+                }
+            }
+        })
+        .await?;
+    if let Some(recorder) = &recorder {
+        recorder.record(Event::Stopped);
+    }
+    if cli.headless {
+        headless::print_summary(&state, cli.ticks, started).await;
+    }
     Ok(())
 }
@@ -0,0 +1,94 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::scenario::Wall;
+use crate::{AppState, NodePosition};
+
+const DEFAULT_TILE_URL: &str = "https://tile.openstreetmap.org/{z}/{x}/{y}.png";
+const DEFAULT_ATTRIBUTION: &str = "© OpenStreetMap contributors";
+
+/// Format of `/api/positions`, picked with `?format=`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PositionsFormat {
+    #[default]
+    Json,
+    GeoJson,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PositionsQuery {
+    #[serde(default)]
+    pub format: PositionsFormat,
+}
+
+/// Map tiles drawn under the nodes in the web UI
+#[derive(Debug, Clone, Serialize)]
+pub struct MapTiles {
+    /// URL template with `{z}`, `{x}` and `{y}`
+    pub url: String,
+    pub attribution: String,
+}
+
+/// Map tiles from `MAP_TILE_URL` and `MAP_TILE_ATTRIBUTION`, OpenStreetMap by default and none
+/// if `MAP_TILE_URL` is set empty
+pub fn tiles_from_env() -> Option<Arc<MapTiles>> {
+    let url = std::env::var("MAP_TILE_URL").unwrap_or_else(|_| DEFAULT_TILE_URL.to_string());
+    if url.is_empty() {
+        return None;
+    }
+    let attribution = std::env::var("MAP_TILE_ATTRIBUTION")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_ATTRIBUTION.to_string());
+    Some(Arc::new(MapTiles { url, attribution }))
+}
+
+/// Map tiles for the web UI, `null` without tiles
+pub async fn get_map_tiles(State(state): State<AppState>) -> Json<Option<MapTiles>> {
+    Json(state.map_tiles.as_deref().cloned())
+}
+
+/// Nodes as GeoJSON points with the properties of `/api/positions`, and walls as line strings
+///
+/// GeoJSON puts the longitude first, and the altitude last.
+pub fn positions_geojson(positions: &[NodePosition], walls: &[Wall]) -> Value {
+    let mut features: Vec<Value> = positions
+        .iter()
+        .map(|position| {
+            json!({
+                "type": "Feature",
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": [position.longitude, position.latitude, position.altitude],
+                },
+                "properties": {
+                    "kind": "node",
+                    "name": position.name,
+                    "color": position.color,
+                    "draggable": position.draggable,
+                    "attack": position.attack,
+                },
+            })
+        })
+        .collect();
+    features.extend(walls.iter().map(|wall| {
+        json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "LineString",
+                "coordinates": wall.ends.map(|[latitude, longitude]| [longitude, latitude]),
+            },
+            "properties": {
+                "kind": "wall",
+                "attenuation_db": wall.attenuation_db,
+            },
+        })
+    }));
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
@@ -0,0 +1,187 @@
+use prometheus::core::Collector;
+use prometheus::proto::Metric;
+use prometheus::{
+    Encoder, Gauge, Histogram, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry,
+    TextEncoder,
+};
+
+/// Noise beyond this many standard deviations of the propagation model is counted as an injected
+/// fault
+pub const NOISE_OUTLIER_SIGMAS: f64 = 2.0;
+
+/// Prometheus metrics describing the traffic generated by the simulator
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    distance: Histogram,
+    average_distance: Gauge,
+    injected_faults: IntCounterVec,
+    link_rssi: HistogramVec,
+    scenario_secs: Gauge,
+    end_secs: Gauge,
+}
+
+/// Value of the label `name` of a metric
+fn label(metric: &Metric, name: &str) -> String {
+    metric
+        .get_label()
+        .iter()
+        .find(|label| label.get_name() == name)
+        .map(|label| label.get_value().to_string())
+        .unwrap_or_default()
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new();
+
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "simulator_requests_total",
+                "Requests served, by requesting node and endpoint",
+            ),
+            &["node", "endpoint"],
+        )?;
+        let distance = Histogram::with_opts(
+            HistogramOpts::new(
+                "simulator_distance_meters",
+                "Simulated distance between the requester and each neighbor",
+            )
+            .buckets(vec![0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0]),
+        )?;
+        let average_distance = Gauge::new(
+            "simulator_average_distance_meters",
+            "Average simulated distance over all RSSI samples served",
+        )?;
+        let injected_faults = IntCounterVec::new(
+            Opts::new(
+                "simulator_injected_faults_total",
+                "Faults injected into served data, by kind",
+            ),
+            &["kind"],
+        )?;
+
+        let link_rssi = HistogramVec::new(
+            HistogramOpts::new(
+                "simulator_link_rssi_dbm",
+                "RSSI served to each requesting node for each neighbor",
+            )
+            .buckets(vec![-100.0, -90.0, -80.0, -70.0, -60.0, -50.0, -40.0]),
+            &["from", "to"],
+        )?;
+        let scenario_secs = Gauge::new(
+            "simulator_scenario_seconds",
+            "Scenario time the simulation clock has run",
+        )?;
+        let end_secs = Gauge::new(
+            "simulator_scenario_end_seconds",
+            "Scenario time a headless run stops at, 0 if it runs until stopped",
+        )?;
+
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(distance.clone()))?;
+        registry.register(Box::new(average_distance.clone()))?;
+        registry.register(Box::new(injected_faults.clone()))?;
+        registry.register(Box::new(link_rssi.clone()))?;
+        registry.register(Box::new(scenario_secs.clone()))?;
+        registry.register(Box::new(end_secs.clone()))?;
+
+        Ok(Self {
+            registry,
+            requests,
+            distance,
+            average_distance,
+            injected_faults,
+            link_rssi,
+            scenario_secs,
+            end_secs,
+        })
+    }
+
+    /// Count a request from `node` to `endpoint`
+    pub fn record_request(&self, node: &str, endpoint: &str) {
+        self.requests.with_label_values(&[node, endpoint]).inc();
+    }
+
+    /// Record the distance used for one simulated RSSI sample
+    pub fn record_distance(&self, meters: f64) {
+        self.distance.observe(meters);
+        let count = self.distance.get_sample_count();
+        if count > 0 {
+            self.average_distance
+                .set(self.distance.get_sample_sum() / count as f64);
+        }
+    }
+
+    /// Count a fault of the given kind injected into a response
+    pub fn record_fault(&self, kind: &str) {
+        self.injected_faults.with_label_values(&[kind]).inc();
+    }
+
+    /// Record the RSSI of `to` served to `from`
+    pub fn record_rssi(&self, from: &str, to: &str, rssi: i16) {
+        self.link_rssi
+            .with_label_values(&[from, to])
+            .observe(rssi.into());
+    }
+
+    /// Record how far the scenario has run, and where a headless run stops
+    pub fn record_progress(&self, secs: f64, end_secs: Option<f64>) {
+        self.scenario_secs.set(secs);
+        self.end_secs.set(end_secs.unwrap_or(0.0));
+    }
+
+    /// Requests served to `node` at `endpoint`
+    pub fn requests(&self, node: &str, endpoint: &str) -> u64 {
+        self.requests.with_label_values(&[node, endpoint]).get()
+    }
+
+    /// RSSI samples served and their average distance
+    pub fn distances(&self) -> (u64, f64) {
+        (
+            self.distance.get_sample_count(),
+            self.average_distance.get(),
+        )
+    }
+
+    /// Faults injected so far, by kind
+    pub fn faults(&self) -> Vec<(String, u64)> {
+        self.injected_faults
+            .collect()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .map(|metric| {
+                (
+                    label(metric, "kind"),
+                    metric.get_counter().get_value() as u64,
+                )
+            })
+            .collect()
+    }
+
+    /// RSSI samples served and their average, by requesting node and neighbor
+    pub fn link_rssi(&self) -> Vec<(String, String, u64, f64)> {
+        self.link_rssi
+            .collect()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .map(|metric| {
+                let histogram = metric.get_histogram();
+                let samples = histogram.get_sample_count();
+                (
+                    label(metric, "from"),
+                    label(metric, "to"),
+                    samples,
+                    histogram.get_sample_sum() / samples.max(1) as f64,
+                )
+            })
+            .collect()
+    }
+
+    /// Encode all metrics in the Prometheus text format
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
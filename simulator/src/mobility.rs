@@ -0,0 +1,292 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+
+use crate::clock::SimulationClock;
+use crate::recording::{Event, Recorder};
+use crate::scenario::{positive, Mobility};
+use crate::{millis_from_env, SharedNodes};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest wall time between two checks of the clock, so accelerated runs still move smoothly
+pub const MAX_WALL_TICK: Duration = Duration::from_millis(100);
+/// Meters per degree of latitude, and of longitude at the equator
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Movement of a node, in meters east and north of its start position
+pub trait MobilityModel: Send {
+    fn name(&self) -> &'static str;
+
+    /// Move on for `dt` seconds and return the new position
+    fn advance(&mut self, dt: f64, rng: &mut StdRng) -> (f64, f64);
+}
+
+/// Wanders between random points within `radius` meters of the start position, pausing at each
+pub struct RandomWaypoint {
+    radius: f64,
+    speed: f64,
+    pause: f64,
+    position: (f64, f64),
+    target: (f64, f64),
+    /// Seconds left to wait at the reached waypoint
+    waiting: f64,
+}
+
+impl RandomWaypoint {
+    fn next_target(&self, rng: &mut StdRng) -> (f64, f64) {
+        // Uniform over the disk, not bunched up at its center
+        let distance = self.radius * rng.gen::<f64>().sqrt();
+        let angle = rng.gen::<f64>() * 2.0 * PI;
+        (distance * angle.cos(), distance * angle.sin())
+    }
+}
+
+impl MobilityModel for RandomWaypoint {
+    fn name(&self) -> &'static str {
+        "random waypoint"
+    }
+
+    fn advance(&mut self, mut dt: f64, rng: &mut StdRng) -> (f64, f64) {
+        while dt > 0.0 {
+            if self.waiting > 0.0 {
+                let waited = self.waiting.min(dt);
+                self.waiting -= waited;
+                dt -= waited;
+                continue;
+            }
+
+            let (dx, dy) = (
+                self.target.0 - self.position.0,
+                self.target.1 - self.position.1,
+            );
+            let remaining = dx.hypot(dy);
+            let step = self.speed * dt;
+            if step < remaining {
+                self.position.0 += dx / remaining * step;
+                self.position.1 += dy / remaining * step;
+                break;
+            }
+
+            // Reached the waypoint, wait there before heading to the next one
+            self.position = self.target;
+            dt -= remaining / self.speed;
+            self.waiting = self.pause;
+            self.target = self.next_target(rng);
+        }
+        self.position
+    }
+}
+
+/// Walks along a path and back again, at a constant speed
+pub struct LinearPath {
+    /// Start position followed by the waypoints
+    points: Vec<(f64, f64)>,
+    speed: f64,
+    /// Index of the point being walked to
+    next: usize,
+    /// Walking towards the end of the path
+    forward: bool,
+    position: (f64, f64),
+}
+
+impl MobilityModel for LinearPath {
+    fn name(&self) -> &'static str {
+        "linear path"
+    }
+
+    fn advance(&mut self, dt: f64, _rng: &mut StdRng) -> (f64, f64) {
+        let mut travel = self.speed * dt;
+        while travel > 0.0 {
+            let target = self.points[self.next];
+            let (dx, dy) = (target.0 - self.position.0, target.1 - self.position.1);
+            let remaining = dx.hypot(dy);
+            if travel < remaining {
+                self.position.0 += dx / remaining * travel;
+                self.position.1 += dy / remaining * travel;
+                break;
+            }
+
+            self.position = target;
+            travel -= remaining;
+            // Turn around at either end of the path
+            if self.forward && self.next + 1 == self.points.len() {
+                self.forward = false;
+            } else if !self.forward && self.next == 0 {
+                self.forward = true;
+            }
+            self.next = if self.forward {
+                self.next + 1
+            } else {
+                self.next - 1
+            };
+        }
+        self.position
+    }
+}
+
+/// Circles counterclockwise around a center `radius` meters west of the start position
+pub struct CircularOrbit {
+    radius: f64,
+    /// Radians per second
+    angular_speed: f64,
+    angle: f64,
+}
+
+impl MobilityModel for CircularOrbit {
+    fn name(&self) -> &'static str {
+        "circular orbit"
+    }
+
+    fn advance(&mut self, dt: f64, _rng: &mut StdRng) -> (f64, f64) {
+        self.angle = (self.angle + self.angular_speed * dt) % (2.0 * PI);
+        (
+            self.radius * self.angle.cos() - self.radius,
+            self.radius * self.angle.sin(),
+        )
+    }
+}
+
+/// Meters east and north of `(latitude, longitude)`
+fn to_meters(origin: (f64, f64), latitude: f64, longitude: f64) -> (f64, f64) {
+    (
+        (longitude - origin.1) * METERS_PER_DEGREE * origin.0.to_radians().cos(),
+        (latitude - origin.0) * METERS_PER_DEGREE,
+    )
+}
+
+/// Latitude and longitude of a point `offset` meters east and north of `origin`
+pub fn to_degrees(origin: (f64, f64), offset: (f64, f64)) -> (f64, f64) {
+    (
+        origin.0 + offset.1 / METERS_PER_DEGREE,
+        origin.1 + offset.0 / (METERS_PER_DEGREE * origin.0.to_radians().cos()),
+    )
+}
+
+impl Mobility {
+    /// Model moving a node that starts at `start`, `None` if it only moves when told to
+    pub fn model(
+        &self,
+        start: (f64, f64),
+        rng: &mut StdRng,
+    ) -> Result<Option<Box<dyn MobilityModel>>, String> {
+        Ok(match self {
+            Mobility::Static | Mobility::Manual => None,
+            Mobility::RandomWaypoint {
+                radius_m,
+                speed_mps,
+                pause_secs,
+            } => {
+                let mut model = RandomWaypoint {
+                    radius: positive(*radius_m, "radius_m")?,
+                    speed: positive(*speed_mps, "speed_mps")?,
+                    pause: pause_secs.max(0.0),
+                    position: (0.0, 0.0),
+                    target: (0.0, 0.0),
+                    waiting: 0.0,
+                };
+                model.target = model.next_target(rng);
+                Some(Box::new(model))
+            }
+            Mobility::Linear { path, speed_mps } => {
+                if path.is_empty() {
+                    return Err("path needs at least one waypoint".to_string());
+                }
+                let points = std::iter::once((0.0, 0.0))
+                    .chain(
+                        path.iter()
+                            .map(|[latitude, longitude]| to_meters(start, *latitude, *longitude)),
+                    )
+                    .collect::<Vec<_>>();
+                if points.windows(2).all(|pair| pair[0] == pair[1]) {
+                    return Err("path does not go anywhere".to_string());
+                }
+                Some(Box::new(LinearPath {
+                    points,
+                    speed: positive(*speed_mps, "speed_mps")?,
+                    next: 1,
+                    forward: true,
+                    position: (0.0, 0.0),
+                }))
+            }
+            Mobility::Circular {
+                radius_m,
+                period_secs,
+            } => Some(Box::new(CircularOrbit {
+                radius: positive(*radius_m, "radius_m")?,
+                angular_speed: 2.0 * PI / positive(*period_secs, "period_secs")?,
+                angle: 0.0,
+            })),
+        })
+    }
+}
+
+/// How often the nodes are moved, given by `MOBILITY_INTERVAL_MS`
+pub fn interval_from_env() -> Result<Duration, String> {
+    millis_from_env("MOBILITY_INTERVAL_MS", DEFAULT_INTERVAL)
+}
+
+/// Move every node with a mobility model every `interval` of scenario time
+///
+/// Models move in fixed steps of `interval` as `clock` runs, however fast it runs or is stepped,
+/// and all randomness comes from `rng`, so a seeded run moves the nodes the same way.
+pub async fn start_mobility(
+    nodes: SharedNodes,
+    interval: Duration,
+    mut rng: StdRng,
+    recorder: Option<Arc<Recorder>>,
+    clock: Arc<SimulationClock>,
+) -> Result<(), String> {
+    let mut models = Vec::new();
+    for (index, node) in nodes.read().await.iter().enumerate() {
+        let start = (node.latitude, node.longitude);
+        if let Some(model) = node
+            .mobility
+            .model(start, &mut rng)
+            .map_err(|e| format!("Invalid mobility of {}: {}", node.name, e))?
+        {
+            println!("🚶 {} moves by {}", node.name, model.name());
+            models.push((index, start, model));
+        }
+    }
+    if models.is_empty() {
+        return Ok(());
+    }
+
+    let step = interval.as_secs_f64();
+    // Scenario time the models have moved up to
+    let mut moved = 0.0;
+    let mut ticker = time::interval(interval.min(MAX_WALL_TICK));
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    loop {
+        ticker.tick().await;
+
+        let due = clock.elapsed_secs();
+        let mut offsets = vec![None; models.len()];
+        while moved + step <= due {
+            for (offset, (_, _, model)) in offsets.iter_mut().zip(models.iter_mut()) {
+                *offset = Some(model.advance(step, &mut rng));
+            }
+            moved += step;
+        }
+
+        let mut nodes = nodes.write().await;
+        for (offset, (index, start, _)) in offsets.into_iter().zip(&models) {
+            let Some(offset) = offset else {
+                continue;
+            };
+            let (latitude, longitude) = to_degrees(*start, offset);
+            nodes[*index].latitude = latitude;
+            nodes[*index].longitude = longitude;
+            if let Some(recorder) = &recorder {
+                recorder.record(Event::Position {
+                    name: nodes[*index].name.clone(),
+                    latitude,
+                    longitude,
+                });
+            }
+        }
+    }
+}
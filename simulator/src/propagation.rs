@@ -0,0 +1,205 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Normal, StandardNormal};
+
+use crate::scenario::{positive, Propagation};
+
+/// Samples drawn to estimate the spread of a fading model
+const FADING_SAMPLES: usize = 10_000;
+/// Seed of these samples, so the spread does not depend on the simulation seed
+const FADING_SAMPLES_SEED: u64 = 0;
+
+/// Radio propagation between two simulated nodes
+pub trait PropagationModel: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// RSSI at `distance` meters without the random part
+    fn mean_rssi(&self, distance: f64) -> f64;
+
+    /// RSSI at `distance` meters, and the random part of it in dB
+    fn rssi(&self, distance: f64, rng: &mut StdRng) -> (f64, f64);
+
+    /// Standard deviation of the random part in dB
+    fn sigma(&self) -> f64;
+}
+
+/// RSSI of a log-distance path loss model, 0 at no distance
+fn path_loss_rssi(reference_rssi: f64, path_loss_exponent: f64, distance: f64) -> f64 {
+    if distance != 0.0 {
+        reference_rssi - path_loss_exponent * 10.0 * distance.log10()
+    } else {
+        0.0
+    }
+}
+
+/// Line of sight in empty space, the signal fades with the square of the distance
+pub struct FreeSpace {
+    reference_rssi: f64,
+}
+
+impl PropagationModel for FreeSpace {
+    fn name(&self) -> &'static str {
+        "free space"
+    }
+
+    fn mean_rssi(&self, distance: f64) -> f64 {
+        path_loss_rssi(self.reference_rssi, 2.0, distance)
+    }
+
+    fn rssi(&self, distance: f64, _rng: &mut StdRng) -> (f64, f64) {
+        (self.mean_rssi(distance), 0.0)
+    }
+
+    fn sigma(&self) -> f64 {
+        0.0
+    }
+}
+
+/// Log-distance path loss with Gaussian noise in dB, for obstacles shadowing the signal
+pub struct LogNormalShadowing {
+    reference_rssi: f64,
+    path_loss_exponent: f64,
+    sigma: f64,
+    noise: Normal<f64>,
+}
+
+impl PropagationModel for LogNormalShadowing {
+    fn name(&self) -> &'static str {
+        "log-normal shadowing"
+    }
+
+    fn mean_rssi(&self, distance: f64) -> f64 {
+        path_loss_rssi(self.reference_rssi, self.path_loss_exponent, distance)
+    }
+
+    fn rssi(&self, distance: f64, rng: &mut StdRng) -> (f64, f64) {
+        let noise = rng.sample(self.noise);
+        (self.mean_rssi(distance) + noise, noise)
+    }
+
+    fn sigma(&self) -> f64 {
+        self.sigma
+    }
+}
+
+/// Log-distance path loss with Rician multipath fading, Rayleigh fading without line of sight
+pub struct RicianFading {
+    reference_rssi: f64,
+    path_loss_exponent: f64,
+    /// Power of the line of sight relative to the scattered paths
+    k_factor: f64,
+    sigma: f64,
+}
+
+impl RicianFading {
+    /// Gain in dB of one fade, 0 on average in linear power
+    fn fade(&self, rng: &mut impl Rng) -> f64 {
+        let line_of_sight = (self.k_factor / (self.k_factor + 1.0)).sqrt();
+        let scatter = (1.0 / (2.0 * (self.k_factor + 1.0))).sqrt();
+        let in_phase = line_of_sight + scatter * rng.sample::<f64, _>(StandardNormal);
+        let quadrature = scatter * rng.sample::<f64, _>(StandardNormal);
+        10.0 * (in_phase * in_phase + quadrature * quadrature).log10()
+    }
+}
+
+impl PropagationModel for RicianFading {
+    fn name(&self) -> &'static str {
+        if self.k_factor == 0.0 {
+            "Rayleigh fading"
+        } else {
+            "Rician fading"
+        }
+    }
+
+    fn mean_rssi(&self, distance: f64) -> f64 {
+        path_loss_rssi(self.reference_rssi, self.path_loss_exponent, distance)
+    }
+
+    fn rssi(&self, distance: f64, rng: &mut StdRng) -> (f64, f64) {
+        let fade = self.fade(rng);
+        (self.mean_rssi(distance) + fade, fade)
+    }
+
+    fn sigma(&self) -> f64 {
+        self.sigma
+    }
+}
+
+fn finite(value: f64, name: &str) -> Result<f64, String> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(format!("{} must be a number, got {}", name, value))
+    }
+}
+
+impl Propagation {
+    /// The same propagation with Gaussian noise of `sigma` dB, for parameter sweeps
+    pub fn with_sigma(&self, sigma: f64) -> Result<Propagation, String> {
+        match self {
+            Propagation::FreeSpace { reference_rssi } => Ok(Propagation::LogNormalShadowing {
+                reference_rssi: *reference_rssi,
+                path_loss_exponent: 2.0,
+                sigma,
+            }),
+            Propagation::LogNormalShadowing {
+                reference_rssi,
+                path_loss_exponent,
+                ..
+            } => Ok(Propagation::LogNormalShadowing {
+                reference_rssi: *reference_rssi,
+                path_loss_exponent: *path_loss_exponent,
+                sigma,
+            }),
+            Propagation::Fading { .. } => {
+                Err("sigma cannot be set for fading, whose spread follows k_factor".to_string())
+            }
+        }
+    }
+
+    pub fn model(&self) -> Result<Box<dyn PropagationModel>, String> {
+        Ok(match self {
+            Propagation::FreeSpace { reference_rssi } => Box::new(FreeSpace {
+                reference_rssi: finite(*reference_rssi, "reference_rssi")?,
+            }),
+            Propagation::LogNormalShadowing {
+                reference_rssi,
+                path_loss_exponent,
+                sigma,
+            } => {
+                if !(sigma.is_finite() && *sigma >= 0.0) {
+                    return Err(format!("sigma must not be negative, got {}", sigma));
+                }
+                Box::new(LogNormalShadowing {
+                    reference_rssi: finite(*reference_rssi, "reference_rssi")?,
+                    path_loss_exponent: positive(*path_loss_exponent, "path_loss_exponent")?,
+                    sigma: *sigma,
+                    noise: Normal::new(0.0, *sigma).map_err(|e| e.to_string())?,
+                })
+            }
+            Propagation::Fading {
+                reference_rssi,
+                path_loss_exponent,
+                k_factor,
+            } => {
+                if !(k_factor.is_finite() && *k_factor >= 0.0) {
+                    return Err(format!("k_factor must not be negative, got {}", k_factor));
+                }
+                let mut model = RicianFading {
+                    reference_rssi: finite(*reference_rssi, "reference_rssi")?,
+                    path_loss_exponent: positive(*path_loss_exponent, "path_loss_exponent")?,
+                    k_factor: *k_factor,
+                    sigma: 0.0,
+                };
+                // The spread of a fade in dB has no simple closed form, so measure it
+                let mut rng = StdRng::seed_from_u64(FADING_SAMPLES_SEED);
+                let fades: Vec<f64> = (0..FADING_SAMPLES).map(|_| model.fade(&mut rng)).collect();
+                let mean = fades.iter().sum::<f64>() / FADING_SAMPLES as f64;
+                let variance =
+                    fades.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / FADING_SAMPLES as f64;
+                model.sigma = variance.sqrt();
+                Box::new(model)
+            }
+        })
+    }
+}
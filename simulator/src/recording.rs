@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+
+use crate::scenario::{Mobility, Node};
+use crate::SharedNodes;
+
+/// What happened during a run, one JSON object per line of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The simulation started with these nodes
+    Started { seed: u64, nodes: Vec<Node> },
+    /// A node moved, through the web UI or its mobility model
+    Position {
+        name: String,
+        latitude: f64,
+        longitude: f64,
+    },
+    /// A node was served its neighbors' RSSI, `body` being the hex-encoded response
+    Rssi { node_id: String, body: String },
+    /// A node was served its location, `body` being the hex-encoded response
+    Location { node_id: String, body: String },
+    /// The simulation stopped
+    Stopped,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    /// Milliseconds since the simulation started
+    at_ms: u64,
+    #[serde(flatten)]
+    event: Event,
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 {
+        return Err(format!("Odd length hex: {}", hex));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Appends the events of a run to `RECORD_PATH`
+pub struct Recorder {
+    start: Instant,
+    file: Mutex<BufWriter<File>>,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create recording {}: {}", path, e))?;
+        Ok(Self {
+            start: Instant::now(),
+            file: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub fn record(&self, event: Event) {
+        let entry = Entry {
+            at_ms: self.start.elapsed().as_millis() as u64,
+            event,
+        };
+        let mut file = self.file.lock().unwrap();
+        // Flushed line by line, so a crashed run still leaves a usable recording
+        let written = serde_json::to_writer(&mut *file, &entry)
+            .map_err(|e| e.to_string())
+            .and_then(|_| writeln!(file).map_err(|e| e.to_string()))
+            .and_then(|_| file.flush().map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            eprintln!("⚠️  Failed to record an event: {}", e);
+        }
+    }
+
+    pub fn record_response(&self, endpoint: Endpoint, node_id: &str, body: &[u8]) {
+        let node_id = node_id.to_lowercase();
+        let body = encode_hex(body);
+        self.record(match endpoint {
+            Endpoint::Rssi => Event::Rssi { node_id, body },
+            Endpoint::Location => Event::Location { node_id, body },
+        });
+    }
+}
+
+/// Endpoint whose responses are recorded and replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    Rssi,
+    Location,
+}
+
+/// A recorded run served again from `REPLAY_PATH`
+pub struct Replay {
+    pub seed: u64,
+    pub nodes: Vec<Node>,
+    positions: Vec<(u64, String, f64, f64)>,
+    responses: Mutex<HashMap<(String, Endpoint), VecDeque<(u64, Vec<u8>)>>>,
+    /// When the replay started, set once the server is up
+    start: Mutex<Option<Instant>>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open recording {}: {}", path, e))?;
+        let mut started = None;
+        let mut positions = Vec::new();
+        let mut responses: HashMap<_, VecDeque<_>> = HashMap::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read recording {}: {}", path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: Entry = serde_json::from_str(&line)
+                .map_err(|e| format!("Invalid recording {} line {}: {}", path, number + 1, e))?;
+            match entry.event {
+                Event::Started { seed, nodes } => started = Some((seed, nodes)),
+                Event::Position {
+                    name,
+                    latitude,
+                    longitude,
+                } => positions.push((entry.at_ms, name, latitude, longitude)),
+                Event::Rssi { node_id, body } => responses
+                    .entry((node_id, Endpoint::Rssi))
+                    .or_default()
+                    .push_back((entry.at_ms, decode_hex(&body)?)),
+                Event::Location { node_id, body } => responses
+                    .entry((node_id, Endpoint::Location))
+                    .or_default()
+                    .push_back((entry.at_ms, decode_hex(&body)?)),
+                Event::Stopped => break,
+            }
+        }
+        let (seed, mut nodes) =
+            started.ok_or_else(|| format!("Recording {} has no started event", path))?;
+        // Nodes only move as recorded
+        for node in &mut nodes {
+            node.mobility = Mobility::Static;
+        }
+
+        Ok(Self {
+            seed,
+            nodes,
+            positions,
+            responses: Mutex::new(responses),
+            start: Mutex::new(None),
+        })
+    }
+
+    fn started_at(&self) -> Instant {
+        *self.start.lock().unwrap().get_or_insert_with(Instant::now)
+    }
+
+    /// Next recorded response of `endpoint` to the node, served no earlier than it was recorded
+    pub async fn respond(&self, endpoint: Endpoint, node_id: &str) -> Option<Vec<u8>> {
+        let (at_ms, body) = self
+            .responses
+            .lock()
+            .unwrap()
+            .get_mut(&(node_id.to_lowercase(), endpoint))?
+            .pop_front()?;
+        time::sleep_until(self.started_at() + Duration::from_millis(at_ms)).await;
+        Some(body)
+    }
+
+    /// Move the nodes as they moved in the recording
+    pub async fn start_positions(&self, nodes: SharedNodes) {
+        let start = self.started_at();
+        for (at_ms, name, latitude, longitude) in &self.positions {
+            time::sleep_until(start + Duration::from_millis(*at_ms)).await;
+            if let Some(node) = nodes.write().await.iter_mut().find(|n| &n.name == name) {
+                node.latitude = *latitude;
+                node.longitude = *longitude;
+            }
+        }
+        println!("⏹️  Replayed all recorded positions");
+    }
+}
+
+/// Recorder writing to `RECORD_PATH`, if it is set
+pub fn recorder_from_env() -> Result<Option<Recorder>, String> {
+    match std::env::var("RECORD_PATH").ok().filter(|v| !v.is_empty()) {
+        Some(path) => Recorder::create(&path).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Recording to replay from `REPLAY_PATH`, if it is set
+pub fn replay_from_env() -> Result<Option<Replay>, String> {
+    match std::env::var("REPLAY_PATH").ok().filter(|v| !v.is_empty()) {
+        Some(path) => Replay::load(&path).map(Some),
+        None => Ok(None),
+    }
+}
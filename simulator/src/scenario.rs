@@ -0,0 +1,729 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::f64::consts::PI;
+use std::str::FromStr;
+use subxt::utils::AccountId32;
+use subxt_signer::{sr25519::Keypair, SecretUri};
+
+use crate::mobility::to_degrees;
+
+/// Scenario served when `SCENARIO_PATH` is not set: Alice to Eve around the origin
+const DEFAULT_SCENARIO: &str = include_str!("../scenario.toml");
+
+/// Colors of the nodes that do not set one, in turn
+const PALETTE: &[&str] = &[
+    "#e74c3c", "#3498db", "#2ecc71", "#f39c12", "#9b59b6", "#1abc9c", "#e67e22", "#34495e",
+];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioFile {
+    seed: Option<u64>,
+    #[serde(rename = "node", default)]
+    nodes: Vec<NodeEntry>,
+    #[serde(rename = "cluster", default)]
+    clusters: Vec<Cluster>,
+    #[serde(rename = "collusion", default)]
+    collusions: Vec<Collusion>,
+    #[serde(rename = "wormhole", default)]
+    wormholes: Vec<Wormhole>,
+    #[serde(rename = "link", default)]
+    links: Vec<LossyLink>,
+    #[serde(rename = "wall", default)]
+    walls: Vec<Wall>,
+    #[serde(default)]
+    propagation: Propagation,
+    #[serde(default)]
+    server_faults: ServerFaults,
+    floors: Option<Floors>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeEntry {
+    name: String,
+    node_id: Option<String>,
+    suri: Option<String>,
+    address: String,
+    latitude: f64,
+    longitude: f64,
+    #[serde(default)]
+    altitude: f64,
+    color: Option<String>,
+    #[serde(default)]
+    mobility: Mobility,
+    attack: Option<Attack>,
+    latency: Option<Latency>,
+    rotation: Option<RotationEntry>,
+}
+
+/// Addresses a node rotates through, as written in the scenario
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RotationEntry {
+    period_secs: f64,
+    addresses: Vec<String>,
+}
+
+/// Many similar nodes spread over a disc, for neighborhoods too large to list node by node
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Cluster {
+    /// Prefix of the node names, numbered from 1
+    name: String,
+    count: u16,
+    /// `[latitude, longitude]` of the middle of the disc
+    center: [f64; 2],
+    radius_m: f64,
+    #[serde(default)]
+    mobility: Mobility,
+}
+
+impl Cluster {
+    /// Node entries of the cluster, the `i`th signing with `//{name}{i}` and advertising a
+    /// locally administered address made of the cluster's `index` and `i`
+    ///
+    /// The nodes lie on a sunflower spiral, evenly spread over the disc without any randomness,
+    /// so a cluster always looks the same whatever the seed.
+    fn entries(&self, index: u8) -> Vec<NodeEntry> {
+        let golden_angle = PI * (3.0 - 5f64.sqrt());
+        (1..=self.count)
+            .map(|i| {
+                let radius = self.radius_m * ((i as f64 - 0.5) / self.count as f64).sqrt();
+                let angle = i as f64 * golden_angle;
+                let (latitude, longitude) = to_degrees(
+                    (self.center[0], self.center[1]),
+                    (radius * angle.cos(), radius * angle.sin()),
+                );
+                let [high, low] = i.to_be_bytes();
+                NodeEntry {
+                    name: format!("{}{}", self.name, i),
+                    node_id: None,
+                    suri: Some(format!("//{}{}", self.name, i)),
+                    address: format!("02:00:00:{:02X}:{:02X}:{:02X}", index, high, low),
+                    latitude,
+                    longitude,
+                    altitude: 0.0,
+                    color: None,
+                    mobility: self.mobility.clone(),
+                    attack: None,
+                    latency: None,
+                    rotation: None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// How a node moves during the simulation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Mobility {
+    /// Stays at its start position
+    #[default]
+    Static,
+    /// Moved through the web UI or `/api/update-position`
+    Manual,
+    /// Walks to random points within `radius_m` of its start position, pausing at each
+    RandomWaypoint {
+        radius_m: f64,
+        speed_mps: f64,
+        #[serde(default)]
+        pause_secs: f64,
+    },
+    /// Walks through the `[latitude, longitude]` waypoints of `path` and back to its start
+    Linear { path: Vec<[f64; 2]>, speed_mps: f64 },
+    /// Circles a center `radius_m` west of its start position once every `period_secs`
+    Circular { radius_m: f64, period_secs: f64 },
+}
+
+/// How a malicious node cheats, to show that the trust scores catch it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Attack {
+    /// Reports a false location, while its neighbors hear it at its true position
+    GpsLiar { latitude: f64, longitude: f64 },
+    /// Reports its neighbors `boost_db` stronger than it hears them
+    RssiInflater { boost_db: i16 },
+    /// Claims its position and reports its neighbors without a radio, so no neighbor hears it
+    Ghost,
+}
+
+impl Attack {
+    /// Name of the attack, the `kind` of the injected faults metric
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Attack::GpsLiar { .. } => "gps_liar",
+            Attack::RssiInflater { .. } => "rssi_inflater",
+            Attack::Ghost => "ghost",
+        }
+    }
+}
+
+/// Delay before a node's `/rssi` and `/location` responses are sent, like a slow network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Latency {
+    /// Mean delay in milliseconds
+    pub ms: f64,
+    /// Largest deviation from `ms` either way, drawn uniformly for every response
+    #[serde(default)]
+    pub jitter_ms: f64,
+}
+
+impl Latency {
+    fn validate(&self) -> Result<(), String> {
+        if !(self.ms.is_finite() && self.ms >= 0.0) {
+            return Err(format!("ms must not be negative, got {}", self.ms));
+        }
+        if !(self.jitter_ms.is_finite() && (0.0..=self.ms).contains(&self.jitter_ms)) {
+            return Err(format!(
+                "jitter_ms must be between 0 and ms, got {}",
+                self.jitter_ms
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Bluetooth addresses a node advertises in turn, like a device rotating its MAC for privacy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressRotation {
+    /// Scenario seconds each address is advertised for
+    pub period_secs: f64,
+    /// Addresses taken after the node's own `address`, before starting over with it
+    pub addresses: Vec<[u8; 6]>,
+}
+
+/// Nodes that report each other at a made-up RSSI, whatever their distance
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Collusion {
+    /// Names of the colluding nodes
+    pub members: Vec<String>,
+    /// RSSI the members report for each other
+    pub rssi: i16,
+}
+
+/// Relay tunneling advertisements between two distant areas
+///
+/// Nodes within `range_m` of one end hear the nodes within `range_m` of the other end as if they
+/// were at the near end.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Wormhole {
+    /// `[latitude, longitude]` of both ends
+    pub ends: [[f64; 2]; 2],
+    pub range_m: f64,
+}
+
+/// Pair of nodes that sometimes miss each other's advertisements
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LossyLink {
+    /// Names of the two nodes, in either order
+    pub nodes: [String; 2],
+    /// Probability that a scan of one node misses the other
+    #[serde(default)]
+    pub dropout: f64,
+    /// `[start, end]` scenario seconds during which the nodes do not hear each other at all
+    #[serde(default)]
+    pub outages: Vec<[f64; 2]>,
+}
+
+impl LossyLink {
+    pub fn connects(&self, a: &str, b: &str) -> bool {
+        (self.nodes[0] == a && self.nodes[1] == b) || (self.nodes[0] == b && self.nodes[1] == a)
+    }
+
+    /// Whether the link is down at `secs` of scenario time
+    pub fn in_outage(&self, secs: f64) -> bool {
+        self.outages
+            .iter()
+            .any(|[start, end]| *start <= secs && secs < *end)
+    }
+}
+
+/// Wall weakening the signal between the nodes on either side of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Wall {
+    /// `[latitude, longitude]` of both ends
+    pub ends: [[f64; 2]; 2],
+    /// dB taken off the RSSI of a signal passing through
+    pub attenuation_db: f64,
+}
+
+impl Wall {
+    /// Whether the straight path between two `[latitude, longitude]` points crosses the wall
+    ///
+    /// Paths only touching the wall or running along it pass, as do paths through a gap
+    /// between two walls meeting at their ends.
+    pub fn blocks(&self, a: [f64; 2], b: [f64; 2]) -> bool {
+        // Which side of the line through `p` and `q` the point `r` is on
+        fn side(p: [f64; 2], q: [f64; 2], r: [f64; 2]) -> f64 {
+            (q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])
+        }
+        let [p, q] = self.ends;
+        side(p, q, a) * side(p, q, b) < 0.0 && side(a, b, p) * side(a, b, q) < 0.0
+    }
+}
+
+/// Floors of a building, weakening the signal between nodes at different heights
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Floors {
+    /// Height of a floor in meters, the ground floor starting at altitude 0
+    pub height_m: f64,
+    /// dB taken off the RSSI per floor a signal passes through
+    pub attenuation_db: f64,
+}
+
+impl Floors {
+    /// Number of floors between two altitudes in meters
+    pub fn between(&self, a: f64, b: f64) -> f64 {
+        ((a / self.height_m).floor() - (b / self.height_m).floor()).abs()
+    }
+}
+
+/// Server errors injected into the responses of each endpoint
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerFaults {
+    pub rssi: Option<EndpointFaults>,
+    pub location: Option<EndpointFaults>,
+}
+
+/// Probability of each server error in a response, at most one per response
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointFaults {
+    /// Answered with `500 Internal Server Error`
+    #[serde(default)]
+    pub error: f64,
+    /// Held for `timeout_secs` before being answered
+    #[serde(default)]
+    pub timeout: f64,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: f64,
+    /// SCALE-encoded body cut in half
+    #[serde(default)]
+    pub truncated: f64,
+    /// Timestamped `stale_secs` in the past
+    #[serde(default)]
+    pub stale: f64,
+    #[serde(default = "default_stale_secs")]
+    pub stale_secs: f64,
+}
+
+fn default_timeout_secs() -> f64 {
+    30.0
+}
+
+fn default_stale_secs() -> f64 {
+    60.0
+}
+
+impl EndpointFaults {
+    fn validate(&self) -> Result<(), String> {
+        let probabilities = [
+            ("error", self.error),
+            ("timeout", self.timeout),
+            ("truncated", self.truncated),
+            ("stale", self.stale),
+        ];
+        if let Some((name, p)) = probabilities.iter().find(|(_, p)| !(0.0..=1.0).contains(p)) {
+            return Err(format!("{} must be between 0 and 1, not {}", name, p));
+        }
+        if probabilities.iter().map(|(_, p)| p).sum::<f64>() > 1.0 {
+            return Err("the probabilities add up to more than 1".to_string());
+        }
+        positive(self.timeout_secs, "timeout_secs")?;
+        positive(self.stale_secs, "stale_secs")?;
+        Ok(())
+    }
+}
+
+/// How the signal of a node weakens on its way to a neighbor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Propagation {
+    /// Path loss exponent 2 and no noise
+    FreeSpace { reference_rssi: f64 },
+    /// Log-distance path loss with Gaussian noise of `sigma` dB
+    LogNormalShadowing {
+        reference_rssi: f64,
+        path_loss_exponent: f64,
+        sigma: f64,
+    },
+    /// Log-distance path loss with Rician multipath fading, Rayleigh if `k_factor` is 0
+    Fading {
+        reference_rssi: f64,
+        path_loss_exponent: f64,
+        k_factor: f64,
+    },
+}
+
+impl Default for Propagation {
+    /// Indoor environment of the measurements
+    fn default() -> Self {
+        Propagation::LogNormalShadowing {
+            reference_rssi: -48.0,
+            path_loss_exponent: 4.0,
+            sigma: 2.0,
+        }
+    }
+}
+
+/// Nodes, attacks and radio propagation of a scenario
+pub struct Scenario {
+    /// Seed of all random sampling, fixed to reproduce a run
+    pub seed: Option<u64>,
+    pub nodes: Vec<Node>,
+    pub collusions: Vec<Collusion>,
+    pub wormholes: Vec<Wormhole>,
+    pub links: Vec<LossyLink>,
+    pub walls: Vec<Wall>,
+    pub propagation: Propagation,
+    pub server_faults: ServerFaults,
+    pub floors: Option<Floors>,
+}
+
+/// A simulated node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Node {
+    pub name: String,
+    /// Hex-encoded account ID the offchain worker sends in `X-Node-ID`, lowercase with `0x`
+    pub node_id: String,
+    /// Secret URI of the account key, to drive the chain with; never recorded
+    #[serde(skip)]
+    pub suri: Option<String>,
+    pub address: [u8; 6],
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Height above the ground floor in meters, not reported by `/location`
+    #[serde(default)]
+    pub altitude: f64,
+    pub color: String,
+    pub mobility: Mobility,
+    pub attack: Option<Attack>,
+    /// Delay of the node's responses, none to answer at once
+    pub latency: Option<Latency>,
+    /// Addresses the node rotates through, none to keep `address`
+    pub rotation: Option<AddressRotation>,
+}
+
+impl Node {
+    /// Bluetooth address as `AA:BB:CC:DD:EE:FF`
+    pub fn address_string(&self) -> String {
+        format_address(self.address)
+    }
+
+    /// Bluetooth address the node advertises at `secs` of scenario time
+    pub fn address_at(&self, secs: f64) -> [u8; 6] {
+        let Some(rotation) = &self.rotation else {
+            return self.address;
+        };
+        let turn = (secs / rotation.period_secs) as usize % (rotation.addresses.len() + 1);
+        match turn {
+            0 => self.address,
+            turn => rotation.addresses[turn - 1],
+        }
+    }
+}
+
+/// Bluetooth address as `AA:BB:CC:DD:EE:FF`
+pub fn format_address(address: [u8; 6]) -> String {
+    address
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn parse_bluetooth_address(addr_str: &str) -> Result<[u8; 6], Box<dyn Error>> {
+    let parts: Vec<&str> = addr_str.split(':').collect();
+    if parts.len() != 6 {
+        return Err("Invalid Bluetooth address format".into());
+    }
+    let mut address = [0u8; 6];
+    for (i, part) in parts.iter().enumerate() {
+        address[i] = u8::from_str_radix(part, 16)?;
+    }
+    Ok(address)
+}
+
+/// `value` of the setting `name`, if it is a positive number
+pub fn positive(value: f64, name: &str) -> Result<f64, String> {
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("{} must be positive, got {}", name, value))
+    }
+}
+
+/// Account ID of a node, given directly or derived from its secret URI
+/// Account ID of the node, given by the `suri` of its key or as its `node_id`, a hex-encoded
+/// public key or an SS58 address of any network
+fn node_id(entry: &NodeEntry) -> Result<String, String> {
+    match (&entry.node_id, &entry.suri) {
+        (Some(node_id), None) if node_id.starts_with("0x") => {
+            let hex = node_id.trim_start_matches("0x").to_lowercase();
+            if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Invalid node_id of {}: {}", entry.name, node_id));
+            }
+            Ok(format!("0x{}", hex))
+        }
+        (Some(node_id), None) => {
+            let account = AccountId32::from_str(node_id)
+                .map_err(|e| format!("Invalid node_id of {}: {}: {}", entry.name, node_id, e))?;
+            let hex: String = account.0.iter().map(|b| format!("{:02x}", b)).collect();
+            Ok(format!("0x{}", hex))
+        }
+        (None, Some(suri)) => {
+            let uri = SecretUri::from_str(suri)
+                .map_err(|e| format!("Invalid suri of {}: {}", entry.name, e))?;
+            let keypair = Keypair::from_uri(&uri)
+                .map_err(|e| format!("Invalid suri of {}: {}", entry.name, e))?;
+            let hex: String = keypair
+                .public_key()
+                .0
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            Ok(format!("0x{}", hex))
+        }
+        _ => Err(format!(
+            "{} needs exactly one of node_id and suri",
+            entry.name
+        )),
+    }
+}
+
+/// Parse a scenario, checking that names, node IDs and addresses, rotated ones included, are
+/// unique, that the mobility models, latencies and propagation models are valid and that
+/// colluding groups and lossy links name known nodes
+fn parse(contents: &str) -> Result<Scenario, String> {
+    let mut file: ScenarioFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+    if file.clusters.len() > u8::MAX as usize + 1 {
+        return Err("A scenario has at most 256 clusters".to_string());
+    }
+    for (index, cluster) in file.clusters.iter().enumerate() {
+        if cluster.count == 0 {
+            return Err(format!("Cluster {} has no nodes", cluster.name));
+        }
+        positive(cluster.radius_m, "radius_m")
+            .map_err(|e| format!("Invalid cluster {}: {}", cluster.name, e))?;
+        file.nodes.extend(cluster.entries(index as u8));
+    }
+    if file.nodes.is_empty() {
+        return Err("The scenario has no nodes".to_string());
+    }
+
+    let mut names = HashSet::new();
+    let mut node_ids = HashSet::new();
+    let mut addresses = HashSet::new();
+    let mut nodes = Vec::new();
+    for (i, entry) in file.nodes.into_iter().enumerate() {
+        let node_id = node_id(&entry)?;
+        let address = parse_bluetooth_address(&entry.address)
+            .map_err(|e| format!("Invalid address of {}: {}", entry.name, e))?;
+
+        if !names.insert(entry.name.clone()) {
+            return Err(format!("Duplicate node name: {}", entry.name));
+        }
+        if !node_ids.insert(node_id.clone()) {
+            return Err(format!("Duplicate node ID of {}: {}", entry.name, node_id));
+        }
+        if !addresses.insert(address) {
+            return Err(format!(
+                "Duplicate address of {}: {}",
+                entry.name, entry.address
+            ));
+        }
+
+        // Only checks the model, the simulation builds it again from its own seed
+        entry
+            .mobility
+            .model(
+                (entry.latitude, entry.longitude),
+                &mut StdRng::seed_from_u64(0),
+            )
+            .map_err(|e| format!("Invalid mobility of {}: {}", entry.name, e))?;
+        let rotation = match &entry.rotation {
+            Some(rotation) => {
+                positive(rotation.period_secs, "period_secs")
+                    .map_err(|e| format!("Invalid rotation of {}: {}", entry.name, e))?;
+                if rotation.addresses.is_empty() {
+                    return Err(format!("Invalid rotation of {}: no addresses", entry.name));
+                }
+                let mut rotated = Vec::new();
+                for rotated_address in &rotation.addresses {
+                    let parsed = parse_bluetooth_address(rotated_address).map_err(|e| {
+                        format!("Invalid rotation address of {}: {}", entry.name, e)
+                    })?;
+                    // No two nodes ever advertise the same address, nor a node one of its others
+                    if !addresses.insert(parsed) {
+                        return Err(format!(
+                            "Duplicate address of {}: {}",
+                            entry.name, rotated_address
+                        ));
+                    }
+                    rotated.push(parsed);
+                }
+                Some(AddressRotation {
+                    period_secs: rotation.period_secs,
+                    addresses: rotated,
+                })
+            }
+            None => None,
+        };
+        if !entry.altitude.is_finite() {
+            return Err(format!(
+                "Invalid altitude of {}: {}",
+                entry.name, entry.altitude
+            ));
+        }
+        if let Some(latency) = &entry.latency {
+            latency
+                .validate()
+                .map_err(|e| format!("Invalid latency of {}: {}", entry.name, e))?;
+        }
+
+        nodes.push(Node {
+            name: entry.name,
+            node_id,
+            suri: entry.suri,
+            address,
+            latitude: entry.latitude,
+            longitude: entry.longitude,
+            altitude: entry.altitude,
+            color: entry
+                .color
+                .unwrap_or_else(|| PALETTE[i % PALETTE.len()].to_string()),
+            mobility: entry.mobility,
+            attack: entry.attack,
+            latency: entry.latency,
+            rotation,
+        });
+    }
+
+    for collusion in &file.collusions {
+        if collusion.members.len() < 2 {
+            return Err("A collusion needs at least two members".to_string());
+        }
+        if let Some(unknown) = collusion.members.iter().find(|m| !names.contains(*m)) {
+            return Err(format!("Unknown colluding node: {}", unknown));
+        }
+    }
+    for wormhole in &file.wormholes {
+        positive(wormhole.range_m, "range_m").map_err(|e| format!("Invalid wormhole: {}", e))?;
+    }
+
+    for link in &file.links {
+        if let Some(unknown) = link.nodes.iter().find(|n| !names.contains(*n)) {
+            return Err(format!("Unknown node of a link: {}", unknown));
+        }
+        if link.nodes[0] == link.nodes[1] {
+            return Err(format!(
+                "A link needs two nodes, not {} twice",
+                link.nodes[0]
+            ));
+        }
+        if !(0.0..=1.0).contains(&link.dropout) {
+            return Err(format!(
+                "Invalid link between {} and {}: dropout must be between 0 and 1, not {}",
+                link.nodes[0], link.nodes[1], link.dropout
+            ));
+        }
+        if let Some([start, end]) = link
+            .outages
+            .iter()
+            .find(|[start, end]| !(*start >= 0.0 && start < end))
+        {
+            return Err(format!(
+                "Invalid link between {} and {}: outage [{}, {}] must start at or after 0 and end after it starts",
+                link.nodes[0], link.nodes[1], start, end
+            ));
+        }
+    }
+
+    for wall in &file.walls {
+        positive(wall.attenuation_db, "attenuation_db")
+            .map_err(|e| format!("Invalid wall: {}", e))?;
+        if wall.ends[0] == wall.ends[1] {
+            return Err(format!("Invalid wall: both ends are at {:?}", wall.ends[0]));
+        }
+    }
+
+    if let Some(floors) = &file.floors {
+        positive(floors.height_m, "height_m").map_err(|e| format!("Invalid floors: {}", e))?;
+        positive(floors.attenuation_db, "attenuation_db")
+            .map_err(|e| format!("Invalid floors: {}", e))?;
+    }
+
+    file.propagation
+        .model()
+        .map_err(|e| format!("Invalid propagation: {}", e))?;
+    for (endpoint, faults) in [
+        ("rssi", &file.server_faults.rssi),
+        ("location", &file.server_faults.location),
+    ] {
+        if let Some(faults) = faults {
+            faults
+                .validate()
+                .map_err(|e| format!("Invalid server faults of {}: {}", endpoint, e))?;
+        }
+    }
+
+    Ok(Scenario {
+        seed: file.seed,
+        nodes,
+        collusions: file.collusions,
+        wormholes: file.wormholes,
+        links: file.links,
+        walls: file.walls,
+        propagation: file.propagation,
+        server_faults: file.server_faults,
+        floors: file.floors,
+    })
+}
+
+impl Scenario {
+    /// Seed of the run: `SIMULATION_SEED`, the scenario's `seed`, or a random one
+    pub fn seed(&self) -> Result<u64, String> {
+        match std::env::var("SIMULATION_SEED")
+            .ok()
+            .filter(|v| !v.is_empty())
+        {
+            Some(seed) => seed
+                .parse()
+                .map_err(|_| format!("Invalid SIMULATION_SEED: {}", seed)),
+            None => Ok(self.seed.unwrap_or_else(rand::random)),
+        }
+    }
+}
+
+/// Load the scenario from `SCENARIO_PATH`, or the default one if it is not set
+pub fn load() -> Result<(String, Scenario), String> {
+    match std::env::var("SCENARIO_PATH")
+        .ok()
+        .filter(|v| !v.is_empty())
+    {
+        Some(path) => {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read scenario {}: {}", path, e))?;
+            let scenario =
+                parse(&contents).map_err(|e| format!("Invalid scenario {}: {}", path, e))?;
+            Ok((path, scenario))
+        }
+        None => {
+            let scenario =
+                parse(DEFAULT_SCENARIO).map_err(|e| format!("Invalid default scenario: {}", e))?;
+            Ok(("the default scenario".to_string(), scenario))
+        }
+    }
+}
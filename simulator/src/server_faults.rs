@@ -0,0 +1,121 @@
+use axum::{body::Body, http::StatusCode, response::Response};
+use rand::Rng;
+use std::time::Duration;
+use tokio::time;
+
+use crate::recording::Endpoint;
+use crate::scenario::Node;
+use crate::{unix_millis, AppState};
+
+/// What was injected into one response, to be applied as it is built
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Injected {
+    /// Milliseconds the timestamps are moved into the past
+    stale_ms: u64,
+    truncated: bool,
+}
+
+impl Injected {
+    /// Timestamp to send, stale if the response is
+    pub fn timestamp(&self) -> u64 {
+        unix_millis().saturating_sub(self.stale_ms)
+    }
+
+    /// SCALE-encoded body to send, cut in half if the response is truncated
+    pub fn body(&self, mut encoded: Vec<u8>) -> Vec<u8> {
+        if self.truncated {
+            encoded.truncate(encoded.len() / 2);
+        }
+        encoded
+    }
+}
+
+/// Server error injected into a response
+#[derive(Debug, Clone, Copy)]
+enum Fault {
+    Error,
+    Timeout,
+    Truncated,
+    Stale,
+}
+
+impl Fault {
+    /// `kind` of the injected faults metric
+    fn kind(self) -> &'static str {
+        match self {
+            Fault::Error => "server_error",
+            Fault::Timeout => "timeout",
+            Fault::Truncated => "truncated",
+            Fault::Stale => "stale",
+        }
+    }
+}
+
+/// Draw the server error of a request to `endpoint`, if the scenario injects any
+///
+/// A timeout is waited out here, and an error is returned as the response to send instead.
+pub async fn inject(state: &AppState, endpoint: Endpoint) -> Result<Injected, Response> {
+    let faults = match endpoint {
+        Endpoint::Rssi => &state.server_faults.rssi,
+        Endpoint::Location => &state.server_faults.location,
+    };
+    let Some(faults) = faults else {
+        return Ok(Injected::default());
+    };
+
+    // One draw per response, so the probabilities add up
+    let mut draw: f64 = state.rng.lock().unwrap().gen();
+    let fault = [
+        (Fault::Error, faults.error),
+        (Fault::Timeout, faults.timeout),
+        (Fault::Truncated, faults.truncated),
+        (Fault::Stale, faults.stale),
+    ]
+    .into_iter()
+    .find(|(_, probability)| {
+        draw -= probability;
+        draw < 0.0
+    });
+    let Some((fault, _)) = fault else {
+        return Ok(Injected::default());
+    };
+    state.metrics.record_fault(fault.kind());
+    println!("💥 Injected {}", fault.kind());
+
+    match fault {
+        Fault::Error => Err(Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from("Injected server error"))
+            .unwrap()),
+        Fault::Timeout => {
+            time::sleep(Duration::from_secs_f64(faults.timeout_secs)).await;
+            Ok(Injected::default())
+        }
+        Fault::Truncated => Ok(Injected {
+            truncated: true,
+            ..Default::default()
+        }),
+        Fault::Stale => Ok(Injected {
+            stale_ms: (faults.stale_secs * 1000.0) as u64,
+            ..Default::default()
+        }),
+    }
+}
+
+/// Hold a response of `node` for its latency and jitter, if the scenario gives it any
+pub async fn delay(state: &AppState, node: &Node) {
+    let Some(latency) = &node.latency else {
+        return;
+    };
+    // Only drawn with jitter, so nodes without it keep the noise sequence as it was
+    let ms = if latency.jitter_ms > 0.0 {
+        state
+            .rng
+            .lock()
+            .unwrap()
+            .gen_range(latency.ms - latency.jitter_ms..=latency.ms + latency.jitter_ms)
+    } else {
+        latency.ms
+    };
+    time::sleep(Duration::from_secs_f64(ms / 1000.0)).await;
+}
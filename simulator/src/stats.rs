@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{get_metrics, AppState};
+
+/// Format of `/api/stats`, picked with `?format=`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsFormat {
+    #[default]
+    Json,
+    Prometheus,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    #[serde(default)]
+    format: StatsFormat,
+}
+
+/// How far the scenario has run
+#[derive(Debug, Clone, Serialize)]
+struct Progress {
+    scenario_secs: f64,
+    paused: bool,
+    speed: f64,
+    /// Scenario seconds a headless run stops at, none if it runs until stopped
+    end_secs: Option<f64>,
+    /// Share of a headless run done, from 0 to 1
+    fraction: Option<f64>,
+}
+
+/// Requests a node has sent
+#[derive(Debug, Clone, Serialize)]
+struct NodeStats {
+    name: String,
+    rssi_requests: u64,
+    location_requests: u64,
+}
+
+/// RSSI served to `from` for its neighbor `to`
+#[derive(Debug, Clone, Serialize)]
+struct LinkStats {
+    from: String,
+    to: String,
+    samples: u64,
+    average_rssi: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Stats {
+    progress: Progress,
+    nodes: Vec<NodeStats>,
+    links: Vec<LinkStats>,
+    /// Faults injected so far, by kind
+    faults: BTreeMap<String, u64>,
+}
+
+/// What the simulator has served so far, or the Prometheus metrics with `?format=prometheus`
+pub async fn get_stats(State(state): State<AppState>, Query(query): Query<StatsQuery>) -> Response {
+    if query.format == StatsFormat::Prometheus {
+        return get_metrics(State(state)).await;
+    }
+
+    let clock = state.clock.status();
+    let progress = Progress {
+        scenario_secs: clock.elapsed_secs,
+        paused: clock.paused,
+        speed: clock.speed,
+        end_secs: state.end_secs,
+        fraction: state
+            .end_secs
+            .map(|end| (clock.elapsed_secs / end).min(1.0)),
+    };
+    let nodes = state
+        .nodes
+        .read()
+        .await
+        .iter()
+        .map(|node| NodeStats {
+            name: node.name.clone(),
+            rssi_requests: state.metrics.requests(&node.name, "rssi"),
+            location_requests: state.metrics.requests(&node.name, "location"),
+        })
+        .collect();
+    let mut links: Vec<LinkStats> = state
+        .metrics
+        .link_rssi()
+        .into_iter()
+        .map(|(from, to, samples, average_rssi)| LinkStats {
+            from,
+            to,
+            samples,
+            average_rssi,
+        })
+        .collect();
+    links.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+
+    Json(Stats {
+        progress,
+        nodes,
+        links,
+        faults: state.metrics.faults().into_iter().collect(),
+    })
+    .into_response()
+}
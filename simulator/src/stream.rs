@@ -0,0 +1,119 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::time;
+
+use crate::clock::ClockStatus;
+use crate::scenario::{Attack, Node, Wall};
+use crate::validation::Validation;
+use crate::{
+    floor_loss, heard_distance, millis_from_env, node_positions, wall_loss, AppState, NodePosition,
+};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// RSSI a node hears another with, without noise but through the walls
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Link {
+    from: String,
+    to: String,
+    rssi: f64,
+}
+
+/// Message pushed to the web UI
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct StreamUpdate {
+    nodes: Vec<NodePosition>,
+    links: Vec<Link>,
+    walls: Vec<Wall>,
+    clock: ClockStatus,
+    validation: Option<Validation>,
+}
+
+/// How often the positions are pushed, given by `STREAM_INTERVAL_MS`
+pub fn interval_from_env() -> Result<Duration, String> {
+    millis_from_env("STREAM_INTERVAL_MS", DEFAULT_INTERVAL)
+}
+
+/// Positions of all nodes and the RSSI between every pair that hears each other
+fn stream_update(state: &AppState, nodes: &[Node]) -> StreamUpdate {
+    let secs = state.clock.elapsed_secs();
+    let mut links = Vec::new();
+    // Ghosts have no radio, and wormholes work both ways, so one link per pair
+    let audible: Vec<&Node> = nodes
+        .iter()
+        .filter(|node| node.attack != Some(Attack::Ghost))
+        .collect();
+    for (i, from) in audible.iter().enumerate() {
+        for to in &audible[i + 1..] {
+            let down = state
+                .links
+                .iter()
+                .any(|link| link.connects(&from.name, &to.name) && link.in_outage(secs));
+            if down {
+                continue;
+            }
+            let (distance, tunnel) = heard_distance(from, to, &state.wormholes);
+            links.push(Link {
+                from: from.name.clone(),
+                to: to.name.clone(),
+                rssi: state.propagation.mean_rssi(distance)
+                    - wall_loss(from, to, tunnel, &state.walls)
+                    - floor_loss(from, to, tunnel, state.floors),
+            });
+        }
+    }
+
+    StreamUpdate {
+        nodes: node_positions(nodes),
+        links,
+        walls: state.walls.to_vec(),
+        clock: state.clock.status(),
+        validation: state.validation.lock().unwrap().clone(),
+    }
+}
+
+/// Upgrade to a WebSocket pushing the nodes to the web UI
+pub async fn stream_positions(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| stream(socket, state))
+}
+
+/// Push the nodes every `STREAM_INTERVAL_MS` while they change, until the browser goes away
+async fn stream(mut socket: WebSocket, state: AppState) {
+    let mut ticker = time::interval(state.stream_interval);
+    ticker.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    let mut last = None;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let update = stream_update(&state, &state.nodes.read().await);
+                if last.as_ref() == Some(&update) {
+                    continue;
+                }
+                let text = match serde_json::to_string(&update) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        eprintln!("⚠️  Failed to encode the position stream: {}", e);
+                        break;
+                    }
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+                last = Some(update);
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // The UI sends nothing, moves still go through /api/update-position
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use pallet_proof_of_location::util::{
+    estimate_rssi, trimmed_median_error_with, DEFAULT_TRIM_PERCENT,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::chain::fixed_point;
+use crate::clock::SimulationClock;
+use crate::metrics::Metrics;
+use crate::scenario::{Attack, Node, Scenario};
+use crate::{distance_meters, reported_location, simulate_scan, AppState, PROPAGATION_STREAM};
+
+/// Runtime constants the sweep stands in for, used when the sweep file leaves them out
+const DEFAULT_MAX_DISTANCE: u32 = 10;
+const DEFAULT_REFERENCE_RSSI: i16 = -48;
+const DEFAULT_PATH_LOSS_EXPONENT: u8 = 40;
+
+const CSV_HEADER: &str = "sigma,density,max_distance,true_positives,false_positives,false_negatives,true_negatives,precision,recall,f1";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SweepFile {
+    /// Scans per grid point, each scored like the reports of one block
+    #[serde(default = "default_rounds")]
+    rounds: u32,
+    /// Trust score in dB above which a node is flagged
+    threshold: i16,
+    /// CSV file to write the report to, besides printing it
+    report: Option<String>,
+    #[serde(default)]
+    grid: Grid,
+    #[serde(default)]
+    estimator: Estimator,
+}
+
+fn default_rounds() -> u32 {
+    10
+}
+
+/// Values to sweep, every combination of them being one grid point
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Grid {
+    /// Noise in dB, the scenario's propagation if left out
+    #[serde(default)]
+    sigma: Vec<f64>,
+    /// Nodes per area relative to the scenario, which is shrunk or spread around its center
+    #[serde(default)]
+    density: Vec<f64>,
+    /// `MaxDistance` of the runtime in meters
+    #[serde(default)]
+    max_distance: Vec<u32>,
+}
+
+/// Parameters of the pallet's RSSI estimator, the runtime's if left out
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Estimator {
+    reference_rssi: i16,
+    /// Multiplied by 10, like the runtime constant
+    path_loss_exponent: u8,
+    trim_percent: u8,
+}
+
+impl Default for Estimator {
+    fn default() -> Self {
+        Self {
+            reference_rssi: DEFAULT_REFERENCE_RSSI,
+            path_loss_exponent: DEFAULT_PATH_LOSS_EXPONENT,
+            trim_percent: DEFAULT_TRIM_PERCENT,
+        }
+    }
+}
+
+/// One combination of the grid, `None` keeping the scenario's noise
+struct Point {
+    sigma: Option<f64>,
+    density: f64,
+    max_distance: u32,
+}
+
+/// How the flagged nodes compare with the lying ones, over all rounds of a grid point
+#[derive(Default)]
+struct Detection {
+    true_positives: u32,
+    false_positives: u32,
+    false_negatives: u32,
+    true_negatives: u32,
+}
+
+impl Detection {
+    fn count(&mut self, flagged: bool, lying: bool) {
+        match (flagged, lying) {
+            (true, true) => self.true_positives += 1,
+            (true, false) => self.false_positives += 1,
+            (false, true) => self.false_negatives += 1,
+            (false, false) => self.true_negatives += 1,
+        }
+    }
+
+    fn precision(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    fn recall(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+
+    fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.recall());
+        2.0 * precision * recall / (precision + recall)
+    }
+}
+
+/// `part / whole`, NaN when there is nothing to divide
+fn ratio(part: u32, whole: u32) -> f64 {
+    part as f64 / whole as f64
+}
+
+fn load(path: &Path) -> Result<SweepFile, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read sweep {}: {}", path.display(), e))?;
+    let file: SweepFile = toml::from_str(&contents)
+        .map_err(|e| format!("Invalid sweep {}: {}", path.display(), e))?;
+    if file.rounds == 0 {
+        return Err(format!("Invalid sweep {}: no rounds", path.display()));
+    }
+    if let Some(density) = file
+        .grid
+        .density
+        .iter()
+        .find(|d| !(d.is_finite() && **d > 0.0))
+    {
+        return Err(format!(
+            "Invalid sweep {}: density must be positive, got {}",
+            path.display(),
+            density
+        ));
+    }
+    Ok(file)
+}
+
+/// Every combination of the grid
+fn points(grid: &Grid) -> Vec<Point> {
+    let sigmas: Vec<Option<f64>> = if grid.sigma.is_empty() {
+        vec![None]
+    } else {
+        grid.sigma.iter().copied().map(Some).collect()
+    };
+    let densities = if grid.density.is_empty() {
+        vec![1.0]
+    } else {
+        grid.density.clone()
+    };
+    let max_distances = if grid.max_distance.is_empty() {
+        vec![DEFAULT_MAX_DISTANCE]
+    } else {
+        grid.max_distance.clone()
+    };
+
+    let mut points = Vec::new();
+    for sigma in &sigmas {
+        for density in &densities {
+            for max_distance in &max_distances {
+                points.push(Point {
+                    sigma: *sigma,
+                    density: *density,
+                    max_distance: *max_distance,
+                });
+            }
+        }
+    }
+    points
+}
+
+/// The scenario shrunk or spread around its center to `density` times as many nodes per area
+fn densify(scenario: &Scenario, density: f64) -> Scenario {
+    let count = scenario.nodes.len() as f64;
+    let center = [
+        scenario.nodes.iter().map(|n| n.latitude).sum::<f64>() / count,
+        scenario.nodes.iter().map(|n| n.longitude).sum::<f64>() / count,
+    ];
+    let scale = 1.0 / density.sqrt();
+    let moved = |[latitude, longitude]: [f64; 2]| {
+        [
+            center[0] + (latitude - center[0]) * scale,
+            center[1] + (longitude - center[1]) * scale,
+        ]
+    };
+
+    let nodes: Vec<Node> = scenario
+        .nodes
+        .iter()
+        .cloned()
+        .map(|mut node| {
+            [node.latitude, node.longitude] = moved([node.latitude, node.longitude]);
+            if let Some(Attack::GpsLiar {
+                latitude,
+                longitude,
+            }) = &mut node.attack
+            {
+                [*latitude, *longitude] = moved([*latitude, *longitude]);
+            }
+            node
+        })
+        .collect();
+    let mut wormholes = scenario.wormholes.clone();
+    for wormhole in &mut wormholes {
+        wormhole.ends = wormhole.ends.map(moved);
+    }
+    let mut walls = scenario.walls.clone();
+    for wall in &mut walls {
+        wall.ends = wall.ends.map(moved);
+    }
+
+    Scenario {
+        seed: scenario.seed,
+        nodes,
+        collusions: scenario.collusions.clone(),
+        wormholes,
+        links: scenario.links.clone(),
+        walls,
+        propagation: scenario.propagation.clone(),
+        server_faults: scenario.server_faults.clone(),
+        floors: scenario.floors,
+    }
+}
+
+/// Scan and score the scenario `rounds` times at one grid point
+///
+/// Scoring follows the pallet: the reports about a node are compared with the RSSI estimated
+/// from both registered locations, and the trimmed median of the errors is its trust score.
+/// A node is flagged when the score exceeds the threshold, or when it has none.
+async fn run_point(
+    scenario: &Scenario,
+    file: &SweepFile,
+    point: &Point,
+    seed: u64,
+) -> Result<(f64, Detection), String> {
+    let scenario = densify(scenario, point.density);
+    let nodes = scenario.nodes;
+    let propagation = match point.sigma {
+        Some(sigma) => scenario.propagation.with_sigma(sigma)?,
+        None => scenario.propagation.clone(),
+    };
+    let state = AppState {
+        nodes: Arc::new(RwLock::new(nodes.clone())),
+        collusions: Arc::new(scenario.collusions),
+        wormholes: Arc::new(scenario.wormholes),
+        links: Arc::new(scenario.links),
+        walls: Arc::new(scenario.walls),
+        floors: scenario.floors,
+        propagation: Arc::from(propagation.model()?),
+        // Every grid point draws the same noise, so only the swept parameters differ
+        rng: Arc::new(Mutex::new(StdRng::seed_from_u64(
+            seed.wrapping_add(PROPAGATION_STREAM),
+        ))),
+        recorder: None,
+        replay: None,
+        stream_interval: Duration::ZERO,
+        map_tiles: None,
+        server_faults: Arc::new(scenario.server_faults),
+        clock: Arc::new(SimulationClock::new(1.0)),
+        end_secs: None,
+        metrics: Arc::new(Metrics::new().map_err(|e| e.to_string())?),
+        validation: Arc::new(Mutex::new(None)),
+    };
+    let locations: HashMap<&str, (f64, f64)> = nodes
+        .iter()
+        .map(|node| (node.name.as_str(), reported_location(&state, node)))
+        .collect();
+
+    let mut detection = Detection::default();
+    for _ in 0..file.rounds {
+        // Reports about each node, by reporter, as stored for one block
+        let mut reports: HashMap<&str, Vec<(&str, i16)>> = HashMap::new();
+        for reporter in &nodes {
+            let from = locations[reporter.name.as_str()];
+            for (neighbor, rssi) in simulate_scan(&state, reporter).await {
+                let (about, to) = locations
+                    .get_key_value(neighbor.name.as_str())
+                    .map(|(name, location)| (*name, *location))
+                    .ok_or_else(|| format!("Unknown neighbor {}", neighbor.name))?;
+                // The pallet rejects readings between nodes registered too far apart
+                if distance_meters(from.0, from.1, to.0, to.1) > point.max_distance as f64 {
+                    continue;
+                }
+                reports
+                    .entry(about)
+                    .or_default()
+                    .push((reporter.name.as_str(), rssi));
+            }
+        }
+
+        for node in &nodes {
+            let (latitude, longitude) = fixed_point(locations[node.name.as_str()]);
+            let mut errors: Vec<i16> = reports
+                .get(node.name.as_str())
+                .into_iter()
+                .flatten()
+                .map(|(reporter, rssi)| {
+                    let (reporter_latitude, reporter_longitude) = fixed_point(locations[reporter]);
+                    rssi - estimate_rssi(
+                        latitude,
+                        longitude,
+                        reporter_latitude,
+                        reporter_longitude,
+                        file.estimator.reference_rssi,
+                        file.estimator.path_loss_exponent,
+                    )
+                })
+                .collect();
+            let flagged = errors.is_empty()
+                || trimmed_median_error_with(&mut errors, file.estimator.trim_percent)
+                    > file.threshold;
+            let lying = node.attack.is_some()
+                || state
+                    .collusions
+                    .iter()
+                    .any(|c| c.members.contains(&node.name));
+            detection.count(flagged, lying);
+        }
+    }
+    Ok((state.propagation.sigma(), detection))
+}
+
+/// Run the scenario across the grid of the sweep file at `path` and report the spoofer detection
+/// of every grid point
+pub async fn run(path: &Path, scenario: Scenario, seed: u64) -> Result<(), String> {
+    let file = load(path)?;
+    let points = points(&file.grid);
+    println!(
+        "🔬 Sweeping {} grid point(s) of {} round(s) each, flagging trust scores above {}\n",
+        points.len(),
+        file.rounds,
+        file.threshold
+    );
+
+    let mut rows = vec![CSV_HEADER.to_string()];
+    for point in &points {
+        let (sigma, detection) = run_point(&scenario, &file, point, seed)
+            .await
+            .map_err(|e| {
+                format!(
+                    "Sweep failed at density {} and MaxDistance {}: {}",
+                    point.density, point.max_distance, e
+                )
+            })?;
+        println!(
+            "  σ={:.1} dB, density={}x, MaxDistance={} m: precision {:.2}, recall {:.2}, F1 {:.2}",
+            sigma,
+            point.density,
+            point.max_distance,
+            detection.precision(),
+            detection.recall(),
+            detection.f1()
+        );
+        rows.push(format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            sigma,
+            point.density,
+            point.max_distance,
+            detection.true_positives,
+            detection.false_positives,
+            detection.false_negatives,
+            detection.true_negatives,
+            detection.precision(),
+            detection.recall(),
+            detection.f1()
+        ));
+    }
+
+    if let Some(report) = &file.report {
+        let mut out =
+            File::create(report).map_err(|e| format!("Failed to create {}: {}", report, e))?;
+        writeln!(out, "{}", rows.join("\n"))
+            .map_err(|e| format!("Failed to write {}: {}", report, e))?;
+        println!("\n📋 Report written to {}", report);
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::collections::HashMap;
+use subxt::utils::AccountId32;
+use subxt::{OnlineClient, SubstrateConfig};
+
+use crate::chain::{account, substrate};
+use crate::scenario::Node;
+use crate::AppState;
+
+/// Trust score in dB above which a node counts as detected
+const DEFAULT_THRESHOLD: i16 = 10;
+
+/// How the chain judged a node, next to whether it really lies
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// A lying node flagged by its trust score
+    Detected,
+    /// A lying node the trust score let through
+    Missed,
+    /// An honest node flagged by its trust score
+    FalsePositive,
+    /// An honest node the trust score let through
+    Trusted,
+    /// No trust score, as nobody reported the node in the block
+    Unscored,
+}
+
+/// Trust score of one simulated node in a block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeValidation {
+    name: String,
+    score: Option<i16>,
+    /// Whether the ground truth says the node lies, by an attack or collusion
+    lying: bool,
+    verdict: Verdict,
+}
+
+/// Trust scores of one finalized block compared with the ground truth
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Validation {
+    block: u32,
+    threshold: i16,
+    nodes: Vec<NodeValidation>,
+    detected: usize,
+    missed: usize,
+    false_positives: usize,
+}
+
+/// Substrate node to validate against from `VALIDATION_RPC_URL`, and the trust score threshold
+/// from `VALIDATION_THRESHOLD`
+pub fn validator_from_env() -> Result<Option<(String, i16)>, String> {
+    let Some(url) = std::env::var("VALIDATION_RPC_URL")
+        .ok()
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    let threshold = match std::env::var("VALIDATION_THRESHOLD")
+        .ok()
+        .filter(|v| !v.is_empty())
+    {
+        Some(threshold) => threshold
+            .parse()
+            .map_err(|_| format!("Invalid VALIDATION_THRESHOLD: {}", threshold))?,
+        None => DEFAULT_THRESHOLD,
+    };
+    Ok(Some((url, threshold)))
+}
+
+/// Latest comparison of the chain's trust scores with the ground truth, `null` before the first
+/// finalized block
+pub async fn get_validation(State(state): State<AppState>) -> Json<Option<Validation>> {
+    Json(state.validation.lock().unwrap().clone())
+}
+
+fn validate(
+    state: &AppState,
+    nodes: &[Node],
+    scores: &HashMap<[u8; 32], i16>,
+    block: u32,
+    threshold: i16,
+) -> Validation {
+    let nodes: Vec<NodeValidation> = nodes
+        .iter()
+        .map(|node| {
+            let score = account(node)
+                .ok()
+                .and_then(|account| scores.get(&account).copied());
+            let lying = node.attack.is_some()
+                || state
+                    .collusions
+                    .iter()
+                    .any(|c| c.members.contains(&node.name));
+            let verdict = match (score.map(|score| score > threshold), lying) {
+                (None, _) => Verdict::Unscored,
+                (Some(true), true) => Verdict::Detected,
+                (Some(false), true) => Verdict::Missed,
+                (Some(true), false) => Verdict::FalsePositive,
+                (Some(false), false) => Verdict::Trusted,
+            };
+            NodeValidation {
+                name: node.name.clone(),
+                score,
+                lying,
+                verdict,
+            }
+        })
+        .collect();
+    let count = |verdict| nodes.iter().filter(|node| node.verdict == verdict).count();
+
+    Validation {
+        block,
+        threshold,
+        detected: count(Verdict::Detected),
+        missed: count(Verdict::Missed),
+        false_positives: count(Verdict::FalsePositive),
+        nodes,
+    }
+}
+
+/// Compare the trust scores of every finalized block with the ground truth, for the web UI and
+/// `/api/validation`
+///
+/// Nodes are matched to accounts by their `node_id`; accounts of other nodes are ignored.
+pub async fn start_validation(state: AppState, url: String, threshold: i16) -> Result<(), String> {
+    let api = OnlineClient::<SubstrateConfig>::from_insecure_url(&url)
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    let mut blocks = api
+        .blocks()
+        .subscribe_finalized()
+        .await
+        .map_err(|e| format!("Failed to follow {}: {}", url, e))?;
+
+    while let Some(block) = blocks.next().await {
+        let block = block.map_err(|e| e.to_string())?;
+        let rpc_call = substrate::apis()
+            .trust_score_api()
+            .calculate_trust_scores(block.number());
+        let scores: Vec<(AccountId32, i16)> =
+            match api.runtime_api().at(block.hash()).call(rpc_call).await {
+                Ok(scores) => scores,
+                Err(e) => {
+                    eprintln!(
+                        "⚠️  Failed to fetch the trust scores of block #{}: {}",
+                        block.number(),
+                        e
+                    );
+                    continue;
+                }
+            };
+        let scores: HashMap<[u8; 32], i16> = scores
+            .into_iter()
+            .map(|(account, score)| (account.0, score))
+            .collect();
+
+        let nodes = state.nodes.read().await.clone();
+        let validation = validate(&state, &nodes, &scores, block.number(), threshold);
+        println!(
+            "🔎 Block #{}: detected {} of {} lying node(s), {} false positive(s)",
+            validation.block,
+            validation.detected,
+            validation.detected + validation.missed,
+            validation.false_positives
+        );
+        *state.validation.lock().unwrap() = Some(validation);
+    }
+    Ok(())
+}